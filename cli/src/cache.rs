@@ -0,0 +1,91 @@
+use super::{bang, print};
+use std::{env, fs, path::PathBuf};
+
+const EXTENSION: &str = "chunk";
+
+// Compiled chunks are stored by the hash of their source, so unchanged files aren't compiled again.
+// Defaults to `~/.cache/bang`, but can be set with `BANG_CACHE_DIR`
+pub fn directory() -> Option<PathBuf> {
+  let variable = |name| env::var_os(name).filter(|value| !value.is_empty());
+
+  if let Some(directory) = variable("BANG_CACHE_DIR") {
+    Some(directory.into())
+  } else if let Some(directory) = variable("XDG_CACHE_HOME") {
+    Some(PathBuf::from(directory).join("bang"))
+  } else {
+    variable("HOME").map(|home| PathBuf::from(home).join(".cache").join("bang"))
+  }
+}
+
+fn path(source: &str) -> Option<PathBuf> {
+  let hash = bang::chunk::hash_source(source);
+  Some(directory()?.join(format!("{hash:016x}.{EXTENSION}")))
+}
+
+// A stored chunk which doesn't match is compiled again, and replaced when it is stored
+pub fn load(filename: &str, source: &str) -> Option<bang::Chunk> {
+  let bytes = fs::read(path(source)?).ok()?;
+  let chunk = bang::chunk::Chunk::from_bytes(&bytes)?;
+
+  match chunk.metadata().check(source, false) {
+    Ok(()) => Some(chunk.into()),
+    Err(stale) => {
+      print::warning_message(&format!(
+        "Cached bytecode for '{filename}' is stale, as {stale}"
+      ));
+      None
+    }
+  }
+}
+
+// The cache is only to speed things up, so problems writing to it are ignored
+pub fn store(source: &str, chunk: &bang::Chunk) {
+  if let (Some(path), Some(bytes)) = (path(source), chunk.to_bytes()) {
+    let written = path.parent().map(fs::create_dir_all);
+    if matches!(written, Some(Ok(()))) {
+      let _ = fs::write(path, bytes);
+    }
+  }
+}
+
+fn entries() -> Vec<PathBuf> {
+  let entries = directory().and_then(|directory| fs::read_dir(directory).ok());
+
+  (entries.into_iter().flatten().flatten())
+    .map(|entry| entry.path())
+    .filter(|path| {
+      path
+        .extension()
+        .is_some_and(|extension| extension == EXTENSION)
+    })
+    .collect()
+}
+
+pub fn clear() -> Result<(), ()> {
+  let entries = entries();
+
+  for path in &entries {
+    if fs::remove_file(path).is_err() {
+      print::error_message(&format!("Problem removing '{}'", path.display()));
+      return Err(());
+    }
+  }
+
+  println!("Removed {} cached chunks", entries.len());
+  Ok(())
+}
+
+pub fn stats() {
+  let entries = entries();
+  let size: u64 = (entries.iter())
+    .filter_map(|path| fs::metadata(path).ok())
+    .map(|metadata| metadata.len())
+    .sum();
+
+  match directory() {
+    Some(directory) => println!("Location: {}", directory.display()),
+    None => println!("Location: none, set BANG_CACHE_DIR to enable the cache"),
+  }
+  println!("Chunks: {}", entries.len());
+  println!("Size: {size} bytes");
+}
@@ -1,5 +1,13 @@
-use super::{bang, print};
-use std::fs;
+use super::{bang, cache, print};
+use bang::{
+  ast::{
+    expression::{operators::Unary, Expr, Expression, LiteralType},
+    statement::Stmt,
+  },
+  collections::HashMap,
+  Value,
+};
+use std::{fs, io, path::Path};
 
 pub fn get_filename(args: &clap::ArgMatches) -> Result<&str, ()> {
   if let Some(filename) = args.get_one::<String>("file") {
@@ -10,8 +18,15 @@ pub fn get_filename(args: &clap::ArgMatches) -> Result<&str, ()> {
   }
 }
 
+// A filename of `-` reads the program from stdin
 pub fn read_file(filename: &str) -> Result<String, ()> {
-  if let Ok(file) = fs::read_to_string(filename) {
+  let file = if filename == "-" {
+    io::read_to_string(io::stdin())
+  } else {
+    fs::read_to_string(filename)
+  };
+
+  if let Ok(file) = file {
     if file.is_empty() {
       print::warning_message("File is empty");
     }
@@ -23,36 +38,148 @@ pub fn read_file(filename: &str) -> Result<String, ()> {
   }
 }
 
+pub fn load_config(filename: &str) -> Result<bang::Config, ()> {
+  match bang::Config::discover(Path::new(filename)) {
+    Ok(config) => Ok(config),
+    Err(error) => {
+      print::error_message("Invalid Config");
+      eprintln!("{error}\n");
+      Err(())
+    }
+  }
+}
+
+// Only values given on the command line override the config file
+pub fn cli_value<'a, T>(args: &'a clap::ArgMatches, id: &str) -> Option<&'a T>
+where
+  T: Clone + Send + Sync + 'static,
+{
+  match args.value_source(id) {
+    Some(clap::parser::ValueSource::CommandLine) => args.get_one::<T>(id),
+    _ => None,
+  }
+}
+
+fn report_diagnostic(filename: &str, source: &str, diagnostic: &bang::Diagnostic) {
+  print::error_message(&diagnostic.title);
+  eprintln!("{}\n", &diagnostic.message);
+  print::code_frame(filename, source, diagnostic.line, Some(diagnostic.span));
+}
+
 pub fn parse<'a>(filename: &str, source: &'a str) -> Result<bang::Ast<'a>, ()> {
-  match bang::parse(source) {
-    Ok(statements) => Ok(statements),
-    Err(diagnostic) => {
-      print::error_message(&diagnostic.title);
-      eprintln!("{}\n", &diagnostic.message);
-      print::code_frame(filename, source, diagnostic.line);
+  bang::parse(source).map_err(|diagnostic| report_diagnostic(filename, source, &diagnostic))
+}
+
+pub fn file_result<'a, T>(file: &bang::FileResult<'a, T>) -> Result<&'a T, ()> {
+  (file.result.as_ref())
+    .map_err(|diagnostic| report_diagnostic(file.filename, file.source, diagnostic))
+}
+
+pub fn compile(filename: &str, source: &str) -> Result<bang::Chunk, ()> {
+  bang::compile_with_context(source, &bang::StdContext::default())
+    .map_err(|diagnostic| report_diagnostic(filename, source, &diagnostic))
+}
+
+// Bytecode is reused from the cache if the source hasn't changed since it was compiled
+pub fn compile_cached(filename: &str, source: &str, use_cache: bool) -> Result<bang::Chunk, ()> {
+  if !use_cache {
+    return compile(filename, source);
+  }
+  if let Some(chunk) = cache::load(filename, source) {
+    return Ok(chunk);
+  }
+
+  let chunk = compile(filename, source)?;
+  cache::store(source, &chunk);
+  Ok(chunk)
+}
+
+pub fn compile_keeping_last_value(filename: &str, source: &str) -> Result<bang::Chunk, ()> {
+  bang::compile_keeping_last_value(source, &bang::StdContext::default())
+    .map_err(|diagnostic| report_diagnostic(filename, source, &diagnostic))
+}
 
+pub fn run(vm: &mut bang::VM, filename: &str, source: &str, chunk: &bang::Chunk) -> Result<(), ()> {
+  match vm.run(chunk) {
+    Ok(()) => Ok(()),
+    Err(error) => {
+      print::stack_trace(filename, source, error);
       Err(())
     }
   }
 }
 
-pub fn compile(filename: &str, source: &str) -> Result<bang::Chunk, ()> {
-  match bang::compile(source) {
-    Ok(chunk) => Ok(chunk),
-    Err(diagnostic) => {
-      print::error_message(&diagnostic.title);
-      eprintln!("{}\n", &diagnostic.message);
-      print::code_frame(filename, source, diagnostic.line);
+pub fn call_entry(
+  vm: &mut bang::VM,
+  filename: &str,
+  source: &str,
+  entry: &str,
+  arguments: &[Value],
+) -> Result<(), ()> {
+  let function = match vm.get_global(entry) {
+    Some(function) => function,
+    None => {
+      print::error_message(&format!("No global named '{entry}' to call"));
+      return Err(());
+    }
+  };
 
+  match vm.call(function, arguments) {
+    Ok(value) => {
+      println!("{value}");
+      Ok(())
+    }
+    Err(error) => {
+      print::stack_trace(filename, source, error);
       Err(())
     }
   }
 }
 
-pub fn run(filename: &str, source: &str, chunk: &bang::Chunk) {
-  let context = bang::StdContext::default();
-  match bang::VM::new(&context).run(chunk) {
-    Ok(()) => {}
-    Err(error) => print::stack_trace(filename, source, error),
+fn literal_value(expression: &Expression) -> Option<Value> {
+  match &expression.expr {
+    Expr::Literal { type_, value } => Some(match type_ {
+      LiteralType::True => Value::TRUE,
+      LiteralType::False => Value::FALSE,
+      LiteralType::Null => Value::NULL,
+      LiteralType::Number => Value::from(bang::Parser::number(value)),
+      LiteralType::String => Value::from(bang::Parser::string(value)),
+    }),
+    Expr::Unary {
+      operator: Unary::Minus,
+      expression,
+    } => literal_value(expression)
+      .filter(Value::is_number)
+      .map(|value| Value::from(-value.as_number())),
+    Expr::List { items } => items
+      .iter()
+      .map(literal_value)
+      .collect::<Option<Vec<_>>>()
+      .map(Value::from),
+    Expr::Dictionary { items } => items
+      .iter()
+      .map(|(key, value)| Some((literal_value(key)?.as_dict_key()?, literal_value(value)?)))
+      .collect::<Option<HashMap<_, _>>>()
+      .map(Value::from),
+    _ => None,
   }
 }
+
+pub fn parse_arguments(arguments: &str) -> Result<Vec<Value>, ()> {
+  let ast = bang::parse(arguments).unwrap_or_default();
+
+  let values = match ast.as_slice() {
+    [statement] => match &statement.stmt {
+      Stmt::Expression {
+        expression: Expression {
+          expr: Expr::List { items },
+          ..
+        },
+      } => items.iter().map(literal_value).collect(),
+      _ => None,
+    },
+    _ => None,
+  };
+
+  values.ok_or_else(|| print::error_message("Arguments must be a JSON list"))
+}
@@ -0,0 +1,79 @@
+use super::{bang, print};
+use std::{fs, path::Path};
+
+fn config(lib: bool) -> String {
+  // A library's definitions are used by whatever imports it, so they look unused
+  let disabled = if lib { "\"no-unused-variables\"" } else { "" };
+
+  format!(
+    "\
+# Settings used by `bang` commands for files in this project
+[run]
+strict_equality = false
+
+[typecheck]
+strict = false
+
+[lint]
+disabled = [{disabled}]
+
+[format]
+indent = 2
+quote = \"single\"
+"
+  )
+}
+
+const GITIGNORE: &str = "\
+.DS_Store
+*.log
+";
+
+const MAIN: &str = "print('Hello, World!')\n";
+
+const LIB: &str = "let greet = (name) => `Hello, ${name}!`\n";
+
+fn files(lib: bool) -> [(&'static str, String); 4] {
+  let source = if lib {
+    ("src/lib.bang", LIB.to_string())
+  } else {
+    ("src/main.bang", MAIN.to_string())
+  };
+
+  [
+    (bang::CONFIG_FILENAME, config(lib)),
+    (".gitignore", GITIGNORE.to_string()),
+    source,
+    // Keeps the folder in version control until it has tests in it
+    ("tests/.gitkeep", String::new()),
+  ]
+}
+
+pub fn init(directory: &Path, lib: bool) -> Result<(), ()> {
+  let files = files(lib);
+
+  // Nothing is written if it would replace an existing file
+  for (path, _) in &files {
+    if directory.join(path).exists() {
+      print::error_message(&format!("'{path}' already exists"));
+      return Err(());
+    }
+  }
+
+  for (path, contents) in &files {
+    let path = directory.join(path);
+    let written = path
+      .parent()
+      .map_or(Ok(()), fs::create_dir_all)
+      .and_then(|()| fs::write(&path, contents));
+
+    if written.is_err() {
+      print::error_message(&format!("Problem creating '{}'", path.display()));
+      return Err(());
+    }
+  }
+
+  let kind = if lib { "library" } else { "program" };
+  println!("Created a new {kind} in '{}'", directory.display());
+  Ok(())
+}
@@ -4,28 +4,153 @@ mod bang {
   pub use bang_syntax::*;
   pub use bang_tools::*;
 }
+mod cache;
 mod helpers;
+mod init;
 mod print;
+mod watch;
 
-use clap::{Arg, Command};
-use helpers::{compile, get_filename, parse, read_file, run};
-use std::fs;
+use clap::{Arg, ArgGroup, Command};
+use helpers::{
+  call_entry, cli_value, compile, compile_cached, compile_keeping_last_value, file_result,
+  get_filename, load_config, parse, parse_arguments, read_file, run,
+};
+use std::{
+  env,
+  ffi::OsString,
+  fs,
+  path::Path,
+  time::{Duration, Instant},
+};
 
 const VERSION: &str = "v2.0-alpha";
+const SLOW_EVALUATION: Duration = Duration::from_secs(1);
+
+fn watch_flag() -> Arg {
+  Arg::new("watch")
+    .long("watch")
+    .action(clap::ArgAction::SetTrue)
+    .help("Re-run when the file changes")
+}
+
+// A file can be given without a subcommand, like `bang file.bang`, which also runs scripts with
+// a `#!/usr/bin/env bang` line
+fn arguments(command: &Command) -> Vec<OsString> {
+  let mut arguments: Vec<OsString> = env::args_os().collect();
+
+  // Skip over the global `--color` option, which can come before the file
+  let position = match arguments.get(1).and_then(|argument| argument.to_str()) {
+    Some("--color") => 3,
+    Some(argument) if argument.starts_with("--color=") => 2,
+    _ => 1,
+  };
+
+  let is_file = arguments.get(position).is_some_and(|argument| {
+    command.find_subcommand(argument).is_none() && Path::new(argument).is_file()
+  });
+  if is_file {
+    arguments.insert(position, "run".into());
+  }
+
+  arguments
+}
 
 fn main() {
-  let app = Command::new("bang")
+  let command = Command::new("bang")
     .version(VERSION)
+    .arg(
+      Arg::new("color")
+        .long("color")
+        .global(true)
+        .value_parser(["auto", "always", "never"])
+        .default_value("auto")
+        .help("When to colour output"),
+    )
     .subcommand(Command::new("").about("Open a REPL"))
+    .subcommand(
+      Command::new("repl").about("Open a REPL").arg(
+        Arg::new("load")
+          .long("load")
+          .value_name("file")
+          .help("Run a file before the prompt appears, keeping its variables"),
+      ),
+    )
     .subcommand(
       Command::new("run")
         .about("Execute a Bang program")
-        .arg(Arg::new("file").help("The file to run").required(true)),
+        .arg(
+          Arg::new("file")
+            .help("The file to run, or '-' to read from stdin")
+            .required(true),
+        )
+        .arg(
+          Arg::new("stats")
+            .long("stats")
+            .action(clap::ArgAction::SetTrue)
+            .help("Show the memory used once the program has finished"),
+        )
+        .arg(watch_flag())
+        .arg(
+          Arg::new("hot")
+            .long("hot")
+            .action(clap::ArgAction::SetTrue)
+            .help("Re-run when the file changes, keeping global state but updating functions"),
+        )
+        .arg(
+          Arg::new("no-cache")
+            .long("no-cache")
+            .action(clap::ArgAction::SetTrue)
+            .help("Always compile the file, rather than using cached bytecode"),
+        )
+        .arg(
+          Arg::new("strict-equality")
+            .long("strict-equality")
+            .value_parser(clap::value_parser!(bool))
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("true")
+            .help("Compare numbers exactly, without a tolerance"),
+        )
+        .arg(
+          Arg::new("checked-arithmetic")
+            .long("checked-arithmetic")
+            .action(clap::ArgAction::SetTrue)
+            .help("Error on division by zero, and results which are NaN or too large"),
+        )
+        .arg(
+          Arg::new("entry")
+            .long("entry")
+            .value_name("function")
+            .help("Call a global function after running the file, and print the result"),
+        )
+        .arg(
+          Arg::new("args")
+            .long("args")
+            .requires("entry")
+            .default_value("[]")
+            .help("The arguments for the entry function, as a JSON list"),
+        ),
+    )
+    .subcommand(
+      Command::new("init")
+        .about("Create a new project")
+        .arg(
+          Arg::new("directory")
+            .help("Where to create the project")
+            .default_value("."),
+        )
+        .arg(
+          Arg::new("lib")
+            .long("lib")
+            .action(clap::ArgAction::SetTrue)
+            .help("Create a library, rather than a program"),
+        ),
     )
     .subcommand(
       Command::new("lint")
         .about("Run linter on a bang file")
-        .arg(Arg::new("file").help("The file to lint").required(true)),
+        .arg(Arg::new("file").help("The file to lint").required(true))
+        .arg(watch_flag()),
     )
     .subcommand(
       Command::new("format")
@@ -37,14 +162,92 @@ fn main() {
             .long("dryrun")
             .action(clap::ArgAction::SetTrue)
             .help("Preview the results of the formatting"),
-        ),
+        )
+        .arg(
+          Arg::new("line-ending")
+            .long("line-ending")
+            .value_parser(["preserve", "lf", "crlf"])
+            .default_value("preserve")
+            .help("The line endings to use in the formatted file"),
+        )
+        .arg(
+          Arg::new("indent")
+            .long("indent")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("2")
+            .help("The number of spaces to indent with"),
+        )
+        .arg(
+          Arg::new("quote")
+            .long("quote")
+            .value_parser(["single", "double"])
+            .default_value("single")
+            .help("The preferred quote for strings"),
+        )
+        .arg(
+          Arg::new("line-width")
+            .long("line-width")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("100")
+            .help("The width after which lists are split onto multiple lines"),
+        )
+        .arg(
+          Arg::new("no-trailing-commas")
+            .long("no-trailing-commas")
+            .action(clap::ArgAction::SetTrue)
+            .help("Don't add a comma after the last item of multi-line lists"),
+        )
+        .arg(watch_flag()),
     )
     .subcommand(
       Command::new("typecheck")
         .about("Run typechecker on on a file")
         .arg(
           Arg::new("file")
-            .help("The file to typecheck")
+            .help("The files to typecheck")
+            .num_args(1..)
+            .required(true),
+        )
+        .arg(
+          Arg::new("max-errors")
+            .long("max-errors")
+            .value_name("count")
+            .value_parser(clap::value_parser!(usize))
+            .help("Stop after showing this many errors"),
+        )
+        .arg(watch_flag()),
+    )
+    .subcommand(
+      Command::new("cache")
+        .about("Manage the cache of compiled files")
+        .subcommand_required(true)
+        .subcommand(Command::new("clear").about("Remove all cached bytecode"))
+        .subcommand(Command::new("stats").about("Show where the cache is and how large it is")),
+    )
+    .subcommand(
+      Command::new("highlight")
+        .about("Generate syntax highlighting for an editor")
+        .arg(
+          Arg::new("textmate")
+            .long("textmate")
+            .action(clap::ArgAction::SetTrue)
+            .help("A TextMate grammar, as used by VSCode"),
+        )
+        .arg(
+          Arg::new("vim")
+            .long("vim")
+            .action(clap::ArgAction::SetTrue)
+            .help("A Vim syntax file"),
+        )
+        .arg(
+          Arg::new("tree-sitter-query")
+            .long("tree-sitter-query")
+            .action(clap::ArgAction::SetTrue)
+            .help("A tree-sitter highlights query"),
+        )
+        .group(
+          ArgGroup::new("editor")
+            .args(["textmate", "vim", "tree-sitter-query"])
             .required(true),
         ),
     )
@@ -55,60 +258,192 @@ fn main() {
         .subcommand(
           Command::new("ast")
             .about("Display the Abstract Syntax Tree for a file")
-            .arg(Arg::new("file").help("The file to parse").required(true)),
+            .arg(Arg::new("file").help("The file to parse").required(true))
+            .arg(
+              Arg::new("json")
+                .long("json")
+                .action(clap::ArgAction::SetTrue)
+                .help("Output the tree as JSON"),
+            ),
+        )
+        .subcommand(
+          Command::new("tokens")
+            .about("Display the tokens in a file")
+            .arg(Arg::new("file").help("The file to tokenize").required(true)),
         )
         .subcommand(
           Command::new("bytecode")
             .about("Display the Bytecode from a file")
             .arg(Arg::new("file").help("The file to compile").required(true)),
+        )
+        .subcommand(
+          Command::new("cfg")
+            .about("Display the control flow graph of a file's bytecode, in Graphviz DOT format")
+            .arg(Arg::new("file").help("The file to compile").required(true)),
+        )
+        .subcommand(
+          Command::new("metrics")
+            .about("Display the size and complexity of each function in a file")
+            .arg(Arg::new("file").help("The file to measure").required(true)),
+        )
+        .subcommand(
+          Command::new("purity")
+            .about("Display which functions in a file are free of side effects")
+            .arg(Arg::new("file").help("The file to analyse").required(true)),
         ),
-    )
-    .get_matches();
+    );
+  let arguments = arguments(&command);
+  let app = command.get_matches_from(arguments);
+
+  if let Some(color) = app.get_one::<String>("color") {
+    print::set_color(print::ColorChoice::from_name(color));
+  }
+
+  let mut workspace = bang::Workspace::new();
+  let result = match app.subcommand() {
+    Some(("run", args)) if args.get_flag("hot") => get_filename(args).and_then(watch::hot_reload),
+    Some((_, args)) if matches!(args.try_get_one::<bool>("watch"), Ok(Some(true))) => {
+      get_filename(args)
+        .and_then(|filename| watch::watch(filename, || run_command(&app, &mut workspace)))
+    }
+    _ => run_command(&app, &mut workspace),
+  };
 
-  if run_command(&app).is_err() {
+  if result.is_err() {
     std::process::exit(1)
   }
 }
 
-fn run_command(app: &clap::ArgMatches) -> Result<(), ()> {
+fn run_command(app: &clap::ArgMatches, workspace: &mut bang::Workspace) -> Result<(), ()> {
   match app.subcommand() {
     Some(("run", args)) => {
       let filename = get_filename(args)?;
+      let config = load_config(filename)?;
       let source = &read_file(filename)?;
-      let bytecode = &compile(filename, source)?;
+      let bytecode = &compile_cached(filename, source, !args.get_flag("no-cache"))?;
+
+      let arguments = args.get_one::<String>("args").map_or("[]", String::as_str);
+      let arguments = parse_arguments(arguments)?;
+
+      let context = bang::StdContext::default();
+      let mut vm = bang::VM::new(&context);
+      let strict_equality = cli_value::<bool>(args, "strict-equality").copied();
+      vm.set_strict_equality(strict_equality.or(config.strict_equality) == Some(true));
+      vm.set_checked_arithmetic(args.get_flag("checked-arithmetic"));
+      vm.set_count_instructions(args.get_flag("stats"));
 
-      run(filename, source, bytecode);
+      let finished = run(&mut vm, filename, source, bytecode).is_ok();
+      let result = match args.get_one::<String>("entry") {
+        Some(entry) if finished => call_entry(&mut vm, filename, source, entry, &arguments),
+        _ => Ok(()),
+      };
+
+      if args.get_flag("stats") {
+        print::stats(vm.global_count(), vm.memory_usage(), vm.instruction_count());
+      }
+      result?;
+    }
+    Some(("init", args)) => {
+      let directory = args
+        .get_one::<String>("directory")
+        .map_or(".", String::as_str);
+      init::init(Path::new(directory), args.get_flag("lib"))?;
     }
     Some(("lint", args)) => {
+      use bang::context::Context;
+
       let filename = get_filename(args)?;
-      let source = &read_file(filename)?;
-      let ast = parse(filename, source)?;
+      let config = load_config(filename)?;
+      workspace.set_file(filename, read_file(filename)?);
+      let context = bang::StdContext::default();
+      workspace.set_protected_globals(context.protected_globals());
+      workspace.set_modules(&context.modules());
+
+      let linted = workspace.lint(filename).ok_or(())?;
+      let source = linted.source;
+
+      for diagnostic in file_result(&linted)? {
+        if !config.is_rule_enabled(&diagnostic.title) {
+          continue;
+        }
 
-      for diagnostic in bang::lint(source, &ast) {
         print::warning_message(&diagnostic.title);
         eprintln!("{}\n", &diagnostic.message);
 
-        for line_number in diagnostic.lines {
-          print::code_frame(filename, source, line_number);
+        for (line_number, span) in diagnostic.lines.iter().zip(&diagnostic.spans) {
+          print::code_frame(filename, source, *line_number, Some(*span));
+        }
+        for fix in &diagnostic.fixes {
+          eprintln!("Suggested fix: {}\n", fix.replacement);
         }
       }
     }
     Some(("typecheck", args)) => {
-      let filename = get_filename(args)?;
-      let source = &read_file(filename)?;
-      let ast = parse(filename, source)?;
+      let filenames = args.get_many::<String>("file").into_iter().flatten();
+      let max_errors = args.get_one::<usize>("max-errors").copied();
+      let mut shown_errors = 0;
+      let mut total_errors = 0;
+      let mut summary = Vec::new();
 
-      for error in bang::typecheck(&ast) {
-        print::error_message(error.get_title());
-        eprintln!("{}\n", error.get_description());
-        print::code_frame(filename, source, error.span.get_line_number(source));
+      for filename in filenames {
+        let config = load_config(filename)?;
+        workspace.set_strict(config.strict == Some(true));
+        workspace.set_file(filename, read_file(filename)?);
+        let file = workspace.typecheck(filename).ok_or(())?;
+        let source = file.source;
+        let typechecked = file_result(&file)?;
+
+        let errors = &typechecked.errors;
+        let remaining = max_errors.map_or(errors.len(), |max| max - shown_errors);
+        let shown = remaining.min(errors.len());
+        shown_errors += shown;
+        total_errors += errors.len();
+
+        for error in &errors[..shown] {
+          print::error_message(error.get_title());
+          eprintln!("{}\n", error.get_description());
+          let line_number = error.span.get_line_number(source);
+          print::code_frame(filename, source, line_number, Some(error.span));
+        }
+
+        summary.push((filename.as_str(), typechecked.strictness));
+      }
+
+      if shown_errors < total_errors {
+        print::error_message(&format!(
+          "Showing {shown_errors} of {total_errors} errors, stopped after --max-errors"
+        ));
       }
+      print::typecheck_summary(&summary);
     }
     Some(("format", args)) => {
       let filename = get_filename(args)?;
-      let source = &read_file(filename)?;
-      let ast = parse(filename, source)?;
-      let formatted_source = &bang::format(source, &ast);
+      let config = load_config(filename)?;
+      workspace.set_file(filename, read_file(filename)?);
+
+      let mut options = config.format_options();
+      match cli_value::<String>(args, "line-ending").map(String::as_str) {
+        Some("lf") => options.line_ending = bang::LineEnding::Lf,
+        Some("crlf") => options.line_ending = bang::LineEnding::Crlf,
+        Some(_) => options.line_ending = bang::LineEnding::Preserve,
+        None => {}
+      }
+      match cli_value::<String>(args, "quote").map(String::as_str) {
+        Some("double") => options.quote = bang::QuoteStyle::Double,
+        Some(_) => options.quote = bang::QuoteStyle::Single,
+        None => {}
+      }
+      if let Some(indent) = cli_value::<usize>(args, "indent") {
+        options.indent = *indent;
+      }
+      if let Some(line_width) = cli_value::<usize>(args, "line-width") {
+        options.line_width = *line_width;
+      }
+      if args.get_flag("no-trailing-commas") {
+        options.trailing_commas = false;
+      }
+      let formatted = workspace.format_file(filename, options).ok_or(())?;
+      let (source, formatted_source) = (formatted.source, file_result(&formatted)?);
 
       if args.get_flag("dryrun") {
         return Ok(println!("{formatted_source}"));
@@ -118,13 +453,37 @@ fn run_command(app: &clap::ArgMatches) -> Result<(), ()> {
         print::error_message("Problem writing to file");
       }
     }
+    Some(("cache", args)) => match args.subcommand() {
+      Some(("clear", _)) => cache::clear()?,
+      Some(("stats", _)) => cache::stats(),
+      _ => unreachable!(),
+    },
+    Some(("highlight", args)) => {
+      if args.get_flag("textmate") {
+        print!("{}", bang::textmate_grammar());
+      } else if args.get_flag("vim") {
+        print!("{}", bang::vim_syntax());
+      } else {
+        print!("{}", bang::tree_sitter_highlights());
+      }
+    }
     Some(("print", args)) => match args.subcommand() {
       Some(("ast", args)) => {
         let filename = get_filename(args)?;
         let source = &read_file(filename)?;
         let ast = &parse(filename, source)?;
 
-        print::ast(source, ast);
+        if args.get_flag("json") {
+          println!("{}", bang::ast::to_json(ast));
+        } else {
+          print::ast(ast);
+        }
+      }
+      Some(("tokens", args)) => {
+        let filename = get_filename(args)?;
+        let source = &read_file(filename)?;
+
+        print::tokens(source);
       }
       Some(("bytecode", args)) => {
         let filename = get_filename(args)?;
@@ -133,15 +492,58 @@ fn run_command(app: &clap::ArgMatches) -> Result<(), ()> {
 
         println!("{bytecode:?}");
       }
+      Some(("cfg", args)) => {
+        let filename = get_filename(args)?;
+        let source = &read_file(filename)?;
+        let bytecode = &compile(filename, source)?;
+
+        print!("{}", bytecode.control_flow_graph_dot());
+      }
+      Some(("metrics", args)) => {
+        let filename = get_filename(args)?;
+        let source = &read_file(filename)?;
+        let ast = &parse(filename, source)?;
+        let bytecode = &compile(filename, source)?;
+
+        print::metrics(source, ast, bytecode);
+      }
+      Some(("purity", args)) => {
+        let filename = get_filename(args)?;
+        let source = &read_file(filename)?;
+        let ast = &parse(filename, source)?;
+
+        print::purity(source, ast);
+      }
       _ => unreachable!(),
     },
-    _ => repl(),
+    Some(("repl", args)) => repl(args.get_one::<String>("load")),
+    _ => repl(None),
   };
 
   Ok(())
 }
 
-fn repl() {
+// Make existing globals importable as a module, e.g. `:register utils add subtract`
+fn register_module(vm: &mut bang::VM, arguments: &str) {
+  let mut arguments = arguments.split_whitespace();
+  let module = match arguments.next() {
+    Some(module) => module,
+    None => return print::error_message("Expected a module name"),
+  };
+
+  let mut items = Vec::new();
+  for name in arguments {
+    match vm.get_global(name) {
+      Some(value) => items.push((name, value)),
+      None => return print::error_message(&format!("Variable '{name}' is not defined")),
+    }
+  }
+
+  vm.register_native_module(module, &items);
+}
+
+fn repl(load: Option<&String>) {
+  use bang::ast::statement::Stmt;
   use rustyline::error::ReadlineError;
 
   println!("Bang! ({VERSION})");
@@ -149,6 +551,15 @@ fn repl() {
 
   let context = bang::StdContext::default();
   let mut vm = bang::VM::new(&context);
+  vm.set_count_instructions(true);
+
+  if let Some(filename) = load {
+    // Errors are reported, but the prompt still opens with whatever was defined
+    let _ = read_file(filename)
+      .and_then(|source| Ok((compile(filename, &source)?, source)))
+      .and_then(|(chunk, source)| run(&mut vm, filename, &source, &chunk));
+  }
+  let initial_globals = vm.snapshot();
 
   loop {
     let readline = rl.readline("> ");
@@ -156,23 +567,48 @@ fn repl() {
       Ok(line) => {
         rl.add_history_entry(&line);
 
-        let source = if line.starts_with("from")
-          || line.starts_with("let")
-          || line.starts_with("if")
-          || line.starts_with("while")
-        {
-          line
-        } else if line.is_empty() {
+        if let Some(arguments) = line.strip_prefix(":register") {
+          register_module(&mut vm, arguments);
           continue;
-        } else {
-          format!("print({line})\n")
+        }
+
+        // Rolls back variables to how they were when the prompt opened
+        if line.trim() == ":reset soft" {
+          vm.restore(&initial_globals);
+          continue;
+        }
+
+        // `:time` shows how long a line took, which is always shown for slow lines
+        let (line, show_time) = match line.strip_prefix(":time") {
+          Some(rest) => (rest, true),
+          None => (line.as_str(), false),
+        };
+
+        if line.trim().is_empty() {
+          continue;
+        }
+
+        // The value of an expression is printed, other statements are just run
+        let is_expression = match parse("REPL", line) {
+          Ok(ast) => ast
+            .last()
+            .is_some_and(|statement| matches!(statement.stmt, Stmt::Expression { .. })),
+          Err(()) => continue,
         };
 
-        if let Ok(chunk) = compile("REPL", &source) {
-          match vm.run(&chunk) {
-            Ok(()) => {}
-            Err(error) => print::stack_trace("REPL", &source, error),
+        if let Ok(chunk) = compile_keeping_last_value("REPL", line) {
+          let (start, instructions) = (Instant::now(), vm.instruction_count());
+          let result = vm.evaluate(&chunk);
+          let elapsed = start.elapsed();
+
+          match result {
+            Ok(value) if is_expression => println!("{value}"),
+            Ok(_) => {}
+            Err(error) => print::stack_trace("REPL", line, error),
           };
+          if show_time || elapsed >= SLOW_EVALUATION {
+            print::timing(elapsed, vm.instruction_count() - instructions);
+          }
         }
       }
       Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
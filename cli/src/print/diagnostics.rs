@@ -1,43 +1,159 @@
 use bang_interpreter::errors;
-use bang_syntax::LineNumber;
+use bang_syntax::{LineNumber, Span};
+use bang_tools::TypecheckStrictness;
+use std::{
+  env,
+  io::{self, IsTerminal},
+  sync::atomic::{AtomicBool, Ordering},
+  time::Duration,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+  Auto,
+  Always,
+  Never,
+}
+impl ColorChoice {
+  pub fn from_name(name: &str) -> Self {
+    match name {
+      "always" => Self::Always,
+      "never" => Self::Never,
+      _ => Self::Auto,
+    }
+  }
+
+  // Automatically colour when writing to a terminal, unless `NO_COLOR` is set
+  fn is_enabled(self) -> bool {
+    match self {
+      Self::Always => true,
+      Self::Never => false,
+      Self::Auto => {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        !no_color && io::stderr().is_terminal()
+      }
+    }
+  }
+}
+
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+pub fn set_color(choice: ColorChoice) {
+  COLOR.store(choice.is_enabled(), Ordering::Relaxed);
+}
+
+fn paint(code: &str, text: &str) -> String {
+  if COLOR.load(Ordering::Relaxed) {
+    format!("\u{001b}[{code}m{text}\u{001b}[0m")
+  } else {
+    text.to_string()
+  }
+}
 
 fn red(text: &str) -> String {
-  format!("\u{001b}[31m{text}\u{001b}[0m")
+  paint("31", text)
 }
 
 fn yellow(text: &str) -> String {
-  format!("\u{001b}[33m{text}\u{001b}[0m")
+  paint("33", text)
 }
 
 fn bold(text: &str) -> String {
-  format!("\u{001b}[1m{text}\u{001b}[0m")
+  paint("1", text)
 }
 
-pub fn code_frame(file: &str, source: &str, line_number: LineNumber) {
+// Spans covering more lines than this only show their first and last few lines
+const MAX_SPAN_LINES: LineNumber = 6;
+
+pub fn code_frame(file: &str, source: &str, line_number: LineNumber, span: Option<Span>) {
+  let (first, last) = span.map_or((line_number, line_number), |span| span_lines(source, span));
+
   eprintln!("    ╭─[{file}]");
-  if line_number > 2 {
+  if first > 2 {
     eprintln!("    ·");
   } else {
     eprintln!("    │");
   }
 
-  let start = if line_number <= 2 { 0 } else { line_number - 2 };
-  for i in start..=line_number {
-    if let Some(line) = source.lines().nth(i as usize) {
-      eprintln!("{:>3} │ {line}", i + 1);
+  let lines: Vec<_> = source.lines().collect();
+  let start = if first <= 2 { 1 } else { first - 1 };
+  for i in start..=last.saturating_add(1) {
+    let line = match lines.get(usize::from(i) - 1) {
+      Some(line) => line,
+      None => break,
+    };
+
+    if first == last {
+      eprintln!("{i:>3} │ {line}");
+      if let Some(span) = span.filter(|_| i == line_number) {
+        eprintln!("    │ {}", underline(source, line, line_number, span));
+      }
+      continue;
+    }
+
+    let is_hidden = |i| last - first >= MAX_SPAN_LINES && i > first + 1 && i < last - 1;
+    if is_hidden(i) {
+      if !is_hidden(i - 1) {
+        eprintln!("    · {}", red("┆"));
+      }
+      continue;
     }
+
+    let gutter = match i {
+      i if i == first => red("╭"),
+      i if i == last => red("╰"),
+      i if i > first && i < last => red("│"),
+      _ => " ".to_string(),
+    };
+    eprintln!("{i:>3} │ {gutter} {line}");
   }
-  if (line_number as usize) < (source.lines().count() - 1) {
+
+  if (last as usize) < (source.lines().count() - 1) {
     eprintln!("    ·");
   }
   eprintln!("────╯");
 }
 
+fn span_lines(source: &str, span: Span) -> (LineNumber, LineNumber) {
+  // The last character is used, so a span ending with a newline doesn't include the next line
+  let last_character = Span {
+    start: span.end.saturating_sub(1).max(span.start),
+    end: span.end,
+  };
+
+  (
+    span.get_line_number(source),
+    last_character.get_line_number(source),
+  )
+}
+
+fn underline(source: &str, line: &str, line_number: LineNumber, span: Span) -> String {
+  let start = span.get_column_number(source) as usize;
+  let end = if span.get_line_number_end(source) == line_number {
+    let end = Span {
+      start: span.end,
+      end: span.end,
+    };
+    end.get_column_number(source) as usize
+  } else {
+    line.chars().count() + 1
+  };
+
+  let padding: String = line
+    .chars()
+    .take(start - 1)
+    .map(|c| if c == '\t' { '\t' } else { ' ' })
+    .collect();
+
+  let carets = "^".repeat(end.saturating_sub(start).max(1));
+  format!("{padding}{}", red(&carets))
+}
+
 pub fn stack_trace(filename: &str, source: &str, error: errors::Runtime) {
   error_message(&error.message);
 
   if error.stack[0].line != u16::MAX {
-    code_frame(filename, source, error.stack[0].line);
+    code_frame(filename, source, error.stack[0].line, None);
   }
 
   for location in error.stack {
@@ -65,3 +181,31 @@ pub fn error_message(message: &str) {
 pub fn warning_message(message: &str) {
   eprintln!("{} {}", bold(&yellow("Warning:")), bold(message),);
 }
+
+pub fn watch_message(message: &str) {
+  eprintln!("\n{} {}", bold("Watch:"), message);
+}
+
+pub fn stats(globals: usize, memory: usize, instructions: u64) {
+  eprintln!("\n{}", bold("Stats:"));
+  eprintln!("  Globals: {globals}");
+  eprintln!("  Memory Used: {memory} bytes");
+  eprintln!("  Instructions: {instructions}");
+}
+
+pub fn timing(elapsed: Duration, instructions: u64) {
+  eprintln!(
+    "{} {elapsed:.2?}, {instructions} instructions",
+    bold("Took")
+  );
+}
+
+pub fn typecheck_summary(files: &[(&str, TypecheckStrictness)]) {
+  eprintln!("\n{}", bold("Typechecked:"));
+  for (filename, strictness) in files {
+    match strictness {
+      TypecheckStrictness::NoCheck => eprintln!("  {filename} (skipped, nocheck)"),
+      strictness => eprintln!("  {filename} ({strictness})"),
+    }
+  }
+}
@@ -0,0 +1,43 @@
+use bang_interpreter::{chunk::Metrics, Chunk};
+use bang_syntax::ast::statement::Statement;
+use bang_tools::Complexity;
+
+pub fn print(source: &str, ast: &[Statement], chunk: &Chunk) {
+  let mut complexity = bang_tools::complexity(source, ast);
+
+  println!("  ╭─[Metrics]");
+  println!(
+    "  │ Line │ Function             │ Bytes │ Stack │ Locals │ Constants │ Branches │ Nesting"
+  );
+  for metrics in chunk.metrics() {
+    let Metrics {
+      name,
+      line,
+      bytecode_size,
+      max_stack_depth,
+      locals,
+      constants,
+    } = metrics;
+
+    // Compiling can add or remove functions, so they are matched by where they start
+    let matching = complexity
+      .iter()
+      .position(|complexity| complexity.line == line && complexity.name == name.as_str());
+    let (branches, nesting) = match matching.map(|index| complexity.remove(index)) {
+      Some(Complexity {
+        branches, nesting, ..
+      }) => (branches.to_string(), nesting.to_string()),
+      None => ("-".to_string(), "-".to_string()),
+    };
+    let name = if name.is_empty() {
+      "<anonymous>"
+    } else {
+      &name
+    };
+
+    println!(
+      "  │ {line:>4} │ {name:<20} │ {bytecode_size:>5} │ {max_stack_depth:>5} │ {locals:>6} │ {constants:>9} │ {branches:>8} │ {nesting:>7}"
+    );
+  }
+  println!("──╯");
+}
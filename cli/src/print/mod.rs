@@ -1,9 +1,14 @@
 mod ast;
 mod diagnostics;
-
-fn remove_carriage_returns(value: &str) -> String {
-  str::replace(value, "\r", "")
-}
+mod metrics;
+mod purity;
+mod tokens;
 
 pub use ast::print as ast;
-pub use diagnostics::{code_frame, error_message, stack_trace, warning_message};
+pub use diagnostics::{
+  code_frame, error_message, set_color, stack_trace, stats, timing, typecheck_summary,
+  warning_message, watch_message, ColorChoice,
+};
+pub use metrics::print as metrics;
+pub use purity::print as purity;
+pub use tokens::print as tokens;
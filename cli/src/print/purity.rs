@@ -0,0 +1,20 @@
+use bang_syntax::ast::statement::Statement;
+use bang_tools::Purity;
+
+pub fn print(source: &str, ast: &[Statement]) {
+  let purity = Purity::analyse(source, ast);
+
+  println!("  ╭─[Purity]");
+  println!("  │ Line │ Function             │ Pure");
+  for function in purity.functions {
+    let name = if function.name.is_empty() {
+      "<anonymous>"
+    } else {
+      &function.name
+    };
+    let pure = if function.pure { "yes" } else { "no" };
+
+    println!("  │ {:>4} │ {name:<20} │ {pure}", function.line);
+  }
+  println!("──╯");
+}
@@ -0,0 +1,21 @@
+use bang_syntax::Tokeniser;
+
+pub fn print(source: &str) {
+  let bytes = source.as_bytes();
+
+  println!("  ╭─[Tokens]");
+  println!("  │ Line │ Span        │ Type                 │ Value");
+  for token in Tokeniser::new(source) {
+    let span = format!("{}..{}", token.start, token.end);
+    let ttype = format!("{:?}", token.ttype);
+    let value = token.get_value(bytes);
+    let value = if value.trim().is_empty() || value.contains(char::is_control) {
+      format!("{value:?}")
+    } else {
+      value.to_string()
+    };
+
+    println!("  │ {:>4} │ {span:<11} │ {ttype:<20} │ {value}", token.line);
+  }
+  println!("──╯");
+}
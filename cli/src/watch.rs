@@ -0,0 +1,108 @@
+use super::{
+  bang,
+  helpers::{compile, read_file},
+  print,
+};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+  path::{Path, PathBuf},
+  sync::mpsc,
+  time::{Duration, Instant},
+};
+
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+fn clear_screen() {
+  print!("\u{001b}[2J\u{001b}[1;1H");
+}
+
+fn is_relevant(event: &notify::Result<Event>, files: &[PathBuf]) -> bool {
+  match event {
+    Ok(event) => {
+      matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+      ) && event.paths.iter().any(|path| {
+        let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        files.contains(&path)
+      })
+    }
+    Err(_) => false,
+  }
+}
+
+fn watched_files(filename: &str) -> Result<Vec<PathBuf>, ()> {
+  match Path::new(filename).canonicalize() {
+    Ok(path) => Ok(vec![path]),
+    Err(_) => {
+      print::error_message("Problem reading file");
+      Err(())
+    }
+  }
+}
+
+pub fn watch(filename: &str, mut command: impl FnMut() -> Result<(), ()>) -> Result<(), ()> {
+  let files = watched_files(filename)?;
+
+  let (sender, receiver) = mpsc::channel();
+  let mut watcher = match notify::recommended_watcher(sender) {
+    Ok(watcher) => watcher,
+    Err(_) => {
+      print::error_message("Problem starting file watcher");
+      return Err(());
+    }
+  };
+
+  // Watch the containing directories, as editors often replace files when saving
+  for directory in files.iter().filter_map(|file| file.parent()) {
+    if watcher
+      .watch(directory, RecursiveMode::NonRecursive)
+      .is_err()
+    {
+      print::error_message("Problem watching file");
+      return Err(());
+    }
+  }
+
+  loop {
+    clear_screen();
+    let start = Instant::now();
+    let _ = command();
+    print::watch_message(&format!(
+      "Finished in {:.2?}, watching for changes...",
+      start.elapsed()
+    ));
+
+    loop {
+      match receiver.recv() {
+        Ok(event) if is_relevant(&event, &files) => break,
+        Ok(_) => continue,
+        Err(_) => return Err(()),
+      }
+    }
+    while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+  }
+}
+
+pub fn hot_reload(filename: &str) -> Result<(), ()> {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  let mut first_run = true;
+
+  watch(filename, || {
+    let source = &read_file(filename)?;
+    let chunk = &compile(filename, source)?;
+
+    match vm.hot_reload(chunk) {
+      Ok(updated) => {
+        if !first_run && !updated.is_empty() {
+          print::watch_message(&format!("Reloaded {}", updated.join(", ")));
+        }
+      }
+      Err(error) => print::stack_trace(filename, source, error),
+    }
+
+    first_run = false;
+    Ok(())
+  })
+}
@@ -75,6 +75,9 @@ macro_rules! benchmark_from_file {
 
 benchmark_from_file!(example, arithmetic_bench, "arithmeticBench.bang");
 benchmark_from_file!(example, bubble_sort, "bubbleSort.bang");
+benchmark_from_file!(example, call_bench, "callBench.bang");
+benchmark_from_file!(example, collection_literals, "collectionBench.bang");
 benchmark_from_file!(example, iterative_fibonacci, "iterativeFibonacci.bang");
 benchmark_from_file!(example, recursive_fibonacci, "recursiveFibonacci.bang");
+benchmark_from_file!(example, string_building, "stringBench.bang");
 benchmark_from_file!(example, syntax, "syntax.bang");
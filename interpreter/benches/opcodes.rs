@@ -0,0 +1,46 @@
+#![feature(test)]
+extern crate test;
+use test::{black_box, Bencher};
+
+pub mod bang {
+  pub use bang_interpreter::*;
+  pub use bang_std::*;
+}
+
+// Each benchmark loops over a small amount of code, so time is spent on a few opcodes
+macro_rules! micro_benchmark {
+  ($name:ident, $source:literal) => {
+    #[bench]
+    fn $name(b: &mut Bencher) {
+      let source = concat!(
+        "let total = 0\nlet i = 0\nwhile (i < 1000)\n",
+        $source,
+        "\n  i += 1\n"
+      );
+      let chunk = bang::compile(source).unwrap();
+      let context = bang::StdContext::default();
+
+      b.iter(|| bang::VM::new(&context).run(black_box(&chunk)).unwrap());
+    }
+  };
+}
+
+micro_benchmark!(empty_loop, "  null");
+micro_benchmark!(arithmetic, "  let a = i * 2 + i / 4 - i % 3");
+micro_benchmark!(comparison, "  let a = i < 500 and i != 7 or i >= 900");
+micro_benchmark!(global_variables, "  total = total + i");
+micro_benchmark!(local_variables, "  let a = i\n  let b = a + a\n  a = b");
+micro_benchmark!(
+  function_call,
+  "  let add = (a, b) => a + b\n  add(i, 1)\n  add(i, 2)"
+);
+micro_benchmark!(native_call, "  type(i)");
+micro_benchmark!(closure_capture, "  let get = () => i\n  get()");
+micro_benchmark!(list_construction, "  let a = [i, i, i, i]");
+micro_benchmark!(
+  dict_construction,
+  "  let a = { 'a': i, 'b': i, 'c': i, 'd': i }"
+);
+micro_benchmark!(list_index, "  let a = [i, i, i]\n  a[1] = a[0] + a[2]");
+micro_benchmark!(string_concatenation, "  let a = 'a' + toString(i) + 'b'");
+micro_benchmark!(format_string, "  let a = `${i}-${i}`");
@@ -0,0 +1,21 @@
+use std::{env, fs, path::Path};
+
+// The files which decide what bytecode is produced and how it is stored. Cached chunks from a
+// build where any of them were different are compiled again
+const BYTECODE_FILES: [&str; 3] = ["src/chunk.rs", "src/chunk/serialize.rs", "src/compiler.rs"];
+
+fn main() {
+  let directory = env::var("CARGO_MANIFEST_DIR").unwrap();
+  let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+  for file in BYTECODE_FILES {
+    println!("cargo:rerun-if-changed={file}");
+
+    let source = fs::read(Path::new(&directory).join(file)).unwrap();
+    for byte in source {
+      hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+    }
+  }
+
+  println!("cargo:rustc-env=BANG_BYTECODE_HASH={hash:016x}");
+}
@@ -1,9 +1,18 @@
+mod cfg;
 mod display;
+mod metadata;
+mod metrics;
+mod serialize;
 mod verifier;
 
-use crate::value::Value;
+use crate::value::{Object, Value};
+use alloc::{rc::Rc, vec::Vec};
 use bang_syntax::LineNumber;
-use std::{mem, rc::Rc};
+use core::mem;
+
+pub use cfg::{BasicBlock, Edge};
+pub use metadata::{hash_source, Metadata, Stale, COMPILER_VERSION};
+pub use metrics::Metrics;
 
 #[non_exhaustive]
 #[repr(u8)]
@@ -27,10 +36,14 @@ pub enum OpCode {
   NotEqual,
   GreaterEqual,
   LessEqual,
+  Contains,
   Pop,
   DefineGlobal,
+  DefineGlobalLong,
   GetGlobal,
+  GetGlobalLong,
   SetGlobal,
+  SetGlobalLong,
   Jump,
   JumpIfFalse,
   JumpIfNull,
@@ -51,7 +64,17 @@ pub enum OpCode {
   SetUpvalue,
   GetAllocated,
   SetAllocated,
+  Allocate,
   Import,
+  ImportOptional,
+  Next,
+  Spawn,
+  Join,
+  AssertNonNull,
+  AssertNonNullLong,
+  OmitKeys,
+  GetGlobalOptional,
+  GetGlobalOptionalLong,
   Unknown,
 }
 impl OpCode {
@@ -73,13 +96,17 @@ impl OpCode {
       | Self::NotEqual
       | Self::GreaterEqual
       | Self::LessEqual
+      | Self::Contains
       | Self::Pop
       | Self::Return
       | Self::GetIndex
       | Self::SetIndex
       | Self::ToString
       | Self::Closure
-      | Self::Import => Some(1),
+      | Self::Import
+      | Self::ImportOptional
+      | Self::Spawn
+      | Self::Join => Some(1),
       Self::Constant
       | Self::DefineGlobal
       | Self::GetGlobal
@@ -93,13 +120,23 @@ impl OpCode {
       | Self::GetUpvalue
       | Self::SetUpvalue
       | Self::GetAllocated
-      | Self::SetAllocated => Some(2),
+      | Self::SetAllocated
+      | Self::Allocate
+      | Self::AssertNonNull
+      | Self::OmitKeys
+      | Self::GetGlobalOptional => Some(2),
       Self::Jump
       | Self::JumpIfFalse
       | Self::JumpIfNull
       | Self::Loop
+      | Self::Next
       | Self::ListLong
-      | Self::ConstantLong => Some(3),
+      | Self::ConstantLong
+      | Self::DefineGlobalLong
+      | Self::GetGlobalLong
+      | Self::SetGlobalLong
+      | Self::AssertNonNullLong
+      | Self::GetGlobalOptionalLong => Some(3),
       _ => None,
     }
   }
@@ -168,6 +205,7 @@ pub struct Chunk {
   pub(crate) constants: Vec<Value>,
   pub(crate) strings: Vec<Rc<str>>,
   lines: LineInfo,
+  pub(crate) metadata: Metadata,
 }
 impl Chunk {
   pub fn new() -> Self {
@@ -176,6 +214,7 @@ impl Chunk {
       constants: Vec::new(),
       strings: Vec::new(),
       lines: LineInfo::new(),
+      metadata: Metadata::default(),
     }
   }
 
@@ -262,9 +301,41 @@ impl Chunk {
     unsafe { self.strings.get_unchecked(pointer) }.clone()
   }
 
+  // String operands are a byte, or two bytes for the long variants of the instructions
+  // Gives the location of the string, and the length of the instruction
+  #[inline]
+  pub(crate) fn get_string_operand(&self, position: usize) -> (usize, usize) {
+    match self.get(position) {
+      OpCode::DefineGlobalLong
+      | OpCode::GetGlobalLong
+      | OpCode::SetGlobalLong
+      | OpCode::AssertNonNullLong
+      | OpCode::GetGlobalOptionalLong => (self.get_long_value(position + 1).into(), 3),
+      _ => (self.get_value(position + 1).into(), 2),
+    }
+  }
+
   pub fn get_line_number(&self, opcode_position: usize) -> LineNumber {
     self.lines.get(opcode_position)
   }
+
+  // Compares the bytecode, ignoring line numbers so moved code is still the same
+  pub(crate) fn has_same_code(&self, other: &Self) -> bool {
+    self.code == other.code
+      && self.strings == other.strings
+      && self.constants.len() == other.constants.len()
+      && self.constants.iter().zip(&other.constants).all(|(a, b)| {
+        if a.is_object()
+          && b.is_object()
+          && let Object::Function(a) = a.as_object()
+          && let Object::Function(b) = b.as_object()
+        {
+          a.name == b.name && a.arity == b.arity && a.chunk.has_same_code(&b.chunk)
+        } else {
+          a == b
+        }
+      })
+  }
 }
 
 #[cfg(test)]
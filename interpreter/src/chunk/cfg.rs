@@ -0,0 +1,217 @@
+use super::{display::Instruction, Chunk, OpCode};
+use crate::value::Object;
+use alloc::{
+  rc::Rc,
+  string::{String, ToString},
+  vec,
+  vec::Vec,
+};
+use core::fmt::Write;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+  pub start: usize,
+  pub end: usize,
+  pub successors: Vec<Edge>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+  pub target: usize,
+  pub label: Option<&'static str>,
+}
+
+impl Chunk {
+  pub fn control_flow_graph(&self) -> Vec<BasicBlock> {
+    let mut leaders = vec![false; self.code.len() + 1];
+    let mut ip = 0;
+
+    while ip < self.code.len() {
+      let next = ip + self.get(ip).number_of_bytes().unwrap_or(1);
+
+      if let Some(target) = self.jump_target(ip) {
+        leaders[target.min(self.code.len())] = true;
+      }
+      if self.ends_block(ip) {
+        leaders[next.min(self.code.len())] = true;
+      }
+
+      ip = next;
+    }
+
+    let starts: Vec<_> = (0..self.code.len())
+      .filter(|position| *position == 0 || leaders[*position])
+      .collect();
+    let block_index = |position: usize| starts.iter().position(|start| *start == position);
+
+    let mut blocks = Vec::new();
+    for (index, start) in starts.iter().enumerate() {
+      let end = starts.get(index + 1).copied().unwrap_or(self.code.len());
+
+      let mut last = *start;
+      let mut ip = *start;
+      while ip < end {
+        last = ip;
+        ip += self.get(ip).number_of_bytes().unwrap_or(1);
+      }
+
+      let edge = |target: Option<usize>, label| {
+        target
+          .and_then(block_index)
+          .map(|target| Edge { target, label })
+      };
+      let fallthrough = edge(Some(end), None);
+      let jump = edge(self.jump_target(last), None);
+
+      let successors = match self.get(last) {
+        OpCode::Return | OpCode::Unknown => vec![],
+        OpCode::Jump | OpCode::Loop => vec![jump],
+        OpCode::JumpIfFalse => vec![fallthrough, edge(self.jump_target(last), Some("false"))],
+        OpCode::JumpIfNull => vec![fallthrough, edge(self.jump_target(last), Some("null"))],
+        OpCode::Next => vec![fallthrough, edge(self.jump_target(last), Some("done"))],
+        _ => vec![fallthrough],
+      };
+
+      blocks.push(BasicBlock {
+        start: *start,
+        end,
+        successors: successors.into_iter().flatten().collect(),
+      });
+    }
+
+    blocks
+  }
+
+  fn jump_target(&self, ip: usize) -> Option<usize> {
+    match self.get(ip) {
+      OpCode::Jump | OpCode::JumpIfFalse | OpCode::JumpIfNull | OpCode::Next => {
+        Some(ip + 1 + usize::from(self.get_long_value(ip + 1)))
+      }
+      OpCode::Loop => (ip + 1).checked_sub(self.get_long_value(ip + 1).into()),
+      _ => None,
+    }
+  }
+
+  fn ends_block(&self, ip: usize) -> bool {
+    matches!(
+      self.get(ip),
+      OpCode::Jump
+        | OpCode::JumpIfFalse
+        | OpCode::JumpIfNull
+        | OpCode::Loop
+        | OpCode::Next
+        | OpCode::Return
+        | OpCode::Unknown
+    )
+  }
+
+  // Graphviz DOT, with a cluster for each function
+  pub fn control_flow_graph_dot(&self) -> String {
+    let mut dot = "digraph {\n  node [shape=box fontname=monospace]\n".to_string();
+    let mut functions: Vec<(String, Rc<Self>)> = vec![("Root".into(), self.clone().into())];
+    let mut id = 0;
+
+    while let Some((name, chunk)) = functions.pop() {
+      write_cluster(&mut dot, id, &name, &chunk);
+      id += 1;
+
+      for constant in chunk.constants.iter().rev() {
+        if constant.is_object()
+          && let Object::Function(func) = constant.as_object()
+        {
+          functions.push((func.name.to_string(), func.chunk.clone()));
+        }
+      }
+    }
+
+    dot.push_str("}\n");
+    dot
+  }
+}
+
+fn write_cluster(dot: &mut String, id: usize, name: &str, chunk: &Chunk) {
+  let blocks = chunk.control_flow_graph();
+
+  writeln!(dot, "  subgraph cluster_{id} {{").unwrap();
+  writeln!(dot, "    label=\"{}\"", escape(name)).unwrap();
+
+  for (index, block) in blocks.iter().enumerate() {
+    let mut label = String::new();
+    let mut ip = block.start;
+    while ip < block.end {
+      let instruction = Instruction {
+        chunk,
+        position: ip,
+      }
+      .to_string();
+      write!(label, "{ip:0>4} {}\\l", escape(&instruction)).unwrap();
+      ip += chunk.get(ip).number_of_bytes().unwrap_or(1);
+    }
+
+    writeln!(dot, "    f{id}b{index} [label=\"{label}\"]").unwrap();
+  }
+
+  for (index, block) in blocks.iter().enumerate() {
+    for edge in &block.successors {
+      write!(dot, "    f{id}b{index} -> f{id}b{}", edge.target).unwrap();
+      match edge.label {
+        Some(label) => writeln!(dot, " [label=\"{label}\"]").unwrap(),
+        None => writeln!(dot).unwrap(),
+      }
+    }
+  }
+
+  writeln!(dot, "  }}").unwrap();
+}
+
+fn escape(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+  use super::Edge;
+  use crate::compile;
+
+  #[test]
+  fn splits_blocks_at_jumps() {
+    let source = "
+let a = 1
+if (a > 0) a = 2
+else a = 3
+while (a < 5) a += 1
+";
+    let chunk = compile(source).unwrap();
+    let blocks = chunk.control_flow_graph();
+
+    let edge = |target, label| Edge { target, label };
+    let successors: Vec<_> = blocks
+      .iter()
+      .map(|block| block.successors.clone())
+      .collect();
+    assert_eq!(
+      successors,
+      vec![
+        vec![edge(1, None), edge(2, Some("false"))],
+        vec![edge(3, None)],
+        vec![edge(3, None)],
+        vec![edge(4, None), edge(5, Some("false"))],
+        vec![edge(3, None)],
+        vec![],
+      ]
+    );
+    assert_eq!(blocks[0].start, 0);
+    assert_eq!(blocks[5].end, chunk.length());
+  }
+
+  #[test]
+  fn dot_has_cluster_per_function() {
+    let chunk = compile("let add = (a, b) => a + b\n").unwrap();
+    let dot = chunk.control_flow_graph_dot();
+
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("label=\"Root\""));
+    assert!(dot.contains("label=\"add\""));
+    assert!(dot.contains("Define Global \\\"add\\\""));
+  }
+}
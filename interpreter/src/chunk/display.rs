@@ -3,7 +3,8 @@ use crate::{
   collections::String,
   value::Object,
 };
-use std::{fmt, rc::Rc};
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::fmt;
 
 impl fmt::Debug for Chunk {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -25,6 +26,17 @@ impl fmt::Debug for Chunk {
   }
 }
 
+// A single instruction, formatted the same as in the full disassembly
+pub(super) struct Instruction<'a> {
+  pub chunk: &'a Chunk,
+  pub position: usize,
+}
+impl fmt::Display for Instruction<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    disassemble_instruction(f, self.chunk, self.position)
+  }
+}
+
 fn print_chunk(f: &mut fmt::Formatter<'_>, name: &String, chunk: &Chunk) -> fmt::Result {
   write!(f, "          ╭─[Function: {name}]")?;
 
@@ -69,11 +81,15 @@ fn disassemble_instruction(f: &mut fmt::Formatter<'_>, chunk: &Chunk, pos: usize
     OpCode::NotEqual => write!(f, "Not Equal"),
     OpCode::GreaterEqual => write!(f, "Greater Equal"),
     OpCode::LessEqual => write!(f, "Less Equal"),
+    OpCode::Contains => write!(f, "Contains"),
     OpCode::Pop => write!(f, "Pop"),
     OpCode::Return => write!(f, "Return"),
     OpCode::DefineGlobal => string_instruction(f, "Define Global", chunk, pos),
+    OpCode::DefineGlobalLong => string_instruction(f, "Define Global Long", chunk, pos),
     OpCode::GetGlobal => string_instruction(f, "Get Global", chunk, pos),
+    OpCode::GetGlobalLong => string_instruction(f, "Get Global Long", chunk, pos),
     OpCode::SetGlobal => string_instruction(f, "Set Global", chunk, pos),
+    OpCode::SetGlobalLong => string_instruction(f, "Set Global Long", chunk, pos),
     OpCode::Jump => jump_instruction(f, "Jump", 1, chunk, pos),
     OpCode::JumpIfFalse => jump_instruction(f, "Jump If False", 1, chunk, pos),
     OpCode::JumpIfNull => jump_instruction(f, "Jump If Null", 1, chunk, pos),
@@ -93,7 +109,17 @@ fn disassemble_instruction(f: &mut fmt::Formatter<'_>, chunk: &Chunk, pos: usize
     OpCode::SetUpvalue => byte_instruction(f, "Set Upvalue", chunk, pos),
     OpCode::GetAllocated => byte_instruction(f, "Get Upvalue from Local", chunk, pos),
     OpCode::SetAllocated => byte_instruction(f, "Set Upvalue from Local", chunk, pos),
+    OpCode::Allocate => byte_instruction(f, "Allocate Local", chunk, pos),
     OpCode::Import => write!(f, "Import"),
+    OpCode::ImportOptional => write!(f, "Import Optional"),
+    OpCode::Next => jump_instruction(f, "Next", 1, chunk, pos),
+    OpCode::Spawn => write!(f, "Spawn"),
+    OpCode::Join => write!(f, "Join"),
+    OpCode::AssertNonNull => string_instruction(f, "Assert Non Null", chunk, pos),
+    OpCode::AssertNonNullLong => string_instruction(f, "Assert Non Null Long", chunk, pos),
+    OpCode::OmitKeys => byte_instruction(f, "Omit Keys", chunk, pos),
+    OpCode::GetGlobalOptional => string_instruction(f, "Get Global Optional", chunk, pos),
+    OpCode::GetGlobalOptionalLong => string_instruction(f, "Get Global Optional Long", chunk, pos),
     _ => write!(f, "Unknown OpCode"),
   }
 }
@@ -116,7 +142,7 @@ fn string_instruction(
   chunk: &Chunk,
   position: usize,
 ) -> fmt::Result {
-  let string_location: usize = chunk.get_value(position + 1).into();
+  let (string_location, _) = chunk.get_string_operand(position);
   let string = chunk.get_string(string_location);
 
   write!(f, "{name} {string:?} ({string_location})")
@@ -0,0 +1,112 @@
+use super::Chunk;
+use crate::collections::String;
+use core::fmt;
+
+// The bytecode hash changes with the opcodes, the compiler or the stored format, so chunks cached
+// by a different build aren't used even if the version number is the same
+pub const COMPILER_VERSION: &str =
+  concat!(env!("CARGO_PKG_VERSION"), "+", env!("BANG_BYTECODE_HASH"));
+
+// How a chunk was compiled, to know if a stored chunk still matches its source file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+  pub source_hash: u64,
+  pub compiler_version: String,
+  pub keep_last_value: bool,
+}
+impl Metadata {
+  pub fn new(source: &str, keep_last_value: bool) -> Self {
+    Self {
+      source_hash: hash_source(source),
+      compiler_version: COMPILER_VERSION.into(),
+      keep_last_value,
+    }
+  }
+
+  // Checks the chunk would be the same if the source was compiled now
+  pub fn check(&self, source: &str, keep_last_value: bool) -> Result<(), Stale> {
+    if self.compiler_version.as_str() != COMPILER_VERSION {
+      Err(Stale::CompilerChanged)
+    } else if self.source_hash != hash_source(source) {
+      Err(Stale::SourceChanged)
+    } else if self.keep_last_value != keep_last_value {
+      Err(Stale::OptionsChanged)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stale {
+  SourceChanged,
+  CompilerChanged,
+  OptionsChanged,
+}
+impl fmt::Display for Stale {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::SourceChanged => write!(f, "the source file has changed since it was compiled"),
+      Self::CompilerChanged => write!(f, "it was compiled by a different version of bang"),
+      Self::OptionsChanged => write!(f, "it was compiled with different options"),
+    }
+  }
+}
+
+impl Chunk {
+  pub fn metadata(&self) -> &Metadata {
+    &self.metadata
+  }
+}
+
+// FNV-1a, as it is the same on every platform so can be stored
+pub fn hash_source(source: &str) -> u64 {
+  source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+    (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::{hash_source, Stale, COMPILER_VERSION};
+  use crate::{compile, compile_keeping_last_value, context};
+
+  #[test]
+  fn compiled_chunk_has_metadata() {
+    let chunk = compile("let a = 5").unwrap();
+    let metadata = chunk.metadata();
+
+    assert_eq!(metadata.source_hash, hash_source("let a = 5"));
+    assert_eq!(metadata.compiler_version, COMPILER_VERSION);
+    assert!(!metadata.keep_last_value);
+    assert_eq!(metadata.check("let a = 5", false), Ok(()));
+  }
+
+  #[test]
+  fn stale_when_source_or_options_change() {
+    let chunk = compile_keeping_last_value("5 + 5", &context::Empty).unwrap();
+    let metadata = chunk.metadata();
+
+    assert_eq!(metadata.check("5 + 5", true), Ok(()));
+    assert_eq!(metadata.check("5 + 6", true), Err(Stale::SourceChanged));
+    assert_eq!(metadata.check("5 + 5", false), Err(Stale::OptionsChanged));
+  }
+
+  #[test]
+  fn stale_when_compiler_changes() {
+    let mut metadata = compile("5").unwrap().metadata().clone();
+    metadata.compiler_version = "0.0.0-old".into();
+
+    assert_eq!(metadata.check("5", false), Err(Stale::CompilerChanged));
+
+    metadata.compiler_version = env!("CARGO_PKG_VERSION").into();
+    assert_eq!(metadata.check("5", false), Err(Stale::CompilerChanged));
+  }
+
+  #[test]
+  fn hash_is_stable() {
+    assert_eq!(hash_source(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(hash_source("a"), 0xaf63_dc4c_8601_ec8c);
+    assert_ne!(hash_source("let a = 1"), hash_source("let a = 2"));
+  }
+}
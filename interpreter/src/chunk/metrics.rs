@@ -0,0 +1,181 @@
+use super::{Chunk, OpCode};
+use crate::{collections::String, value::Object};
+use alloc::{vec, vec::Vec};
+use bang_syntax::LineNumber;
+
+#[derive(Debug)]
+pub struct Metrics {
+  pub name: String,
+  pub line: LineNumber,
+  pub bytecode_size: usize,
+  pub max_stack_depth: usize,
+  pub locals: usize,
+  pub constants: usize,
+}
+
+impl Chunk {
+  pub fn metrics(&self) -> Vec<Metrics> {
+    let mut metrics = Vec::new();
+    collect_metrics(&mut metrics, "Root".into(), 1, 0, self);
+    metrics
+  }
+}
+
+fn collect_metrics(
+  metrics: &mut Vec<Metrics>,
+  name: String,
+  line: LineNumber,
+  parameters: usize,
+  chunk: &Chunk,
+) {
+  let (max_stack_depth, locals) = stack_usage(parameters, chunk);
+
+  metrics.push(Metrics {
+    name,
+    line,
+    bytecode_size: chunk.code.len(),
+    max_stack_depth,
+    locals,
+    constants: chunk.constants.len() + chunk.strings.len(),
+  });
+
+  for constant in &chunk.constants {
+    if constant.is_object()
+      && let Object::Function(func) = constant.as_object()
+    {
+      // The final return of a function is on the line the function starts
+      let line = func.chunk.get_line_number(func.chunk.code.len() - 1);
+      collect_metrics(
+        metrics,
+        func.name.clone(),
+        line,
+        func.arity.get_count(),
+        &func.chunk,
+      );
+    }
+  }
+}
+
+fn stack_usage(parameters: usize, chunk: &Chunk) -> (usize, usize) {
+  let mut visited = vec![false; chunk.code.len()];
+  let mut pending = vec![(0, parameters)];
+
+  let mut max_depth = parameters;
+  let mut locals = parameters;
+
+  while let Some((ip, depth)) = pending.pop() {
+    if ip >= chunk.code.len() || visited[ip] {
+      continue;
+    }
+    visited[ip] = true;
+    max_depth = max_depth.max(depth);
+
+    let opcode = chunk.get(ip);
+    let next = ip + opcode.number_of_bytes().unwrap_or(1);
+
+    match opcode {
+      OpCode::GetLocal
+      | OpCode::SetLocal
+      | OpCode::GetAllocated
+      | OpCode::SetAllocated
+      | OpCode::Allocate => {
+        locals = locals.max(usize::from(chunk.get_value(ip + 1)) + 1);
+      }
+      _ => {}
+    }
+
+    match opcode {
+      OpCode::Return | OpCode::Unknown => {}
+      OpCode::Jump => pending.push((ip + usize::from(chunk.get_long_value(ip + 1)) + 1, depth)),
+      OpCode::Loop => {
+        if let Some(target) = (ip + 1).checked_sub(chunk.get_long_value(ip + 1).into()) {
+          pending.push((target, depth));
+        }
+      }
+      OpCode::JumpIfFalse | OpCode::JumpIfNull => {
+        pending.push((ip + usize::from(chunk.get_long_value(ip + 1)) + 1, depth));
+        pending.push((next, depth));
+      }
+      OpCode::Next => {
+        pending.push((ip + usize::from(chunk.get_long_value(ip + 1)) + 1, depth));
+        pending.push((next, depth + 1));
+      }
+      _ => {
+        let (pops, pushes) = stack_effect(chunk, ip);
+        pending.push((next, depth.saturating_sub(pops) + pushes));
+      }
+    }
+  }
+
+  (max_depth, locals)
+}
+
+fn stack_effect(chunk: &Chunk, ip: usize) -> (usize, usize) {
+  match chunk.get(ip) {
+    OpCode::Constant
+    | OpCode::ConstantLong
+    | OpCode::Null
+    | OpCode::True
+    | OpCode::False
+    | OpCode::GetGlobal
+    | OpCode::GetGlobalLong
+    | OpCode::GetGlobalOptional
+    | OpCode::GetGlobalOptionalLong
+    | OpCode::GetLocal
+    | OpCode::GetTemp
+    | OpCode::GetUpvalue
+    | OpCode::GetAllocated => (0, 1),
+    OpCode::Add
+    | OpCode::Subtract
+    | OpCode::Multiply
+    | OpCode::Divide
+    | OpCode::Remainder
+    | OpCode::Equal
+    | OpCode::Greater
+    | OpCode::Less
+    | OpCode::NotEqual
+    | OpCode::GreaterEqual
+    | OpCode::LessEqual
+    | OpCode::Contains
+    | OpCode::GetIndex
+    | OpCode::Import
+    | OpCode::ImportOptional => (2, 1),
+    OpCode::Pop | OpCode::DefineGlobal | OpCode::DefineGlobalLong => (1, 0),
+    OpCode::SetIndex => (3, 1),
+    OpCode::Call => (usize::from(chunk.get_value(ip + 1)) + 1, 1),
+    OpCode::List => (chunk.get_value(ip + 1).into(), 1),
+    OpCode::ListLong => (chunk.get_long_value(ip + 1).into(), 1),
+    OpCode::Dict => (usize::from(chunk.get_value(ip + 1)) * 2, 1),
+    OpCode::OmitKeys => (usize::from(chunk.get_value(ip + 1)) + 1, 1),
+    _ => (0, 0),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::compile;
+
+  #[test]
+  fn function_metrics() {
+    let source = "
+let add = (a, b) => a + b
+let outer = (x) ->
+  let y = x * 2
+  let inner = () => y
+  inner()
+";
+    let chunk = compile(source).unwrap();
+    let metrics = chunk.metrics();
+
+    let names: Vec<_> = metrics.iter().map(|metric| metric.name.as_str()).collect();
+    assert_eq!(names, vec!["Root", "add", "outer", "inner"]);
+
+    let lines: Vec<_> = metrics.iter().map(|metric| metric.line).collect();
+    assert_eq!(lines, vec![1, 2, 3, 5]);
+
+    assert_eq!(metrics[1].locals, 2);
+    assert_eq!(metrics[1].max_stack_depth, 4);
+    assert_eq!(metrics[2].locals, 3);
+    assert!(metrics.iter().all(|metric| metric.bytecode_size > 0));
+  }
+}
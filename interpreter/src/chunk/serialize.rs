@@ -0,0 +1,311 @@
+use super::{Chunk, LineInfo, Metadata};
+use crate::{
+  collections::String,
+  value::{Arity, ClosureKind, Function, Object, Value},
+};
+use alloc::{rc::Rc, vec::Vec};
+use core::str;
+
+// Increased whenever the layout changes, so older files are not read
+const FORMAT: &[u8] = b"bang\x01";
+
+enum Constant {
+  Number,
+  Null,
+  True,
+  False,
+  String,
+  Function,
+}
+
+impl Chunk {
+  // Gives `None` if a constant can't be stored, such as a list from the config
+  pub fn to_bytes(&self) -> Option<Vec<u8>> {
+    let mut writer = Writer(Vec::from(FORMAT));
+    writer.u64(self.metadata.source_hash);
+    writer.string(&self.metadata.compiler_version)?;
+    writer.u8(self.metadata.keep_last_value.into());
+    writer.chunk(self)?;
+
+    Some(writer.0)
+  }
+
+  // Gives `None` if the bytes are not a valid chunk from `Chunk::to_bytes`
+  pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.bytes(FORMAT.len())? != FORMAT {
+      return None;
+    }
+
+    let metadata = Metadata {
+      source_hash: reader.u64()?,
+      compiler_version: reader.string()?.into(),
+      keep_last_value: reader.u8()? == 1,
+    };
+    let mut chunk = reader.chunk()?;
+    chunk.metadata = metadata;
+
+    let finished = reader.position == bytes.len();
+    (finished && chunk.verify().is_ok()).then_some(chunk)
+  }
+}
+
+struct Writer(Vec<u8>);
+impl Writer {
+  fn u8(&mut self, value: u8) {
+    self.0.push(value);
+  }
+
+  fn u32(&mut self, value: usize) -> Option<()> {
+    let value = u32::try_from(value).ok()?;
+    self.0.extend_from_slice(&value.to_le_bytes());
+    Some(())
+  }
+
+  fn u64(&mut self, value: u64) {
+    self.0.extend_from_slice(&value.to_le_bytes());
+  }
+
+  fn string(&mut self, string: &str) -> Option<()> {
+    self.u32(string.len())?;
+    self.0.extend_from_slice(string.as_bytes());
+    Some(())
+  }
+
+  fn chunk(&mut self, chunk: &Chunk) -> Option<()> {
+    self.u32(chunk.code.len())?;
+    self.0.extend_from_slice(&chunk.code);
+
+    self.u32(chunk.lines.lines.len())?;
+    for (line, tokens) in &chunk.lines.lines {
+      self.0.extend_from_slice(&line.to_le_bytes());
+      self.0.extend_from_slice(&tokens.to_le_bytes());
+    }
+
+    self.u32(chunk.strings.len())?;
+    for string in &chunk.strings {
+      self.string(string)?;
+    }
+
+    self.u32(chunk.constants.len())?;
+    for constant in &chunk.constants {
+      self.constant(constant)?;
+    }
+
+    Some(())
+  }
+
+  fn constant(&mut self, value: &Value) -> Option<()> {
+    if value.is_number() {
+      self.u8(Constant::Number as u8);
+      self.u64(value.as_number().to_bits());
+    } else if *value == Value::NULL {
+      self.u8(Constant::Null as u8);
+    } else if *value == Value::TRUE {
+      self.u8(Constant::True as u8);
+    } else if *value == Value::FALSE {
+      self.u8(Constant::False as u8);
+    } else if !value.is_object() {
+      return None;
+    } else {
+      match value.as_object() {
+        Object::String(string) => {
+          self.u8(Constant::String as u8);
+          self.string(string)?;
+        }
+        Object::Function(function) => {
+          self.u8(Constant::Function as u8);
+          self.string(&function.name)?;
+          self.u8(u8::try_from(function.arity.get_count()).ok()?);
+          let optional = function.arity.get_count() - function.arity.get_required_count();
+          self.u8(u8::try_from(optional).ok()?);
+
+          self.u8(u8::try_from(function.upvalues.len()).ok()?);
+          for (index, kind) in &function.upvalues {
+            self.u8(*index);
+            self.u8(*kind as u8);
+          }
+
+          self.chunk(&function.chunk)?;
+        }
+        _ => return None,
+      }
+    }
+
+    Some(())
+  }
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  position: usize,
+}
+impl<'a> Reader<'a> {
+  fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+    let bytes = self
+      .bytes
+      .get(self.position..self.position.checked_add(length)?)?;
+    self.position += length;
+    Some(bytes)
+  }
+
+  fn u8(&mut self) -> Option<u8> {
+    Some(self.bytes(1)?[0])
+  }
+
+  fn u16(&mut self) -> Option<u16> {
+    Some(u16::from_le_bytes(self.bytes(2)?.try_into().ok()?))
+  }
+
+  fn u32(&mut self) -> Option<usize> {
+    let value = u32::from_le_bytes(self.bytes(4)?.try_into().ok()?);
+    usize::try_from(value).ok()
+  }
+
+  fn u64(&mut self) -> Option<u64> {
+    Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+  }
+
+  fn string(&mut self) -> Option<&'a str> {
+    let length = self.u32()?;
+    str::from_utf8(self.bytes(length)?).ok()
+  }
+
+  fn chunk(&mut self) -> Option<Chunk> {
+    let mut chunk = Chunk::new();
+
+    let length = self.u32()?;
+    chunk.code = self.bytes(length)?.to_vec();
+
+    let lines = self.u32()?;
+    chunk.lines = LineInfo {
+      lines: (0..lines)
+        .map(|_| Some((self.u16()?, self.u16()?)))
+        .collect::<Option<_>>()?,
+      last: 0,
+      repeated: 0,
+    };
+
+    let strings = self.u32()?;
+    chunk.strings = (0..strings)
+      .map(|_| self.string().map(Rc::from))
+      .collect::<Option<_>>()?;
+
+    let constants = self.u32()?;
+    chunk.constants = (0..constants)
+      .map(|_| self.constant())
+      .collect::<Option<_>>()?;
+
+    Some(chunk)
+  }
+
+  fn constant(&mut self) -> Option<Value> {
+    let value = match self.u8()? {
+      tag if tag == Constant::Number as u8 => Value::from(f64::from_bits(self.u64()?)),
+      tag if tag == Constant::Null as u8 => Value::NULL,
+      tag if tag == Constant::True as u8 => Value::TRUE,
+      tag if tag == Constant::False as u8 => Value::FALSE,
+      tag if tag == Constant::String as u8 => Value::from(self.string()?),
+      tag if tag == Constant::Function as u8 => {
+        let name = String::from(self.string()?);
+        let arity = Arity::with_optional(self.u8()?, self.u8()?);
+
+        let upvalues = self.u8()?;
+        let upvalues = (0..upvalues)
+          .map(|_| Some((self.u8()?, self.closure_kind()?)))
+          .collect::<Option<_>>()?;
+
+        Value::from(Function {
+          name,
+          arity,
+          chunk: Rc::new(self.chunk()?),
+          upvalues,
+        })
+      }
+      _ => return None,
+    };
+
+    Some(value)
+  }
+
+  fn closure_kind(&mut self) -> Option<ClosureKind> {
+    match self.u8()? {
+      kind if kind == ClosureKind::Open as u8 => Some(ClosureKind::Open),
+      kind if kind == ClosureKind::Closed as u8 => Some(ClosureKind::Closed),
+      kind if kind == ClosureKind::Upvalue as u8 => Some(ClosureKind::Upvalue),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    chunk::{Chunk, Stale},
+    compile, context, VM,
+  };
+  use alloc::rc::Rc;
+
+  fn round_trip(source: &str) -> Chunk {
+    let chunk = compile(source).unwrap();
+    Chunk::from_bytes(&chunk.to_bytes().unwrap()).unwrap()
+  }
+
+  #[test]
+  fn keeps_metadata() {
+    let chunk = round_trip("let a = 5");
+    assert_eq!(chunk.metadata(), compile("let a = 5").unwrap().metadata());
+  }
+
+  #[test]
+  fn runs_after_round_trip() {
+    let chunk = round_trip(
+      "
+let add = (a, b = 2) => a + b
+let counter = () ->
+  let count = 0
+  return () => count += 1
+let increment = counter()
+increment()
+let result = `${add(1)} ${increment()} ${null} ${true} ${false}`
+",
+    );
+
+    let mut vm = VM::new(&context::Empty);
+    vm.run(&Rc::new(chunk)).unwrap();
+    assert_eq!(
+      vm.get_global("result").unwrap().as_str(),
+      "3 2 null true false"
+    );
+  }
+
+  #[test]
+  fn loaded_chunk_from_other_compiler_is_stale() {
+    let mut chunk = compile("let a = 5").unwrap().as_ref().clone();
+    chunk.metadata.compiler_version = "0.0.0-old".into();
+
+    let loaded = Chunk::from_bytes(&chunk.to_bytes().unwrap()).unwrap();
+    assert_eq!(loaded.metadata().compiler_version, "0.0.0-old");
+    assert_eq!(
+      loaded.metadata().check("let a = 5", false),
+      Err(Stale::CompilerChanged)
+    );
+  }
+
+  #[test]
+  fn rejects_invalid_bytes() {
+    let bytes = compile("let a = 5").unwrap().to_bytes().unwrap();
+
+    assert!(Chunk::from_bytes(&[]).is_none());
+    assert!(Chunk::from_bytes(b"not a chunk").is_none());
+    assert!(Chunk::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert!(Chunk::from_bytes(&extra).is_none());
+
+    let mut format = bytes;
+    format[4] = 0;
+    assert!(Chunk::from_bytes(&format).is_none());
+  }
+}
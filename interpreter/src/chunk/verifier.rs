@@ -43,7 +43,7 @@ impl Chunk {
           }
         }
 
-        OpCode::JumpIfFalse | OpCode::JumpIfNull | OpCode::Jump => {
+        OpCode::JumpIfFalse | OpCode::JumpIfNull | OpCode::Jump | OpCode::Next => {
           let offset: usize = self.get_long_value(ip + 1).into();
           if ip + offset + 1 >= self.code.len() {
             return Err(Error::UnknownLocation);
@@ -56,8 +56,17 @@ impl Chunk {
           }
         }
 
-        OpCode::DefineGlobal | OpCode::GetGlobal | OpCode::SetGlobal => {
-          let name_location: usize = self.get_value(ip + 1).into();
+        OpCode::DefineGlobal
+        | OpCode::DefineGlobalLong
+        | OpCode::GetGlobal
+        | OpCode::GetGlobalLong
+        | OpCode::SetGlobal
+        | OpCode::SetGlobalLong
+        | OpCode::AssertNonNull
+        | OpCode::AssertNonNullLong
+        | OpCode::GetGlobalOptional
+        | OpCode::GetGlobalOptionalLong => {
+          let (name_location, _) = self.get_string_operand(ip);
           if name_location >= self.strings.len() {
             return Err(Error::UnknownGlobalName);
           }
@@ -164,5 +173,12 @@ mod test {
       ..Default::default()
     };
     assert!(chunk.verify().is_err());
+
+    let chunk = Chunk {
+      code: vec![OpCode::GetGlobalLong as u8, 1, 0],
+      strings: vec!["a".into()],
+      ..Default::default()
+    };
+    assert!(chunk.verify().is_err());
   }
 }
@@ -1,16 +1,27 @@
 use crate::{
-  chunk::{Chunk, OpCode},
+  chunk::{Chunk, Metadata, OpCode},
+  collections::HashMap,
+  context::Context,
   value::{Arity, ClosureKind, Function, Value},
 };
+use alloc::{
+  boxed::Box,
+  format,
+  rc::Rc,
+  string::{String, ToString},
+  vec,
+  vec::Vec,
+};
 use bang_syntax::{
   ast::{
-    expression::{operators, Expr, Expression, LiteralType},
-    statement::{DeclarationIdentifier, Statement, Stmt},
+    expression::{operators, Expr, Expression, LiteralType, Parameter},
+    statement::{AliasItem, DeclarationIdentifier, Statement, Stmt},
+    Visitor,
   },
   Diagnostic, Parser, Span,
 };
+use core::{mem, slice};
 use smallvec::SmallVec;
-use std::{mem, rc::Rc};
 
 enum Error {
   TooBigJump,
@@ -21,6 +32,12 @@ enum Error {
   TooLongList,
   TooLargeDict,
   VariableAlreadyExists,
+  ProtectedGlobal,
+  BreakOutsideLoop,
+  BreakValueFromWhile,
+  ItemNotFound { module: String },
+  ImportCycle,
+  InModule(Diagnostic),
 }
 impl Error {
   fn get_title(&self) -> &'static str {
@@ -30,9 +47,15 @@ impl Error {
       Self::TooManyArguments => "Too Many Arguments",
       Self::TooManyParameters => "Too Many Parameters",
       Self::VariableAlreadyExists => "Variable Already Exists",
+      Self::ProtectedGlobal => "Protected Global",
       Self::TooManyLocals => "Too Many Local Variables",
       Self::TooLongList => "Too Long List",
       Self::TooLargeDict => "Too Large Dict",
+      Self::BreakOutsideLoop => "Break Outside Loop",
+      Self::BreakValueFromWhile => "Break Value From While",
+      Self::ItemNotFound { .. } => "Item Not Found",
+      Self::ImportCycle => "Import Cycle",
+      Self::InModule(_) => "Error In Module",
     }
   }
 
@@ -47,10 +70,21 @@ impl Error {
       Self::TooManyParameters => "There is a limit of 255 parameters for a function".to_string(),
       Self::TooManyLocals => "There is a limit of 255 local variables at once".to_string(),
       Self::VariableAlreadyExists => format!("Variable '{value}' has been defined already"),
+      Self::ProtectedGlobal => format!("Builtin '{value}' can't be redefined or reassigned"),
       Self::TooLongList => "List is too long, can have a maximum of 2^16 elements".to_string(),
       Self::TooLargeDict => {
         "Dictionary is too large, can have a maximum of 255 static items".to_string()
       }
+      Self::BreakOutsideLoop => "`break` can only be used inside a loop".to_string(),
+      Self::BreakValueFromWhile => {
+        "Only `loop` expressions can break with a value, `while` loops have no value".to_string()
+      }
+      Self::ItemNotFound { module } => format!("Item '{value}' not found in module '{module}'"),
+      Self::ImportCycle => format!("Module '{value}' imports itself through a cycle of imports"),
+      Self::InModule(error) => format!(
+        "{} in module '{value}' on line {}: {}",
+        error.title, error.line, error.message
+      ),
     }
   }
 
@@ -59,6 +93,7 @@ impl Error {
       title: self.get_title().to_string(),
       message: self.get_message(value),
       line: span.get_line_number(source),
+      column: span.get_column_number(source),
       span,
     }
   }
@@ -70,6 +105,56 @@ struct Local<'s> {
   closed: bool,
 }
 
+// Finds locals which are used by functions, so they can be allocated before a loop
+struct CapturedLocals<'a, 's> {
+  locals: &'a [Local<'s>],
+  captured: Vec<usize>,
+  function_depth: usize,
+}
+impl Visitor for CapturedLocals<'_, '_> {
+  fn enter_expression(&mut self, expression: &Expression) {
+    match &expression.expr {
+      Expr::Function { .. } => self.function_depth += 1,
+      Expr::Variable { name: identifier } | Expr::Assignment { identifier, .. }
+        if self.function_depth > 0 =>
+      {
+        let index = self
+          .locals
+          .iter()
+          .rposition(|local| local.name == *identifier);
+        if let Some(index) = index
+          && !self.locals[index].closed
+          && !self.captured.contains(&index)
+        {
+          self.captured.push(index);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn exit_expression(&mut self, expression: &Expression) {
+    if let Expr::Function { .. } = expression.expr {
+      self.function_depth -= 1;
+    }
+  }
+}
+
+struct Loop {
+  scope_depth: u8,
+  breaks: Vec<usize>,
+  // Local slot which holds the value of a loop expression
+  result: Option<usize>,
+}
+
+// A module from the context's import resolver, compiled into a function which returns a dict of
+// the module's top level variables
+#[derive(Clone)]
+struct ResolvedModule {
+  function: Value,
+  exports: Vec<String>,
+}
+
 #[derive(Default)]
 struct Compiler<'s> {
   source: &'s str,
@@ -77,10 +162,20 @@ struct Compiler<'s> {
   locals: Vec<Vec<Local<'s>>>,
   closures: Vec<SmallVec<[(u8, ClosureKind); 8]>>,
   scope_depth: u8,
+  loops: Vec<Loop>,
+  // Values on the stack above the locals, whilst compiling part of an expression
+  temporaries: usize,
 
   chunk: Chunk,
   chunk_stack: Vec<Chunk>,
-
+  module_aliases: HashMap<&'s str, &'s str>,
+  resolved_modules: HashMap<String, ResolvedModule>,
+  // Modules currently being compiled, to detect import cycles
+  importing: Vec<String>,
+  in_module: bool,
+
+  context: &'s dyn Context,
+  protected_globals: &'s [&'s str],
   error: Option<Diagnostic>,
 }
 
@@ -124,11 +219,24 @@ impl Compiler<'_> {
     constant_position
   }
 
-  fn emit_constant_string(&mut self, span: Span, value: &str) {
+  fn emit_constant_string(&mut self, span: Span, instruction: OpCode, value: &str) {
     let constant_position = self.chunk.add_constant_string(value);
 
+    let long_instruction = match instruction {
+      OpCode::DefineGlobal => OpCode::DefineGlobalLong,
+      OpCode::GetGlobal => OpCode::GetGlobalLong,
+      OpCode::SetGlobal => OpCode::SetGlobalLong,
+      OpCode::AssertNonNull => OpCode::AssertNonNullLong,
+      OpCode::GetGlobalOptional => OpCode::GetGlobalOptionalLong,
+      _ => unreachable!("instruction doesn't take a string"),
+    };
+
     if let Ok(constant_position) = u8::try_from(constant_position) {
+      self.emit_opcode(span, instruction);
       self.emit_value(span, constant_position);
+    } else if let Ok(constant_position) = u16::try_from(constant_position) {
+      self.emit_opcode(span, long_instruction);
+      self.emit_long_value(span, constant_position);
     } else {
       self.error(Error::TooManyConstants, span, "");
     }
@@ -150,6 +258,18 @@ impl Compiler<'_> {
     }
   }
 
+  fn emit_loop(&mut self, span: Span, loop_start: usize) {
+    self.emit_opcode(span, OpCode::Loop);
+
+    let offset = self.length() - loop_start;
+
+    if let Ok(offset) = u16::try_from(offset) {
+      self.emit_long_value(span, offset);
+    } else {
+      self.error(Error::TooBigJump, span, "");
+    }
+  }
+
   fn emit_local_index(&mut self, index: usize, span: Span) {
     if let Ok(index) = u8::try_from(index) {
       self.emit_value(span, index);
@@ -213,13 +333,33 @@ impl<'s> Compiler<'s> {
   }
 
   fn error(&mut self, error: Error, span: Span, value: &str) {
-    self.error = Some(error.into_diagnostic(value, span, self.source));
+    // Keep the first error, as later errors are often caused by it
+    if self.error.is_none() {
+      self.error = Some(error.into_diagnostic(value, span, self.source));
+    }
   }
 
   fn compile_statement(&mut self, statement: &Statement<'s>) {
     let span = statement.span;
 
     match &statement.stmt {
+      Stmt::Declaration {
+        expression,
+        identifier: DeclarationIdentifier::Variable(identifier),
+        ..
+      } if self.is_module_top_level() => {
+        // Declared before the value, so functions in the module can refer to themselves
+        self.emit_opcode(span, OpCode::Null);
+        let slot = self.define_variable(identifier, span);
+
+        if let Some(expression) = expression {
+          self.compile_expression(expression);
+        } else {
+          self.emit_opcode(span, OpCode::Null);
+        }
+        self.set_local(slot, span);
+        self.emit_opcode(span, OpCode::Pop);
+      }
       Stmt::Declaration {
         expression,
         identifier,
@@ -236,37 +376,46 @@ impl<'s> Compiler<'s> {
             self.define_variable(identifier, span);
           }
           DeclarationIdentifier::Ordered(identifiers) => {
-            identifiers
-              .iter()
-              .enumerate()
-              .for_each(|(index, identifier)| {
-                let locals = self.locals.last().expect("Local stack to have item");
-                let temp_local_location = u8::try_from(locals.len())
-                  .map_err(|_| self.error(Error::TooManyLocals, span, ""))
-                  .unwrap_or(0);
-
-                self.emit_opcode(span, OpCode::GetLocal);
-                self.emit_value(span, temp_local_location);
-                self.emit_constant(span, Value::from(index));
-                self.emit_opcode(span, OpCode::GetIndex);
-                self.define_variable(identifier, span);
-              });
-            self.emit_opcode(span, OpCode::Pop);
+            let source = self.destructuring_source(span);
+            for (index, identifier) in identifiers.iter().enumerate() {
+              self.emit_opcode(span, OpCode::GetLocal);
+              self.emit_local_index(source, span);
+              self.emit_constant(span, Value::from(index));
+              self.emit_opcode(span, OpCode::GetIndex);
+              self.define_variable(identifier, span);
+            }
+            self.finish_destructuring(span);
           }
-          DeclarationIdentifier::Named(identifiers) => {
+          DeclarationIdentifier::Named(identifiers, rest) => {
+            let source = self.destructuring_source(span);
             for identifier in identifiers {
-              let locals = self.locals.last().expect("Local stack to have item");
-              let temp_local_location = u8::try_from(locals.len())
-                .map_err(|_| self.error(Error::TooManyLocals, span, ""))
-                .unwrap_or(0);
+              match &identifier.default {
+                Some(default) => self.dict_item_or_default(source, identifier.name, default, span),
+                None => {
+                  self.emit_opcode(span, OpCode::GetLocal);
+                  self.emit_local_index(source, span);
+                  self.emit_constant(span, Value::from(identifier.name));
+                  self.emit_opcode(span, OpCode::GetIndex);
+                }
+              }
+              self.define_variable(identifier.get_name(), span);
+            }
 
+            if let Some(rest) = rest {
               self.emit_opcode(span, OpCode::GetLocal);
-              self.emit_value(span, temp_local_location);
-              self.emit_constant(span, Value::from(identifier.name));
-              self.emit_opcode(span, OpCode::GetIndex);
-              self.define_variable(identifier.get_name(), span);
+              self.emit_local_index(source, span);
+              for identifier in identifiers {
+                self.emit_constant(span, Value::from(identifier.name));
+              }
+              if let Ok(length) = u8::try_from(identifiers.len()) {
+                self.emit_opcode(span, OpCode::OmitKeys);
+                self.emit_value(span, length);
+              } else {
+                self.error(Error::TooLargeDict, span, "");
+              }
+              self.define_variable(rest, span);
             }
-            self.emit_opcode(span, OpCode::Pop);
+            self.finish_destructuring(span);
           }
         }
       }
@@ -276,6 +425,16 @@ impl<'s> Compiler<'s> {
         otherwise,
         ..
       } => {
+        // Only compile the branch which will run if the config decides the condition
+        if let Some(condition) = self.config_condition(condition) {
+          if !condition.is_falsy() {
+            self.compile_statement(then);
+          } else if let Some(otherwise) = otherwise {
+            self.compile_statement(otherwise);
+          }
+          return;
+        }
+
         self.compile_expression(condition);
 
         let then_jump = self.emit_jump(span, OpCode::JumpIfFalse);
@@ -293,37 +452,64 @@ impl<'s> Compiler<'s> {
 
         self.patch_jump(span, else_jump);
       }
-      Stmt::Import { module, items, .. } => {
+      Stmt::Import {
+        module,
+        alias,
+        items,
+        optional,
+      } => {
+        if let Some(alias) = alias {
+          self.module_aliases.insert(alias, module);
+        }
+
+        if let Some(resolved) = self.resolve_module(module, span) {
+          self.import_resolved_module(module, &resolved, items, span);
+          return;
+        }
+
+        // Optional imports are checked when run, as the context decides which modules exist
+        let opcode = if *optional {
+          OpCode::ImportOptional
+        } else {
+          OpCode::Import
+        };
         for item in items {
           self.emit_constant(span, (*module).into());
           self.emit_constant(span, item.name.into());
-          self.emit_opcode(span, OpCode::Import);
+          self.emit_opcode(span, opcode);
           self.define_variable(item.get_name(), item.span);
         }
       }
       Stmt::While {
         condition, body, ..
       } => {
+        if self
+          .config_condition(condition)
+          .is_some_and(|condition| condition.is_falsy())
+        {
+          return;
+        }
+
+        self.allocate_captured_locals(span, Some(condition), body);
         let loop_start = self.length();
         self.compile_expression(condition);
 
         let exit_jump = self.emit_jump(span, OpCode::JumpIfFalse);
         self.emit_opcode(span, OpCode::Pop);
 
+        self.loops.push(Loop {
+          scope_depth: self.scope_depth,
+          breaks: Vec::new(),
+          result: None,
+        });
         self.compile_statement(body);
-        self.emit_opcode(span, OpCode::Loop);
-
-        let offset = self.length() - loop_start;
-
-        if let Ok(offset) = u16::try_from(offset) {
-          self.emit_long_value(span, offset);
-        } else {
-          self.error(Error::TooBigJump, span, "");
-        }
+        self.emit_loop(span, loop_start);
 
         self.patch_jump(span, exit_jump);
         self.emit_opcode(span, OpCode::Pop);
+        self.patch_breaks(span);
       }
+      Stmt::Break { expression } => self.break_statement(span, expression.as_ref()),
       Stmt::Return { expression, .. } => {
         if let Some(expression) = expression {
           self.compile_expression(expression);
@@ -347,9 +533,23 @@ impl<'s> Compiler<'s> {
     }
   }
 
+  fn compile_assignment_operator(&mut self, operator: operators::Assignment, span: Span) {
+    match operator {
+      operators::Assignment::Plus => self.emit_opcode(span, OpCode::Add),
+      operators::Assignment::Minus => self.emit_opcode(span, OpCode::Subtract),
+      operators::Assignment::Multiply => self.emit_opcode(span, OpCode::Multiply),
+      operators::Assignment::Divide => self.emit_opcode(span, OpCode::Divide),
+    }
+  }
+
   fn compile_expression(&mut self, expression: &Expression<'s>) {
     let span = expression.span;
 
+    if let Some(value) = self.config_value(expression) {
+      self.emit_constant(span, value);
+      return;
+    }
+
     match &expression.expr {
       Expr::Literal { type_, value } => match type_ {
         LiteralType::True => self.emit_opcode(span, OpCode::True),
@@ -359,12 +559,20 @@ impl<'s> Compiler<'s> {
           self.emit_constant(span, Value::from(Parser::number(value)));
         }
         LiteralType::String => {
-          self.emit_constant(span, Value::from(*value));
+          self.emit_constant(span, Value::from(Parser::string(value)));
         }
       },
-      Expr::Group { expression, .. } => {
+      Expr::Group { expression, .. }
+      | Expr::Cast { expression, .. }
+      | Expr::Ascription { expression, .. } => {
         self.compile_expression(expression);
       }
+      Expr::NonNull { expression: inner } => {
+        self.compile_expression(inner);
+
+        let text = &self.source[inner.span.start as usize..inner.span.end as usize];
+        self.emit_constant_string(span, OpCode::AssertNonNull, text);
+      }
       Expr::Unary {
         expression,
         operator,
@@ -387,11 +595,14 @@ impl<'s> Compiler<'s> {
           operators::Binary::And => return self.and(span, left, right),
           operators::Binary::Or => return self.or(span, left, right),
           operators::Binary::Pipeline => return self.pipeline(span, left, right),
+          operators::Binary::Compose => return self.compose(span, left, right),
           _ => {}
         }
 
         self.compile_expression(left);
+        self.temporaries += 1;
         self.compile_expression(right);
+        self.temporaries -= 1;
 
         match operator {
           operators::Binary::Plus => self.emit_opcode(span, OpCode::Add),
@@ -405,124 +616,68 @@ impl<'s> Compiler<'s> {
           operators::Binary::NotEqual => self.emit_opcode(span, OpCode::NotEqual),
           operators::Binary::GreaterEqual => self.emit_opcode(span, OpCode::GreaterEqual),
           operators::Binary::LessEqual => self.emit_opcode(span, OpCode::LessEqual),
+          operators::Binary::In => self.emit_opcode(span, OpCode::Contains),
           operators::Binary::And
           | operators::Binary::Or
           | operators::Binary::Nullish
-          | operators::Binary::Pipeline => unreachable!(),
+          | operators::Binary::Pipeline
+          | operators::Binary::Compose => unreachable!(),
         }
       }
       Expr::Assignment {
         identifier,
         expression,
+        assignment_operator,
       } => {
-        self.compile_expression(expression);
+        if let Some(operator) = *assignment_operator {
+          let variable = Expression {
+            expr: Expr::Variable { name: identifier },
+            span,
+            id: 0,
+          };
+          self.compile_expression(&variable);
+          self.temporaries += 1;
+          self.compile_expression(expression);
+          self.temporaries -= 1;
+          self.compile_assignment_operator(operator, span);
+        } else {
+          self.compile_expression(expression);
+        }
 
         let locals = self.locals.last().expect("Local stack to have item");
         if let Some(index) = locals.iter().rposition(|local| local.name == *identifier) {
-          let local = &locals[index];
-
-          if local.closed {
-            self.emit_opcode(span, OpCode::SetAllocated);
-          } else {
-            self.emit_opcode(span, OpCode::SetLocal);
-          }
-          self.emit_local_index(index, span);
-
+          self.set_local(index, span);
           return;
         }
 
-        if let Some((scope_index, local_index)) = self
-          .locals
-          .iter()
-          .enumerate()
-          .rev()
-          .skip(1)
-          .find_map(|(scope_index, locals)| {
-            locals
-              .iter()
-              .rposition(|local| local.name == *identifier)
-              .map(|local_index| (scope_index, local_index))
-          })
-        {
-          let closure_kind =
-            mem::replace(&mut self.locals[scope_index][local_index].closed, true).into();
-
-          let (upvalue_index, _) = self.closures.iter_mut().skip(scope_index).fold(
-            (local_index, closure_kind),
-            |(index, closure_kind), closures| {
-              let index = closures
-                .iter()
-                .rposition(|(i, _)| usize::from(*i) == index)
-                .unwrap_or_else(|| {
-                  closures.push((u8::try_from(index).unwrap_or(0), closure_kind));
-                  closures.len() - 1
-                });
-              (index, ClosureKind::Upvalue)
-            },
-          );
-
+        if let Some(upvalue_index) = self.resolve_upvalue(identifier) {
           self.emit_opcode(span, OpCode::SetUpvalue);
           self.emit_local_index(upvalue_index, span);
 
           return;
         }
 
-        self.emit_opcode(span, OpCode::SetGlobal);
-        self.emit_constant_string(span, identifier);
+        if self.protected_globals.contains(identifier) {
+          self.error(Error::ProtectedGlobal, span, identifier);
+        }
+
+        self.emit_constant_string(span, OpCode::SetGlobal, identifier);
       }
       Expr::Variable { name } => {
         let locals = self.locals.last().expect("Local stack to have item");
         if let Some(index) = locals.iter().rposition(|local| local.name == *name) {
-          let local = &locals[index];
-
-          if local.closed {
-            self.emit_opcode(span, OpCode::GetAllocated);
-          } else {
-            self.emit_opcode(span, OpCode::GetLocal);
-          }
-          self.emit_local_index(index, span);
-
+          self.get_local(index, span);
           return;
         }
 
-        if let Some((scope_index, local_index)) = self
-          .locals
-          .iter()
-          .enumerate()
-          .rev()
-          .skip(1)
-          .find_map(|(scope_index, locals)| {
-            locals
-              .iter()
-              .rposition(|local| local.name == *name)
-              .map(|local_index| (scope_index, local_index))
-          })
-        {
-          let closure_kind =
-            mem::replace(&mut self.locals[scope_index][local_index].closed, true).into();
-
-          let (upvalue_index, _) = self.closures.iter_mut().skip(scope_index).fold(
-            (local_index, closure_kind),
-            |(index, closure_kind), closures| {
-              let index = closures
-                .iter()
-                .rposition(|(i, _)| usize::from(*i) == index)
-                .unwrap_or_else(|| {
-                  closures.push((u8::try_from(index).unwrap_or(0), closure_kind));
-                  closures.len() - 1
-                });
-              (index, ClosureKind::Upvalue)
-            },
-          );
-
+        if let Some(upvalue_index) = self.resolve_upvalue(name) {
           self.emit_opcode(span, OpCode::GetUpvalue);
           self.emit_local_index(upvalue_index, span);
 
           return;
         }
 
-        self.emit_opcode(span, OpCode::GetGlobal);
-        self.emit_constant_string(span, name);
+        self.emit_constant_string(span, OpCode::GetGlobal, name);
       }
       Expr::Call {
         expression,
@@ -536,9 +691,7 @@ impl<'s> Compiler<'s> {
           255
         });
 
-        for argument in arguments {
-          self.compile_expression(argument);
-        }
+        self.compile_arguments(arguments, 1);
 
         self.emit_opcode(span, OpCode::Call);
         self.emit_value(span, arguments_length);
@@ -555,15 +708,54 @@ impl<'s> Compiler<'s> {
           255
         });
 
+        let optional = parameters.iter().filter(|p| p.default.is_some()).count();
+        let optional = u8::try_from(optional).unwrap_or(arity);
+
+        let loops = mem::take(&mut self.loops);
+        let temporaries = mem::take(&mut self.temporaries);
         self.closures.push(SmallVec::new());
         self.new_chunk();
         for parameter in parameters {
           self.define_variable(parameter.name, parameter.span);
         }
+        for parameter in parameters {
+          if let Some(default) = &parameter.default {
+            // Compiled as `parameter = parameter ?? default`
+            let variable = Expression {
+              expr: Expr::Variable {
+                name: parameter.name,
+              },
+              span: parameter.span,
+              id: 0,
+            };
+            let assignment = Expression {
+              expr: Expr::Assignment {
+                identifier: parameter.name,
+                expression: Box::new(Expression {
+                  expr: Expr::Binary {
+                    operator: operators::Binary::Nullish,
+                    left: Box::new(variable),
+                    right: Box::new(default.clone()),
+                  },
+                  span: default.span,
+                  id: 0,
+                }),
+                assignment_operator: None,
+              },
+              span: default.span,
+              id: 0,
+            };
+
+            self.compile_expression(&assignment);
+            self.emit_opcode(default.span, OpCode::Pop);
+          }
+        }
         self.compile_statement(body);
         self.emit_opcode(span, OpCode::Null);
         self.emit_opcode(span, OpCode::Return);
         let chunk = self.finish_chunk();
+        self.loops = loops;
+        self.temporaries = temporaries;
 
         let upvalues = self.closures.pop().expect("Closure stack to have item");
         let has_closure = !upvalues.is_empty();
@@ -572,7 +764,7 @@ impl<'s> Compiler<'s> {
           span,
           Value::from(Function {
             name: name.unwrap_or("").into(),
-            arity: Arity::new(arity),
+            arity: Arity::with_optional(arity, optional),
             chunk: chunk.into(),
             upvalues,
           }),
@@ -584,9 +776,7 @@ impl<'s> Compiler<'s> {
       }
       Expr::Comment { expression, .. } => self.compile_expression(expression),
       Expr::List { items } => {
-        for item in items {
-          self.compile_expression(item);
-        }
+        self.compile_arguments(items, 0);
 
         if let Ok(length) = u8::try_from(items.len()) {
           self.emit_opcode(span, OpCode::List);
@@ -599,10 +789,14 @@ impl<'s> Compiler<'s> {
         }
       }
       Expr::Dictionary { items } => {
+        let temporaries = self.temporaries;
         for (key, value) in items {
           self.compile_expression(key);
+          self.temporaries += 1;
           self.compile_expression(value);
+          self.temporaries += 1;
         }
+        self.temporaries = temporaries;
 
         if let Ok(length) = u8::try_from(items.len()) {
           self.emit_opcode(span, OpCode::Dict);
@@ -613,7 +807,9 @@ impl<'s> Compiler<'s> {
       }
       Expr::Index { expression, index } => {
         self.compile_expression(expression);
+        self.temporaries += 1;
         self.compile_expression(index);
+        self.temporaries -= 1;
         self.emit_opcode(span, OpCode::GetIndex);
       }
       Expr::IndexAssignment {
@@ -623,7 +819,9 @@ impl<'s> Compiler<'s> {
         assignment_operator,
       } => {
         self.compile_expression(expression);
+        self.temporaries += 1;
         self.compile_expression(index);
+        self.temporaries += 1;
 
         if let Some(operator) = *assignment_operator {
           self.emit_opcode(span, OpCode::GetTemp);
@@ -632,16 +830,14 @@ impl<'s> Compiler<'s> {
           self.emit_value(span, 1);
 
           self.emit_opcode(span, OpCode::GetIndex);
+          self.temporaries += 1;
           self.compile_expression(value);
-          match operator {
-            operators::Assignment::Plus => self.emit_opcode(span, OpCode::Add),
-            operators::Assignment::Minus => self.emit_opcode(span, OpCode::Subtract),
-            operators::Assignment::Multiply => self.emit_opcode(span, OpCode::Multiply),
-            operators::Assignment::Divide => self.emit_opcode(span, OpCode::Divide),
-          }
+          self.temporaries -= 1;
+          self.compile_assignment_operator(operator, span);
         } else {
           self.compile_expression(value);
         }
+        self.temporaries -= 2;
 
         self.emit_opcode(span, OpCode::SetIndex);
       }
@@ -650,6 +846,7 @@ impl<'s> Compiler<'s> {
         strings,
       } => {
         self.emit_constant(span, strings[0].clone().into());
+        self.temporaries += 1;
         for (index, expression) in expressions.iter().enumerate() {
           self.compile_expression(expression);
           self.emit_opcode(span, OpCode::ToString);
@@ -657,15 +854,87 @@ impl<'s> Compiler<'s> {
           self.emit_constant(span, strings[index + 1].clone().into());
           self.emit_opcode(span, OpCode::Add);
         }
+        self.temporaries -= 1;
       }
+      Expr::Do { body } => self.do_expression(span, body),
+      Expr::Loop { body } => self.loop_expression(span, body),
       Expr::ModuleAccess { module, item } => {
-        self.emit_constant(span, (*module).into());
+        let module = *self.module_aliases.get(module).unwrap_or(module);
+
+        if let Some(resolved) = self.resolve_module(module, span) {
+          self.get_resolved_module(module, &resolved, span);
+          self.check_module_exports(module, &resolved, item, span);
+          self.emit_constant(span, (*item).into());
+          self.emit_opcode(span, OpCode::GetIndex);
+          return;
+        }
+
+        self.emit_constant(span, module.into());
         self.emit_constant(span, (*item).into());
         self.emit_opcode(span, OpCode::Import);
       }
     }
   }
 
+  // A local from outside a loop which is captured inside it is allocated before the loop, as it
+  // would otherwise be allocated part way through the first iteration. Locals declared inside the
+  // loop are new each iteration, so each closure captures its own.
+  fn allocate_captured_locals(
+    &mut self,
+    span: Span,
+    condition: Option<&Expression<'s>>,
+    body: &Statement<'s>,
+  ) {
+    let mut captured = CapturedLocals {
+      locals: self.locals.last().expect("Local stack to have item"),
+      captured: Vec::new(),
+      function_depth: 0,
+    };
+    if let Some(condition) = condition {
+      captured.visit_expression(condition);
+    }
+    captured.visit_statement(body);
+
+    for index in captured.captured {
+      self.locals.last_mut().expect("Local stack to have item")[index].closed = true;
+      self.emit_opcode(span, OpCode::Allocate);
+      self.emit_local_index(index, span);
+    }
+  }
+
+  fn resolve_upvalue(&mut self, name: &str) -> Option<usize> {
+    let find_local = |(scope_index, locals): (usize, &Vec<Local>)| {
+      let local_index = locals.iter().rposition(|local| local.name == name)?;
+      Some((scope_index, local_index))
+    };
+    let (scope_index, local_index) = self
+      .locals
+      .iter()
+      .enumerate()
+      .rev()
+      .skip(1)
+      .find_map(find_local)?;
+
+    let closure_kind = mem::replace(&mut self.locals[scope_index][local_index].closed, true).into();
+
+    // If an index doesn't fit in a byte, the upvalue index is out of range and is reported when emitted
+    let upvalue = self.closures.iter_mut().skip(scope_index).try_fold(
+      (local_index, closure_kind),
+      |(index, closure_kind), closures| {
+        let index = match closures.iter().rposition(|(i, _)| usize::from(*i) == index) {
+          Some(index) => index,
+          None => {
+            closures.push((u8::try_from(index).ok()?, closure_kind));
+            closures.len() - 1
+          }
+        };
+        Some((index, ClosureKind::Upvalue))
+      },
+    );
+
+    Some(upvalue.map_or(usize::MAX, |(index, _)| index))
+  }
+
   fn define_variable(&mut self, identifier: &'s str, span: Span) -> usize {
     if self.scope_depth > 0 {
       let locals = self.locals.last_mut().expect("Local stack to have item");
@@ -675,6 +944,8 @@ impl<'s> Compiler<'s> {
         .any(|local| local.name == identifier && local.depth == self.scope_depth)
       {
         self.error(Error::VariableAlreadyExists, span, identifier);
+      } else if locals.len() >= usize::from(u8::MAX) {
+        self.error(Error::TooManyLocals, span, "");
       } else {
         locals.push(Local {
           name: identifier,
@@ -684,8 +955,11 @@ impl<'s> Compiler<'s> {
         return locals.len().saturating_sub(1);
       }
     } else {
-      self.emit_opcode(span, OpCode::DefineGlobal);
-      self.emit_constant_string(span, identifier);
+      if self.protected_globals.contains(&identifier) {
+        self.error(Error::ProtectedGlobal, span, identifier);
+      }
+
+      self.emit_constant_string(span, OpCode::DefineGlobal, identifier);
     }
 
     0
@@ -746,33 +1020,549 @@ impl<'s> Compiler<'s> {
         255
       };
 
+      self.temporaries += 1;
       self.compile_expression(left);
-      for argument in arguments {
-        self.compile_expression(argument);
-      }
+      self.compile_arguments(arguments, 1);
+      self.temporaries -= 1;
 
       self.emit_opcode(span, OpCode::Call);
       self.emit_value(span, arguments_length);
     } else {
       self.compile_expression(right);
+      self.temporaries += 1;
       self.compile_expression(left);
+      self.temporaries -= 1;
       self.emit_opcode(span, OpCode::Call);
       self.emit_value(span, 1);
     }
   }
+
+  fn compose(&mut self, span: Span, left: &Expression<'s>, right: &Expression<'s>) {
+    // Compiled as `((f, g) => (x) => f(g(x)))(left, right)`
+    let expression = |expr| Expression { expr, span, id: 0 };
+    let variable = |name| expression(Expr::Variable { name });
+    let call = |function, argument| {
+      expression(Expr::Call {
+        expression: Box::new(function),
+        arguments: vec![argument],
+      })
+    };
+    let function = |parameters: &[&'s str], body| {
+      let parameters = (parameters.iter())
+        .map(|name| Parameter {
+          name,
+          span,
+          type_: None,
+          default: None,
+        })
+        .collect();
+      let body = Statement {
+        stmt: Stmt::Return {
+          expression: Some(body),
+        },
+        span,
+        id: 0,
+      };
+
+      expression(Expr::Function {
+        parameters,
+        return_type: None,
+        body: Box::new(body),
+        name: None,
+      })
+    };
+
+    let applied = call(variable("f"), call(variable("g"), variable("x")));
+    let composed = function(&["x"], applied);
+    let composition = expression(Expr::Call {
+      expression: Box::new(function(&["f", "g"], composed)),
+      arguments: vec![left.clone(), right.clone()],
+    });
+
+    self.compile_expression(&composition);
+  }
+}
+
+// Loops
+impl<'s> Compiler<'s> {
+  // Expressions which stay on the stack after `already_on_stack` other values
+  fn compile_arguments(&mut self, arguments: &[Expression<'s>], already_on_stack: usize) {
+    let temporaries = self.temporaries;
+    self.temporaries += already_on_stack;
+
+    for argument in arguments {
+      self.compile_expression(argument);
+      self.temporaries += 1;
+    }
+
+    self.temporaries = temporaries;
+  }
+
+  // Inside a scope the value being destructured is a placeholder local, until the scope ends
+  fn destructuring_source(&mut self, span: Span) -> usize {
+    if self.scope_depth > 0 {
+      self.add_placeholder_local(span)
+    } else {
+      let locals = self.locals.last().expect("Local stack to have item");
+      locals.len()
+    }
+  }
+
+  fn finish_destructuring(&mut self, span: Span) {
+    if self.scope_depth == 0 {
+      self.emit_opcode(span, OpCode::Pop);
+    }
+  }
+
+  // Compiled as `if (key in source) source[key] else default`
+  fn dict_item_or_default(
+    &mut self,
+    source: usize,
+    key: &str,
+    default: &Expression<'s>,
+    span: Span,
+  ) {
+    self.emit_constant(span, Value::from(key));
+    self.emit_opcode(span, OpCode::GetLocal);
+    self.emit_local_index(source, span);
+    self.emit_opcode(span, OpCode::Contains);
+
+    let else_jump = self.emit_jump(span, OpCode::JumpIfFalse);
+    self.emit_opcode(span, OpCode::Pop);
+    self.emit_opcode(span, OpCode::GetLocal);
+    self.emit_local_index(source, span);
+    self.emit_constant(span, Value::from(key));
+    self.emit_opcode(span, OpCode::GetIndex);
+    let end_jump = self.emit_jump(span, OpCode::Jump);
+
+    self.patch_jump(span, else_jump);
+    self.emit_opcode(span, OpCode::Pop);
+    // Outside a scope, the value being destructured is still on the stack
+    let temporaries = self.temporaries;
+    if self.scope_depth == 0 {
+      self.temporaries += 1;
+    }
+    self.compile_expression(default);
+    self.temporaries = temporaries;
+    self.patch_jump(span, end_jump);
+  }
+
+  fn add_placeholder_local(&mut self, span: Span) -> usize {
+    let locals = self.locals.last().expect("Local stack to have item");
+    if locals.len() >= usize::from(u8::MAX) {
+      self.error(Error::TooManyLocals, span, "");
+    }
+
+    let locals = self.locals.last_mut().expect("Local stack to have item");
+    locals.push(Local {
+      name: "",
+      depth: self.scope_depth,
+      closed: false,
+    });
+    locals.len() - 1
+  }
+
+  fn is_module_top_level(&self) -> bool {
+    self.in_module && self.locals.len() == 1 && self.scope_depth == 1
+  }
+
+  fn set_local(&mut self, index: usize, span: Span) {
+    let locals = self.locals.last().expect("Local stack to have item");
+
+    if locals[index].closed {
+      self.emit_opcode(span, OpCode::SetAllocated);
+    } else {
+      self.emit_opcode(span, OpCode::SetLocal);
+    }
+    self.emit_local_index(index, span);
+  }
+
+  fn get_local(&mut self, index: usize, span: Span) {
+    let locals = self.locals.last().expect("Local stack to have item");
+
+    if locals[index].closed {
+      self.emit_opcode(span, OpCode::GetAllocated);
+    } else {
+      self.emit_opcode(span, OpCode::GetLocal);
+    }
+    self.emit_local_index(index, span);
+  }
+
+  fn resolve_module(&mut self, module: &str, span: Span) -> Option<ResolvedModule> {
+    if self.context.modules().contains(&module) {
+      return None;
+    }
+    if let Some(resolved) = self.resolved_modules.get(module) {
+      return Some(resolved.clone());
+    }
+
+    let source = self.context.import_resolver()?.resolve(module)?;
+    if self.importing.iter().any(|importing| importing == module) {
+      self.error(Error::ImportCycle, span, module);
+      return None;
+    }
+
+    match self.compile_module(module, &source) {
+      Ok(resolved) => {
+        self
+          .resolved_modules
+          .insert(module.to_string(), resolved.clone());
+        Some(resolved)
+      }
+      Err(error) => {
+        self.error(Error::InModule(error), span, module);
+        None
+      }
+    }
+  }
+
+  fn compile_module(&mut self, module: &str, source: &str) -> Result<ResolvedModule, Diagnostic> {
+    let parser = Parser::new(source);
+    let mut compiler = Compiler::new(source);
+    compiler.context = self.context;
+    compiler.protected_globals = self.protected_globals;
+    compiler.resolved_modules = mem::take(&mut self.resolved_modules);
+    compiler.importing = mem::take(&mut self.importing);
+    compiler.importing.push(module.to_string());
+    compiler.in_module = true;
+
+    // Top level variables are locals, so the module doesn't define globals when imported
+    compiler.begin_scope();
+    for statement in parser {
+      match statement {
+        Ok(statement) => compiler.compile_statement(&statement),
+        Err(error) => compiler.error = compiler.error.or(Some(error)),
+      }
+
+      if compiler.error.is_some() {
+        break;
+      }
+    }
+
+    let span = Span { start: 0, end: 0 };
+    let exports: Vec<_> = compiler.locals[0]
+      .iter()
+      .map(|local| local.name)
+      .enumerate()
+      .filter(|(_, name)| !name.is_empty())
+      .collect();
+    for (slot, name) in &exports {
+      compiler.emit_constant(span, (*name).into());
+      compiler.get_local(*slot, span);
+    }
+    if let Ok(length) = u8::try_from(exports.len()) {
+      compiler.emit_opcode(span, OpCode::Dict);
+      compiler.emit_value(span, length);
+    } else {
+      compiler.error(Error::TooLargeDict, span, "");
+    }
+    compiler.emit_opcode(span, OpCode::Return);
+
+    self.resolved_modules = mem::take(&mut compiler.resolved_modules);
+    self.importing = mem::take(&mut compiler.importing);
+    self.importing.pop();
+
+    if let Some(error) = compiler.error {
+      return Err(error);
+    }
+
+    Ok(ResolvedModule {
+      function: Value::from(Function {
+        name: module.into(),
+        arity: Arity::new(0),
+        chunk: compiler.chunk.finalize().into(),
+        upvalues: SmallVec::new(),
+      }),
+      exports: exports
+        .iter()
+        .map(|(_, name)| (*name).to_string())
+        .collect(),
+    })
+  }
+
+  // Leaves a dict of the module's variables on the stack. The top level of the module is only run
+  // the first time, then the dict is kept in a global which scripts can't name
+  fn get_resolved_module(&mut self, module: &str, resolved: &ResolvedModule, span: Span) {
+    let global = format!("<module {module}>");
+    self.emit_constant_string(span, OpCode::GetGlobalOptional, &global);
+    let run_module = self.emit_jump(span, OpCode::JumpIfNull);
+    let already_run = self.emit_jump(span, OpCode::Jump);
+
+    self.patch_jump(span, run_module);
+    self.emit_opcode(span, OpCode::Pop);
+    self.emit_constant(span, resolved.function.clone());
+    self.emit_opcode(span, OpCode::Call);
+    self.emit_value(span, 0);
+    self.emit_constant_string(span, OpCode::DefineGlobal, &global);
+    self.emit_constant_string(span, OpCode::GetGlobal, &global);
+
+    self.patch_jump(span, already_run);
+  }
+
+  fn check_module_exports(
+    &mut self,
+    module: &str,
+    resolved: &ResolvedModule,
+    item: &str,
+    span: Span,
+  ) {
+    if !resolved.exports.iter().any(|export| export == item) {
+      let module = module.to_string();
+      self.error(Error::ItemNotFound { module }, span, item);
+    }
+  }
+
+  fn import_resolved_module(
+    &mut self,
+    module: &str,
+    resolved: &ResolvedModule,
+    items: &[AliasItem<'s>],
+    span: Span,
+  ) {
+    self.get_resolved_module(module, resolved, span);
+
+    // Inside a scope the dict is kept as a placeholder local, so the imported locals are above it
+    let locals = self.locals.last().expect("Local stack to have item");
+    let module_slot = locals.len();
+    if self.scope_depth > 0 {
+      self.add_placeholder_local(span);
+    }
+
+    for item in items {
+      self.check_module_exports(module, resolved, item.name, item.span);
+
+      self.emit_opcode(span, OpCode::GetLocal);
+      self.emit_local_index(module_slot, span);
+      self.emit_constant(span, item.name.into());
+      self.emit_opcode(span, OpCode::GetIndex);
+      self.define_variable(item.get_name(), item.span);
+    }
+
+    if self.scope_depth == 0 {
+      self.emit_opcode(span, OpCode::Pop);
+    }
+  }
+
+  fn loop_expression(&mut self, span: Span, body: &Statement<'s>) {
+    // Values already on the stack are given placeholder locals, so locals
+    // declared inside the loop are given the correct stack slot
+    let temporaries = mem::take(&mut self.temporaries);
+    for _ in 0..temporaries {
+      self.add_placeholder_local(span);
+    }
+
+    self.emit_opcode(span, OpCode::Null);
+    let result = self.add_placeholder_local(span);
+    self.allocate_captured_locals(span, None, body);
+
+    self.loops.push(Loop {
+      scope_depth: self.scope_depth,
+      breaks: Vec::new(),
+      result: Some(result),
+    });
+
+    let loop_start = self.length();
+    self.begin_scope();
+    self.compile_statement(body);
+    self.end_scope();
+    self.emit_loop(span, loop_start);
+    self.patch_breaks(span);
+
+    // The result is left on the stack as the value of the expression
+    let locals = self.locals.last_mut().expect("Local stack to have item");
+    locals.truncate(locals.len() - temporaries - 1);
+    self.temporaries = temporaries;
+  }
+
+  fn do_expression(&mut self, span: Span, body: &Statement<'s>) {
+    // Like loops, values already on the stack are given placeholder locals
+    let temporaries = mem::take(&mut self.temporaries);
+    for _ in 0..temporaries {
+      self.add_placeholder_local(span);
+    }
+
+    self.emit_opcode(span, OpCode::Null);
+    let result = self.add_placeholder_local(span);
+
+    let statements = match &body.stmt {
+      Stmt::Block { body } => body.as_slice(),
+      _ => slice::from_ref(body),
+    };
+    let last = statements
+      .iter()
+      .rposition(|statement| !matches!(statement.stmt, Stmt::Comment { .. }));
+
+    self.begin_scope();
+    for (index, statement) in statements.iter().enumerate() {
+      match &statement.stmt {
+        Stmt::Expression { expression } if Some(index) == last => {
+          self.compile_expression(expression);
+          self.emit_opcode(statement.span, OpCode::SetLocal);
+          self.emit_local_index(result, statement.span);
+          self.emit_opcode(statement.span, OpCode::Pop);
+        }
+        _ => self.compile_statement(statement),
+      }
+    }
+    self.end_scope();
+
+    // The result is left on the stack as the value of the expression
+    let locals = self.locals.last_mut().expect("Local stack to have item");
+    locals.truncate(locals.len() - temporaries - 1);
+    self.temporaries = temporaries;
+  }
+
+  fn break_statement(&mut self, span: Span, expression: Option<&Expression<'s>>) {
+    let (scope_depth, result) = match self.loops.last() {
+      Some(loop_) => (loop_.scope_depth, loop_.result),
+      None => return self.error(Error::BreakOutsideLoop, span, ""),
+    };
+
+    match (expression, result) {
+      (Some(expression), Some(result)) => {
+        self.compile_expression(expression);
+        self.emit_opcode(span, OpCode::SetLocal);
+        self.emit_local_index(result, span);
+        self.emit_opcode(span, OpCode::Pop);
+      }
+      (Some(_), None) => return self.error(Error::BreakValueFromWhile, span, ""),
+      (None, _) => {}
+    }
+
+    let locals = self.locals.last().expect("Local stack to have item");
+    let inside_loop = locals
+      .iter()
+      .rev()
+      .take_while(|local| local.depth > scope_depth)
+      .count();
+    (0..inside_loop).for_each(|_| self.emit_opcode(span, OpCode::Pop));
+
+    let jump = self.emit_jump(span, OpCode::Jump);
+    let loop_ = self.loops.last_mut().expect("Loop stack to have item");
+    loop_.breaks.push(jump);
+  }
+
+  fn patch_breaks(&mut self, span: Span) {
+    let loop_ = self.loops.pop().expect("Loop stack to have item");
+
+    for jump in loop_.breaks {
+      self.patch_jump(span, jump);
+    }
+  }
+}
+
+// Compile Time Values
+impl Compiler<'_> {
+  fn config_value(&self, expression: &Expression) -> Option<Value> {
+    if let Expr::Call {
+      expression,
+      arguments,
+      ..
+    } = &expression.expr
+      && let Expr::ModuleAccess {
+        module: "config",
+        item: "get",
+      } = expression.expr
+      && let [argument] = arguments.as_slice()
+      && let Expr::Literal {
+        type_: LiteralType::String,
+        value,
+      } = argument.expr
+    {
+      Some(self.context.config(&Parser::string(value)))
+    } else {
+      None
+    }
+  }
+
+  // Conditions which only depend on `config::get`, so which branch runs is known
+  fn config_condition(&self, expression: &Expression) -> Option<Value> {
+    match &expression.expr {
+      Expr::Group { expression, .. }
+      | Expr::Comment { expression, .. }
+      | Expr::Cast { expression, .. }
+      | Expr::Ascription { expression, .. } => self.config_condition(expression),
+      Expr::Unary {
+        operator: operators::Unary::Not,
+        expression,
+      } => self
+        .config_condition(expression)
+        .map(|value| value.is_falsy().into()),
+      _ => self.config_value(expression),
+    }
+  }
 }
 
 pub fn compile(source: &str) -> Result<Rc<Chunk>, Diagnostic> {
+  compile_with_context(source, &crate::context::Empty)
+}
+
+pub fn compile_with_context(source: &str, context: &dyn Context) -> Result<Rc<Chunk>, Diagnostic> {
+  compile_source(source, context, false)
+}
+
+// If the last statement is an expression, its value is returned instead of being discarded
+pub fn compile_keeping_last_value(
+  source: &str,
+  context: &dyn Context,
+) -> Result<Rc<Chunk>, Diagnostic> {
+  compile_source(source, context, true)
+}
+
+fn compile_source(
+  source: &str,
+  context: &dyn Context,
+  keep_last_value: bool,
+) -> Result<Rc<Chunk>, Diagnostic> {
   let parser = Parser::new(source);
   let mut compiler = Compiler::new(source);
+  compiler.context = context;
+  compiler.protected_globals = context.protected_globals();
 
-  for statement in parser {
-    compiler.compile_statement(&statement?);
+  let mut statements = parser.peekable();
+  while let Some(statement) = statements.next() {
+    let statement = statement?;
+
+    if keep_last_value
+      && statements.peek().is_none()
+      && let Stmt::Expression { expression } = &statement.stmt
+    {
+      compiler.compile_expression(expression);
+      compiler.emit_opcode_blank(OpCode::Return);
+    } else {
+      compiler.compile_statement(&statement);
+    }
 
     if let Some(error) = compiler.error {
       return Err(error);
     }
   }
 
-  Ok(compiler.finish().into())
+  let mut chunk = compiler.finish();
+  chunk.metadata = Metadata::new(source, keep_last_value);
+  Ok(chunk.into())
+}
+
+pub(crate) fn compile_statements<'s>(
+  source: &'s str,
+  context: &'s dyn Context,
+) -> impl Iterator<Item = Result<Rc<Chunk>, Diagnostic>> + 's {
+  let mut module_aliases = HashMap::default();
+  let mut resolved_modules = HashMap::default();
+
+  Parser::new(source).map(move |statement| {
+    let mut compiler = Compiler::new(source);
+    compiler.context = context;
+    compiler.protected_globals = context.protected_globals();
+    compiler.module_aliases = mem::take(&mut module_aliases);
+    compiler.resolved_modules = mem::take(&mut resolved_modules);
+    compiler.compile_statement(&statement?);
+    module_aliases = mem::take(&mut compiler.module_aliases);
+    resolved_modules = mem::take(&mut compiler.resolved_modules);
+
+    match compiler.error {
+      Some(error) => Err(error),
+      None => Ok(compiler.finish().into()),
+    }
+  })
 }
@@ -1,7 +1,9 @@
 use crate::{
   chunk::{Chunk, OpCode},
+  collections::String,
   Value, VM,
 };
+use alloc::{borrow::Cow, vec::Vec};
 
 #[derive(Clone)]
 pub enum ImportValue {
@@ -19,9 +21,54 @@ impl ImportValue {
   }
 }
 
+// Supplies the source code of modules which aren't built in, e.g. from editor buffers or a
+// database. Sources are compiled when an import is compiled, and reused by later imports.
+pub trait ImportResolver {
+  fn resolve(&self, module: &str) -> Option<Cow<'_, str>>;
+}
+impl<F> ImportResolver for F
+where
+  F: Fn(&str) -> Option<alloc::string::String>,
+{
+  fn resolve(&self, module: &str) -> Option<Cow<'_, str>> {
+    self(module).map(Cow::Owned)
+  }
+}
+
 pub trait Context {
   fn get_value(&self, module: &str, value: &str) -> ImportValue;
   fn define_globals(&self, vm: &mut VM);
+
+  // Globals which scripts can't redefine
+  fn protected_globals(&self) -> &[&str] {
+    &[]
+  }
+
+  fn modules(&self) -> Vec<&str> {
+    Vec::new()
+  }
+  fn items(&self, _module: &str) -> Vec<&str> {
+    Vec::new()
+  }
+
+  // Values for `config::get`, which are resolved at compile time when the flag is a literal
+  fn config(&self, _flag: &str) -> Value {
+    Value::NULL
+  }
+
+  // Used when converting values to strings in template strings, `None` uses the default format
+  fn format_value(&self, _value: &Value) -> Option<String> {
+    None
+  }
+
+  fn import_resolver(&self) -> Option<&dyn ImportResolver> {
+    None
+  }
+
+  // Reads a line for `input` after showing the prompt, `None` when there is no more input
+  fn read_line(&self, _prompt: &str) -> Option<String> {
+    None
+  }
 }
 
 pub struct Empty;
@@ -0,0 +1,34 @@
+use crate::{compiler::compile_statements, context::Context, RuntimeError, VM};
+use bang_syntax::Diagnostic;
+use core::fmt;
+
+#[derive(Debug)]
+pub enum InterpretError {
+  Compile(Diagnostic),
+  Runtime(RuntimeError),
+}
+impl fmt::Display for InterpretError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Compile(diagnostic) => write!(f, "{diagnostic}"),
+      Self::Runtime(error) => write!(f, "{error}"),
+    }
+  }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for InterpretError {}
+
+pub fn interpret_streaming<'c>(
+  source: &str,
+  context: &'c dyn Context,
+) -> Result<VM<'c>, InterpretError> {
+  let mut vm = VM::new(context);
+
+  // Each top level statement is run as soon as it is compiled, so globals persist between them
+  for chunk in compile_statements(source, context) {
+    let chunk = chunk.map_err(InterpretError::Compile)?;
+    vm.run(&chunk).map_err(InterpretError::Runtime)?;
+  }
+
+  Ok(vm)
+}
@@ -1,24 +1,43 @@
 #![feature(let_chains)]
 #![feature(strict_provenance)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod chunk;
 mod compiler;
 pub mod context;
+mod interpret;
 pub mod value;
 mod vm;
 
-pub type Chunk = std::rc::Rc<chunk::Chunk>;
-pub use compiler::compile;
+pub type Chunk = alloc::rc::Rc<chunk::Chunk>;
+pub use compiler::{compile, compile_keeping_last_value, compile_with_context};
+pub use interpret::{interpret_streaming, InterpretError};
 pub use value::Value;
-pub use vm::{RuntimeError, VM};
+pub use vm::{GlobalsSnapshot, RuntimeError, VM};
 
 pub mod errors {
+  pub use super::value::ConversionError as Conversion;
   pub use super::vm::RuntimeError as Runtime;
   pub use super::vm::{StackTraceLocation, StackTraceLocationKind};
 }
 
 pub mod collections {
-  pub use rustc_hash::FxHashMap as HashMap;
-  pub use rustc_hash::FxHashSet as HashSet;
   pub use smartstring::alias::String;
+
+  #[cfg(feature = "std")]
+  pub use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+  #[cfg(feature = "std")]
+  pub use std::collections::hash_map;
+
+  // Without std, use hashbrown (which std's maps are built on) with the same hasher
+  #[cfg(not(feature = "std"))]
+  type FxBuildHasher = core::hash::BuildHasherDefault<rustc_hash::FxHasher>;
+  #[cfg(not(feature = "std"))]
+  pub use hashbrown::hash_map;
+  #[cfg(not(feature = "std"))]
+  pub type HashMap<K, V> = hashbrown::HashMap<K, V, FxBuildHasher>;
+  #[cfg(not(feature = "std"))]
+  pub type HashSet<V> = hashbrown::HashSet<V, FxBuildHasher>;
 }
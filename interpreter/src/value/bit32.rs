@@ -1,14 +1,17 @@
 pub use super::Object;
-use std::{cell::RefCell, mem, ptr, rc::Rc};
+use alloc::rc::Rc;
+use core::{cell::RefCell, ptr};
 
+// The high half of a number is stored in the tag, and the low half in the pointer. Pointers are
+// only 32 bits on 32-bit platforms, but on 64-bit platforms (when testing the layout with the
+// `bit32` feature) the pointer is wider, so the halves are always split explicitly
 #[derive(Copy, Clone, PartialEq, Eq)]
-#[repr(C)]
 pub struct Inner {
-  tag: usize,
+  tag: u32,
   pointer: *const Object,
 }
 
-pub struct Value(Inner);
+pub struct Value(pub(super) Inner);
 
 impl Value {
   pub const NULL: Self = Self(NULL);
@@ -26,11 +29,11 @@ impl Value {
     (self.0.tag & IS_NUMBER) != IS_NUMBER
   }
   pub fn as_number(&self) -> f64 {
-    unsafe { mem::transmute(self.0) }
+    f64::from_bits(self.as_bytes())
   }
 
   pub fn as_bytes(&self) -> u64 {
-    unsafe { mem::transmute(self.0) }
+    (u64::from(self.0.tag) << 32) ^ self.0.pointer.addr() as u64
   }
 
   #[must_use]
@@ -80,11 +83,12 @@ impl Drop for Value {
 }
 
 impl From<f64> for Value {
+  #[allow(clippy::cast_possible_truncation)] // each half is 32 bits
   fn from(value: f64) -> Self {
-    let [byte1, byte2]: [usize; 2] = unsafe { mem::transmute(value) };
+    let bits = value.to_bits();
     Self(Inner {
-      tag: byte1,
-      pointer: ptr::invalid(byte2),
+      tag: (bits >> 32) as u32,
+      pointer: ptr::invalid(bits as u32 as usize),
     })
   }
 }
@@ -99,9 +103,9 @@ impl From<Rc<Object>> for Value {
   }
 }
 
-const IS_PTR: usize = 0b1111_1111_1111_1111_1111_1111_1111_1110;
-const IS_ALLOCATED: usize = 0b1111_1111_1111_1111_1111_1111_1111_1100;
-const IS_NUMBER: usize = 0b0111_1111_1111_1000_0000_0000_0000_0000;
+const IS_PTR: u32 = 0b1111_1111_1111_1111_1111_1111_1111_1110;
+const IS_ALLOCATED: u32 = 0b1111_1111_1111_1111_1111_1111_1111_1100;
+const IS_NUMBER: u32 = 0b0111_1111_1111_1100_0000_0000_0000_0000;
 
 pub const TRUE: Inner = Inner {
   tag: 0b1111_1111_1111_1100_0000_0000_0000_0000,
@@ -1,7 +1,8 @@
 pub use super::Object;
-use std::{cell::RefCell, mem, ptr, rc::Rc};
+use alloc::rc::Rc;
+use core::{cell::RefCell, mem, ptr};
 
-pub struct Value(*const Object);
+pub struct Value(pub(super) *const Object);
 
 impl Value {
   pub const NULL: Self = Self(NULL);
@@ -94,7 +95,7 @@ impl From<Rc<Object>> for Value {
   }
 }
 
-const _ALIGNMENT_ASSERT: () = debug_assert!(std::mem::align_of::<Object>() >= 8);
+const _ALIGNMENT_ASSERT: () = debug_assert!(core::mem::align_of::<Object>() >= 8);
 
 const TO_STORED: usize =
   0b1111_1111_1111_1111_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0101;
@@ -0,0 +1,126 @@
+use super::{Object, Value};
+use alloc::collections::BTreeSet;
+use core::fmt::{self, Write};
+use smartstring::alias::String;
+
+// Where two values first differ, such as `[1]['a']`, with `None` for a missing item
+#[derive(Debug)]
+pub struct Mismatch {
+  pub path: String,
+  pub left: Option<Value>,
+  pub right: Option<Value>,
+}
+impl fmt::Display for Mismatch {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if !self.path.is_empty() {
+      write!(f, "at {}: ", self.path)?;
+    }
+
+    match (&self.left, &self.right) {
+      (Some(left), Some(right)) => write!(f, "{left:?} != {right:?}"),
+      (Some(left), None) => write!(f, "{left:?} is missing on the right"),
+      (None, Some(right)) => write!(f, "{right:?} is missing on the left"),
+      (None, None) => write!(f, "missing on both sides"),
+    }
+  }
+}
+
+impl Value {
+  // Compares lists and dicts by their items, with numbers allowed to differ by the tolerance.
+  // Like `Value::equals` it is safe for cyclic values, but also gives where the values differ.
+  pub fn deep_eq_with_tolerance(&self, other: &Self, tolerance: f64) -> Result<(), Mismatch> {
+    let mut comparison = Comparison {
+      tolerance,
+      seen: BTreeSet::new(),
+      path: String::new(),
+    };
+    comparison.compare(self, other)
+  }
+}
+
+struct Comparison {
+  tolerance: f64,
+  seen: BTreeSet<(u64, u64)>,
+  path: String,
+}
+impl Comparison {
+  fn mismatch(&self, left: Option<&Value>, right: Option<&Value>) -> Mismatch {
+    Mismatch {
+      path: self.path.clone(),
+      left: left.cloned(),
+      right: right.cloned(),
+    }
+  }
+
+  fn compare(&mut self, a: &Value, b: &Value) -> Result<(), Mismatch> {
+    if a.is_number() && b.is_number() {
+      let (x, y) = (a.as_number(), b.as_number());
+      let equal = x == y || (x.is_nan() && y.is_nan()) || (x - y).abs() <= self.tolerance;
+      return if equal {
+        Ok(())
+      } else {
+        Err(self.mismatch(Some(a), Some(b)))
+      };
+    }
+
+    // A pair already being compared is part of a cycle, which matches if nothing else differs
+    if a.as_bytes() == b.as_bytes() || !self.seen.insert((a.as_bytes(), b.as_bytes())) {
+      return Ok(());
+    }
+    if !a.is_object() || !b.is_object() {
+      return Err(self.mismatch(Some(a), Some(b)));
+    }
+
+    match (a.as_object(), b.as_object()) {
+      (Object::List(left), Object::List(right)) => {
+        let (left, right) = (left.borrow(), right.borrow());
+
+        for index in 0..left.len().max(right.len()) {
+          self.at_path(format_args!("[{index}]"), |comparison| {
+            match (left.get(index), right.get(index)) {
+              (Some(left), Some(right)) => comparison.compare(left, right),
+              (left, right) => Err(comparison.mismatch(left, right)),
+            }
+          })?;
+        }
+        Ok(())
+      }
+      (Object::Dict(left), Object::Dict(right)) => {
+        let (left, right) = (left.borrow(), right.borrow());
+
+        for (key, value) in left.iter() {
+          self.at_path(format_args!("[{key:?}]"), |comparison| {
+            match right.get(key) {
+              Some(other) => comparison.compare(value, other),
+              None => Err(comparison.mismatch(Some(value), None)),
+            }
+          })?;
+        }
+        for (key, value) in right.iter().filter(|(key, _)| !left.contains_key(*key)) {
+          self.at_path(format_args!("[{key:?}]"), |comparison| {
+            Err(comparison.mismatch(None, Some(value)))
+          })?;
+        }
+        Ok(())
+      }
+      _ if Value::equals(a, b, false, &mut BTreeSet::new()) => Ok(()),
+      _ => Err(self.mismatch(Some(a), Some(b))),
+    }
+  }
+
+  fn at_path(
+    &mut self,
+    segment: fmt::Arguments,
+    compare: impl FnOnce(&mut Self) -> Result<(), Mismatch>,
+  ) -> Result<(), Mismatch> {
+    let length = self.path.len();
+    self
+      .path
+      .write_fmt(segment)
+      .expect("No errors writing to a string");
+
+    compare(self)?;
+    self.path.truncate(length);
+    Ok(())
+  }
+}
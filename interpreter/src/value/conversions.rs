@@ -0,0 +1,123 @@
+use super::{Object, Value};
+use core::fmt;
+use smartstring::alias::String;
+
+// Numbers are stored as f64, so integers beyond 2^53 can't all be represented exactly
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+  WrongType {
+    expected: &'static str,
+    found: &'static str,
+  },
+  NotInteger,
+  OutOfRange,
+}
+impl fmt::Display for ConversionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::WrongType { expected, found } => write!(f, "Expected {expected}, but got {found}"),
+      Self::NotInteger => write!(f, "Number is not a whole number"),
+      Self::OutOfRange => write!(f, "Number is too large to be represented exactly"),
+    }
+  }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
+
+impl Value {
+  pub fn try_from_i64(value: i64) -> Result<Self, ConversionError> {
+    if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&value) {
+      #[allow(clippy::cast_precision_loss)]
+      Ok(Self::from(value as f64))
+    } else {
+      Err(ConversionError::OutOfRange)
+    }
+  }
+
+  pub fn try_from_u64(value: u64) -> Result<Self, ConversionError> {
+    i64::try_from(value)
+      .map_err(|_| ConversionError::OutOfRange)
+      .and_then(Self::try_from_i64)
+  }
+
+  // Only whole numbers which are exactly representable convert, nothing is rounded
+  pub fn as_i64_checked(&self) -> Option<i64> {
+    self.to_i64().ok()
+  }
+
+  #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+  fn to_i64(&self) -> Result<i64, ConversionError> {
+    if !self.is_number() {
+      return Err(ConversionError::WrongType {
+        expected: "number",
+        found: self.get_type(),
+      });
+    }
+
+    let number = self.as_number();
+    let integer = number as i64;
+    if !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&integer) {
+      Err(ConversionError::OutOfRange)
+    } else if integer as f64 != number {
+      Err(ConversionError::NotInteger)
+    } else {
+      Ok(integer)
+    }
+  }
+}
+
+impl TryFrom<Value> for i64 {
+  type Error = ConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    value.to_i64()
+  }
+}
+impl TryFrom<Value> for usize {
+  type Error = ConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    Self::try_from(value.to_i64()?).map_err(|_| ConversionError::OutOfRange)
+  }
+}
+impl TryFrom<Value> for bool {
+  type Error = ConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    if value == Value::TRUE {
+      Ok(true)
+    } else if value == Value::FALSE {
+      Ok(false)
+    } else {
+      Err(ConversionError::WrongType {
+        expected: "boolean",
+        found: value.get_type(),
+      })
+    }
+  }
+}
+impl TryFrom<Value> for String {
+  type Error = ConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    if value.is_object()
+      && let Object::String(string) = value.as_object()
+    {
+      Ok(string.clone())
+    } else {
+      Err(ConversionError::WrongType {
+        expected: "string",
+        found: value.get_type(),
+      })
+    }
+  }
+}
+impl TryFrom<Value> for alloc::string::String {
+  type Error = ConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    String::try_from(value).map(Into::into)
+  }
+}
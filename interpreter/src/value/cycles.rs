@@ -0,0 +1,86 @@
+use super::{Object, Value};
+use alloc::vec::Vec;
+use core::fmt;
+
+// A collection which contains itself is labelled `<ref *1>`, and where it appears inside itself is
+// shown as `[Circular *1]`, so it is clear where each cycle points back to
+pub(crate) struct Cycles {
+  // Collections currently being formatted, from the outermost
+  path: Vec<u64>,
+  targets: Vec<u64>,
+}
+impl Cycles {
+  pub(crate) fn new(value: &Value) -> Self {
+    let mut cycles = Self {
+      path: Vec::new(),
+      targets: Vec::new(),
+    };
+    cycles.find(value);
+    cycles
+  }
+
+  fn find(&mut self, value: &Value) {
+    if !value.is_object() || !value.as_object().is_possibly_cyclic() {
+      return;
+    }
+
+    let id = value.as_bytes();
+    if self.path.contains(&id) {
+      if !self.targets.contains(&id) {
+        self.targets.push(id);
+      }
+      return;
+    }
+
+    self.path.push(id);
+    match value.as_object() {
+      Object::List(list) => list.borrow().iter().for_each(|item| self.find(item)),
+      Object::Set(set) => set.borrow().iter().for_each(|item| self.find(item)),
+      Object::Dict(dict) => dict.borrow().iter().for_each(|(key, value)| {
+        self.find(key);
+        self.find(value);
+      }),
+      _ => {}
+    }
+    self.path.pop();
+  }
+
+  // How deeply nested the items of the current collection are
+  pub(crate) fn depth(&self) -> usize {
+    self.path.len()
+  }
+
+  pub(crate) fn format(
+    &mut self,
+    f: &mut fmt::Formatter<'_>,
+    value: &Value,
+    debug: bool,
+  ) -> fmt::Result {
+    if !value.is_object() {
+      return if value.is_allocated() {
+        write!(f, "pointer")
+      } else {
+        write!(f, "{value}")
+      };
+    }
+
+    let object = value.as_object();
+    if !object.is_possibly_cyclic() {
+      return Object::format(f, object, self, debug);
+    }
+
+    let id = value.as_bytes();
+    let label = self.targets.iter().position(|target| *target == id);
+    if let Some(label) = label {
+      if self.path.contains(&id) {
+        return write!(f, "[Circular *{}]", label + 1);
+      }
+      write!(f, "<ref *{}> ", label + 1)?;
+    }
+
+    self.path.push(id);
+    let result = Object::format(f, object, self, debug);
+    self.path.pop();
+    result
+  }
+}
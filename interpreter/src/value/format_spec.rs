@@ -0,0 +1,128 @@
+use super::Value;
+use alloc::{format, string::String};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FormatKind {
+  Default,
+  String,
+  Integer,
+  Fixed,
+  Exponent,
+  Hex,
+}
+
+// How a value is shown, such as `-08.2f`: flags, then width, precision and type
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatSpec {
+  pub left_align: bool,
+  pub sign: bool,
+  pub zero_pad: bool,
+  pub width: usize,
+  pub precision: Option<usize>,
+  pub kind: FormatKind,
+}
+impl FormatSpec {
+  // Reads a spec from the start of the string, also giving the rest of the string
+  pub fn parse(spec: &str) -> Option<(Self, &str)> {
+    let mut result = Self {
+      left_align: false,
+      sign: false,
+      zero_pad: false,
+      width: 0,
+      precision: None,
+      kind: FormatKind::Default,
+    };
+
+    let mut rest = spec;
+    loop {
+      match rest.as_bytes().first() {
+        Some(b'-') => result.left_align = true,
+        Some(b'+') => result.sign = true,
+        Some(b'0') => result.zero_pad = true,
+        _ => break,
+      }
+      rest = &rest[1..];
+    }
+
+    let (width, remaining) = leading_number(rest);
+    result.width = width.unwrap_or(0);
+    rest = remaining;
+
+    if let Some(remaining) = rest.strip_prefix('.') {
+      let (precision, remaining) = leading_number(remaining);
+      result.precision = Some(precision?);
+      rest = remaining;
+    }
+
+    result.kind = match rest.as_bytes().first() {
+      Some(b's') => FormatKind::String,
+      Some(b'd') => FormatKind::Integer,
+      Some(b'f') => FormatKind::Fixed,
+      Some(b'e') => FormatKind::Exponent,
+      Some(b'x') => FormatKind::Hex,
+      _ => return Some((result, rest)),
+    };
+
+    Some((result, &rest[1..]))
+  }
+
+  // Numeric types only accept numbers, so gives `None` for other values
+  pub fn format(&self, value: &Value) -> Option<String> {
+    let number = || value.is_number().then(|| value.as_number());
+
+    let body = match self.kind {
+      FormatKind::Default | FormatKind::String => {
+        let string = value.to_string();
+        return Some(self.pad("", &self.truncate(&string)));
+      }
+      // Adding zero turns `-0` into `0`, so truncating `-0.5` doesn't show a sign
+      FormatKind::Integer => format!("{}", libm::trunc(number()?) + 0.0),
+      FormatKind::Fixed => format!("{:.*}", self.precision.unwrap_or(6), number()?),
+      FormatKind::Exponent => format!("{:.*e}", self.precision.unwrap_or(6), number()?),
+      FormatKind::Hex => {
+        let number = libm::trunc(number().filter(|number| number.is_finite())?);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let digits = format!("{:x}", number.abs() as u64);
+        if number < 0.0 {
+          format!("-{digits}")
+        } else {
+          digits
+        }
+      }
+    };
+
+    let (sign, digits) = match body.strip_prefix('-') {
+      Some(digits) => ("-", digits),
+      None if self.sign => ("+", body.as_str()),
+      None => ("", body.as_str()),
+    };
+    Some(self.pad(sign, digits))
+  }
+
+  fn truncate(&self, string: &str) -> String {
+    match self.precision {
+      Some(precision) => string.chars().take(precision).collect(),
+      None => string.into(),
+    }
+  }
+
+  fn pad(&self, sign: &str, body: &str) -> String {
+    let length = sign.len() + body.chars().count();
+    let padding = self.width.saturating_sub(length);
+    let is_number = !matches!(self.kind, FormatKind::Default | FormatKind::String);
+
+    if self.left_align {
+      format!("{sign}{body}{}", " ".repeat(padding))
+    } else if self.zero_pad && is_number {
+      format!("{sign}{}{body}", "0".repeat(padding))
+    } else {
+      format!("{}{sign}{body}", " ".repeat(padding))
+    }
+  }
+}
+
+fn leading_number(string: &str) -> (Option<usize>, &str) {
+  let length = string.bytes().take_while(u8::is_ascii_digit).count();
+  (string[..length].parse().ok(), &string[length..])
+}
@@ -1,29 +1,51 @@
 use super::Value;
-use crate::chunk::Chunk;
+use crate::{chunk::Chunk, context::Context};
+use alloc::rc::Rc;
+use core::fmt;
 use smallvec::SmallVec;
 use smartstring::alias::String;
-use std::rc::Rc;
 
 #[derive(Clone, Copy, Default, PartialEq, Eq)]
 pub struct Arity {
   count: u8,
+  optional: u8,
 }
 impl Arity {
   pub fn new(count: u8) -> Self {
-    Self { count }
+    Self { count, optional: 0 }
+  }
+
+  pub fn with_optional(count: u8, optional: u8) -> Self {
+    Self {
+      count,
+      optional: optional.min(count),
+    }
   }
 
   pub fn get_count(self) -> usize {
     usize::from(self.count)
   }
 
+  pub fn get_required_count(self) -> usize {
+    usize::from(self.count - self.optional)
+  }
+
   pub fn check_arg_count(self, provided: u8) -> bool {
-    self.count == provided
+    provided <= self.count && provided >= self.count - self.optional
+  }
+}
+impl fmt::Display for Arity {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match (self.count, self.optional) {
+      (1, 0) => write!(f, "1 param"),
+      (count, 0) => write!(f, "{count} params"),
+      (count, optional) => write!(f, "{}-{count} params", count - optional),
+    }
   }
 }
 impl From<u8> for Arity {
   fn from(count: u8) -> Self {
-    Self { count }
+    Self::new(count)
   }
 }
 
@@ -61,11 +83,18 @@ impl Default for Function {
   }
 }
 
+pub type ContextFunction = fn(context: &dyn Context, args: &[Value]) -> Value;
+pub type FallibleFunction = fn(args: &[Value]) -> Result<Value, String>;
+
 #[derive(Clone)]
 pub struct NativeFunction {
   pub name: &'static str,
   pub arity: Arity,
   pub func: fn(args: &[Value]) -> Value,
+  // Called instead of `func` when set, for functions which use the context, like reading input
+  pub context_func: Option<ContextFunction>,
+  // Called instead of `func` when set, for functions which can fail, like pushing to a frozen list
+  pub fallible_func: Option<FallibleFunction>,
 }
 impl NativeFunction {
   pub fn new(name: &'static str, arity: u8, func: fn(args: &[Value]) -> Value) -> Self {
@@ -73,6 +102,28 @@ impl NativeFunction {
       name,
       func,
       arity: arity.into(),
+      context_func: None,
+      fallible_func: None,
+    }
+  }
+
+  pub fn with_context(name: &'static str, arity: u8, func: ContextFunction) -> Self {
+    Self {
+      name,
+      func: |_| Value::NULL,
+      arity: arity.into(),
+      context_func: Some(func),
+      fallible_func: None,
+    }
+  }
+
+  pub fn fallible(name: &'static str, arity: u8, func: FallibleFunction) -> Self {
+    Self {
+      name,
+      func: |_| Value::NULL,
+      arity: arity.into(),
+      context_func: None,
+      fallible_func: Some(func),
     }
   }
 }
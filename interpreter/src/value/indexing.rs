@@ -1,5 +1,10 @@
 use super::{Object, Value};
+use alloc::vec::Vec;
 use smartstring::alias::String;
+
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
 
 pub enum GetResult {
@@ -21,6 +26,8 @@ pub enum SetResult {
   Set,
   NotFound,
   NotSupported,
+  InvalidKey,
+  Frozen,
 }
 
 pub trait Index {
@@ -34,7 +41,10 @@ pub trait Index {
 
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
 pub fn calculate_index(number: f64, length: usize) -> usize {
+  #[cfg(feature = "std")]
   let index = number.round().abs() as usize;
+  #[cfg(not(feature = "std"))]
+  let index = libm::round(number).abs() as usize;
 
   if index > length {
     length
@@ -62,6 +72,8 @@ impl Index for Value {
   fn set_property(&mut self, index: &Value, value: Value) -> SetResult {
     if self.is_object() {
       match self.as_object() {
+        Object::List(list) if list.is_frozen() => SetResult::Frozen,
+        Object::Dict(dict) if dict.is_frozen() => SetResult::Frozen,
         Object::List(list) => list.borrow_mut().set_property(index, value),
         Object::Dict(dict) => dict.borrow_mut().set_property(index, value),
         _ => SetResult::NotSupported,
@@ -107,13 +119,17 @@ impl Index for Vec<Value> {
     SetResult::NotSupported
   }
 }
-impl<S: ::std::hash::BuildHasher> Index for HashMap<Value, Value, S> {
+impl<S: core::hash::BuildHasher> Index for HashMap<Value, Value, S> {
   fn get_property(&self, index: &Value) -> GetResult {
     self.get(index).cloned().into()
   }
 
   fn set_property(&mut self, index: &Value, value: Value) -> SetResult {
-    self.insert(index.clone(), value);
-    SetResult::Set
+    if let Some(key) = index.as_dict_key() {
+      self.insert(key, value);
+      SetResult::Set
+    } else {
+      SetResult::InvalidKey
+    }
   }
 }
@@ -1,35 +1,46 @@
+mod comparison;
+mod conversions;
+mod cycles;
+mod format_spec;
 mod functions;
 pub mod indexing;
 mod objects;
+mod sequence;
 
-#[cfg(target_pointer_width = "32")]
+// The `bit32` feature uses the 32-bit layout on 64-bit platforms, so both layouts can be tested
+#[cfg(any(target_pointer_width = "32", feature = "bit32"))]
 mod bit32;
-#[cfg(target_pointer_width = "32")]
+#[cfg(any(target_pointer_width = "32", feature = "bit32"))]
 pub use bit32::Value;
-#[cfg(target_pointer_width = "32")]
+#[cfg(any(target_pointer_width = "32", feature = "bit32"))]
 use bit32::{FALSE, NULL, TRUE};
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(all(target_pointer_width = "64", not(feature = "bit32")))]
 mod bit64;
-#[cfg(target_pointer_width = "64")]
+#[cfg(all(target_pointer_width = "64", not(feature = "bit32")))]
 pub use bit64::Value;
-#[cfg(target_pointer_width = "64")]
+#[cfg(all(target_pointer_width = "64", not(feature = "bit32")))]
 use bit64::{FALSE, NULL, TRUE};
 
 #[cfg(test)]
 mod test;
 
-use crate::collections::HashSet;
+use crate::collections::{HashMap, HashSet};
+use alloc::{collections::BTreeSet, rc::Rc};
+pub use comparison::Mismatch;
+pub use conversions::{ConversionError, MAX_SAFE_INTEGER};
+use core::{
+  cell::RefCell,
+  fmt::{self, Write},
+  hash, mem,
+};
+use cycles::Cycles;
+pub use format_spec::{FormatKind, FormatSpec};
 pub use functions::{Arity, Closure, ClosureKind, Function, NativeFunction};
 pub use indexing::calculate_index;
-pub use objects::Object;
+pub use objects::{Freezable, Object};
+pub use sequence::{Sequence, Step};
 use smartstring::alias::String;
-use std::{
-  collections::BTreeSet,
-  fmt::{self, Write},
-  hash,
-  rc::Rc,
-};
 
 impl Value {
   pub fn is_falsy(&self) -> bool {
@@ -49,6 +60,20 @@ impl Value {
     }
   }
 
+  // Dictionary keys must compare consistently, so -0 is stored as 0 and NaN is rejected.
+  // Collections and functions are keys by identity, so cyclic values are fine.
+  pub fn as_dict_key(&self) -> Option<Self> {
+    if !self.is_number() {
+      Some(self.clone())
+    } else if self.as_number().is_nan() {
+      None
+    } else if self.as_number() == 0.0 {
+      Some(Self::from(0.0))
+    } else {
+      Some(self.clone())
+    }
+  }
+
   pub fn to_string(&self) -> String {
     let mut string = String::new();
     write!(string, "{self}").expect("No errors in Display trait");
@@ -63,7 +88,54 @@ impl Value {
     }
   }
 
-  pub fn equals(a: &Self, b: &Self, seen: &mut BTreeSet<u64>) -> bool {
+  pub fn deep_size(&self) -> usize {
+    mem::size_of::<Self>() + self.heap_size(&mut HashSet::default())
+  }
+
+  pub(crate) fn heap_size(&self, seen: &mut HashSet<u64>) -> usize {
+    // Shared values are only counted the first time they are seen
+    if !(self.is_object() || self.is_allocated()) || !seen.insert(self.as_bytes()) {
+      return 0;
+    }
+
+    let reference_counts = 2 * mem::size_of::<usize>();
+    if self.is_object() {
+      reference_counts + self.as_object().heap_size(seen)
+    } else {
+      let allocated = self.as_allocated();
+      let inner = allocated.borrow().heap_size(seen);
+      reference_counts + mem::size_of::<RefCell<Self>>() + inner
+    }
+  }
+
+  // A copy of every collection inside the value, which can be changed without affecting the original
+  pub fn deep_copy(&self) -> Self {
+    self.deep_clone(&mut HashMap::default())
+  }
+
+  // Collections are copied, keeping any sharing and cycles between them. Everything else is
+  // immutable or compared by identity, so is shared with the original.
+  pub(crate) fn deep_clone(&self, copies: &mut HashMap<u64, Self>) -> Self {
+    if !self.is_object() || !self.as_object().is_possibly_cyclic() {
+      return self.clone();
+    }
+
+    if let Some(copy) = copies.get(&self.as_bytes()) {
+      return copy.clone();
+    }
+
+    let copy = Self::from(self.as_object().empty_copy());
+    copies.insert(self.as_bytes(), copy.clone());
+    self.as_object().fill_copy(copy.as_object(), copies);
+    copy
+  }
+
+  pub fn equals(a: &Self, b: &Self, strict: bool, seen: &mut BTreeSet<u64>) -> bool {
+    // Strict equality follows IEEE 754, so NaN is never equal and there is no tolerance
+    if strict && a.is_number() && b.is_number() {
+      return a.as_number() == b.as_number();
+    }
+
     if a.as_bytes() == b.as_bytes() {
       return true;
     }
@@ -89,21 +161,7 @@ impl Value {
       seen.insert(b.as_bytes());
     }
 
-    Object::equals(a.as_object(), b.as_object(), seen)
-  }
-
-  fn format(f: &mut fmt::Formatter, value: &Self, seen: &mut HashSet<u64>) -> fmt::Result {
-    match value {
-      a if a.is_object() => {
-        let obj = &a.as_object();
-        if obj.is_possibly_cyclic() {
-          seen.insert(a.as_bytes());
-        }
-        Object::format(f, obj, seen, true)
-      }
-      b if b.is_allocated() => write!(f, "pointer"),
-      c => write!(f, "{c}"),
-    }
+    Object::equals(a.as_object(), b.as_object(), strict, seen)
   }
 }
 
@@ -143,6 +201,8 @@ impl hash::Hash for Value {
     .hash(state);
 
     match self {
+      // -0 and 0 are equal, so must have the same hash
+      a if a.is_number() && a.as_number() == 0.0 => 0.0_f64.to_le_bytes().hash(state),
       a if a.is_number() => a.as_number().to_le_bytes().hash(state),
       b if b.is_object() => b.as_object().hash(state),
       _ => {}
@@ -157,24 +217,14 @@ impl fmt::Display for Value {
       Self(TRUE) => write!(f, "true"),
       Self(FALSE) => write!(f, "false"),
       a if a.is_number() => write!(f, "{}", a.as_number()),
-      b => {
-        let mut seen = HashSet::default();
-        seen.insert(self.as_bytes());
-
-        Object::format(f, b.as_object(), &mut seen, false)
-      }
+      b => Cycles::new(b).format(f, b, false),
     }
   }
 }
 impl fmt::Debug for Value {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
-      a if a.is_object() => {
-        let mut seen = HashSet::default();
-        seen.insert(self.as_bytes());
-
-        Object::format(f, a.as_object(), &mut seen, true)
-      }
+      a if a.is_object() => Cycles::new(a).format(f, a, true),
       b if b.is_allocated() => write!(f, "pointer"),
       c => write!(f, "{c}"),
     }
@@ -1,16 +1,52 @@
-use super::{Closure, Function, NativeFunction, Value};
+use super::{cycles::Cycles, Arity, Closure, Function, NativeFunction, Sequence, Value};
 use crate::collections::{HashMap, HashSet};
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::{
+  cell::{Cell, Ref, RefCell, RefMut},
+  fmt, hash, mem, ptr, str,
+};
 use smartstring::alias::String;
-use std::{cell::RefCell, collections::BTreeSet, fmt, hash, mem, ptr, str};
 
 pub enum Object {
   String(String),
   Function(Function),
   NativeFunction(NativeFunction),
   Closure(Closure),
-  List(RefCell<Vec<Value>>),
+  List(Freezable<Vec<Value>>),
   Set(RefCell<HashSet<Value>>),
-  Dict(RefCell<HashMap<Value, Value>>),
+  Dict(Freezable<HashMap<Value, Value>>),
+  Sequence(RefCell<Sequence>),
+  Task(usize),
+}
+
+// A collection which can be made immutable, once frozen it can't be unfrozen
+pub struct Freezable<T> {
+  value: RefCell<T>,
+  frozen: Cell<bool>,
+}
+impl<T> Freezable<T> {
+  pub fn new(value: T) -> Self {
+    Self {
+      value: RefCell::new(value),
+      frozen: Cell::new(false),
+    }
+  }
+
+  pub fn borrow(&self) -> Ref<'_, T> {
+    self.value.borrow()
+  }
+
+  pub fn borrow_mut(&self) -> RefMut<'_, T> {
+    self.value.borrow_mut()
+  }
+
+  pub fn is_frozen(&self) -> bool {
+    self.frozen.get()
+  }
+
+  pub fn freeze(&self) {
+    self.frozen.set(true);
+  }
 }
 
 impl Object {
@@ -21,12 +57,18 @@ impl Object {
       Self::List(value) => value.borrow().is_empty(),
       Self::Set(value) => value.borrow().is_empty(),
       Self::Dict(value) => value.borrow().is_empty(),
+      Self::Sequence(_) | Self::Task(_) => false,
     }
   }
 
   pub fn is_possibly_cyclic(&self) -> bool {
     match self {
-      Self::String(_) | Self::Function(_) | Self::NativeFunction(_) | Self::Closure(_) => false,
+      Self::String(_)
+      | Self::Function(_)
+      | Self::NativeFunction(_)
+      | Self::Closure(_)
+      | Self::Sequence(_)
+      | Self::Task(_) => false,
       Self::List(_) | Self::Set(_) | Self::Dict(_) => true,
     }
   }
@@ -38,10 +80,12 @@ impl Object {
       Self::List(_) => "list",
       Self::Set(_) => "set",
       Self::Dict(_) => "dict",
+      Self::Sequence(_) => "sequence",
+      Self::Task(_) => "task",
     }
   }
 
-  pub(crate) fn get_function_name(&self) -> String {
+  pub fn get_function_name(&self) -> String {
     match self {
       Self::Function(func) => func.name.clone(),
       Self::NativeFunction(func) => func.name.into(),
@@ -50,10 +94,130 @@ impl Object {
     }
   }
 
-  pub fn equals(a: &Self, b: &Self, seen: &mut BTreeSet<u64>) -> bool {
+  pub fn get_function_arity(&self) -> Option<Arity> {
+    match self {
+      Self::Function(func) => Some(func.arity),
+      Self::NativeFunction(func) => Some(func.arity),
+      Self::Closure(func) => Some(func.func.arity),
+      _ => None,
+    }
+  }
+
+  pub fn length(&self) -> Option<usize> {
+    match self {
+      Self::String(value) => Some(value.len()),
+      Self::List(value) => Some(value.borrow().len()),
+      Self::Set(value) => Some(value.borrow().len()),
+      Self::Dict(value) => Some(value.borrow().len()),
+      _ => None,
+    }
+  }
+
+  pub fn is_frozen(&self) -> bool {
+    match self {
+      Self::List(value) => value.is_frozen(),
+      Self::Dict(value) => value.is_frozen(),
+      _ => false,
+    }
+  }
+
+  pub fn get_dict_keys(&self) -> Option<Vec<Value>> {
+    match self {
+      Self::Dict(value) => Some(value.borrow().keys().cloned().collect()),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn empty_copy(&self) -> Self {
+    match self {
+      Self::List(_) => Self::from(Vec::new()),
+      Self::Set(_) => Self::from(HashSet::default()),
+      Self::Dict(_) => Self::from(HashMap::default()),
+      _ => unreachable!("Only collections are copied"),
+    }
+  }
+
+  // The copy is registered before its items are cloned, so cycles point back to it
+  pub(crate) fn fill_copy(&self, copy: &Self, copies: &mut HashMap<u64, Value>) {
+    match (self, copy) {
+      (Self::List(value), Self::List(copy)) => {
+        let items = value
+          .borrow()
+          .iter()
+          .map(|item| item.deep_clone(copies))
+          .collect();
+        *copy.borrow_mut() = items;
+
+        if value.is_frozen() {
+          copy.freeze();
+        }
+      }
+      (Self::Set(value), Self::Set(copy)) => {
+        let items = value
+          .borrow()
+          .iter()
+          .map(|item| item.deep_clone(copies))
+          .collect();
+        *copy.borrow_mut() = items;
+      }
+      (Self::Dict(value), Self::Dict(copy)) => {
+        let items = value
+          .borrow()
+          .iter()
+          .map(|(key, value)| (key.deep_clone(copies), value.deep_clone(copies)))
+          .collect();
+        *copy.borrow_mut() = items;
+
+        if value.is_frozen() {
+          copy.freeze();
+        }
+      }
+      _ => {}
+    }
+  }
+
+  pub fn deep_size(&self) -> usize {
+    self.heap_size(&mut HashSet::default())
+  }
+
+  pub(crate) fn heap_size(&self, seen: &mut HashSet<u64>) -> usize {
+    let contents = match self {
+      Self::String(value) if value.is_inline() => 0,
+      Self::String(value) => value.capacity(),
+      Self::Function(func) => func.name.capacity(),
+      Self::NativeFunction(_) | Self::Sequence(_) | Self::Task(_) => 0,
+      Self::Closure(closure) => {
+        let upvalues = closure.upvalues.iter();
+        let upvalues: usize = upvalues.map(|value| value.heap_size(seen)).sum();
+        closure.func.name.capacity() + upvalues
+      }
+      Self::List(value) => {
+        let value = value.borrow();
+        let items: usize = value.iter().map(|item| item.heap_size(seen)).sum();
+        value.capacity() * mem::size_of::<Value>() + items
+      }
+      Self::Set(value) => {
+        let value = value.borrow();
+        let items: usize = value.iter().map(|item| item.heap_size(seen)).sum();
+        value.capacity() * mem::size_of::<Value>() + items
+      }
+      Self::Dict(value) => {
+        let value = value.borrow();
+        let items: usize = value
+          .iter()
+          .map(|(key, value)| key.heap_size(seen) + value.heap_size(seen))
+          .sum();
+        value.capacity() * mem::size_of::<(Value, Value)>() + items
+      }
+    };
+
+    mem::size_of::<Self>() + contents
+  }
+
+  pub fn equals(a: &Self, b: &Self, strict: bool, seen: &mut BTreeSet<u64>) -> bool {
     match (a, b) {
       (Self::String(value), Self::String(other)) => value == other,
-      // Function types (Function, Native, Closure) are compared by pointer in Value::eq
+      // Function types (Function, Native, Closure), Sequences and Tasks are compared by pointer in Value::eq
       (Self::Set(value), Self::Set(other)) => *value.borrow() == *other.borrow(),
       (Self::List(value), Self::List(other)) => {
         let value = value.borrow();
@@ -66,7 +230,7 @@ impl Object {
         value
           .iter()
           .zip(other.iter())
-          .all(|(a, b)| Value::equals(a, b, seen))
+          .all(|(a, b)| Value::equals(a, b, strict, seen))
       }
       (Self::Dict(value), Self::Dict(other)) => {
         let value = value.borrow();
@@ -79,98 +243,85 @@ impl Object {
         value.iter().all(|(key, value)| {
           other
             .get(key)
-            .map_or(false, |v| Value::equals(value, v, seen))
+            .map_or(false, |v| Value::equals(value, v, strict, seen))
         })
       }
       _ => false,
     }
   }
 
-  pub fn format(
+  pub(crate) fn format(
     f: &mut fmt::Formatter<'_>,
     value: &Self,
-    seen: &mut HashSet<u64>,
+    cycles: &mut Cycles,
     debug: bool,
   ) -> fmt::Result {
     match value {
       Self::String(value) if debug => write!(f, "'{value}'"),
       Self::String(value) => write!(f, "{value}"),
-      Self::Function(value) => write!(f, "<function {}>", value.name),
-      Self::NativeFunction(value) => write!(f, "<function {}>", value.name),
-      Self::Closure(value) => write!(f, "<function {}>", value.func.name),
+      Self::Function(value) => write!(f, "<function {}({})>", value.name, value.arity),
+      Self::NativeFunction(value) => write!(f, "<function {}({})>", value.name, value.arity),
+      Self::Closure(value) => write!(f, "<function {}({})>", value.func.name, value.func.arity),
       Self::List(value) => {
-        write!(f, "[")?;
-        value
-          .borrow()
-          .iter()
-          .enumerate()
-          .try_for_each(|(index, item)| {
-            if seen.contains(&item.as_bytes()) {
-              write!(f, "...")?;
-            } else {
-              Value::format(f, item, &mut seen.clone())?;
-            }
-
-            if index != value.borrow().len() - 1 {
-              write!(f, ", ")?;
-            }
-
-            Ok(())
-          })?;
-        write!(f, "]")
+        let items = value.borrow();
+        format_collection(f, cycles, ("[", "]"), items.iter(), |f, cycles, item| {
+          cycles.format(f, item, true)
+        })
       }
       Self::Set(value) => {
-        write!(f, "set(")?;
-        value
-          .borrow()
-          .iter()
-          .enumerate()
-          .try_for_each(|(index, item)| {
-            if seen.contains(&item.as_bytes()) {
-              write!(f, "...")?;
-            } else {
-              Value::format(f, item, &mut seen.clone())?;
-            }
-
-            if index != value.borrow().len() - 1 {
-              write!(f, ", ")?;
-            }
-
-            Ok(())
-          })?;
-        write!(f, ")")
+        let items = value.borrow();
+        format_collection(f, cycles, ("set(", ")"), items.iter(), |f, cycles, item| {
+          cycles.format(f, item, true)
+        })
       }
       Self::Dict(value) => {
-        write!(f, "{{ ")?;
-        value
-          .borrow()
-          .iter()
-          .enumerate()
-          .try_for_each(|(index, (k, v))| {
-            if seen.contains(&k.as_bytes()) {
-              write!(f, "...")?;
-            } else {
-              Value::format(f, k, seen)?;
-            }
-
+        let items = value.borrow();
+        format_collection(
+          f,
+          cycles,
+          ("{ ", " }"),
+          items.iter(),
+          |f, cycles, (k, v)| {
+            cycles.format(f, k, true)?;
             write!(f, ": ")?;
+            cycles.format(f, v, true)
+          },
+        )
+      }
+      Self::Sequence(_) => write!(f, "<sequence>"),
+      Self::Task(id) => write!(f, "<task {id}>"),
+    }
+  }
+}
 
-            if seen.contains(&v.as_bytes()) {
-              write!(f, "...")?;
-            } else {
-              Value::format(f, v, &mut seen.clone())?;
-            }
+// Items are separated by commas, or with `{:#}` are each on their own indented line
+fn format_collection<T>(
+  f: &mut fmt::Formatter<'_>,
+  cycles: &mut Cycles,
+  (open, close): (&str, &str),
+  items: impl ExactSizeIterator<Item = T>,
+  mut format_item: impl FnMut(&mut fmt::Formatter<'_>, &mut Cycles, T) -> fmt::Result,
+) -> fmt::Result {
+  if f.alternate() && items.len() > 0 {
+    let indent = 2 * cycles.depth();
 
-            if index != value.borrow().len() - 1 {
-              write!(f, ", ")?;
-            }
+    writeln!(f, "{}", open.trim_end())?;
+    for item in items {
+      write!(f, "{:indent$}", "")?;
+      format_item(f, cycles, item)?;
+      writeln!(f, ",")?;
+    }
+    return write!(f, "{:1$}{2}", "", indent - 2, close.trim_start());
+  }
 
-            Ok(())
-          })?;
-        write!(f, " }}")
-      }
+  write!(f, "{open}")?;
+  for (index, item) in items.enumerate() {
+    if index != 0 {
+      write!(f, ", ")?;
     }
+    format_item(f, cycles, item)?;
   }
+  write!(f, "{close}")
 }
 
 impl hash::Hash for Object {
@@ -185,6 +336,8 @@ impl hash::Hash for Object {
       Self::List(value) => ptr::hash(value, state),
       Self::Set(value) => ptr::hash(value, state),
       Self::Dict(value) => ptr::hash(value, state),
+      Self::Sequence(value) => ptr::hash(value, state),
+      Self::Task(id) => id.hash(state),
     }
   }
 }
@@ -206,8 +359,8 @@ impl From<char> for Object {
     Self::String(string)
   }
 }
-impl From<std::string::String> for Object {
-  fn from(value: std::string::String) -> Self {
+impl From<alloc::string::String> for Object {
+  fn from(value: alloc::string::String) -> Self {
     Self::String(value.into())
   }
 }
@@ -230,7 +383,7 @@ impl From<Closure> for Object {
 
 impl From<Vec<Value>> for Object {
   fn from(value: Vec<Value>) -> Self {
-    Self::List(RefCell::new(value))
+    Self::List(Freezable::new(value))
   }
 }
 impl From<HashSet<Value>> for Object {
@@ -240,6 +393,11 @@ impl From<HashSet<Value>> for Object {
 }
 impl From<HashMap<Value, Value>> for Object {
   fn from(value: HashMap<Value, Value>) -> Self {
-    Self::Dict(RefCell::new(value))
+    Self::Dict(Freezable::new(value))
+  }
+}
+impl From<Sequence> for Object {
+  fn from(value: Sequence) -> Self {
+    Self::Sequence(RefCell::new(value))
   }
 }
@@ -0,0 +1,98 @@
+use super::{Object, Value};
+
+#[derive(Clone)]
+pub enum Sequence {
+  Range { current: f64, end: f64 },
+  List { list: Value, index: usize },
+  String { string: Value, index: usize },
+  Repeat { value: Value },
+  Map { sequence: Value, func: Value },
+  Filter { sequence: Value, func: Value },
+  Take { sequence: Value, remaining: usize },
+}
+
+pub enum Step {
+  Yield(Value),
+  Done,
+  Inner(Value),
+  Map(Value, Value),
+  Filter(Value, Value),
+}
+
+impl Sequence {
+  pub fn from_value(value: &Value) -> Option<Value> {
+    if !value.is_object() {
+      return None;
+    }
+
+    match value.as_object() {
+      Object::Sequence(_) => Some(value.clone()),
+      Object::List(_) => Some(
+        Self::List {
+          list: value.clone(),
+          index: 0,
+        }
+        .into(),
+      ),
+      Object::String(_) => Some(
+        Self::String {
+          string: value.clone(),
+          index: 0,
+        }
+        .into(),
+      ),
+      _ => None,
+    }
+  }
+
+  pub fn step(&mut self) -> Step {
+    match self {
+      Self::Range { current, end } => {
+        if *current >= *end {
+          return Step::Done;
+        }
+
+        let value = *current;
+        *current += 1.0;
+        Step::Yield(value.into())
+      }
+      Self::List { list, index } => {
+        let item = match list.as_object() {
+          Object::List(list) => list.borrow().get(*index).cloned(),
+          _ => None,
+        };
+
+        *index += 1;
+        item.map_or(Step::Done, Step::Yield)
+      }
+      Self::String { string, index } => {
+        let character = match string.as_object() {
+          Object::String(string) => string[*index..].chars().next(),
+          _ => None,
+        };
+
+        match character {
+          Some(character) => {
+            *index += character.len_utf8();
+            Step::Yield(character.into())
+          }
+          None => Step::Done,
+        }
+      }
+      Self::Repeat { value } => Step::Yield(value.clone()),
+      Self::Map { sequence, func } => Step::Map(sequence.clone(), func.clone()),
+      Self::Filter { sequence, func } => Step::Filter(sequence.clone(), func.clone()),
+      Self::Take {
+        sequence,
+        remaining,
+      } => {
+        if *remaining == 0 {
+          return Step::Done;
+        }
+
+        *remaining -= 1;
+        Step::Inner(sequence.clone())
+      }
+    }
+  }
+}
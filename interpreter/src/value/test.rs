@@ -1,4 +1,7 @@
-use super::{Function, NativeFunction, Object, Value};
+use super::{
+  ConversionError, FormatKind, FormatSpec, Function, NativeFunction, Object, Value,
+  MAX_SAFE_INTEGER,
+};
 use crate::collections::{HashMap, HashSet};
 use std::{
   collections::hash_map::DefaultHasher,
@@ -59,6 +62,32 @@ fn number() {
   assert!(num.as_number().is_nan());
 }
 
+#[test]
+fn number_bit_patterns() {
+  // Numbers which share bits with the tags of the other values, in either half
+  for bits in [
+    0x0000_0000_FFFF_FFFE,
+    0x0000_0000_FFFF_FFFC,
+    0x0000_0000_FFFC_0000,
+    0x0000_0000_FFFF_0000,
+    0x7FF0_0000_0000_0001,
+    0x7FF8_0000_0000_0000,
+    0xFFF8_0000_0000_0000,
+    0x8000_0000_0000_0000,
+  ] {
+    let num = Value::from(f64::from_bits(bits));
+    assert!(num.is_number());
+    assert!(!num.is_object());
+    assert!(!num.is_allocated());
+    assert_eq!(num.as_number().to_bits(), bits);
+    assert_eq!(num.as_bytes(), bits);
+
+    assert_ne!(num.as_bytes(), Value::TRUE.as_bytes());
+    assert_ne!(num.as_bytes(), Value::FALSE.as_bytes());
+    assert_ne!(num.as_bytes(), Value::NULL.as_bytes());
+  }
+}
+
 #[test]
 fn objects() {
   let string = Value::from("hello");
@@ -114,6 +143,8 @@ fn get_type() {
     name: "native",
     arity: 0.into(),
     func: |_| Value::NULL,
+    context_func: None,
+    fallible_func: None,
   };
   assert_eq!(Value::from(native_function).get_type(), "function");
 
@@ -142,23 +173,63 @@ fn displays_correctly() {
       ..Default::default()
     })
     .to_string(),
-    "<function hello>"
+    "<function hello(0 params)>"
   );
 
   assert_eq!(
     Value::from(NativeFunction {
       name: "native",
       arity: 0.into(),
-      func: |_| Value::NULL
+      func: |_| Value::NULL,
+      context_func: None,
+      fallible_func: None,
     })
     .to_string(),
-    "<function native>"
+    "<function native(0 params)>"
   );
 
   assert_eq!(Value::from(HashSet::default()).to_string(), "set()");
   assert_eq!(Value::from(HashMap::default()).to_string(), "{  }");
 }
 
+#[test]
+fn displays_cycles() {
+  let inner = Value::from(vec![1.into()]);
+  let outer = Value::from(vec![inner.clone(), inner.clone()]);
+  if let Object::List(list) = inner.as_object() {
+    list.borrow_mut().push(outer.clone());
+  }
+
+  assert_eq!(
+    outer.to_string(),
+    "<ref *1> [[1, [Circular *1]], [1, [Circular *1]]]"
+  );
+  assert_eq!(
+    inner.to_string(),
+    "<ref *1> [1, [[Circular *1], [Circular *1]]]"
+  );
+}
+
+#[test]
+fn pretty_prints() {
+  let dict: HashMap<_, _> = [("a".into(), vec![1.into(), vec![].into()].into())]
+    .into_iter()
+    .collect();
+  let list = Value::from(vec!["b".into(), dict.into()]);
+
+  assert_eq!(
+    format!("{list:#?}"),
+    "[\n  'b',\n  {\n    'a': [\n      1,\n      [],\n    ],\n  },\n]"
+  );
+  assert_eq!(format!("{:#?}", Value::from(HashMap::default())), "{  }");
+
+  if let Object::List(items) = list.as_object() {
+    items.borrow_mut().push(list.clone());
+  }
+  assert!(format!("{list:#?}").starts_with("<ref *1> [\n  'b',"));
+  assert!(format!("{list:#?}").ends_with("  [Circular *1],\n]"));
+}
+
 #[test]
 fn equality() {
   let function = Value::from(Function {
@@ -169,6 +240,8 @@ fn equality() {
     name: "native",
     arity: 0.into(),
     func: |_| Value::NULL,
+    context_func: None,
+    fallible_func: None,
   };
   let number = Value::from(0);
 
@@ -200,6 +273,72 @@ fn equality() {
   assert_ne!(Value::from(result_error), Value::FALSE);
 }
 
+#[test]
+fn deep_equality_with_tolerance() {
+  let dict = |items: &[(&str, Value)]| {
+    let dict: HashMap<Value, Value> = items
+      .iter()
+      .map(|(key, value)| (Value::from(*key), value.clone()))
+      .collect();
+    Value::from(dict)
+  };
+
+  let a = Value::from(vec![
+    1.0.into(),
+    dict(&[("x", 0.1.into()), ("y", "a".into())]),
+  ]);
+  let b = Value::from(vec![
+    1.001.into(),
+    dict(&[("y", "a".into()), ("x", 0.1.into())]),
+  ]);
+  assert!(a.deep_eq_with_tolerance(&b, 0.01).is_ok());
+  assert!(Value::from(f64::NAN)
+    .deep_eq_with_tolerance(&f64::NAN.into(), 0.0)
+    .is_ok());
+
+  let mismatch = a.deep_eq_with_tolerance(&b, 0.0).unwrap_err();
+  assert_eq!(mismatch.path, "[0]");
+  assert_eq!(mismatch.to_string(), "at [0]: 1 != 1.001");
+
+  let c = Value::from(vec![
+    1.0.into(),
+    dict(&[("x", 0.2.into()), ("y", "a".into())]),
+  ]);
+  let mismatch = a.deep_eq_with_tolerance(&c, 0.01).unwrap_err();
+  assert_eq!(mismatch.to_string(), "at [1]['x']: 0.1 != 0.2");
+
+  let d = Value::from(vec![1.0.into()]);
+  let mismatch = a.deep_eq_with_tolerance(&d, 0.01).unwrap_err();
+  assert_eq!(mismatch.path, "[1]");
+  assert!(mismatch.right.is_none());
+
+  let e = dict(&[("x", 0.1.into()), ("z", Value::NULL)]);
+  let mismatch = dict(&[("x", 0.1.into())])
+    .deep_eq_with_tolerance(&e, 0.0)
+    .unwrap_err();
+  assert_eq!(
+    mismatch.to_string(),
+    "at ['z']: null is missing on the left"
+  );
+  assert!(Value::from("a")
+    .deep_eq_with_tolerance(&1.into(), 0.0)
+    .is_err());
+
+  let cyclic = |item: Value| {
+    let list = Value::from(vec![item]);
+    if let Object::List(items) = list.as_object() {
+      items.borrow_mut().push(list.clone());
+    }
+    list
+  };
+  assert!(cyclic(1.0.into())
+    .deep_eq_with_tolerance(&cyclic(1.0.into()), 0.0)
+    .is_ok());
+  assert!(cyclic(1.0.into())
+    .deep_eq_with_tolerance(&cyclic(2.0.into()), 0.0)
+    .is_err());
+}
+
 macro_rules! assert_hash_eq {
   ($a:expr, $b:expr) => {
     let hasher_a = &mut DefaultHasher::new();
@@ -246,6 +385,8 @@ fn hash() {
     name: "native",
     arity: 0.into(),
     func: |_| Value::NULL,
+    context_func: None,
+    fallible_func: None,
   });
   assert_hash_eq!(function, function.clone());
   assert_hash_ne!(function, native);
@@ -278,3 +419,107 @@ fn allocate() {
   );
   assert_eq!(string.as_allocated().clone(), string.as_allocated().clone(),);
 }
+
+#[test]
+fn deep_size() {
+  let value_size = std::mem::size_of::<Value>();
+  assert_eq!(Value::NULL.deep_size(), value_size);
+  assert_eq!(Value::from(5.0).deep_size(), value_size);
+
+  let short = Value::from("hello");
+  let long = Value::from("a string which is too long to be stored inline");
+  assert!(short.deep_size() > value_size);
+  assert!(long.deep_size() > short.deep_size());
+
+  let list = Value::from(vec![short.clone(), long.clone()]);
+  assert!(list.deep_size() > short.deep_size() + long.deep_size() - value_size);
+
+  let shared = Value::from(vec![long.clone(), long.clone()]);
+  let separate = Value::from(vec![long.clone(), Value::from(long.as_str())]);
+  assert!(shared.deep_size() < separate.deep_size());
+
+  let cyclic = Value::from(Vec::new());
+  if let Object::List(items) = cyclic.as_object() {
+    items.borrow_mut().push(cyclic.clone());
+  }
+  assert!(cyclic.deep_size() > value_size);
+}
+
+#[test]
+fn checked_integer_conversions() {
+  assert_eq!(Value::try_from_i64(-5).unwrap(), Value::from(-5));
+  assert_eq!(
+    Value::try_from_i64(MAX_SAFE_INTEGER + 1),
+    Err(ConversionError::OutOfRange)
+  );
+  assert_eq!(
+    Value::try_from_u64(u64::MAX),
+    Err(ConversionError::OutOfRange)
+  );
+
+  assert_eq!(Value::from(42).as_i64_checked(), Some(42));
+  assert_eq!(Value::from(-0.0).as_i64_checked(), Some(0));
+  assert_eq!(Value::from(1.5).as_i64_checked(), None);
+  assert_eq!(Value::from(1e300).as_i64_checked(), None);
+  assert_eq!(Value::from(f64::NAN).as_i64_checked(), None);
+  assert_eq!(Value::from("1").as_i64_checked(), None);
+
+  assert_eq!(usize::try_from(Value::from(3)), Ok(3));
+  assert_eq!(
+    usize::try_from(Value::from(-3)),
+    Err(ConversionError::OutOfRange)
+  );
+  assert_eq!(
+    i64::try_from(Value::from(2.5)),
+    Err(ConversionError::NotInteger)
+  );
+}
+
+#[test]
+fn checked_conversions() {
+  assert_eq!(bool::try_from(Value::TRUE), Ok(true));
+  assert_eq!(bool::try_from(Value::FALSE), Ok(false));
+  assert_eq!(
+    bool::try_from(Value::NULL),
+    Err(ConversionError::WrongType {
+      expected: "boolean",
+      found: "null"
+    })
+  );
+
+  assert_eq!(
+    std::string::String::try_from(Value::from("hello")).unwrap(),
+    "hello"
+  );
+  assert_eq!(
+    std::string::String::try_from(Value::from(1)),
+    Err(ConversionError::WrongType {
+      expected: "string",
+      found: "number"
+    })
+  );
+}
+
+#[test]
+fn format_specs() {
+  let format = |spec, value: Value| {
+    let (spec, rest) = FormatSpec::parse(spec).unwrap();
+    assert_eq!(rest, "");
+    spec.format(&value)
+  };
+
+  assert_eq!(format("06.2f", Value::from(1.23456)).unwrap(), "001.23");
+  assert_eq!(format("+.1e", Value::from(1500)).unwrap(), "+1.5e3");
+  assert_eq!(format("-6d", Value::from(-7.9)).unwrap(), "-7    ");
+  assert_eq!(format("d", Value::from(-0.5)).unwrap(), "0");
+  assert_eq!(format("04x", Value::from(255)).unwrap(), "00ff");
+  assert_eq!(format("5.3s", Value::from("hello")).unwrap(), "  hel");
+  assert_eq!(format("", Value::TRUE).unwrap(), "true");
+  assert_eq!(format("f", Value::from("1")), None);
+  assert_eq!(format("x", Value::from(f64::INFINITY)), None);
+
+  assert_eq!(FormatSpec::parse("3.f"), None);
+  let (spec, rest) = FormatSpec::parse("3d apples").unwrap();
+  assert_eq!((spec.width, spec.kind), (3, FormatKind::Integer));
+  assert_eq!(rest, " apples");
+}
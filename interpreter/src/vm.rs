@@ -1,18 +1,26 @@
 use crate::{
   chunk::OpCode,
-  collections::HashMap,
+  collections::{hash_map, HashMap, HashSet},
   context::{self, Context, ImportValue},
   value::{
     indexing::{GetResult, Index, SetResult},
-    Closure, ClosureKind, Object, Value,
+    Arity, Closure, ClosureKind, Object, Step, Value,
   },
   Chunk,
 };
+use alloc::{
+  collections::{BTreeSet, VecDeque},
+  format,
+  rc::Rc,
+  string::ToString,
+  vec,
+  vec::Vec,
+};
 use bang_syntax::LineNumber;
+use core::{fmt, mem};
 use itertools::Itertools;
 use smallvec::SmallVec;
 use smartstring::alias::String;
-use std::{collections::hash_map, collections::BTreeSet, error, fmt, mem, rc::Rc};
 
 #[derive(Debug)]
 pub struct StackTraceLocation {
@@ -41,7 +49,8 @@ impl fmt::Display for RuntimeError {
     )
   }
 }
-impl error::Error for RuntimeError {}
+#[cfg(feature = "std")]
+impl std::error::Error for RuntimeError {}
 
 macro_rules! runtime_error {
   (traceback, $vm:expr, $chunk:expr, $ip:expr, $offset:expr) => {{
@@ -62,14 +71,15 @@ macro_rules! runtime_error {
 
   (($vm:expr, $chunk:expr), $($message:tt)+) => {{
     let stack =
-      std::iter::once(runtime_error!(traceback, $vm, $chunk, $vm.ip, $vm.offset))
+      core::iter::once(runtime_error!(traceback, $vm, $chunk, $vm.ip, $vm.offset))
       .chain(
         $vm
           .frames
           .iter()
           .rev()
-          .map(|frame| runtime_error!(traceback, $vm, $chunk, frame.ip, frame.offset)),
+          .map(|frame| runtime_error!(traceback, $vm, frame.chunk, frame.ip, frame.offset)),
       )
+      .take(STACK_TRACE_LIMIT)
       .collect();
 
     $vm.stack.clear();
@@ -82,14 +92,28 @@ macro_rules! runtime_error {
 }
 
 macro_rules! function_arity_check {
-  (($vm:expr, $chunk:expr), $arity:expr, $arg_count:expr) => {{
+  (($vm:expr, $chunk:expr), $name:expr, $arity:expr, $arg_count:expr) => {{
     if !$arity.check_arg_count($arg_count) {
-      break runtime_error!(
-        ($vm, $chunk),
-        "Expected {} arguments but got {}.",
-        $arity.get_count(),
-        $arg_count
-      );
+      let name: &str = &$name;
+      let expected = if $arity.get_required_count() == $arity.get_count() {
+        $arity.get_count().to_string()
+      } else {
+        format!("{} to {}", $arity.get_required_count(), $arity.get_count())
+      };
+
+      break if name.is_empty() {
+        runtime_error!(
+          ($vm, $chunk),
+          "Anonymous function expected {expected} arguments but got {}.",
+          $arg_count
+        )
+      } else {
+        runtime_error!(
+          ($vm, $chunk),
+          "Function '{name}' expected {expected} arguments but got {}.",
+          $arg_count
+        )
+      };
     }
   }};
 }
@@ -99,7 +123,16 @@ macro_rules! numeric_expression {
     let (right, left) = ($vm.pop(), $vm.pop());
 
     if left.is_number() && right.is_number() {
-      $vm.push(Value::from(left.as_number() $token right.as_number()));
+      let (left, right) = (left.as_number(), right.as_number());
+      let result = left $token right;
+
+      if !result.is_finite()
+        && $vm.checked_arithmetic
+        && let Some(message) = arithmetic_error(stringify!($token), left, right, result)
+      {
+        break runtime_error!(($vm, $chunk), "{message}");
+      }
+      $vm.push(Value::from(result));
     } else {
       break runtime_error!(($vm, $chunk), "Both operands must be numbers.");
     }
@@ -117,8 +150,19 @@ macro_rules! comparison_expression {
       && let Object::String(right) = right.as_object()
     {
       $vm.push(Value::from(left $token right));
+    } else if left.get_type() == "list" && right.get_type() == "list" {
+      break runtime_error!(
+        ($vm, $chunk),
+        "Lists can't be compared with '{}', use `list::compare` instead.",
+        stringify!($token)
+      );
     } else {
-      break runtime_error!(($vm, $chunk), "Operands must be two numbers or two strings.");
+      break runtime_error!(
+        ($vm, $chunk),
+        "Operands must be two numbers or two strings, but got {} and {}.",
+        left.get_type(),
+        right.get_type()
+      );
     }
   };
 }
@@ -130,6 +174,29 @@ struct CallFrame {
   upvalues: SmallVec<[Value; 4]>,
 }
 
+const MAIN_TASK: usize = 0;
+const STRING_CACHE_SIZE: usize = 256;
+const MAX_CALL_DEPTH: usize = 10_000;
+const MAX_STACK_SIZE: usize = 1 << 20;
+const STACK_TRACE_LIMIT: usize = 32;
+const NEAREST_KEYS: usize = 3;
+
+struct Task {
+  id: usize,
+  ip: usize,
+  offset: usize,
+  chunk: Chunk,
+  stack: Vec<Value>,
+  frames: Vec<CallFrame>,
+}
+
+// A copy of the global variables, which can be restored onto a VM.
+// Lists, sets and dicts are copied, other values are shared with the VM.
+#[derive(Clone)]
+pub struct GlobalsSnapshot {
+  globals: HashMap<Rc<str>, Value>,
+}
+
 pub struct VM<'context> {
   ip: usize,
   offset: usize,
@@ -137,7 +204,24 @@ pub struct VM<'context> {
   stack: Vec<Value>,
   frames: Vec<CallFrame>,
   globals: HashMap<Rc<str>, Value>,
+  native_modules: HashMap<Rc<str>, HashMap<Rc<str>, Value>>,
   cyclic: BTreeSet<u64>,
+  strings: HashMap<String, Value>,
+  hot_reloading: bool,
+  strict_equality: bool,
+  checked_arithmetic: bool,
+  call_depth: usize,
+  max_call_depth: usize,
+  max_stack_size: usize,
+  count_instructions: bool,
+  instructions: u64,
+
+  task: usize,
+  tasks: VecDeque<Task>,
+  task_results: HashMap<usize, Value>,
+  task_count: usize,
+  resumed: bool,
+  blocked: usize,
 
   context: &'context dyn Context,
 }
@@ -193,13 +277,162 @@ impl VM<'_> {
     self.stack.push(value);
   }
 
+  // Short strings are shared to avoid allocating a new object each time they are built
+  fn small_string(&mut self, string: String) -> Value {
+    if !string.is_inline() {
+      return string.into();
+    }
+    if let Some(value) = self.strings.get(&string) {
+      return value.clone();
+    }
+
+    if self.strings.len() >= STRING_CACHE_SIZE {
+      self.strings.clear();
+    }
+
+    let value = Value::from(string.clone());
+    self.strings.insert(string, value.clone());
+    value
+  }
+
+  // Lists compare items like `==`, strings look for substrings, and sets and dicts check keys
+  fn contains(&mut self, collection: &Value, item: &Value) -> Option<bool> {
+    if !collection.is_object() {
+      return None;
+    }
+
+    let contains = match collection.as_object() {
+      Object::String(string) => {
+        item.is_object()
+          && matches!(item.as_object(), Object::String(sub) if string.contains(sub.as_str()))
+      }
+      Object::List(list) => list.borrow().iter().any(|value| {
+        let equals = Value::equals(item, value, self.strict_equality, &mut self.cyclic);
+        self.cyclic.clear();
+        equals
+      }),
+      Object::Set(set) => set.borrow().contains(item),
+      Object::Dict(dict) => item
+        .as_dict_key()
+        .is_some_and(|key| dict.borrow().contains_key(&key)),
+      _ => return None,
+    };
+
+    Some(contains)
+  }
+
+  #[inline]
+  fn missing_arguments(&mut self, arity: Arity, arg_count: u8) {
+    // Missing optional arguments are null, and replaced by their defaults in the function
+    for _ in usize::from(arg_count)..arity.get_count() {
+      self.push(Value::NULL);
+    }
+  }
+
+  fn suspend_task(&mut self, chunk: Chunk) {
+    self.tasks.push_back(Task {
+      id: self.task,
+      ip: self.ip,
+      offset: self.offset,
+      chunk,
+      stack: mem::take(&mut self.stack),
+      frames: mem::take(&mut self.frames),
+    });
+  }
+
+  fn resume_task(&mut self, task: Task) -> Chunk {
+    self.task = task.id;
+    self.ip = task.ip;
+    self.offset = task.offset;
+    self.stack = task.stack;
+    self.frames = task.frames;
+    self.resumed = true;
+
+    task.chunk
+  }
+
+  // Continues the next waiting task, or the current task if no others are waiting
+  fn switch_task(&mut self, chunk: Chunk) -> Chunk {
+    match self.tasks.pop_front() {
+      Some(task) => {
+        self.suspend_task(chunk);
+        self.resume_task(task)
+      }
+      None => chunk,
+    }
+  }
+
+  pub fn call(&mut self, callee: Value, args: &[Value]) -> Result<Value, RuntimeError> {
+    let (ip, offset, task) = (self.ip, self.offset, self.task);
+    let frames = mem::take(&mut self.frames);
+    let tasks = mem::take(&mut self.tasks);
+
+    // Calls from native functions start with no frames, so remember how deep the caller was
+    let depth = frames.len() + 1;
+    self.call_depth += depth;
+
+    self.push(callee);
+    self.stack.extend_from_slice(args);
+
+    let mut chunk = crate::chunk::Chunk::new();
+    chunk.write_opcode(OpCode::Call, u16::MAX);
+    chunk.write_value(u8::try_from(args.len()).unwrap_or(u8::MAX), u16::MAX);
+    chunk.write_opcode(OpCode::Return, u16::MAX);
+
+    let result = self.run(&chunk.finalize().into()).map(|()| self.pop());
+
+    self.ip = ip;
+    self.offset = offset;
+    self.frames = frames;
+    self.tasks = tasks;
+    self.task = task;
+    self.call_depth -= depth;
+
+    result
+  }
+
+  fn next_in_sequence(&mut self, sequence: &Value) -> Result<Option<Value>, RuntimeError> {
+    let step = match sequence.as_object() {
+      Object::Sequence(sequence) => sequence.borrow_mut().step(),
+      _ => Step::Done,
+    };
+
+    match step {
+      Step::Yield(value) => Ok(Some(value)),
+      Step::Done => Ok(None),
+      Step::Inner(sequence) => self.next_in_sequence(&sequence),
+      Step::Map(sequence, func) => match self.next_in_sequence(&sequence)? {
+        Some(value) => self.call(func, &[value]).map(Some),
+        None => Ok(None),
+      },
+      Step::Filter(sequence, func) => {
+        while let Some(value) = self.next_in_sequence(&sequence)? {
+          if !self
+            .call(func.clone(), core::slice::from_ref(&value))?
+            .is_falsy()
+          {
+            return Ok(Some(value));
+          }
+        }
+
+        Ok(None)
+      }
+    }
+  }
+
   pub fn run(&mut self, chunk: &Chunk) -> Result<(), RuntimeError> {
     self.ip = 0;
     self.offset = 0;
+    self.task = MAIN_TASK;
+    self.tasks.clear();
+    let first_task = self.task_count;
     let mut chunk: Chunk = chunk.clone();
 
     loop {
       let instruction = chunk.get(self.ip);
+      if self.count_instructions {
+        self.instructions += 1;
+      }
 
       match instruction {
         OpCode::Constant => {
@@ -231,14 +464,24 @@ impl VM<'_> {
           let (right, left) = (self.pop(), self.pop());
 
           if left.is_number() && right.is_number() {
-            self.push(Value::from(left.as_number() + right.as_number()));
+            let (left, right) = (left.as_number(), right.as_number());
+            let result = left + right;
+
+            if !result.is_finite()
+              && self.checked_arithmetic
+              && let Some(message) = arithmetic_error("+", left, right, result)
+            {
+              break runtime_error!((self, chunk), "{message}");
+            }
+            self.push(Value::from(result));
           } else if left.is_object() && right.is_object()
             && let Object::String(left) = left.as_object()
             && let Object::String(right) = right.as_object()
           {
             let mut new = left.clone();
             new.push_str(right);
-            self.push(new.into());
+            let new = self.small_string(new);
+            self.push(new);
           } else {
             break runtime_error!(
               (self, chunk),
@@ -287,7 +530,7 @@ impl VM<'_> {
 
         OpCode::Equal => {
           let (right, left) = (self.pop(), self.pop());
-          let equals = Value::equals(&left, &right, &mut self.cyclic);
+          let equals = Value::equals(&left, &right, self.strict_equality, &mut self.cyclic);
           self.push(equals.into());
 
           self.cyclic.clear();
@@ -295,7 +538,7 @@ impl VM<'_> {
         }
         OpCode::NotEqual => {
           let (right, left) = (self.pop(), self.pop());
-          let not_equals = !Value::equals(&left, &right, &mut self.cyclic);
+          let not_equals = !Value::equals(&left, &right, self.strict_equality, &mut self.cyclic);
           self.push(not_equals.into());
 
           self.cyclic.clear();
@@ -317,47 +560,81 @@ impl VM<'_> {
           comparison_expression!((self, chunk), >=);
           self.ip += 1;
         }
+        OpCode::Contains => {
+          let (collection, item) = (self.pop(), self.pop());
+
+          if let Some(contains) = self.contains(&collection, &item) {
+            self.push(contains.into());
+          } else {
+            break runtime_error!(
+              (self, chunk),
+              "Can't check membership of type {}",
+              collection.get_type()
+            );
+          }
+
+          self.ip += 1;
+        }
 
         OpCode::Pop => {
           self.stack.pop(); // Don't unwrap as could be empty.
           self.ip += 1;
         }
 
-        OpCode::DefineGlobal => {
-          let name_location = chunk.get_value(self.ip + 1);
-          let name = chunk.get_string(name_location.into());
+        OpCode::DefineGlobal | OpCode::DefineGlobalLong => {
+          let (name_location, length) = chunk.get_string_operand(self.ip);
+          let name = chunk.get_string(name_location);
 
           let value = self.pop();
-          self.globals.insert(name, value);
 
-          self.ip += 2;
+          // When hot reloading, keep the existing state and only replace functions
+          let keep_existing = self.hot_reloading
+            && self.globals.get(&name).is_some_and(|existing| {
+              !existing.is_object() || existing.as_object().get_function_arity().is_none()
+            });
+          if !keep_existing {
+            self.globals.insert(name, value);
+          }
+
+          self.ip += length;
         }
-        OpCode::GetGlobal => {
-          let name_location = chunk.get_value(self.ip + 1);
-          let name = chunk.get_string(name_location.into());
+        OpCode::GetGlobal | OpCode::GetGlobalLong => {
+          let (name_location, length) = chunk.get_string_operand(self.ip);
+          let name = chunk.get_string(name_location);
 
           let value = self.globals.get(&name).cloned();
 
           if let Some(value) = value {
             self.push(value);
           } else {
-            break runtime_error!((self, chunk), "Undefined variable '{}'", name);
+            let suggestion = did_you_mean(&name, self.globals.keys().map(AsRef::as_ref));
+            break runtime_error!((self, chunk), "Undefined variable '{}'{}", name, suggestion);
           }
 
-          self.ip += 2;
+          self.ip += length;
         }
-        OpCode::SetGlobal => {
-          let name_location = chunk.get_value(self.ip + 1);
-          let name = chunk.get_string(name_location.into());
+        OpCode::GetGlobalOptional | OpCode::GetGlobalOptionalLong => {
+          let (name_location, length) = chunk.get_string_operand(self.ip);
+          let name = chunk.get_string(name_location);
+
+          let value = self.globals.get(&name).cloned();
+          self.push(value.unwrap_or(Value::NULL));
+
+          self.ip += length;
+        }
+        OpCode::SetGlobal | OpCode::SetGlobalLong => {
+          let (name_location, length) = chunk.get_string_operand(self.ip);
+          let name = chunk.get_string(name_location);
           let value = self.peek().clone();
 
           if let hash_map::Entry::Occupied(mut entry) = self.globals.entry(name.clone()) {
             entry.insert(value);
           } else {
-            break runtime_error!((self, chunk), "Undefined variable '{}'", name);
+            let suggestion = did_you_mean(&name, self.globals.keys().map(AsRef::as_ref));
+            break runtime_error!((self, chunk), "Undefined variable '{}'{}", name, suggestion);
           }
 
-          self.ip += 2;
+          self.ip += length;
         }
         OpCode::GetLocal => {
           let slot = chunk.get_value(self.ip + 1);
@@ -401,9 +678,24 @@ impl VM<'_> {
         }
 
         OpCode::Return => {
-          if self.frames.is_empty() {
+          if self.frames.is_empty() && self.task == MAIN_TASK {
+            // Tasks which haven't been joined are cancelled, and results of this run are dropped
+            self.tasks.clear();
+            self.task_results.retain(|id, _| *id <= first_task);
             break Ok(());
           }
+          if self.frames.is_empty() {
+            let result = self.pop();
+            self.task_results.insert(self.task, result);
+
+            self.blocked = 0;
+            // The main task is always waiting whilst a spawned task runs
+            match self.tasks.pop_front() {
+              Some(task) => chunk = self.resume_task(task),
+              None => break runtime_error!((self, chunk), "No task is waiting to resume."),
+            }
+            continue;
+          }
 
           let result = self.pop();
           self.stack.drain(self.offset - 1..);
@@ -412,6 +704,12 @@ impl VM<'_> {
           chunk = self.restore_frame();
         }
         OpCode::Call => {
+          if !self.tasks.is_empty() && !mem::take(&mut self.resumed) {
+            self.blocked = 0;
+            chunk = self.switch_task(chunk);
+            continue;
+          }
+
           let arg_count = chunk.get_value(self.ip + 1);
           let pos = self.stack.len() - usize::from(arg_count) - 1;
           let callee = self.stack[pos].clone();
@@ -419,10 +717,17 @@ impl VM<'_> {
           if !callee.is_object() {
             break runtime_error!((self, chunk), "Can only call functions.");
           }
+          if self.frames.len() + self.call_depth >= self.max_call_depth {
+            break runtime_error!((self, chunk), "Maximum call depth exceeded.");
+          }
+          if self.stack.len() >= self.max_stack_size {
+            break runtime_error!((self, chunk), "Maximum stack size exceeded.");
+          }
 
           match callee.as_object() {
             Object::Function(func) => {
-              function_arity_check!((self, chunk), func.arity, arg_count);
+              function_arity_check!((self, chunk), func.name, func.arity, arg_count);
+              self.missing_arguments(func.arity, arg_count);
 
               let chunk = mem::replace(&mut chunk, func.chunk.clone());
               self.store_frame(chunk, SmallVec::new());
@@ -431,7 +736,13 @@ impl VM<'_> {
               self.offset = self.stack.len() - func.arity.get_count();
             }
             Object::Closure(closure) => {
-              function_arity_check!((self, chunk), closure.func.arity, arg_count);
+              function_arity_check!(
+                (self, chunk),
+                closure.func.name,
+                closure.func.arity,
+                arg_count
+              );
+              self.missing_arguments(closure.func.arity, arg_count);
 
               let chunk = mem::replace(&mut chunk, closure.func.chunk.clone());
               self.store_frame(chunk, closure.upvalues.clone());
@@ -440,15 +751,26 @@ impl VM<'_> {
               self.offset = self.stack.len() - closure.func.arity.get_count();
             }
             Object::NativeFunction(func) => {
-              function_arity_check!((self, chunk), func.arity, arg_count);
+              function_arity_check!((self, chunk), func.name, func.arity, arg_count);
+              self.missing_arguments(func.arity, arg_count);
 
               let start_of_args = self.stack.len() - func.arity.get_count();
+              let context = self.context;
               let result = {
                 let args = self.stack.drain(start_of_args..);
-                (func.func)(args.as_slice())
+                match (func.context_func, func.fallible_func) {
+                  (Some(context_func), _) => Ok(context_func(context, args.as_slice())),
+                  (None, Some(fallible_func)) => fallible_func(args.as_slice()),
+                  (None, None) => Ok((func.func)(args.as_slice())),
+                }
               };
-              self.pop();
-              self.push(result);
+              match result {
+                Ok(result) => {
+                  self.pop();
+                  self.push(result);
+                }
+                Err(message) => break runtime_error!((self, chunk), "{}", message),
+              }
 
               self.ip += 2;
             }
@@ -480,13 +802,44 @@ impl VM<'_> {
           let length = chunk.get_value(self.ip + 1);
           let start_of_items = self.stack.len() - usize::from(length) * 2;
 
-          let items = self
-            .stack
-            .drain(start_of_items..)
-            .tuples()
-            .collect::<HashMap<_, _>>();
+          let dict = HashMap::with_capacity_and_hasher(length.into(), Default::default());
+          let items = self.stack.drain(start_of_items..).tuples().try_fold(
+            dict,
+            |mut items, (key, value)| {
+              items.insert(key.as_dict_key()?, value);
+              Some(items)
+            },
+          );
+
+          match items {
+            Some(items) => self.push(items.into()),
+            None => break runtime_error!((self, chunk), "Can't use NaN as a dictionary key"),
+          }
 
-          self.push(items.into());
+          self.ip += 2;
+        }
+
+        OpCode::OmitKeys => {
+          let length = chunk.get_value(self.ip + 1);
+          let start_of_keys = self.stack.len() - usize::from(length);
+          let keys = self.stack.drain(start_of_keys..).collect::<Vec<_>>();
+          let dict = self.pop();
+
+          if dict.is_object()
+            && let Object::Dict(dict) = dict.as_object()
+          {
+            let mut rest = dict.borrow().clone();
+            for key in keys.iter().filter_map(Value::as_dict_key) {
+              rest.remove(&key);
+            }
+            self.push(rest.into());
+          } else {
+            break runtime_error!(
+              (self, chunk),
+              "Can only take the rest of a dict, but got {}.",
+              dict.get_type()
+            );
+          }
 
           self.ip += 2;
         }
@@ -498,7 +851,7 @@ impl VM<'_> {
           match item.get_property(&index) {
             GetResult::Found(value) => self.push(value),
             GetResult::NotFound => {
-              break runtime_error!((self, chunk), "Index '{}' not found", index);
+              break runtime_error!((self, chunk), "{}", index_not_found(&item, &index));
             }
             GetResult::NotSupported => {
               break runtime_error!((self, chunk), "Can't index type {}", item.get_type());
@@ -515,11 +868,17 @@ impl VM<'_> {
           match item.set_property(&index, value.clone()) {
             SetResult::Set => {}
             SetResult::NotFound => {
-              break runtime_error!((self, chunk), "Index '{}' not found", index);
+              break runtime_error!((self, chunk), "{}", index_not_found(&item, &index));
             }
             SetResult::NotSupported => {
               break runtime_error!((self, chunk), "Can't index type {}", item.get_type());
             }
+            SetResult::InvalidKey => {
+              break runtime_error!((self, chunk), "Can't use {} as a dictionary key", index);
+            }
+            SetResult::Frozen => {
+              break runtime_error!((self, chunk), "Can't modify a frozen {}", item.get_type());
+            }
           }
 
           self.push(value);
@@ -528,7 +887,9 @@ impl VM<'_> {
 
         OpCode::ToString => {
           let value = self.pop();
-          self.push(value.to_string().into());
+          let string = self.context.format_value(&value);
+          let string = self.small_string(string.unwrap_or_else(|| value.to_string()));
+          self.push(string);
 
           self.ip += 1;
         }
@@ -595,24 +956,138 @@ impl VM<'_> {
 
           self.ip += 2;
         }
+        OpCode::Allocate => {
+          let slot = chunk.get_value(self.ip + 1);
+          let local = &mut self.stack[self.offset + usize::from(slot)];
+          *local = local.clone().allocate();
+
+          self.ip += 2;
+        }
 
         OpCode::Import => {
           let (item, module) = (self.pop(), self.pop());
 
-          match self.context.get_value(module.as_str(), item.as_str()) {
+          match self.import_value(module.as_str(), item.as_str()) {
             ImportValue::Constant(value) => {
               self.push(value.clone());
             }
             ImportValue::ModuleNotFound => {
-              break runtime_error!((self, chunk), "Module Not Found",);
+              let suggestion = did_you_mean(module.as_str(), self.modules());
+              break runtime_error!(
+                (self, chunk),
+                "Module '{}' not found{}",
+                module.as_str(),
+                suggestion
+              );
             }
             ImportValue::ItemNotFound => {
-              break runtime_error!((self, chunk), "Item Not Found");
+              let items = self.module_items(module.as_str());
+              let suggestion = did_you_mean(item.as_str(), items);
+              break runtime_error!(
+                (self, chunk),
+                "Item '{}' not found in module '{}'{}",
+                item.as_str(),
+                module.as_str(),
+                suggestion
+              );
             }
           };
 
           self.ip += 1;
         }
+        OpCode::ImportOptional => {
+          let (item, module) = (self.pop(), self.pop());
+
+          match self.import_value(module.as_str(), item.as_str()) {
+            ImportValue::Constant(value) => self.push(value.clone()),
+            ImportValue::ModuleNotFound | ImportValue::ItemNotFound => self.push(Value::NULL),
+          }
+
+          self.ip += 1;
+        }
+
+        OpCode::Next => {
+          let sequence = self.peek().clone();
+
+          if !sequence.is_object() || !matches!(sequence.as_object(), Object::Sequence(_)) {
+            break runtime_error!((self, chunk), "Can only iterate over sequences");
+          }
+
+          match self.next_in_sequence(&sequence) {
+            Ok(Some(value)) => {
+              self.push(value);
+              self.ip += 3;
+            }
+            Ok(None) => {
+              let jump = chunk.get_long_value(self.ip + 1);
+              self.ip += usize::from(jump) + 1;
+            }
+            Err(error) => break Err(error),
+          }
+        }
+
+        OpCode::Spawn => {
+          let callee = self.pop();
+
+          if !callee.is_object() || callee.as_object().get_function_arity().is_none() {
+            break runtime_error!((self, chunk), "Can only spawn functions.");
+          }
+
+          let mut entry = crate::chunk::Chunk::new();
+          entry.write_opcode(OpCode::Call, u16::MAX);
+          entry.write_value(0, u16::MAX);
+          entry.write_opcode(OpCode::Return, u16::MAX);
+
+          self.task_count += 1;
+          self.tasks.push_back(Task {
+            id: self.task_count,
+            ip: 0,
+            offset: 0,
+            chunk: entry.finalize().into(),
+            stack: vec![callee],
+            frames: Vec::new(),
+          });
+
+          self.push(Object::Task(self.task_count).into());
+          self.ip += 1;
+        }
+        OpCode::Join => {
+          let handle = self.peek();
+          let id = if handle.is_object()
+            && let Object::Task(id) = handle.as_object()
+          {
+            *id
+          } else {
+            break runtime_error!((self, chunk), "Can only join tasks.");
+          };
+
+          if let Some(result) = self.task_results.get(&id) {
+            let result = result.clone();
+            self.pop();
+            self.push(result);
+
+            self.blocked = 0;
+            self.ip += 1;
+          } else if id == self.task {
+            break runtime_error!((self, chunk), "A task can't join itself.");
+          } else if !self.tasks.iter().any(|task| task.id == id) {
+            break runtime_error!((self, chunk), "Task has been cancelled.");
+          } else if self.blocked > self.tasks.len() {
+            break runtime_error!((self, chunk), "Deadlock, all tasks are waiting to join.");
+          } else {
+            self.blocked += 1;
+            chunk = self.switch_task(chunk);
+          }
+        }
+        OpCode::AssertNonNull | OpCode::AssertNonNullLong => {
+          let (expression_location, length) = chunk.get_string_operand(self.ip);
+          if *self.peek() == Value::NULL {
+            let expression = chunk.get_string(expression_location);
+            break runtime_error!((self, chunk), "Expected '{}' not to be null", expression);
+          }
+
+          self.ip += length;
+        }
 
         _ => {
           break runtime_error!((self, chunk), "Unknown OpCode");
@@ -624,6 +1099,11 @@ impl VM<'_> {
     }
   }
 
+  // The value returned from the chunk, which is the last expression for `compile_keeping_last_value`
+  pub fn evaluate(&mut self, chunk: &Chunk) -> Result<Value, RuntimeError> {
+    self.run(chunk).map(|()| self.pop())
+  }
+
   pub fn define_global(&mut self, name: &str, value: Value) {
     self.globals.insert(Rc::from(name), value);
   }
@@ -632,6 +1112,158 @@ impl VM<'_> {
     self.globals.get(name).cloned()
   }
 
+  pub fn register_native_module(&mut self, name: &str, items: &[(&str, Value)]) {
+    let items = items
+      .iter()
+      .map(|(item, value)| (Rc::from(*item), value.clone()));
+
+    self.native_modules.insert(Rc::from(name), items.collect());
+  }
+
+  // Modules registered on the VM are checked before those from the context, which they extend
+  fn import_value(&self, module: &str, item: &str) -> ImportValue {
+    let items = self.native_modules.get(module);
+    match items.and_then(|items| items.get(item)) {
+      Some(value) => ImportValue::Constant(value.clone()),
+      None => match self.context.get_value(module, item) {
+        ImportValue::ModuleNotFound if items.is_some() => ImportValue::ItemNotFound,
+        value => value,
+      },
+    }
+  }
+
+  fn modules(&self) -> Vec<&str> {
+    let mut modules = self.context.modules();
+    modules.extend(self.native_modules.keys().map(AsRef::as_ref));
+    modules
+  }
+
+  fn module_items(&self, module: &str) -> Vec<&str> {
+    let mut items = self.context.items(module);
+    if let Some(native_items) = self.native_modules.get(module) {
+      items.extend(native_items.keys().map(AsRef::as_ref));
+    }
+    items
+  }
+
+  pub fn set_strict_equality(&mut self, strict: bool) {
+    self.strict_equality = strict;
+  }
+
+  // Dividing by zero, or results which are NaN or too large, are errors rather than values
+  pub fn set_checked_arithmetic(&mut self, checked: bool) {
+    self.checked_arithmetic = checked;
+  }
+
+  pub fn set_max_call_depth(&mut self, depth: usize) {
+    self.max_call_depth = depth;
+  }
+
+  pub fn set_max_stack_size(&mut self, size: usize) {
+    self.max_stack_size = size;
+  }
+
+  // Instructions are only counted when asked for, as it slows down every instruction
+  pub fn set_count_instructions(&mut self, count: bool) {
+    self.count_instructions = count;
+  }
+
+  pub fn hot_reload(&mut self, chunk: &Chunk) -> Result<Vec<String>, RuntimeError> {
+    let updated = self.changed_functions(chunk);
+
+    self.hot_reloading = true;
+    let result = self.run(chunk);
+    self.hot_reloading = false;
+
+    result.map(|()| updated)
+  }
+
+  fn changed_functions(&self, chunk: &Chunk) -> Vec<String> {
+    let mut changed = Vec::new();
+    let mut position = 0;
+
+    while position < chunk.length() {
+      let opcode = chunk.get(position);
+      let next = position + opcode.number_of_bytes().unwrap_or(1);
+
+      let constant = match opcode {
+        OpCode::Constant => Some(chunk.get_constant(chunk.get_value(position + 1).into())),
+        OpCode::ConstantLong => Some(chunk.get_constant(chunk.get_long_value(position + 1).into())),
+        _ => None,
+      };
+
+      if let Some(constant) = constant
+        && next < chunk.length()
+        && matches!(
+          chunk.get(next),
+          OpCode::DefineGlobal | OpCode::DefineGlobalLong
+        )
+        && constant.is_object()
+        && let Object::Function(function) = constant.as_object()
+      {
+        let name = chunk.get_string(chunk.get_string_operand(next).0);
+        let unchanged = match self.globals.get(&name) {
+          Some(existing) if existing.is_object() => match existing.as_object() {
+            Object::Function(existing) => existing.chunk.has_same_code(&function.chunk),
+            _ => false,
+          },
+          _ => false,
+        };
+
+        if !unchanged {
+          changed.push(name.as_ref().into());
+        }
+      }
+
+      position = next;
+    }
+
+    changed
+  }
+
+  pub fn snapshot(&self) -> GlobalsSnapshot {
+    let mut copies = HashMap::default();
+    let globals = self
+      .globals
+      .iter()
+      .map(|(name, value)| (name.clone(), value.deep_clone(&mut copies)));
+
+    GlobalsSnapshot {
+      globals: globals.collect(),
+    }
+  }
+
+  // The snapshot is copied again, so it can be restored more than once
+  pub fn restore(&mut self, snapshot: &GlobalsSnapshot) {
+    let mut copies = HashMap::default();
+    let globals = snapshot
+      .globals
+      .iter()
+      .map(|(name, value)| (name.clone(), value.deep_clone(&mut copies)));
+
+    self.globals = globals.collect();
+  }
+
+  // How many instructions have been run while counting, including by earlier chunks
+  pub fn instruction_count(&self) -> u64 {
+    self.instructions
+  }
+
+  pub fn global_count(&self) -> usize {
+    self.globals.len()
+  }
+
+  pub fn memory_usage(&self) -> usize {
+    let mut seen = HashSet::default();
+
+    self
+      .globals
+      .values()
+      .chain(self.stack.iter())
+      .map(|value| mem::size_of::<Value>() + value.heap_size(&mut seen))
+      .sum()
+  }
+
   #[cfg(feature = "debug")]
   fn print_stack(&self, ip: usize) {
     println!(
@@ -654,9 +1286,112 @@ impl Default for VM<'_> {
       stack: Vec::with_capacity(64),
       frames: Vec::with_capacity(16),
       globals: HashMap::default(),
+      native_modules: HashMap::default(),
       cyclic: BTreeSet::default(),
+      strings: HashMap::default(),
+      hot_reloading: false,
+      strict_equality: false,
+      checked_arithmetic: false,
+      call_depth: 0,
+      max_call_depth: MAX_CALL_DEPTH,
+      max_stack_size: MAX_STACK_SIZE,
+      count_instructions: false,
+      instructions: 0,
+
+      task: MAIN_TASK,
+      tasks: VecDeque::new(),
+      task_results: HashMap::default(),
+      task_count: 0,
+      resumed: false,
+      blocked: 0,
 
       context: &context::Empty,
     }
   }
 }
+
+// Optimal string alignment distance, so swapped letters count as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+  let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+  let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+  for (i, row) in distances.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, distance) in distances[0].iter_mut().enumerate() {
+    *distance = j;
+  }
+
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      let mut distance = (distances[i - 1][j] + 1)
+        .min(distances[i][j - 1] + 1)
+        .min(distances[i - 1][j - 1] + cost);
+
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        distance = distance.min(distances[i - 2][j - 2] + 1);
+      }
+
+      distances[i][j] = distance;
+    }
+  }
+
+  distances[a.len()][b.len()]
+}
+
+// NaN or infinite operands already came from somewhere, so only new ones are errors
+fn arithmetic_error(operator: &str, left: f64, right: f64, result: f64) -> Option<&'static str> {
+  if matches!(operator, "/" | "%") && right == 0.0 {
+    Some("Division by zero.")
+  } else if result.is_nan() && !left.is_nan() && !right.is_nan() {
+    Some("Result of arithmetic is not a number.")
+  } else if result.is_infinite() && left.is_finite() && right.is_finite() {
+    Some("Arithmetic overflow, the result is too large to be represented.")
+  } else {
+    None
+  }
+}
+
+// Says how big the collection is, and for dicts which keys are closest to the missing one
+fn index_not_found(item: &Value, index: &Value) -> String {
+  let message = format!("Index '{index}' not found");
+
+  match item.as_object() {
+    Object::String(string) => {
+      format!("{message} in string of length {}", string.chars().count()).into()
+    }
+    Object::List(list) => format!("{message} in list of length {}", list.borrow().len()).into(),
+    Object::Dict(dict) if dict.borrow().is_empty() => format!("{message} in empty dict").into(),
+    Object::Dict(dict) => {
+      let dict = dict.borrow();
+      let missing = index.to_string();
+      let nearest = (dict.keys())
+        .map(|key| key.to_string())
+        .map(|key| (edit_distance(&missing, &key), key))
+        .sorted()
+        .take(NEAREST_KEYS)
+        .map(|(_, key)| format!("'{key}'"))
+        .join(", ");
+      let size = match dict.len() {
+        1 => "1 key".to_string(),
+        size => format!("{size} keys"),
+      };
+
+      format!("{message} in dict of {size}. Nearest keys: {nearest}").into()
+    }
+    _ => message.into(),
+  }
+}
+
+fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+  let max_distance = (name.len() / 3).max(1);
+
+  candidates
+    .into_iter()
+    .map(|candidate| (edit_distance(name, candidate), candidate))
+    .filter(|(distance, _)| *distance <= max_distance)
+    .min()
+    .map(|(_, candidate)| format!(". Did you mean '{candidate}'?").into())
+    .unwrap_or_default()
+}
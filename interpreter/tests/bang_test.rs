@@ -6,7 +6,7 @@ pub mod bang {
 }
 
 pub enum RunResult<'a> {
-  Success(bang::VM<'a>),
+  Success(Box<bang::VM<'a>>),
   RuntimeError,
   CompileError,
   ValidationError,
@@ -39,7 +39,7 @@ pub fn run<'a>(source: &str, context: &'a dyn bang::context::Context) -> RunResu
 
   let mut vm = bang::VM::new(context);
   match vm.run(&chunk) {
-    Ok(_) => RunResult::Success(vm),
+    Ok(_) => RunResult::Success(Box::new(vm)),
     Err(_) => RunResult::RuntimeError,
   }
 }
@@ -58,7 +58,9 @@ macro_rules! bang_test {
             let variable = vm.get_global(stringify!($var)).unwrap();
             let expected = bang::Value::from($expected);
 
-            assert!(variable == expected, "Expected {expected}, got {variable}");
+            if let Err(mismatch) = variable.deep_eq_with_tolerance(&expected, f64::EPSILON) {
+              panic!("Expected {expected}, got {variable} ({mismatch})");
+            }
           };
         )*
       } else {
@@ -69,6 +69,65 @@ let squareRootIsFunction = type(squareRoot) == 'function'
   squareRootIsFunction == true
 );
 
+bang_test!(deep_copy
+"
+let a = { 'list': [1, [2]], 'name': 'a' }
+let b = deepCopy(a)
+b['list'][1][0] = 3
+let c = a['list'][1][0]
+let d = b['list'][1][0]
+let e = deepCopy(a) == a
+let f = deepCopy(5)
+"
+  c == 2
+  d == 3
+  e == true
+  f == 5
+);
+
+bang_test!(deep_copy_cycles
+"
+let a = [1]
+a >> list::push(a)
+let b = deepCopy(a)
+b[0] = 2
+let c = a[0]
+let d = b[1][0]
+"
+  c == 1
+  d == 2
+);
+
+bang_test!(deep_copy_keeps_frozen
+"
+let a = deepCopy(list::freeze([1, [2]]))
+a[1][0] = 3
+a[0] = 5
+"
+  RuntimeError
+);
+
+bang_test!(inspect_value
+"
+let a = inspect(3)['type']
+let b = inspect('hello')['length']
+let c = inspect([1, 2, 3])['length']
+let d = inspect({ 'x': 1 })['keys'] == ['x']
+let e = inspect(maths::floor)['name']
+let f = inspect((a, b) => a)['arity']
+let g = inspect(null) == { 'type': 'null' }
+let h = inspect(set::new())['length']
+"
+  a == "number"
+  b == 5
+  c == 3
+  d == true
+  e == "maths::floor"
+  f == 2
+  g == true
+  h == 0
+);
+
 bang_test!(unknown_module_access
   "unknown_module::stuff"
   RuntimeError
@@ -85,7 +144,21 @@ let a = maths::cos(0)
 let b = '${list::push}'
   "
   a == 1
-  b == "<function list::push>"
+  b == "<function list::push(2 params)>"
+);
+
+bang_test!(module_alias
+  "
+from maths as m
+from list as l import { length }
+
+let a = m::cos(0)
+let b = m::sin == maths::sin
+let c = l::length == length
+  "
+  a == 1
+  b == true
+  c == true
 );
 
 bang_test!(equality
@@ -287,6 +360,40 @@ let b = radiansToDegrees(PI)
     a ==  3.141592653589793
     b == 180
   );
+
+  bang_test!(approx_equal
+    "
+from maths import { approxEqual, INFINITY }
+
+let a = approxEqual(0.1 + 0.2, 0.3, 0.0001)
+let b = approxEqual(1, 1.1, 0.01)
+let c = approxEqual(INFINITY, INFINITY, 0)
+  "
+    a == true
+    b == false
+    c == true
+  );
+
+  bang_test!(nan_and_infinity
+    "
+from maths import { NAN, INFINITY, isNaN, isFinite }
+
+let a = isNaN(NAN)
+let b = isNaN(5)
+let c = isNaN(INFINITY - INFINITY)
+let d = isFinite(5)
+let e = isFinite(INFINITY)
+let f = isFinite(-INFINITY)
+let g = isFinite(NAN)
+  "
+    a == true
+    b == false
+    c == true
+    d == true
+    e == false
+    f == false
+    g == false
+  );
 }
 
 mod string {
@@ -331,10 +438,14 @@ from string import { repeat }
 let a = repeat('-', 3)
 let b = 'ello ' >> repeat(3)
 let c = repeat(3, '-') == null
+let d = repeat('-', -1) == null
+let e = repeat('-', 1.5) == null
 "
     a == "---"
     b == "ello ello ello "
     c == true
+    d == true
+    e == true
   );
 
   bang_test!(includes
@@ -433,6 +544,47 @@ let [j] = 'hello' >> split('d')
    i == "o"
    j == "hello"
   );
+
+  bang_test!(format
+"
+from string import { format }
+
+let name = 'World'
+let a = format('Hello {name}!', { name })
+let b = format('{count:03d} {{items}}', { 'count': 7 })
+let c = format('{price:.2f}', { 'price': 2.5 })
+let d = format('{missing}', { name }) == null
+let e = format('{name', { name }) == null
+"
+    a == "Hello World!"
+    b == "007 {items}"
+    c == "2.50"
+    d == true
+    e == true
+  );
+
+  bang_test!(sprintf
+"
+from string import { sprintf }
+
+let a = sprintf('%05.2f', [1.23456])
+let b = sprintf('%s has %d items (100%%)', ['list', 3])
+let c = sprintf('[%-4s]', ['ab'])
+let d = sprintf('%x', [255])
+let e = sprintf('%d %d', [1]) == null
+let f = sprintf('%d', ['a']) == null
+let g = sprintf('%s', [[1, 2]])
+let h = sprintf('%d', 5) == null
+"
+    a == "01.23"
+    b == "list has 3 items (100%)"
+    c == "[ab  ]"
+    d == "ff"
+    e == true
+    f == true
+    g == "[1, 2]"
+    h == true
+  );
 }
 
 mod list {
@@ -575,10 +727,10 @@ a >> list::push(7)
 let w = toString(a)
 let z = toString(b)
   "
-    x == "[[...]]"
-    y == "[[...]]"
-    w == "[[...], 7]"
-    z == "[[..., 7]]"
+    x == "<ref *1> [[[Circular *1]]]"
+    y == "<ref *1> [[[Circular *1]]]"
+    w == "<ref *1> [[[Circular *1]], 7]"
+    z == "<ref *1> [[[Circular *1], 7]]"
   );
 
   bang_test!(complex_cyclic_to_string
@@ -592,8 +744,8 @@ b >> list::push(c)
 let x = toString(a)
 let y = toString(b)
 "
-    x == "['a', ['b', ['c', ...]]]"
-    y == "['b', ['c', ...]]"
+    x == "['a', <ref *1> ['b', ['c', [Circular *1]]]]"
+    y == "<ref *1> ['b', ['c', [Circular *1]]]"
   );
 
   bang_test!(complex_cyclic_equals
@@ -660,6 +812,49 @@ let c = b == null
     c == true
   );
 
+  bang_test!(find_index
+  "
+from list import { findIndex }
+
+let a = [1, 2, 3, 4] >> findIndex((x) => x >= 2)
+let b = findIndex([1, 2, 3], (x) => x >= 55) == null
+  "
+    a == 1
+    b == true
+  );
+
+  bang_test!(index_of
+  "
+from list import { indexOf, lastIndexOf, count }
+
+let a = [1, 2, 3, 2] >> indexOf(2)
+let b = [1, 2, 3, 2] >> lastIndexOf(2)
+let c = indexOf([1, 2, 3], 4) == null
+let d = lastIndexOf([], 4) == null
+let e = ['a', 'b', 'a'] >> count('a')
+let f = [1, 2, 3] >> count(4)
+  "
+    a == 1
+    b == 3
+    c == true
+    d == true
+    e == 2
+    f == 0
+  );
+
+  bang_test!(sum
+  "
+from list import { sum }
+
+let a = sum([1, 2, 3.5])
+let b = sum([])
+let c = sum([1, 'a']) == null
+  "
+    a == 6.5
+    b == 0
+    c == true
+  );
+
   bang_test!(for_each
   "
 let a = 0
@@ -716,6 +911,108 @@ let h = min([2, 1, 3])
   g == 1
   h == 1
   );
+
+  bang_test!(compare
+  "
+from list import { compare }
+
+let a = compare([1, 2, 3], [1, 2, 3])
+let b = compare([1, 2], [1, 3])
+let c = compare([2], [1, 5])
+let d = compare([1, 2], [1, 2, 0])
+let e = compare(['b'], ['a', 'c'])
+let f = compare([1, 2], [1], (x, y) => y - x)
+let g = compare([1, 3], [1, 2], (x, y) => y - x)
+  "
+  a == 0
+  b == -1
+  c == 1
+  d == -1
+  e == 1
+  f == 1
+  g == -1
+  );
+
+  bang_test!(compare_with_less_than
+  "[1, 2] < [1, 3]"
+  RuntimeError
+  );
+
+  #[test]
+  fn compare_error_message() {
+    let context = bang::StdContext::default();
+
+    let chunk = bang::compile("[1, 2] <= [1, 3]").unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+    assert_eq!(
+      error.message,
+      "Lists can't be compared with '<=', use `list::compare` instead."
+    );
+
+    let chunk = bang::compile("{} > 1").unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+    assert_eq!(
+      error.message,
+      "Operands must be two numbers or two strings, but got dict and number."
+    );
+  }
+
+  bang_test!(freeze
+  "
+from list import { freeze, get, length }
+
+let a = freeze([1, 2])
+let d = a >> length()
+let e = a >> get(1)
+let f = freeze({}) == null
+  "
+  d == 2
+  e == 2
+  f == true
+  );
+
+  bang_test!(freeze_push
+  "
+let a = list::freeze([1, 2])
+a >> list::push(3)
+  "
+  RuntimeError
+  );
+
+  bang_test!(freeze_pop
+  "
+let a = list::freeze([1, 2])
+a >> list::pop()
+  "
+  RuntimeError
+  );
+
+  bang_test!(freeze_index_assignment
+  "
+let a = list::freeze([1, 2])
+a[0] = 3
+  "
+  RuntimeError
+  );
+
+  bang_test!(copy
+  "
+from list import { copy, freeze, push, length }
+
+let a = [1, [2]]
+let b = copy(a)
+b >> push(3)
+b[1] >> push(4)
+let c = a >> length()
+let d = a[1] >> length()
+let e = copy(freeze([1])) >> push(2) >> length()
+let f = copy(5) == null
+  "
+  c == 2
+  d == 2
+  e == 2
+  f == true
+  );
 }
 
 mod set {
@@ -864,7 +1161,7 @@ a >> set::insert(b)
 // No assertion as set ordering isn't constant
 let z = toString(a)
 "
-    z == "set(set(...))"
+    z == "<ref *1> set(set([Circular *1]))"
   );
 
   bang_test!(falsy
@@ -883,6 +1180,62 @@ if (c)
     a == 0
     b == 1
   );
+
+  bang_test!(list_conversion
+"
+from set import { fromList, toList, size, includes }
+
+let a = fromList([1, 2, 2, 3, 3, 3])
+let b = size(a)
+let c = a >> includes(2)
+let d = list::length(toList(a))
+let e = toString(toList(fromList([4])))
+"
+    b == 3
+    c == true
+    d == 3
+    e == "[4]"
+  );
+
+  bang_test!(copy
+"
+from set import { fromList, copy, insert, size }
+
+let a = fromList([1, 2])
+let b = copy(a)
+b >> insert(3)
+let c = size(a)
+let d = size(b)
+let e = copy(a) == a
+"
+    c == 2
+    d == 3
+    e == true
+  );
+
+  bang_test!(map_filter_fold
+"
+from set import { fromList, map, filter, fold, size, includes }
+
+let a = fromList([1, 2, 3, 4])
+let b = a >> map((x) => x * 10)
+let c = size(b)
+let d = b >> includes(40)
+let e = type(b)
+let f = a >> filter((x) => x > 2)
+let g = size(f)
+let h = f >> includes(1)
+let i = a >> fold(0, (total, x) => total + x)
+let j = size(fromList([1, 2, 3]) >> map((x) => x % 2))
+"
+    c == 4
+    d == true
+    e == "set"
+    g == 2
+    h == false
+    i == 10
+    j == 2
+  );
 }
 
 mod dict {
@@ -948,6 +1301,72 @@ let j = (a >> get(44)) == null
       j == true
     );
 
+  bang_test!(has_key
+"
+from dict import { hasKey }
+
+let a = { 'hello': null, 1: 2, true: 3 }
+a[null] = 4
+let b = hasKey(a, 'hello')
+let c = hasKey(a, 'world')
+let d = hasKey(a, 1)
+let e = hasKey(a, true)
+let f = hasKey(a, null)
+let g = hasKey(a, false)
+"
+    b == true
+    c == false
+    d == true
+    e == true
+    f == true
+    g == false
+  );
+
+  bang_test!(negative_zero_key
+"
+let a = { -0: 'zero' }
+a[0] = 'positive'
+let b = dict::size(a)
+let c = a[-0]
+let d = dict::hasKey({ 0: 1 }, -0)
+"
+    b == 1
+    c == "positive"
+    d == true
+  );
+
+  bang_test!(sorted
+"
+from dict import { sortedKeys, toSortedList }
+
+let a = { 'b': 2, 10: 'ten', 'a': 1, 2: 'two', false: 0 }
+let b = toString(sortedKeys(a))
+let c = toString(sortedKeys({ 'b': 1, 'cc': 2, 'a': 3 }, (x, y) => string::length(y) - string::length(x)))
+let d = toString(toSortedList({ 'z': 26, 'y': 25 }))
+let e = toString(sortedKeys({}))
+"
+    b == "[2, 10, 'a', 'b', false]"
+    c == "['cc', 'a', 'b']"
+    d == "[['y', 25], ['z', 26]]"
+    e == "[]"
+  );
+
+  bang_test!(nan_key_literal "let a = { (0 / 0): 1 }" RuntimeError);
+  bang_test!(nan_key_assignment "let a = {}\na[0 / 0] = 1" RuntimeError);
+  bang_test!(identity_keys
+"
+let key = [1, 2]
+let f = () => 1
+let a = { key: 1, f: 2 }
+let b = dict::hasKey(a, key)
+let c = dict::hasKey(a, [1, 2])
+let d = a[f]
+"
+    b == true
+    c == false
+    d == 2
+  );
+
   bang_test!(falsy
 "
 let a = 0
@@ -1032,8 +1451,8 @@ b[a] = 4
 let x = toString(a)
 let y = toString(b)
         "
-    x == "{ ...: 7 }"
-    y == "{ { ...: 7 }: 4 }"
+    x == "<ref *1> { [Circular *1]: 7 }"
+    y == "{ <ref *1> { [Circular *1]: 7 }: 4 }"
   );
 
   bang_test!(cyclic_keys_value
@@ -1047,7 +1466,693 @@ b[a] = 7
 let x = toString(a)
 let y = toString(b)
   "
-    x == "{ 7: ... }"
-    y == "{ { 7: ... }: 7 }"
+    x == "<ref *1> { 7: [Circular *1] }"
+    y == "{ <ref *1> { 7: [Circular *1] }: 7 }"
   );
+
+  bang_test!(freeze
+    "
+from dict import { freeze, get }
+
+let a = { 'a': 1 }
+let b = freeze(a) >> get('a')
+let c = freeze(3) == null
+  "
+    b == 1
+    c == true
+  );
+
+  bang_test!(freeze_index_assignment
+    "
+let a = dict::freeze({ 'a': 1 })
+a['b'] = 2
+  "
+    RuntimeError
+  );
+
+  bang_test!(copy
+    "
+from dict import { copy, freeze, size }
+
+let a = { 'a': [1] }
+let b = copy(a)
+b['b'] = 2
+b['a'][0] = 5
+let c = a >> size()
+let d = a['a'][0]
+let e = copy(freeze({ 'a': 1 }))
+e['b'] = 2
+let f = e >> size()
+  "
+    c == 1
+    d == 5
+    f == 2
+  );
+}
+
+mod function {
+  use super::*;
+
+  bang_test!(name
+"
+from function import { name }
+
+let add = (a, b) => a + b
+let a = name(add)
+let b = name(print)
+let c = name(list::push)
+let d = name(5) == null
+"
+    a == "add"
+    b == "print"
+    c == "list::push"
+    d == true
+  );
+
+  bang_test!(arity
+"
+from function import { arity }
+
+let add = (a, b) => a + b
+let a = arity(add)
+let b = arity(() => 5)
+let c = arity(print)
+let d = arity('hello') == null
+"
+    a == 2
+    b == 0
+    c == 1
+    d == true
+  );
+
+  bang_test!(display
+"
+let add = (a, b) => a + b
+let a = toString(add)
+let b = toString(print)
+"
+    a == "<function add(2 params)>"
+    b == "<function print(1 param)>"
+  );
+}
+
+mod iter {
+  use super::*;
+
+  bang_test!(range
+"
+from iter import { range, collect }
+
+let a = toString(collect(range(0, 5)))
+let b = toString(collect(range(3, 1)))
+"
+    a == "[0, 1, 2, 3, 4]"
+    b == "[]"
+  );
+
+  bang_test!(from_list_and_string
+"
+from iter import { collect }
+
+let a = toString(collect([1, 2, 3]))
+let b = toString(collect('abc'))
+"
+    a == "[1, 2, 3]"
+    b == "['a', 'b', 'c']"
+  );
+
+  bang_test!(pipeline
+"
+from iter import { range, map, filter, take, collect }
+
+let a = toString(range(0, 10) >> map((x) => x * 2) >> filter((x) => x > 5) >> take(3) >> collect())
+let b = toString(collect(map([1, 2, 3], (x) => x + 1)))
+"
+    a == "[6, 8, 10]"
+    b == "[2, 3, 4]"
+  );
+
+  bang_test!(lazy
+"
+from iter import { repeat, range, map, take, collect }
+
+let a = toString(collect(take(repeat('a'), 3)))
+
+let calls = 0
+let count = (x) ->
+  calls = calls + 1
+  return x
+let b = toString(collect(take(map(range(0, 100), count), 2)))
+let c = calls
+"
+    a == "['a', 'a', 'a']"
+    b == "[0, 1]"
+    c == 2
+  );
+
+  bang_test!(type_and_display
+"
+from iter import { range }
+
+let a = type(range(0, 1))
+let b = toString(range(0, 1))
+"
+    a == "sequence"
+    b == "<sequence>"
+  );
+}
+
+mod task {
+  use super::*;
+
+  bang_test!(spawn_and_join
+"
+from task import { spawn, join }
+
+let double = (x) => x * 2
+let handle = spawn(() => double(21))
+let a = join(handle)
+let b = join(handle)
+let c = type(handle)
+"
+    a == 42
+    b == 42
+    c == "task"
+  );
+
+  bang_test!(interleaves_tasks
+"
+from task import { spawn, join }
+from list import { push }
+
+let log = []
+let work = (name) ->
+  push(log, name + '1')
+  push(log, name + '2')
+  push(log, name + '3')
+  return name
+
+let a = spawn(() => work('a'))
+let b = spawn(() => work('b'))
+let results = toString([join(a), join(b)])
+let order = toString(log)
+"
+    results == "['a', 'b']"
+    order == "['a1', 'a2', 'b1', 'a3', 'b2', 'b3']"
+  );
+
+  bang_test!(unjoined_tasks_are_cancelled
+"
+from task import { spawn }
+
+let ran = false
+spawn(() => ran = true)
+"
+    ran == false
+  );
+
+  bang_test!(join_non_task
+"
+from task import { join }
+join(5)
+"
+    RuntimeError
+  );
+
+  bang_test!(spawn_non_function
+"
+from task import { spawn }
+spawn(5)
+"
+    RuntimeError
+  );
+
+  #[test]
+  fn deadlock() {
+    let context = bang::StdContext::default();
+    let source = "
+from task import { spawn, join }
+
+let a = null
+let getA = () => a
+let b = spawn(() => join(getA()))
+a = spawn(() => join(b))
+join(a)
+";
+    let chunk = bang::compile(source).unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+    assert_eq!(error.message, "Deadlock, all tasks are waiting to join.");
+  }
+
+  bang_test!(error_in_task
+"
+from task import { spawn, join }
+join(spawn(() => 5 + 'a'))
+"
+    RuntimeError
+  );
+}
+
+mod memory {
+  use super::*;
+
+  bang_test!(usage
+"
+from memory import { usage }
+
+let a = usage(null) == usage(5)
+let b = usage([1, 2, 3]) > usage([])
+let c = usage({ 'key': [1, 2, 3] }) > usage([1, 2, 3])
+let d = type(usage('hello'))
+"
+    a == true
+    b == true
+    c == true
+    d == "number"
+  );
+
+  bang_test!(cyclic_values
+"
+from memory import { usage }
+from list import { push }
+
+let a = []
+push(a, a)
+let b = usage(a) > usage([])
+"
+    b == true
+  );
+}
+
+#[test]
+fn registered_module() {
+  use bang::value::NativeFunction;
+
+  let double = NativeFunction::new("double", 1, |args| (args[0].as_number() * 2.0).into());
+
+  let mut context = bang::StdContext::default();
+  context.register_module(
+    "mylib",
+    &[("double", double.into()), ("answer", 42.0.into())],
+  );
+
+  let source = "
+from mylib import { double, answer }
+let a = double(answer)
+let b = type(mylib::double)
+";
+  if let RunResult::Success(vm) = run(source, &context) {
+    assert_eq!(vm.get_global("a").unwrap(), 84.0.into());
+    assert_eq!(vm.get_global("b").unwrap(), "function".into());
+  } else {
+    panic!("Execution not successful")
+  }
+
+  assert_eq!(
+    run("from mylib import { unknown }", &context),
+    RunResult::RuntimeError
+  );
+  assert_eq!(
+    run("from otherlib import { double }", &context),
+    RunResult::RuntimeError
+  );
+}
+
+#[test]
+fn import_resolver_modules() {
+  let mut context = bang::StdContext::default();
+  context.set_import_resolver(|module: &str| match module {
+    "./shapes" => Some("let sides = 4\nlet area = (width) => width * width\n".to_string()),
+    "./scaled" => Some("from './shapes' import { area }\nlet scale = 2\nlet scaledArea = (width) => area(width * scale)\n".to_string()),
+    "./cycle" => Some("from './cycle' import { a }\nlet a = 1\n".to_string()),
+    "./broken" => Some("let a = 1\nlet a = 2\n".to_string()),
+    "./state" => Some("let items = []\n".to_string()),
+    "./rec" => Some("let fact = (n) -> number\n  if (n <= 1) return 1\n  return n * fact(n - 1)\n".to_string()),
+    _ => None,
+  });
+
+  let source = "
+from './shapes' import { sides, area }
+from './scaled' import { scaledArea as scaled }
+from './shapes' as shapes
+let a = area(sides)
+let b = scaled(3)
+let c = () ->
+  from './shapes' import { area }
+  return area(5) + shapes::sides
+let d = c()
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), 16.0.into());
+  assert_eq!(vm.get_global("b").unwrap(), 36.0.into());
+  assert_eq!(vm.get_global("d").unwrap(), 29.0.into());
+  assert!(vm.get_global("scale").is_none());
+
+  // A module is only run once, so its values are shared between imports
+  let source = "
+from './state' import { items }
+from './state' as state
+list::push(items, 1)
+let add = () ->
+  from './state' import { items }
+  list::push(items, 2)
+add()
+add()
+let e = list::length(state::items)
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("e").unwrap(), 3.0.into());
+
+  // Functions in a module can call themselves, without using the importer's globals
+  let source = "
+from './rec' import { fact as f }
+from './rec' as rec
+let fact = (n) => 0
+let g = f(5)
+let h = rec::fact(4)
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("g").unwrap(), 120.0.into());
+  assert_eq!(vm.get_global("h").unwrap(), 24.0.into());
+
+  let chunk =
+    bang::compile_with_context("from './rec' import { fact }\nlet i = fact(3)", &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("i").unwrap(), 6.0.into());
+
+  let error = |source| bang::compile_with_context(source, &context).unwrap_err();
+  assert_eq!(
+    error("from './shapes' import { volume }").message,
+    "Item 'volume' not found in module './shapes'"
+  );
+  assert_eq!(
+    error("from './cycle' import { a }").title,
+    "Error In Module"
+  );
+  assert_eq!(
+    error("from './broken' import { a }").message,
+    "Variable Already Exists in module './broken' on line 2: Variable 'a' has been defined already"
+  );
+
+  let chunk = bang::compile_with_context("from './missing' import { a }", &context).unwrap();
+  assert!(bang::VM::new(&context).run(&chunk).is_err());
+}
+
+#[test]
+fn vm_registered_module() {
+  use bang::value::NativeFunction;
+
+  let double = NativeFunction::new("double", 1, |args| (args[0].as_number() * 2.0).into());
+
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.register_native_module("mylib", &[("double", double.into())]);
+  vm.register_native_module("maths", &[("answer", 42.0.into())]);
+
+  let chunk = bang::compile("from mylib import { double }\nlet a = double(maths::answer)").unwrap();
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), 84.0.into());
+
+  let chunk = bang::compile("from maths import { floor }\nlet b = floor(maths::sqrt(10))").unwrap();
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("b").unwrap(), 3.0.into());
+
+  let chunk = bang::compile("from maths import { answr }").unwrap();
+  let error = vm.run(&chunk).unwrap_err();
+  assert_eq!(
+    error.message,
+    "Item 'answr' not found in module 'maths'. Did you mean 'answer'?"
+  );
+
+  let chunk = bang::compile("from mylib import { doubel }").unwrap();
+  let error = vm.run(&chunk).unwrap_err();
+  assert_eq!(
+    error.message,
+    "Item 'doubel' not found in module 'mylib'. Did you mean 'double'?"
+  );
+
+  let chunk = bang::compile("mylb::double").unwrap();
+  let error = vm.run(&chunk).unwrap_err();
+  assert_eq!(
+    error.message,
+    "Module 'mylb' not found. Did you mean 'mylib'?"
+  );
+}
+
+#[test]
+fn import_errors_name_module_and_item() {
+  let context = bang::StdContext::default();
+  let messages = [
+    ("let a = unknown::item", "Module 'unknown' not found"),
+    (
+      "from maths import { tau }",
+      "Item 'tau' not found in module 'maths'. Did you mean 'tan'?",
+    ),
+    (
+      "let a = list::unknown",
+      "Item 'unknown' not found in module 'list'",
+    ),
+  ];
+
+  for (source, message) in messages {
+    let chunk = bang::compile(source).unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+    assert_eq!(error.message, message);
+  }
+}
+
+bang_test!(optional_imports
+"
+from maths import? { floor, tau }
+from unknown import? { item }
+from maths as m import? { ceil }
+
+let a = floor(2.5)
+let b = tau == null
+let c = item ?? 'fallback'
+let d = ceil(1.5)
+"
+  a == 2.0
+  b == true
+  c == "fallback"
+  d == 2.0
+);
+
+#[test]
+fn optional_import_of_registered_module() {
+  let source = "
+from extra import? { value }
+let a = value ?? 'missing'
+";
+  let chunk = bang::compile(source).unwrap();
+
+  let mut context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), "missing".into());
+
+  context.register_module("extra", &[("value", "found".into())]);
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), "found".into());
+}
+
+#[test]
+fn errors_suggest_similar_names() {
+  let context = bang::StdContext::default();
+  let messages = [
+    (
+      "from string import { lenght }",
+      "Item 'lenght' not found in module 'string'. Did you mean 'length'?",
+    ),
+    (
+      "let total = 5\ntotl + 1",
+      "Undefined variable 'totl'. Did you mean 'total'?",
+    ),
+    (
+      "let total = 5\ntotl = 1",
+      "Undefined variable 'totl'. Did you mean 'total'?",
+    ),
+    (
+      "prnt('hello')",
+      "Undefined variable 'prnt'. Did you mean 'print'?",
+    ),
+    ("let total = 5\nsomething", "Undefined variable 'something'"),
+    (
+      "let a = mahts::PI",
+      "Module 'mahts' not found. Did you mean 'maths'?",
+    ),
+  ];
+
+  for (source, message) in messages {
+    let chunk = bang::compile(source).unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+    assert_eq!(error.message, message);
+  }
+}
+
+#[test]
+fn context_lists_modules_and_items() {
+  use bang::context::Context;
+
+  let mut context = bang::StdContext::default();
+  context.register_module("mylib", &[("double", bang::Value::from(2.0))]);
+
+  let modules = context.modules();
+  assert!(modules.contains(&"maths"));
+  assert!(modules.contains(&"task"));
+  assert!(modules.contains(&"mylib"));
+
+  let items = context.items("maths");
+  assert!(items.contains(&"PI"));
+  assert!(items.contains(&"floor"));
+  assert!(context.items("list").contains(&"map"));
+  assert_eq!(context.items("mylib"), vec!["double"]);
+  assert!(context.items("unknown").is_empty());
+
+  assert!(bang::context::Empty.modules().is_empty());
+}
+
+#[test]
+fn compile_time_config() {
+  let mut context = bang::StdContext::default();
+  context.set_config("DEBUG", bang::Value::TRUE);
+  context.set_config("LEVEL", bang::Value::from(3.0));
+
+  let source = "
+let mode = 'release'
+if (config::get('DEBUG')) mode = 'debug'
+if (!config::get('RELEASE')) mode = `${mode}!`
+else releaseOnly()
+while (config::get('RELEASE')) releaseOnly()
+let level = config::get('LEVEL')
+let missing = config::get('MISSING')
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  assert!(!format!("{chunk:?}").contains("releaseOnly"));
+
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("mode").unwrap(), "debug!".into());
+  assert_eq!(vm.get_global("level").unwrap(), 3.0.into());
+  assert_eq!(vm.get_global("missing").unwrap(), bang::Value::NULL);
+
+  // Flags which aren't literals are looked up when run
+  let source = "
+let flag = 'LEVEL'
+let level = config::get(flag)
+from config import { get }
+let debug = get('DEBUG')
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("level").unwrap(), 3.0.into());
+  assert_eq!(vm.get_global("debug").unwrap(), bang::Value::TRUE);
+
+  // Only conditions decided by the config remove branches, so errors in others are reported
+  assert!(bang::compile_with_context("if (false) print = 1", &context).is_err());
+  assert!(bang::compile_with_context("while (false) print = 1", &context).is_err());
+}
+
+#[test]
+fn custom_value_formatter() {
+  let mut context = bang::StdContext::default();
+  context.set_value_formatter(|value| {
+    if value.is_number() {
+      Some(format!("{:.2}", value.as_number()).into())
+    } else {
+      None
+    }
+  });
+
+  let source = "
+let price = 1.5
+let a = `${price} for ${'one'}`
+let b = toString(price)
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+
+  assert_eq!(vm.get_global("a").unwrap(), "1.50 for one".into());
+  assert_eq!(vm.get_global("b").unwrap(), "1.5".into());
+}
+
+#[test]
+fn input_from_context() {
+  let mut context = bang::StdContext::default();
+  context.set_input(std::io::Cursor::new("Alice\r\n42\nlast"));
+
+  let source = "
+let name = input('Name: ')
+let age = input()
+let last = input()
+let end = input('More? ')
+";
+  let chunk = bang::compile_with_context(source, &context).unwrap();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+
+  assert_eq!(vm.get_global("name").unwrap(), "Alice".into());
+  assert_eq!(vm.get_global("age").unwrap(), "42".into());
+  assert_eq!(vm.get_global("last").unwrap(), "last".into());
+  assert_eq!(vm.get_global("end").unwrap(), bang::Value::NULL);
+}
+
+#[test]
+fn protected_globals() {
+  let context = bang::StdContext::default();
+
+  for source in [
+    "let print = 5",
+    "type = 5",
+    "from maths import { floor as toString }",
+  ] {
+    let error = bang::compile_with_context(source, &context).unwrap_err();
+    assert_eq!(error.title, "Protected Global");
+  }
+
+  for source in [
+    "let a = (print) => print",
+    "let a = () ->\n  let type = 5\n  type",
+    "let inspect = 5",
+    "let input = 5",
+    "let deepCopy = 5",
+    "let prettyPrint = 5",
+  ] {
+    assert!(bang::compile_with_context(source, &context).is_ok());
+  }
+
+  assert!(bang::compile("let print = 5").is_ok());
+}
+
+#[test]
+fn bytecode_functions_are_cached() {
+  use bang::{context::Context, value::Object};
+  use std::rc::Rc;
+
+  let chunk = |context: &bang::StdContext| {
+    let value = context.get_value("list", "map").unwrap_constant();
+    match value.as_object() {
+      Object::Function(function) => function.chunk.clone(),
+      _ => panic!("Expected list::map to be a bytecode function"),
+    }
+  };
+
+  bang::precompile_bytecode();
+  let first = chunk(&bang::StdContext::default());
+  let second = chunk(&bang::StdContext::default());
+
+  assert!(Rc::ptr_eq(&first, &second));
 }
@@ -125,12 +125,12 @@ let outer = () ->
 
 let x = '${outer()}'
 "
-  x == "<function get>"
+  x == "<function get(0 params)>"
 );
 
 bang_test!(function_closed_with_parameter
 "
-let run = (do) => do()
+let run = (callback) => callback()
 
 let x = (y) -> null
   let n = 7
@@ -203,3 +203,77 @@ let x = a()()()
 "
 x == 16
 );
+
+bang_test!(assignment_operator_upvalues
+"
+let total = 1
+let outer = () ->
+  let count = 1
+  let inner = () ->
+    let deeper = () ->
+      count *= 10
+      total += count
+    deeper()
+    count -= 5
+  inner()
+  return count
+
+let x = outer()
+"
+  x == 5
+  total == 11
+);
+
+bang_test!(loop_declarations_are_captured_each_iteration
+"
+let outer = () ->
+  let closures = [null, null, null]
+  let i = 0
+  while (i < 3)
+    let j = i
+    closures[i] = () => j
+    i += 1
+  return closures
+
+let closures = outer()
+let a = closures[0]()
+let b = closures[1]()
+let c = closures[2]()
+"
+  a == 0
+  b == 1
+  c == 2
+);
+
+bang_test!(capture_variable_from_outside_loop
+"
+let outer = () ->
+  let closures = [null, null, null]
+  let i = 0
+  while (i < 3)
+    closures[i] = () => i
+    i += 1
+  return closures
+
+let closures = outer()
+let a = closures[0]()
+let b = closures[2]()
+"
+  a == 3
+  b == 3
+);
+
+bang_test!(set_variable_from_outside_loop
+"
+let outer = () ->
+  let count = 0
+  let total = loop
+    let increment = () => count += 1
+    increment()
+    if (count >= 3) break count
+  return total + count
+
+let x = outer()
+"
+  x == 6
+);
@@ -71,3 +71,10 @@ else
 "
   a == "boolean"
 );
+
+bang_test!(crlf_line_endings
+"let a = 1\r\n// comment\r\nif (a == 1)\r\n  a = 2 // comment\r\nlet b = 'multi\r\nline'\r\nlet c = `format\r\n${a}`\r\n"
+  a == 2.0
+  b == "multi\nline"
+  c == "format\n2"
+);
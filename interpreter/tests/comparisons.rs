@@ -294,3 +294,74 @@ let i = [1, 'hello', null] == g
   h == true
   i == true
 );
+
+#[test]
+fn strict_equality() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.set_strict_equality(true);
+
+  let source = "
+let a = 0.3 == (0.1 + 0.2)
+let b = [0.3] != [0.1 + 0.2]
+let c = 0 == -0
+let d = (0 / 0) == (0 / 0)
+let e = 1.5 == 1.5
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+
+  assert_eq!(vm.get_global("a").unwrap(), false.into());
+  assert_eq!(vm.get_global("b").unwrap(), true.into());
+  assert_eq!(vm.get_global("c").unwrap(), true.into());
+  assert_eq!(vm.get_global("d").unwrap(), false.into());
+  assert_eq!(vm.get_global("e").unwrap(), true.into());
+}
+
+bang_test!(in_operator
+"
+from set import { fromList }
+
+let list = [1, 'hello', [2], null]
+let a = 1 in list
+let b = 'hello' in list
+let c = [2] in list
+let d = 3 in list
+let e = null in list
+
+let f = 'ell' in 'hello'
+let g = 'hello' in 'ell'
+let h = '' in 'hello'
+let i = 5 in '5'
+
+let dict = { 'key': 1, 0: 2 }
+let j = 'key' in dict
+let k = 1 in dict
+let l = -0 in dict
+
+let m = 2 in fromList([1, 2])
+let n = 3 in fromList([1, 2])
+let o = 1 + 1 in [2] and !(3 in [2])
+"
+  a == true
+  b == true
+  c == true
+  d == false
+  e == true
+  f == true
+  g == false
+  h == true
+  i == false
+  j == true
+  k == false
+  l == true
+  m == true
+  n == false
+  o == true
+);
+
+bang_test!(in_unsupported_type
+"
+let a = 1 in 5
+"
+  RuntimeError
+);
@@ -0,0 +1,78 @@
+mod bang_test;
+use bang_test::*;
+
+fn lines(count: usize, line: impl Fn(usize) -> String) -> String {
+  (0..count).map(line).collect()
+}
+
+#[test]
+fn thousands_of_distinct_literals() {
+  let source = format!(
+    "let strings = [{}]\nlet numbers = [{}]\n",
+    lines(2000, |i| format!("'s{i}',")),
+    lines(2000, |i| format!("{i}.5,")),
+  );
+
+  let context = bang::StdContext::default();
+  if let RunResult::Success(vm) = run(&source, &context) {
+    let strings = vm.get_global("strings").unwrap();
+    let numbers = vm.get_global("numbers").unwrap();
+    assert!(strings.to_string().contains("'s1999'"));
+    assert!(numbers.to_string().contains("1999.5"));
+  } else {
+    panic!("Expected program to run");
+  }
+}
+
+#[test]
+fn thousands_of_distinct_globals() {
+  let source = format!(
+    "{}{}let last = v999\n",
+    lines(1000, |i| format!("let v{i} = {i}\n")),
+    lines(1000, |i| format!("v{i} += 1\n")),
+  );
+
+  let context = bang::StdContext::default();
+  if let RunResult::Success(vm) = run(&source, &context) {
+    assert_eq!(vm.get_global("v0").unwrap(), bang::Value::from(1.0));
+    assert_eq!(vm.get_global("last").unwrap(), bang::Value::from(1000.0));
+  } else {
+    panic!("Expected program to run");
+  }
+}
+
+#[test]
+fn each_function_has_its_own_pool() {
+  // Each function has its own constants, so they stay small however large the file is
+  let source = format!(
+    "{}let total = 0\n{}",
+    lines(500, |i| format!("let f{i} = () => 'function {i}'\n")),
+    lines(500, |i| format!("total = total + 1\nlet r{i} = f{i}()\n")),
+  );
+
+  let chunk = bang::compile(&source).unwrap();
+  assert!(chunk.verify().is_ok());
+  let disassembly = format!("{chunk:?}");
+  assert!(disassembly.contains("Define Global Long \"f499\""));
+  assert!(disassembly.contains("Constant 'function 499' (0)"));
+
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.get_global("total").unwrap(), bang::Value::from(500.0));
+  assert_eq!(
+    vm.get_global("r499").unwrap(),
+    bang::Value::from("function 499")
+  );
+}
+
+#[test]
+fn non_null_assertion_with_many_strings() {
+  let source = format!(
+    "{}let a = null\nlet b = a!\n",
+    lines(300, |i| format!("let v{i} = {i}\n")),
+  );
+
+  let context = bang::StdContext::default();
+  assert_eq!(run(&source, &context), RunResult::RuntimeError);
+}
@@ -0,0 +1,83 @@
+mod bang_test;
+use bang_test::*;
+
+bang_test!(do_expression
+"
+let a = do
+  let x = 2
+  let y = 3
+  x * y
+"
+  a == 6
+);
+
+bang_test!(do_expression_without_value
+"
+let a = do
+  let x = 2
+let isNull = a == null
+"
+  isNull == true
+);
+
+bang_test!(do_expression_function_body
+"
+let add = (a, b) => do
+  let total = a + b
+  total * 10
+
+let result = add(1, 2)
+"
+  result == 30
+);
+
+bang_test!(do_expression_with_values_on_stack
+"
+let calculate = (a) ->
+  let total = a + 2 * do
+    let b = 5
+    let c = 1 + do
+      let d = b
+      d + 1
+    c + b
+  return total
+
+let result = calculate(3)
+"
+  result == 27
+);
+
+bang_test!(do_expression_with_return
+"
+let early = () ->
+  let a = do
+    return 5
+  return 7
+
+let result = early()
+"
+  result == 5
+);
+
+bang_test!(do_expression_inside_loop
+"
+let i = 0
+let a = loop
+  let b = do
+    i += 1
+    i * 2
+  if (b > 5) break b
+"
+  a == 6
+);
+
+bang_test!(do_expression_scope
+"
+let x = 1
+let a = do
+  let x = 2
+  x
+"
+  a == 2
+  x == 1
+);
@@ -109,6 +109,32 @@ test(3)
   RuntimeError
 );
 
+#[test]
+fn arity_error_names_function() {
+  let context = bang::StdContext::default();
+  let messages = [
+    (
+      "let add = (a, b) => a + b\nadd(1)",
+      "Function 'add' expected 2 arguments but got 1.",
+    ),
+    (
+      "((a) => a)()",
+      "Anonymous function expected 1 arguments but got 0.",
+    ),
+    (
+      "print(1, 2)",
+      "Function 'print' expected 1 arguments but got 2.",
+    ),
+  ];
+
+  for (source, message) in messages {
+    let chunk = bang::compile(source).unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+    assert_eq!(error.message, message);
+  }
+}
+
 bang_test!(iterative_fibonacci
 "
 let fib_iterative = (n: number) -> number
@@ -146,14 +172,14 @@ bang_test!(recursive_loop
 "
 let iterations = 0
 
-let loop = (n: number) -> number
+let recurse = (n: number) -> number
   iterations += 1
   if (n == 0)
     return 0
   else
-    return loop(n - 1)
+    return recurse(n - 1)
 
-let result = loop(10)
+let result = recurse(10)
 "
   result == 0.0
   iterations == 11.0
@@ -361,6 +387,43 @@ let b = false and true >> not >> not
   b == false
 );
 
+bang_test!(function_composition
+  "
+let double = (x: number) => x * 2
+let increment = (x: number) => x + 1
+
+let a = (double << increment)(3)
+let b = (increment << double)(3)
+let c = (type << double << increment)(1)
+let d = 3 >> (double << increment)
+"
+  a == 8.0
+  b == 7.0
+  c == "number"
+  d == 8.0
+);
+
+bang_test!(function_composition_in_function
+  "
+let compose = (f, g) ->
+  let x = f << g
+  return x
+let x = 5
+let f = (y) => y + x
+
+let a = compose(f, f)(1)
+"
+  a == 11.0
+);
+
+bang_test!(function_composition_of_non_function
+  "
+let composed = 5 << type
+composed(2)
+"
+  RuntimeError
+);
+
 bang_test!(pipeline_with_comment
   "
 let identity = (x: any) => x
@@ -417,3 +480,155 @@ let a = () -> (+)
 "
   CompileError
 );
+
+bang_test!(default_parameters
+"
+let add = (x, y = 10) => x + y
+let a = add(1)
+let b = add(1, 2)
+let c = add(1, null)
+
+let greet = (name, greeting = 'Hello', end = '!') -> string
+  return greeting + ' ' + name + end
+let d = greet('Bob')
+let e = greet('Bob', 'Hi')
+let f = greet('Bob', 'Hi', '?')
+
+let double = (x = 5) => x * 2
+let g = double()
+let h = ((x, y = x * 2) => y)(4)
+let i = toString(add)
+"
+  a == 11
+  b == 3
+  c == 11
+  d == "Hello Bob!"
+  e == "Hi Bob!"
+  f == "Hi Bob?"
+  g == 10
+  h == 8
+  i == "<function add(1-2 params)>"
+);
+
+bang_test!(default_parameters_captured
+"
+let offset = 3
+let shift = (x, by = offset) ->
+  let inner = () => x + by
+  return inner()
+let a = shift(1)
+let b = shift(1, 1)
+"
+  a == 4
+  b == 2
+);
+
+bang_test!(default_parameters_too_few_arguments
+"
+let add = (x, y = 10) => x + y
+add()
+"
+  RuntimeError
+);
+
+bang_test!(default_parameters_too_many_arguments
+"
+let add = (x, y = 10) => x + y
+add(1, 2, 3)
+"
+  RuntimeError
+);
+
+#[test]
+fn call_from_host() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.run(&bang::compile("let add = (x, y = 10) => x + y").unwrap())
+    .unwrap();
+
+  let add = vm.get_global("add").unwrap();
+  let result = vm.call(add.clone(), &[1.0.into(), 2.0.into()]);
+  assert_eq!(result.unwrap(), 3.0.into());
+  assert_eq!(vm.call(add.clone(), &[1.0.into()]).unwrap(), 11.0.into());
+  assert!(vm.call(add, &[]).is_err());
+}
+
+bang_test!(deep_recursion
+"
+let count = (n) ->
+  if (n == 0) return 0
+  return 1 + count(n - 1)
+let a = count(5000)
+"
+  a == 5000
+);
+
+bang_test!(trailing_function
+"
+let twice = (x, f) => f(f(x))
+let a = twice(3) => it * 2
+let b = 0
+twice(5) ->
+  b += it
+  return it + 1
+let c = [1, 2, 3] >> list::map() => it + 1
+let d = c == [2, 3, 4]
+"
+  a == 12
+  b == 11
+  d == true
+);
+
+bang_test!(infinite_recursion
+"
+let forever = (n) => forever(n + 1)
+forever(0)
+"
+  RuntimeError
+);
+
+bang_test!(call_site_with_different_functions
+"
+let double = (x) => x * 2
+let negate = (x) => -x
+let apply = (f, x) => f(x)
+
+let a = apply(double, 3)
+let b = apply(negate, 3)
+let c = apply(double, 4)
+let d = apply((x, y = 10) => x + y, 5)
+"
+  a == 6
+  b == -3
+  c == 8
+  d == 15
+);
+
+#[test]
+fn stack_overflow_message() {
+  let context = bang::StdContext::default();
+  let chunk = bang::compile("let forever = (n) => forever(n + 1)\nforever(0)").unwrap();
+  let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+  assert_eq!(error.message, "Maximum call depth exceeded.");
+  assert_eq!(error.stack.len(), 32);
+}
+
+#[test]
+fn configurable_call_depth() {
+  let context = bang::StdContext::default();
+  let chunk = bang::compile("let count = (n) => n > 0 and count(n - 1)\ncount(20)").unwrap();
+
+  let mut vm = bang::VM::new(&context);
+  vm.set_max_call_depth(10);
+  assert!(vm.run(&chunk).is_err());
+
+  let mut vm = bang::VM::new(&context);
+  vm.set_max_call_depth(100);
+  assert!(vm.run(&chunk).is_ok());
+
+  let mut vm = bang::VM::new(&context);
+  vm.set_max_stack_size(10);
+  let error = vm.run(&chunk).unwrap_err();
+  assert_eq!(error.message, "Maximum stack size exceeded.");
+}
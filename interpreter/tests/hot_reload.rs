@@ -0,0 +1,101 @@
+mod bang {
+  pub use bang_interpreter::*;
+  pub use bang_std::*;
+}
+
+#[test]
+fn keeps_state_and_swaps_functions() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+
+  let first = "
+let count = 0
+let step = () => 1
+let unchanged = (x) => x
+count = count + step()
+";
+  let updated = vm.hot_reload(&bang::compile(first).unwrap()).unwrap();
+  assert_eq!(updated, vec!["step", "unchanged"]);
+  assert_eq!(vm.get_global("count").unwrap(), 1.0.into());
+
+  let second = "
+let count = 0
+let step = () => 10
+
+let unchanged = (x) => x
+count = count + step()
+";
+  let updated = vm.hot_reload(&bang::compile(second).unwrap()).unwrap();
+  assert_eq!(updated, vec!["step"]);
+  assert_eq!(vm.get_global("count").unwrap(), 11.0.into());
+}
+
+#[test]
+fn run_replaces_state() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+
+  vm.run(&bang::compile("let count = 1").unwrap()).unwrap();
+  vm.run(&bang::compile("let count = 2").unwrap()).unwrap();
+  assert_eq!(vm.get_global("count").unwrap(), 2.0.into());
+}
+
+#[test]
+fn restores_snapshot_of_globals() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+
+  vm.run(&bang::compile("let a = [1, 2]\nlet b = a\nlet count = 1").unwrap())
+    .unwrap();
+  let snapshot = vm.snapshot();
+
+  let source = "
+from list import { push }
+a >> push(3)
+count = 5
+let extra = true
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+  assert_eq!(vm.get_global("count").unwrap(), 5.0.into());
+
+  vm.restore(&snapshot);
+  assert_eq!(vm.get_global("count").unwrap(), 1.0.into());
+  assert!(vm.get_global("extra").is_none());
+
+  // Lists shared between globals are still shared after restoring
+  let source = "
+from list import { push, length }
+a >> push(4)
+let shared = length(b) == 3 && b[2] == 4
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+  assert_eq!(vm.get_global("shared").unwrap(), true.into());
+
+  // A snapshot can be restored more than once
+  vm.restore(&snapshot);
+  let source = "
+from list import { length }
+let size = length(a)
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+  assert_eq!(vm.get_global("size").unwrap(), 2.0.into());
+}
+
+#[test]
+fn snapshot_copies_cyclic_lists() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+
+  let source = "
+from list import { push }
+let a = [1]
+a >> push(a)
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+  let snapshot = vm.snapshot();
+  vm.restore(&snapshot);
+
+  vm.run(&bang::compile("let cyclic = a[1][1][0] == 1").unwrap())
+    .unwrap();
+  assert_eq!(vm.get_global("cyclic").unwrap(), true.into());
+}
@@ -251,6 +251,39 @@ let { a as f, g, b as h } = x
   h == 6
 );
 
+bang_test!(named_destructuring_defaults_and_rest
+  "
+let x = { 'a': 1, 'b': 2, 'c': 3 }
+let { a = 10, z = 26, b as bee = 20, ..rest } = x
+let { ..all } = x
+let r = rest == { 'c': 3 }
+all['d'] = 4
+let s = all != x and !('d' in x)
+  "
+  a == 1
+  z == 26
+  bee == 2
+  r == true
+  s == true
+);
+
+bang_test!(destructuring_in_functions
+  "
+let f = (x) ->
+  let y = 1
+  let { a, b = 5, ..rest } = x
+  let [c, d] = [a, b]
+  return [y, a, b, c, d, rest]
+let a = f({ 'a': 2, 'e': 4 }) == [1, 2, 5, 2, 5, { 'e': 4 }]
+  "
+  a == true
+);
+
+bang_test!(named_destructuring_rest_of_list
+  "let { ..rest } = [1, 2]"
+  RuntimeError
+);
+
 bang_test!(named_destructuring_non_existant_key
   "let { a } = dict::new()"
   RuntimeError
@@ -280,3 +313,32 @@ let e = list[0]
   d == 0.8
   e == 3
 );
+
+#[test]
+fn index_errors_describe_collection() {
+  let context = bang::StdContext::default();
+  let messages = [
+    ("[1, 2, 3][12]", "Index '12' not found in list of length 3"),
+    (
+      "let a = [1]\na[5] = 2",
+      "Index '5' not found in list of length 1",
+    ),
+    ("'hello'[7]", "Index '7' not found in string of length 5"),
+    ("{}['a']", "Index 'a' not found in empty dict"),
+    (
+      "{ 'a': 1 }['b']",
+      "Index 'b' not found in dict of 1 key. Nearest keys: 'a'",
+    ),
+    (
+      "{ 'name': 1, 'age': 2, 'names': 3, 'email': 4 }['nmae']",
+      "Index 'nmae' not found in dict of 4 keys. Nearest keys: 'name', 'names', 'age'",
+    ),
+  ];
+
+  for (source, message) in messages {
+    let chunk = bang::compile(source).unwrap();
+    let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+    assert_eq!(error.message, message);
+  }
+}
@@ -126,3 +126,24 @@ bang_test!(cant_assign_to_call
   "print() = 7"
   CompileError
 );
+
+#[test]
+fn too_many_locals_errors_at_declaration() {
+  let declarations = |count| {
+    (0..count)
+      .map(|i| format!("  let v{i} = {i}\n"))
+      .collect::<String>()
+  };
+
+  let source = format!("let global\n{}  global = v254\n", declarations(255));
+  assert!(bang::compile(&source).is_ok());
+
+  let source = format!("let global\n{}", declarations(300));
+  let error = bang::compile(&source).unwrap_err();
+  assert_eq!(error.title, "Too Many Local Variables");
+  assert_eq!(error.line, 257);
+  assert_eq!(
+    &source[error.span.start as usize..error.span.end as usize],
+    "let v255 = 255"
+  );
+}
@@ -131,3 +131,32 @@ let b = \"before\"
   a == false
   b == true
 );
+
+bang_test!(non_null_assertion
+"
+let list = [1, 2]
+let a = list[0]!
+let b = 'hello' as string
+let c = (list[1]! + 1) as number
+"
+  a == 1.0
+  b == "hello"
+  c == 3.0
+);
+
+bang_test!(non_null_assertion_of_null
+"
+let find = () => null
+find()!
+"
+  RuntimeError
+);
+
+#[test]
+fn non_null_assertion_message() {
+  let context = bang::StdContext::default();
+  let chunk = bang::compile("let find = (list) => null\nfind([1, 2])!").unwrap();
+  let error = bang::VM::new(&context).run(&chunk).unwrap_err();
+
+  assert_eq!(error.message, "Expected 'find([1, 2])' not to be null");
+}
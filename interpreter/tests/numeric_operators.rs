@@ -219,3 +219,49 @@ let d = -5 % -3
   c == 2
   d == -2
 );
+
+#[test]
+fn checked_arithmetic() {
+  let context = bang::StdContext::default();
+  let error = |source: &str| {
+    let mut vm = bang::VM::new(&context);
+    vm.set_checked_arithmetic(true);
+    let error = vm.run(&bang::compile(source).unwrap()).unwrap_err();
+    (error.message.to_string(), error.stack[0].line)
+  };
+
+  assert_eq!(error("1 / 0"), ("Division by zero.".to_string(), 1));
+  assert_eq!(
+    error("let a = 1\n\na % 0"),
+    ("Division by zero.".to_string(), 3)
+  );
+  assert_eq!(
+    error("maths::INFINITY - maths::INFINITY").0,
+    "Result of arithmetic is not a number."
+  );
+  assert_eq!(
+    error("maths::pow(10, 308) * 10").0,
+    "Arithmetic overflow, the result is too large to be represented."
+  );
+  assert_eq!(
+    error("let a = maths::pow(10, 308)\na + a").0,
+    "Arithmetic overflow, the result is too large to be represented."
+  );
+
+  let mut vm = bang::VM::new(&context);
+  vm.set_checked_arithmetic(true);
+  let source = "
+let a = 7 / 2 % 3
+let b = maths::INFINITY + 1
+let c = maths::isNaN(maths::NAN * 2)
+";
+  vm.run(&bang::compile(source).unwrap()).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), 0.5.into());
+  assert_eq!(vm.get_global("b").unwrap(), f64::INFINITY.into());
+  assert_eq!(vm.get_global("c").unwrap(), true.into());
+
+  // Without checking, the results are values
+  let mut vm = bang::VM::new(&context);
+  vm.run(&bang::compile("let a = 1 / 0").unwrap()).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), f64::INFINITY.into());
+}
@@ -0,0 +1,55 @@
+mod bang {
+  pub use bang_interpreter::*;
+  pub use bang_std::*;
+}
+
+#[test]
+fn runs_each_statement() {
+  let context = bang::StdContext::default();
+  let source = "
+let a = 5
+let double = (x) => x * 2
+
+let b = double(a)
+if (b > 5)
+  a = 1
+let c = toString([a, b])
+";
+
+  let vm = bang::interpret_streaming(source, &context).unwrap();
+  assert_eq!(vm.get_global("a").unwrap(), 1.0.into());
+  assert_eq!(vm.get_global("b").unwrap(), 10.0.into());
+  assert_eq!(vm.get_global("c").unwrap(), "[1, 10]".into());
+}
+
+#[test]
+fn runs_statements_before_later_errors() {
+  let context = bang::StdContext::default();
+
+  let source = "let a = 5 + 'a'\nlet b = (";
+  assert!(matches!(
+    bang::interpret_streaming(source, &context),
+    Err(bang::InterpretError::Runtime(_))
+  ));
+
+  let source = "let a = 5\nlet b = (";
+  assert!(matches!(
+    bang::interpret_streaming(source, &context),
+    Err(bang::InterpretError::Compile(_))
+  ));
+}
+
+#[test]
+fn evaluates_last_expression() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+
+  let chunk = bang::compile_keeping_last_value("let a = 5\na * 2", &context).unwrap();
+  assert_eq!(vm.evaluate(&chunk).unwrap(), 10.0.into());
+
+  let chunk = bang::compile_keeping_last_value("a + 1\nlet b = a", &context).unwrap();
+  assert_eq!(vm.evaluate(&chunk).unwrap(), bang::Value::NULL);
+
+  let chunk = bang::compile_keeping_last_value("// a comment", &context).unwrap();
+  assert_eq!(vm.evaluate(&chunk).unwrap(), bang::Value::NULL);
+}
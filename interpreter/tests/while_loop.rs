@@ -66,3 +66,131 @@ let x = loopy(9)
 "
   x == 9
 );
+
+bang_test!(break_from_while
+"
+let x = 0
+while (true)
+  let next = x + 1
+  if (next > 5) break
+  x = next
+"
+  x == 5
+);
+
+bang_test!(loop_expression
+"
+let numbers = [3, 8, 12, 5, 20]
+let i = 0
+let found = loop
+  let number = numbers[i]
+  if (number > 10) break number
+  i += 1
+"
+  found == 12
+  i == 2
+);
+
+bang_test!(loop_break_without_value
+"
+let a = loop break
+let isNull = a == null
+"
+  isNull == true
+);
+
+bang_test!(loop_expression_with_values_on_stack
+"
+let calculate = (a) ->
+  let total = a + 2 * loop
+    let b = 5
+    let c = 1 + loop
+      let d = b
+      break d + 1
+    break c + b
+  return total
+
+let result = calculate(3)
+"
+  result == 27
+);
+
+bang_test!(loop_expression_with_closures
+"
+let last = () ->
+  let i = 0
+  let value = loop
+    let captured = i
+    let get = () => captured
+    i += 1
+    if (i > 2) break get()
+  return value
+
+let result = last()
+"
+  result == 2
+);
+
+bang_test!(break_targets_innermost_loop
+"
+let outer = 0
+let inner = loop
+  outer += 1
+  while (true) break
+  if (outer == 3) break outer * 10
+"
+  outer == 3
+  inner == 30
+);
+
+bang_test!(break_outside_loop
+"
+break
+"
+  CompileError
+);
+
+bang_test!(break_value_from_while
+"
+while (true) break 5
+"
+  CompileError
+);
+
+bang_test!(break_inside_function_in_loop
+"
+loop
+  let f = () -> break
+  break
+"
+  CompileError
+);
+
+#[test]
+fn counts_instructions() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  vm.set_count_instructions(true);
+  assert_eq!(vm.instruction_count(), 0);
+
+  let run = |vm: &mut bang::VM, iterations: usize| {
+    let before = vm.instruction_count();
+    let source = format!("let i = 0\nwhile (i < {iterations}) i += 1");
+    vm.run(&bang::compile(&source).unwrap()).unwrap();
+    vm.instruction_count() - before
+  };
+
+  let short = run(&mut vm, 10);
+  let long = run(&mut vm, 20);
+  assert!(short > 10);
+  assert!(long > short);
+}
+
+#[test]
+fn only_counts_instructions_when_enabled() {
+  let context = bang::StdContext::default();
+  let mut vm = bang::VM::new(&context);
+  let chunk = bang::compile("let i = 0\nwhile (i < 10) i += 1").unwrap();
+  vm.run(&chunk).unwrap();
+  assert_eq!(vm.instruction_count(), 0);
+}
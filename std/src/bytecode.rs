@@ -1,8 +1,25 @@
 use crate::macros::bytecode;
 use bang_interpreter::{
   chunk::{Chunk, OpCode},
+  collections::HashMap,
   context::BytecodeFunctionCreator,
 };
+use std::{cell::RefCell, rc::Rc};
+
+thread_local! {
+  // Bytecode functions are the same for every context, so they are only built once per thread
+  static CACHE: RefCell<HashMap<&'static str, Rc<Chunk>>> = RefCell::default();
+}
+
+pub fn cached(identifier: &'static str, create: fn() -> Chunk) -> Rc<Chunk> {
+  CACHE.with(|cache| {
+    let mut cache = cache.borrow_mut();
+    cache
+      .entry(identifier)
+      .or_insert_with(|| Rc::new(create()))
+      .clone()
+  })
+}
 
 pub const ANY: fn() -> Chunk = bytecode![
   const 0,
@@ -189,6 +206,42 @@ pub const FIND: fn() -> Chunk = bytecode![
   Return,
 ];
 
+pub const FIND_INDEX: fn() -> Chunk = bytecode![
+  const 0,
+  const "list",
+  const "length",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  GetLocal, 2,
+  GetLocal, 3,
+  Less,
+  JumpIfFalse, long 37,
+  Pop,
+  GetLocal, 0,
+  GetLocal, 2,
+  GetIndex,
+  GetLocal, 1,
+  GetLocal, 4,
+  Call, 1,
+  JumpIfFalse, long 9,
+  Pop,
+  GetLocal, 2,
+  Return,
+  Jump, long 3,
+  Pop,
+  GetLocal, 2,
+  const 1,
+  Add,
+  SetLocal, 2,
+  Pop,
+  Pop,
+  Loop, long 41,
+  Pop,
+  Null,
+  Return,
+];
+
 pub const FOR_EACH: fn() -> Chunk = bytecode![
   const 0,
   const "list",
@@ -326,3 +379,296 @@ pub const MIN: fn() -> Chunk = bytecode![
   GetLocal, 2,
   Return,
 ];
+
+pub const COMPARE: fn() -> Chunk = bytecode![
+  const 0,
+  const "list",
+  const "length",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  const "list",
+  const "length",
+  Import,
+  GetLocal, 1,
+  Call, 1,
+  GetLocal, 3,
+  GetLocal, 4,
+  Less,
+  JumpIfFalse, long 8,
+  Pop,
+  GetLocal, 3,
+  GetLocal, 5,
+  Less,
+  JumpIfFalse, long 103,
+  Pop,
+  GetLocal, 0,
+  GetLocal, 3,
+  GetIndex,
+  GetLocal, 1,
+  GetLocal, 3,
+  GetIndex,
+  const 0,
+  GetLocal, 2,
+  JumpIfFalse, long 17,
+  Pop,
+  GetLocal, 2,
+  GetLocal, 6,
+  GetLocal, 7,
+  Call, 2,
+  SetLocal, 8,
+  Pop,
+  Jump, long 40,
+  Pop,
+  GetLocal, 6,
+  GetLocal, 7,
+  Less,
+  JumpIfFalse, long 12,
+  Pop,
+  const 1,
+  Negate,
+  SetLocal, 8,
+  Pop,
+  Jump, long 21,
+  Pop,
+  GetLocal, 6,
+  GetLocal, 7,
+  Greater,
+  JumpIfFalse, long 11,
+  Pop,
+  const 1,
+  SetLocal, 8,
+  Pop,
+  Jump, long 3,
+  Pop,
+  GetLocal, 8,
+  const 0,
+  NotEqual,
+  JumpIfFalse, long 9,
+  Pop,
+  GetLocal, 8,
+  Return,
+  Jump, long 3,
+  Pop,
+  GetLocal, 3,
+  const 1,
+  Add,
+  SetLocal, 3,
+  Pop,
+  Pop,
+  Pop,
+  Pop,
+  Loop, long 116,
+  Pop,
+  GetLocal, 4,
+  GetLocal, 5,
+  Less,
+  JumpIfFalse, long 10,
+  Pop,
+  const 1,
+  Negate,
+  Return,
+  Jump, long 3,
+  Pop,
+  GetLocal, 4,
+  GetLocal, 5,
+  Greater,
+  JumpIfFalse, long 9,
+  Pop,
+  const 1,
+  Return,
+  Jump, long 3,
+  Pop,
+  const 0,
+  Return,
+];
+
+pub const SORTED_KEYS: fn() -> Chunk = bytecode![
+  const "dict",
+  const "toSortedList",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  List, 0,
+  const 0,
+  GetLocal, 4,
+  const "list",
+  const "length",
+  Import,
+  GetLocal, 2,
+  Call, 1,
+  Less,
+  JumpIfFalse, long 32,
+  Pop,
+  const "list",
+  const "push",
+  Import,
+  GetLocal, 3,
+  GetLocal, 2,
+  GetLocal, 4,
+  GetIndex,
+  const 0,
+  GetIndex,
+  Call, 2,
+  Pop,
+  GetLocal, 4,
+  const 1,
+  Add,
+  SetLocal, 4,
+  Pop,
+  Loop, long 43,
+  Pop,
+  GetLocal, 1,
+  JumpIfFalse, long 115,
+  Pop,
+  const 1,
+  SetLocal, 4,
+  Pop,
+  GetLocal, 4,
+  const "list",
+  const "length",
+  Import,
+  GetLocal, 3,
+  Call, 1,
+  Less,
+  JumpIfFalse, long 90,
+  Pop,
+  GetLocal, 3,
+  GetLocal, 4,
+  GetIndex,
+  GetLocal, 4,
+  const 1,
+  Subtract,
+  GetLocal, 6,
+  const 0,
+  GreaterEqual,
+  JumpIfFalse, long 17,
+  Pop,
+  GetLocal, 1,
+  GetLocal, 3,
+  GetLocal, 6,
+  GetIndex,
+  GetLocal, 5,
+  Call, 2,
+  const 0,
+  Greater,
+  JumpIfFalse, long 28,
+  Pop,
+  GetLocal, 3,
+  GetLocal, 6,
+  const 1,
+  Add,
+  GetLocal, 3,
+  GetLocal, 6,
+  GetIndex,
+  SetIndex,
+  Pop,
+  GetLocal, 6,
+  const 1,
+  Subtract,
+  SetLocal, 6,
+  Pop,
+  Loop, long 50,
+  Pop,
+  GetLocal, 3,
+  GetLocal, 6,
+  const 1,
+  Add,
+  GetLocal, 5,
+  SetIndex,
+  Pop,
+  GetLocal, 4,
+  const 1,
+  Add,
+  SetLocal, 4,
+  Pop,
+  Pop,
+  Pop,
+  Loop, long 101,
+  Pop,
+  Jump, long 3,
+  Pop,
+  GetLocal, 3,
+  Return,
+
+];
+
+pub const COLLECT: fn() -> Chunk = bytecode![
+  const "iter",
+  const "from",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  SetLocal, 0,
+  Pop,
+  List, 0,
+  GetLocal, 0,
+  Next, long 18,
+  const "list",
+  const "push",
+  Import,
+  GetLocal, 1,
+  GetLocal, 3,
+  Call, 2,
+  Pop,
+  Pop,
+  Loop, long 17,
+  Pop,
+  GetLocal, 1,
+  Return,
+];
+
+pub const SET_MAP: fn() -> Chunk = bytecode![
+  const "list",
+  const "toSet",
+  Import,
+  const "list",
+  const "map",
+  Import,
+  const "set",
+  const "toList",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  GetLocal, 1,
+  Call, 2,
+  Call, 1,
+  Return,
+];
+
+pub const SET_FILTER: fn() -> Chunk = bytecode![
+  const "list",
+  const "toSet",
+  Import,
+  const "list",
+  const "filter",
+  Import,
+  const "set",
+  const "toList",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  GetLocal, 1,
+  Call, 2,
+  Call, 1,
+  Return,
+];
+
+pub const SET_FOLD: fn() -> Chunk = bytecode![
+  const "list",
+  const "reduce",
+  Import,
+  const "set",
+  const "toList",
+  Import,
+  GetLocal, 0,
+  Call, 1,
+  GetLocal, 1,
+  GetLocal, 2,
+  Call, 3,
+  Return,
+];
+
+pub const SPAWN: fn() -> Chunk = bytecode![GetLocal, 0, Spawn, Return,];
+
+pub const JOIN: fn() -> Chunk = bytecode![GetLocal, 0, Join, Return,];
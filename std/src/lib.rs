@@ -1,10 +1,13 @@
 use bang_interpreter::{
   collections::{HashMap, String},
-  context::{Context, ImportValue},
-  value::NativeFunction,
-  VM,
+  context::{Context, ImportResolver, ImportValue},
+  value::{Arity, NativeFunction},
+  Value, VM,
+};
+use std::{
+  cell::RefCell,
+  io::{self, BufRead, Write},
 };
-use std::cell::RefCell;
 
 mod bytecode;
 mod macros;
@@ -18,9 +21,93 @@ fn construct_module_identifier(module: &str, item: &str) -> String {
   module_identifier
 }
 
+// Builds every bytecode function ahead of time, so later compilations reuse them
+pub fn precompile_bytecode() {
+  let context = StdContext::default();
+
+  for module in context.modules() {
+    for item in context.items(module) {
+      context.get_value(module, item);
+    }
+  }
+}
+
+// Describes a value as a dict, e.g. `{ 'type': 'list', 'length': 3 }`
+fn inspect(value: &Value) -> Value {
+  let mut fields = HashMap::default();
+  fields.insert("type".into(), value.get_type().into());
+
+  if value.is_object() {
+    let object = value.as_object();
+
+    if let Some(length) = object.length() {
+      fields.insert("length".into(), length.into());
+    }
+    if let Some(keys) = object.get_dict_keys() {
+      fields.insert("keys".into(), keys.into());
+    }
+    if let Some(arity) = object.get_function_arity() {
+      fields.insert("name".into(), object.get_function_name().into());
+      fields.insert("arity".into(), arity.get_count().into());
+    }
+  }
+
+  fields.into()
+}
+
+fn read_line(reader: &mut dyn BufRead) -> Option<String> {
+  let mut line = std::string::String::new();
+  match reader.read_line(&mut line) {
+    Ok(0) | Err(_) => None,
+    Ok(_) => {
+      let length = line.trim_end_matches(['\n', '\r']).len();
+      line.truncate(length);
+      Some(line.into())
+    }
+  }
+}
+
 #[derive(Default)]
 pub struct StdContext {
   import_cache: RefCell<HashMap<String, ImportValue>>,
+  modules: HashMap<String, HashMap<String, Value>>,
+  config: HashMap<String, Value>,
+  value_formatter: Option<fn(&Value) -> Option<String>>,
+  import_resolver: Option<Box<dyn ImportResolver>>,
+  input: Option<RefCell<Box<dyn BufRead>>>,
+}
+impl StdContext {
+  pub fn register_module(&mut self, module: &str, items: &[(&str, Value)]) {
+    // Lookups may have already found the module missing
+    let prefix = construct_module_identifier(module, "");
+    self
+      .import_cache
+      .get_mut()
+      .retain(|identifier, _| !identifier.starts_with(prefix.as_str()));
+
+    let module = self.modules.entry(module.into()).or_default();
+
+    for (name, value) in items {
+      module.insert((*name).into(), value.clone());
+    }
+  }
+
+  pub fn set_config(&mut self, flag: &str, value: Value) {
+    self.config.insert(flag.into(), value);
+  }
+
+  pub fn set_value_formatter(&mut self, formatter: fn(&Value) -> Option<String>) {
+    self.value_formatter = Some(formatter);
+  }
+
+  pub fn set_import_resolver(&mut self, resolver: impl ImportResolver + 'static) {
+    self.import_resolver = Some(Box::new(resolver));
+  }
+
+  // Reads `input` from the reader instead of stdin, so it can be provided by tests or embedders
+  pub fn set_input(&mut self, reader: impl BufRead + 'static) {
+    self.input = Some(RefCell::new(Box::new(reader)));
+  }
 }
 impl Context for StdContext {
   fn get_value(&self, module: &str, item: &str) -> ImportValue {
@@ -29,12 +116,24 @@ impl Context for StdContext {
       return value.clone();
     }
 
+    if let Some(items) = self.modules.get(module) {
+      return match items.get(item) {
+        Some(value) => ImportValue::Constant(value.clone()),
+        None => ImportValue::ItemNotFound,
+      };
+    }
+
     let value = match module {
       "maths" => modules::maths(item),
       "string" => modules::string(item),
       "list" => modules::list(item),
       "set" => modules::set(item),
       "dict" => modules::dict(item),
+      "function" => modules::function(item),
+      "iter" => modules::iter(item),
+      "task" => modules::task(item),
+      "memory" => modules::memory(item),
+      "config" => modules::config(item),
 
       #[cfg(feature = "fs")]
       "fs" => modules::fs(item),
@@ -54,11 +153,99 @@ impl Context for StdContext {
       println!("{}", &args[0]);
       args[0].clone()
     });
+    // Nested collections are shown over multiple lines, with strings quoted
+    let pretty_print = NativeFunction::new("prettyPrint", 1, |args| {
+      println!("{:#?}", &args[0]);
+      args[0].clone()
+    });
     let type_ = NativeFunction::new("type", 1, |args| args[0].get_type().into());
     let to_string = NativeFunction::new("toString", 1, |args| args[0].to_string().into());
+    let inspect = NativeFunction::new("inspect", 1, |args| inspect(&args[0]));
+    let deep_copy = NativeFunction::new("deepCopy", 1, |args| args[0].deep_copy());
+    let input = NativeFunction {
+      arity: Arity::with_optional(1, 1),
+      ..NativeFunction::with_context("input", 1, |context, args| {
+        let prompt = if args[0] == Value::NULL {
+          String::new()
+        } else {
+          args[0].to_string()
+        };
+        context.read_line(&prompt).into()
+      })
+    };
 
     vm.define_global("print", print.into());
+    vm.define_global("prettyPrint", pretty_print.into());
     vm.define_global("type", type_.into());
     vm.define_global("toString", to_string.into());
+    vm.define_global("inspect", inspect.into());
+    vm.define_global("deepCopy", deep_copy.into());
+    vm.define_global("input", input.into());
+  }
+
+  fn protected_globals(&self) -> &[&str] {
+    &["print", "type", "toString"]
+  }
+
+  fn config(&self, flag: &str) -> Value {
+    self.config.get(flag).cloned().unwrap_or(Value::NULL)
+  }
+
+  fn format_value(&self, value: &Value) -> Option<String> {
+    self.value_formatter.and_then(|formatter| formatter(value))
+  }
+
+  fn import_resolver(&self) -> Option<&dyn ImportResolver> {
+    self.import_resolver.as_deref()
+  }
+
+  fn read_line(&self, prompt: &str) -> Option<String> {
+    if let Some(input) = &self.input {
+      return read_line(input.borrow_mut().as_mut());
+    }
+
+    print!("{prompt}");
+    io::stdout().flush().ok()?;
+    read_line(&mut io::stdin().lock())
+  }
+
+  fn modules(&self) -> Vec<&str> {
+    let mut modules = vec![
+      "maths", "string", "list", "set", "dict", "function", "iter", "task", "memory", "config",
+    ];
+    #[cfg(feature = "fs")]
+    modules.push("fs");
+
+    for module in self.modules.keys() {
+      if !modules.contains(&module.as_str()) {
+        modules.push(module);
+      }
+    }
+
+    modules
+  }
+
+  fn items(&self, module: &str) -> Vec<&str> {
+    if let Some(items) = self.modules.get(module) {
+      return items.keys().map(String::as_str).collect();
+    }
+
+    match module {
+      "maths" => modules::maths::ITEMS.to_vec(),
+      "string" => modules::string::ITEMS.to_vec(),
+      "list" => modules::list::ITEMS.to_vec(),
+      "set" => modules::set::ITEMS.to_vec(),
+      "dict" => modules::dict::ITEMS.to_vec(),
+      "function" => modules::function::ITEMS.to_vec(),
+      "iter" => modules::iter::ITEMS.to_vec(),
+      "task" => modules::task::ITEMS.to_vec(),
+      "memory" => modules::memory::ITEMS.to_vec(),
+      "config" => modules::config::ITEMS.to_vec(),
+
+      #[cfg(feature = "fs")]
+      "fs" => modules::fs::ITEMS.to_vec(),
+
+      _ => Vec::new(),
+    }
   }
 }
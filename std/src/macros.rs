@@ -7,6 +7,18 @@ macro_rules! unwrap_type {
   (, $args: expr, $do: expr) => {{
     $do().into()
   }};
+  (Any, $args: expr, $do: expr) => {{
+    $do($args[0].clone()).into()
+  }};
+  (Any Any, $args: expr, $do: expr) => {{
+    $do($args[0].clone(), $args[1].clone()).into()
+  }};
+  (Any Usize, $args: expr, $do: expr) => {{
+    if let Ok(size) = usize::try_from($args[1].clone()) {
+      return $do($args[0].clone(), size).into();
+    }
+    Value::NULL
+  }};
   (Number, $args: expr, $do: expr) => {{
     if $args[0].is_number() {
       return $do($args[0].as_number()).into();
@@ -24,10 +36,13 @@ macro_rules! unwrap_type {
   (List, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
       if let Object::List(value) = $args[0].as_object() {
-        return $do(&mut value.borrow_mut()).into();
+        if value.is_frozen() {
+          return Err("Can't modify a frozen list".into());
+        }
+        return Ok($do(&mut value.borrow_mut()).into());
       }
     }
-    Value::NULL
+    Ok(Value::NULL)
   }};
   (ListRef, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
@@ -43,6 +58,17 @@ macro_rules! unwrap_type {
     }
     Value::NULL
   }};
+  (Number Number Number, $args: expr, $do: expr) => {{
+    if $args[0].is_number() && $args[1].is_number() && $args[2].is_number() {
+      let (a, b, c) = (
+        $args[0].as_number(),
+        $args[1].as_number(),
+        $args[2].as_number(),
+      );
+      return $do(a, b, c).into();
+    }
+    Value::NULL
+  }};
   (String String, $args: expr, $do: expr) => {{
     if $args[0].is_object() && $args[1].is_object() {
       if let Object::String(a) = $args[0].as_object() {
@@ -66,17 +92,37 @@ macro_rules! unwrap_type {
     Value::NULL
   }};
   (String Usize, $args: expr, $do: expr) => {{
-    if $args[0].is_object() && $args[1].is_number() {
+    if $args[0].is_object() {
+      if let (Object::String(value), Ok(size)) =
+        ($args[0].as_object(), usize::try_from($args[1].clone()))
+      {
+        return $do(value as &str, size).into();
+      }
+    }
+    Value::NULL
+  }};
+  (String Any, $args: expr, $do: expr) => {{
+    if $args[0].is_object() {
       if let Object::String(value) = $args[0].as_object() {
-        return $do(value as &str, $args[1].as_number() as usize).into();
+        return $do(value as &str, $args[1].clone()).into();
       }
     }
     Value::NULL
   }};
-  (List Any, $args: expr, $do: expr) => {{
+  (String DictRef, $args: expr, $do: expr) => {{
+    if $args[0].is_object() && $args[1].is_object() {
+      if let Object::String(a) = $args[0].as_object() {
+        if let Object::Dict(b) = $args[1].as_object() {
+          return $do(a as &str, &b.borrow()).into();
+        }
+      }
+    }
+    Value::NULL
+  }};
+  (ListRef Any, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
       if let Object::List(value) = $args[0].as_object() {
-        return $do(&mut value.borrow_mut(), $args[1].clone()).into();
+        return $do(&value.borrow(), $args[1].clone()).into();
       }
     }
     Value::NULL
@@ -84,16 +130,28 @@ macro_rules! unwrap_type {
   (ListReturned Any, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
       if let Object::List(value) = $args[0].as_object() {
+        if value.is_frozen() {
+          return Err("Can't modify a frozen list".into());
+        }
         $do(&mut value.borrow_mut(), $args[1].clone());
-        return $args[0].clone();
+        return Ok($args[0].clone());
       }
     }
-    Value::NULL
+    Ok(Value::NULL)
   }};
-  (List Number, $args: expr, $do: expr) => {{
+  (ListRef Number, $args: expr, $do: expr) => {{
     if $args[0].is_object() && $args[1].is_number() {
       if let Object::List(value) = $args[0].as_object() {
-        return $do(&mut value.borrow_mut(), $args[1].as_number()).into();
+        return $do(&value.borrow(), $args[1].as_number()).into();
+      }
+    }
+    Value::NULL
+  }};
+  (ListFreeze, $args: expr, $do: expr) => {{
+    if $args[0].is_object() {
+      if let Object::List(value) = $args[0].as_object() {
+        $do(value);
+        return $args[0].clone();
       }
     }
     Value::NULL
@@ -161,6 +219,15 @@ macro_rules! unwrap_type {
     }
     Value::NULL
   }};
+  (Function, $args: expr, $do: expr) => {{
+    if $args[0].is_object() {
+      let object = $args[0].as_object();
+      if let Some(arity) = object.get_function_arity() {
+        return $do(object.get_function_name(), arity).into();
+      }
+    }
+    Value::NULL
+  }};
   (DictRef, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
       if let Object::Dict(value) = $args[0].as_object() {
@@ -169,6 +236,15 @@ macro_rules! unwrap_type {
     }
     Value::NULL
   }};
+  (DictFreeze, $args: expr, $do: expr) => {{
+    if $args[0].is_object() {
+      if let Object::Dict(value) = $args[0].as_object() {
+        $do(value);
+        return $args[0].clone();
+      }
+    }
+    Value::NULL
+  }};
   (DictRef Any, $args: expr, $do: expr) => {{
     if $args[0].is_object() {
       if let Object::Dict(value) = $args[0].as_object() {
@@ -183,7 +259,10 @@ macro_rules! module {
   ($name:ident, {
     $(const $value_name:ident = $value:expr;)*
     $(fn $item_name:ident($($type:ident),*) -> $item_value:expr;)*
-    $(bytecode fn $bytecode_item_name:ident($($by_type:ident),*) -> $bytecode_item_value:expr;)*
+    $(fallible fn $fallible_item_name:ident($($fallible_type:ident),*) -> $fallible_item_value:expr;)*
+    $(bytecode fn $bytecode_item_name:ident(
+      $($by_type:ident),* $(; $($by_optional_type:ident),+)?
+    ) -> $bytecode_item_value:expr;)*
   }) => {
     pub fn $name(key: &str) -> ImportValue {
       #![allow(unused_variables)]
@@ -201,16 +280,25 @@ macro_rules! module {
             ).into()
           ),
         )*
+        $(
+          stringify!($fallible_item_name) => ImportValue::Constant(
+            NativeFunction::fallible(
+              concat!(stringify!($name), "::", stringify!($fallible_item_name)),
+              count!($($fallible_type)*),
+              |args| unwrap_type!($($fallible_type)*, args, $fallible_item_value),
+            ).into()
+          ),
+        )*
         $(
           stringify!($bytecode_item_name) => {
+            let name = concat!(stringify!($name), "::", stringify!($bytecode_item_name));
             let function = Function {
-              name: concat!(
-                stringify!($name),
-                "::",
-                stringify!($bytecode_item_name)
-              ).into(),
-              arity: count!($($by_type)*).into(),
-              chunk: $bytecode_item_value().into(),
+              name: name.into(),
+              arity: Arity::with_optional(
+                count!($($by_type)* $($($by_optional_type)+)?),
+                count!($($($by_optional_type)+)?),
+              ),
+              chunk: $crate::bytecode::cached(name, $bytecode_item_value),
               upvalues: Default::default(),
             };
 
@@ -220,6 +308,15 @@ macro_rules! module {
         _ => ImportValue::ItemNotFound,
       }
     }
+
+    pub mod $name {
+      pub const ITEMS: &[&str] = &[
+        $(stringify!($value_name),)*
+        $(stringify!($item_name),)*
+        $(stringify!($fallible_item_name),)*
+        $(stringify!($bytecode_item_name),)*
+      ];
+    }
   };
 }
 
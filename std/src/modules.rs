@@ -5,15 +5,22 @@ use crate::{
 use bang_interpreter::{
   collections::{HashMap as BangHashMap, HashSet as BangHashSet},
   context::ImportValue,
-  value::{calculate_index, Function, NativeFunction, Object},
+  value::{
+    calculate_index, Arity, FormatKind, FormatSpec, Freezable, Function, NativeFunction, Object,
+    Sequence,
+  },
   Value,
 };
-use std::collections::{HashMap, HashSet};
+use std::{
+  cmp::Ordering,
+  collections::{HashMap, HashSet},
+};
 
 module!(maths, {
   const PI = std::f64::consts::PI;
   const E = std::f64::consts::E;
   const INFINITY = f64::INFINITY;
+  const NAN = f64::NAN;
   fn floor(Number) -> f64::floor;
   fn ceil(Number) -> f64::ceil;
   fn round(Number) -> f64::round;
@@ -33,14 +40,88 @@ module!(maths, {
   fn acosh(Number) -> f64::acosh;
   fn atanh(Number) -> f64::atanh;
   fn isNan(Number) -> f64::is_nan;
+  fn isNaN(Number) -> f64::is_nan;
+  fn isFinite(Number) -> f64::is_finite;
   fn exp(Number) -> f64::exp;
   fn ln(Number) -> f64::ln;
   fn pow(Number, Number) -> f64::powf;
   fn log(Number, Number) -> f64::log;
   fn radiansToDegrees(Number) -> f64::to_degrees;
   fn degreesToRadians(Number) -> f64::to_radians;
+  fn approxEqual(Number, Number, Number) -> |a: f64, b: f64, epsilon: f64| a == b || (a - b).abs() <= epsilon;
 });
 
+// Replaces `{key}` or `{key:spec}` with the value from the dict, and `{{` or `}}` with a brace
+fn format_template(template: &str, values: &BangHashMap<Value, Value>) -> Option<String> {
+  let mut result = String::new();
+  let mut rest = template;
+
+  while let Some(position) = rest.find(['{', '}']) {
+    result.push_str(&rest[..position]);
+    let brace = &rest[position..=position];
+    rest = &rest[position + 1..];
+
+    if let Some(remaining) = rest.strip_prefix(brace) {
+      result.push_str(brace);
+      rest = remaining;
+      continue;
+    }
+    if brace == "}" {
+      return None;
+    }
+
+    let end = rest.find('}')?;
+    let (key, spec) = rest[..end].split_once(':').unwrap_or((&rest[..end], ""));
+    let spec = match FormatSpec::parse(spec)? {
+      (spec, "") => spec,
+      _ => return None,
+    };
+
+    let value = values.get(&Value::from(key.trim()))?;
+    result.push_str(&spec.format(value)?);
+    rest = &rest[end + 1..];
+  }
+
+  result.push_str(rest);
+  Some(result)
+}
+
+// Replaces each `%spec` with the next value from the list
+fn sprintf(template: &str, values: Value) -> Option<String> {
+  if !values.is_object() {
+    return None;
+  }
+  let values = match values.as_object() {
+    Object::List(list) => list.borrow().clone(),
+    _ => return None,
+  };
+  let mut values = values.iter();
+
+  let mut result = String::new();
+  let mut rest = template;
+
+  while let Some(position) = rest.find('%') {
+    result.push_str(&rest[..position]);
+    rest = &rest[position + 1..];
+
+    if let Some(remaining) = rest.strip_prefix('%') {
+      result.push('%');
+      rest = remaining;
+      continue;
+    }
+
+    let (spec, remaining) = FormatSpec::parse(rest)?;
+    if spec.kind == FormatKind::Default {
+      return None;
+    }
+    result.push_str(&spec.format(values.next()?)?);
+    rest = remaining;
+  }
+
+  result.push_str(rest);
+  Some(result)
+}
+
 module!(string, {
   const NEW_LINE = "\n";
   const TAB = "\t";
@@ -59,26 +140,38 @@ module!(string, {
   fn replaceOne(String, String, String) -> |a,b,c| str::replacen(a, b, c, 1);
   fn toNumber(String) -> |s| str::parse::<f64>(s).unwrap_or(f64::NAN);
   fn split(String, String) -> |a, b| str::split(a, b).filter(|x| !x.is_empty()).map(Value::from).collect::<Vec<_>>();
+  fn format(String, DictRef) -> format_template;
+  fn sprintf(String, Any) -> sprintf;
 });
 
 module!(list, {
   fn length(ListRef) -> Vec::len;
   fn isEmpty(ListRef) -> Vec::is_empty;
-  fn push(ListReturned, Any) -> Vec::push;
-  fn pop(List) -> Vec::pop;
-  fn includes(List, Any) -> |list: &mut Vec<_>, value| list.contains(&value);
-  fn reverse(List) -> |l: &mut Vec<_>| l.iter().rev().cloned().collect::<Vec<_>>();
-  fn get(List, Number) -> |l: &mut Vec<_>, i| l.get(calculate_index(i, l.len())).cloned();
+  fn includes(ListRef, Any) -> |list: &Vec<_>, value| list.contains(&value);
+  fn reverse(ListRef) -> |l: &Vec<_>| l.iter().rev().cloned().collect::<Vec<_>>();
+  fn get(ListRef, Number) -> |l: &Vec<_>, i| l.get(calculate_index(i, l.len())).cloned();
   fn toSet(ListRef) -> |l: &Vec<Value>| l.iter().cloned().collect::<BangHashSet<Value>>();
+  fn indexOf(ListRef, Any) -> |l: &Vec<_>, value| l.iter().position(|item| *item == value);
+  fn lastIndexOf(ListRef, Any) -> |l: &Vec<_>, value| l.iter().rposition(|item| *item == value);
+  fn count(ListRef, Any) -> |l: &Vec<_>, value| l.iter().filter(|item| **item == value).count();
+  fn sum(ListRef) -> |l: &Vec<Value>| l.iter()
+    .map(|item| item.is_number().then(|| item.as_number()))
+    .sum::<Option<f64>>();
+  fn freeze(ListFreeze) -> Freezable::freeze;
+  fn copy(ListRef) -> |l: &Vec<Value>| l.clone();
+  fallible fn push(ListReturned, Any) -> Vec::push;
+  fallible fn pop(List) -> Vec::pop;
   bytecode fn any(List, Predicate) -> bytecode::ANY;
   bytecode fn all(List, Predicate) -> bytecode::ALL;
   bytecode fn map(List, Transform) -> bytecode::MAP;
   bytecode fn filter(List, Predicate) -> bytecode::FILTER;
   bytecode fn reduce(List, Accum, Transform) -> bytecode::REDUCE;
   bytecode fn find(List, Predicate) -> bytecode::FIND;
+  bytecode fn findIndex(List, Predicate) -> bytecode::FIND_INDEX;
   bytecode fn forEach(List, Operation) -> bytecode::FOR_EACH;
   bytecode fn max(List) -> bytecode::MAX;
   bytecode fn min(List) -> bytecode::MIN;
+  bytecode fn compare(List, List; Comparator) -> bytecode::COMPARE;
 });
 
 module!(set, {
@@ -97,8 +190,47 @@ module!(set, {
   fn intersection(SetCloned, Set) -> HashSet::intersection;
   fn symmetricDifference(SetCloned, Set) -> HashSet::symmetric_difference;
   fn toList(SetRef) -> |s: &BangHashSet<_>| s.iter().cloned().collect::<Vec<_>>();
+  fn fromList(ListRef) -> |l: &Vec<Value>| l.iter().cloned().collect::<BangHashSet<Value>>();
+  fn copy(SetRef) -> |s: &BangHashSet<_>| s.clone();
+  bytecode fn map(Set, Transform) -> bytecode::SET_MAP;
+  bytecode fn filter(Set, Predicate) -> bytecode::SET_FILTER;
+  bytecode fn fold(Set, Accum, Transform) -> bytecode::SET_FOLD;
 });
 
+// Numbers come before strings, which come before other values, so any dict has a stable order
+fn key_order(a: &Value, b: &Value) -> Ordering {
+  let rank = |value: &Value| {
+    if value.is_number() {
+      0
+    } else if value.is_object() && matches!(value.as_object(), Object::String(_)) {
+      1
+    } else {
+      2
+    }
+  };
+
+  rank(a)
+    .cmp(&rank(b))
+    .then_with(|| match (rank(a), rank(b)) {
+      (0, 0) => a.as_number().total_cmp(&b.as_number()),
+      (1, 1) => match (a.as_object(), b.as_object()) {
+        (Object::String(a), Object::String(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+      },
+      _ => (a.get_type(), a.to_string()).cmp(&(b.get_type(), b.to_string())),
+    })
+}
+
+fn sorted_entries(dict: &BangHashMap<Value, Value>) -> Vec<Value> {
+  let mut entries: Vec<_> = dict.iter().collect();
+  entries.sort_by(|(a, _), (b, _)| key_order(a, b));
+
+  entries
+    .into_iter()
+    .map(|(key, value)| Value::from(vec![key.clone(), value.clone()]))
+    .collect()
+}
+
 module!(dict, {
   fn new() -> BangHashMap::default;
   fn dict() -> BangHashMap::default;
@@ -107,8 +239,57 @@ module!(dict, {
   fn keys(DictRef) -> |d: &BangHashMap<_, _>| d.keys().cloned().collect::<Vec<_>>();
   fn values(DictRef) -> |d: &BangHashMap<_, _>| d.values().cloned().collect::<Vec<_>>();
   fn get(DictRef, Any) -> |dict: &BangHashMap<_, _>, index| dict.get(index).cloned();
+  fn hasKey(DictRef, Any) -> |dict: &BangHashMap<_, _>, index| dict.contains_key(index);
+  fn freeze(DictFreeze) -> Freezable::freeze;
+  fn copy(DictRef) -> |d: &BangHashMap<Value, Value>| d.clone();
+  fn toSortedList(DictRef) -> sorted_entries;
+  bytecode fn sortedKeys(Dict; Comparator) -> bytecode::SORTED_KEYS;
+});
+
+module!(function, {
+  fn name(Function) -> |name, _| name;
+  fn arity(Function) -> |_, arity: Arity| arity.get_count();
+});
+
+module!(iter, {
+  fn range(Number, Number) -> |current, end| Sequence::Range { current, end };
+  fn repeat(Any) -> |value| Sequence::Repeat { value };
+  fn from(Any) -> |value| Sequence::from_value(&value);
+  fn map(Any, Any) -> |sequence, func| Sequence::from_value(&sequence)
+    .map(|sequence| Value::from(Sequence::Map { sequence, func }));
+  fn filter(Any, Any) -> |sequence, func| Sequence::from_value(&sequence)
+    .map(|sequence| Value::from(Sequence::Filter { sequence, func }));
+  fn take(Any, Usize) -> |sequence, remaining| Sequence::from_value(&sequence)
+    .map(|sequence| Value::from(Sequence::Take { sequence, remaining }));
+  bytecode fn collect(Sequence) -> bytecode::COLLECT;
 });
 
+module!(memory, {
+  fn usage(Any) -> |value: Value| value.deep_size();
+});
+
+module!(task, {
+  bytecode fn spawn(Any) -> bytecode::SPAWN;
+  bytecode fn join(Any) -> bytecode::JOIN;
+});
+
+// Flags which are literals are resolved at compile time, others are looked up when run
+pub fn config(key: &str) -> ImportValue {
+  match key {
+    "get" => ImportValue::Constant(
+      NativeFunction::with_context("config::get", 1, |context, args| {
+        context.config(args[0].as_str())
+      })
+      .into(),
+    ),
+    _ => ImportValue::ItemNotFound,
+  }
+}
+
+pub mod config {
+  pub const ITEMS: &[&str] = &["get"];
+}
+
 #[cfg(feature = "fs")]
 module!(fs, {
   fn read(String) -> std::fs::read_to_string;
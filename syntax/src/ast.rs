@@ -1,8 +1,20 @@
-use crate::tokens::{CharacterPosition, LineNumber, Token};
+use crate::tokens::{CharacterPosition, ColumnNumber, LineNumber, Token};
 
 pub mod expression;
+mod json;
+mod map;
+mod pretty;
 pub mod statement;
 pub mod types;
+pub mod visitor;
+
+pub use json::to_json;
+pub use map::{AstMap, Node};
+pub use pretty::pretty;
+pub use visitor::{Transformer, Visitor};
+
+// The parser numbers nodes from 1 in the order they appear, 0 is for nodes built outside the parser
+pub type NodeId = u32;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Span {
@@ -14,18 +26,35 @@ impl Span {
     let mut line: LineNumber = 1;
 
     for (i, byte) in source.as_bytes().iter().enumerate() {
-      if *byte == b'\n' {
-        line += 1;
-      }
-
       if i == self.start as usize {
         return line;
       }
+
+      if *byte == b'\n' {
+        line += 1;
+      }
     }
 
     line
   }
 
+  pub fn get_column_number(&self, source: &str) -> ColumnNumber {
+    let start = (self.start as usize).min(source.len());
+    let line_start = source[..start]
+      .rfind('\n')
+      .map_or(0, |position| position + 1);
+
+    #[allow(clippy::cast_possible_truncation)]
+    // assume lines are less than 2^32 characters
+    let column = source[line_start..start].chars().count() as ColumnNumber;
+
+    column + 1
+  }
+
+  pub fn get_line_column(&self, source: &str) -> (LineNumber, ColumnNumber) {
+    (self.get_line_number(source), self.get_column_number(source))
+  }
+
   pub fn get_line_number_end(&self, source: &str) -> LineNumber {
     let mut line: LineNumber = 1;
 
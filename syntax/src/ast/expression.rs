@@ -1,12 +1,14 @@
-use super::{statement::Statement, types::TypeExpression, Span};
+use super::{statement::Statement, types::TypeExpression, NodeId, Span};
 use crate::tokens::TokenType;
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
 use smartstring::alias::String;
-use std::fmt;
 
 #[derive(Clone, Debug)]
 pub struct Expression<'s> {
   pub expr: Expr<'s>,
   pub span: Span,
+  pub id: NodeId,
 }
 
 macro_rules! expression {
@@ -17,6 +19,7 @@ macro_rules! expression {
       Expression {
         expr: Expr::$type $struct,
         span: Span { start: start.start, end: end.end  },
+        id: 0,
       }
     }};
 
@@ -28,9 +31,15 @@ pub(crate) use expression;
 
 #[derive(Clone, Debug)]
 pub enum Expr<'source> {
+  // `(expression: type)`, which is checked unlike a cast
+  Ascription {
+    expression: Box<Expression<'source>>,
+    type_: TypeExpression<'source>,
+  },
   Assignment {
     identifier: &'source str,
     expression: Box<Expression<'source>>,
+    assignment_operator: Option<operators::Assignment>,
   },
   Binary {
     operator: operators::Binary,
@@ -41,10 +50,17 @@ pub enum Expr<'source> {
     expression: Box<Expression<'source>>,
     arguments: Vec<Expression<'source>>,
   },
+  Cast {
+    expression: Box<Expression<'source>>,
+    type_: TypeExpression<'source>,
+  },
   Comment {
     expression: Box<Expression<'source>>,
     text: &'source str,
   },
+  Do {
+    body: Box<Statement<'source>>,
+  },
   FormatString {
     strings: Vec<String>,
     expressions: Vec<Expression<'source>>,
@@ -78,10 +94,16 @@ pub enum Expr<'source> {
     type_: LiteralType,
     value: &'source str,
   },
+  Loop {
+    body: Box<Statement<'source>>,
+  },
   ModuleAccess {
     module: &'source str,
     item: &'source str,
   },
+  NonNull {
+    expression: Box<Expression<'source>>,
+  },
   Unary {
     operator: operators::Unary,
     expression: Box<Expression<'source>>,
@@ -91,7 +113,7 @@ pub enum Expr<'source> {
   },
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum LiteralType {
   String,
   Number,
@@ -125,7 +147,7 @@ impl fmt::Display for LiteralType {
 
 pub mod operators {
   use super::TokenType;
-  use std::fmt;
+  use core::fmt;
 
   #[derive(Copy, Clone, Debug, PartialEq, Eq)]
   pub enum Binary {
@@ -143,7 +165,9 @@ pub mod operators {
     Or,
     Nullish,
     Pipeline,
+    Compose,
     Remainder,
+    In,
   }
   impl From<TokenType> for Binary {
     fn from(token_type: TokenType) -> Self {
@@ -162,7 +186,9 @@ pub mod operators {
         TokenType::Or => Self::Or,
         TokenType::QuestionQuestion => Self::Nullish,
         TokenType::RightRight => Self::Pipeline,
+        TokenType::LeftLeft => Self::Compose,
         TokenType::Percent => Self::Remainder,
+        TokenType::In => Self::In,
         _ => unreachable!(),
       }
     }
@@ -184,7 +210,9 @@ pub mod operators {
         Self::Or => write!(f, "or"),
         Self::Nullish => write!(f, "??"),
         Self::Pipeline => write!(f, ">>"),
+        Self::Compose => write!(f, "<<"),
         Self::Remainder => write!(f, "%"),
+        Self::In => write!(f, "in"),
       }
     }
   }
@@ -217,13 +245,12 @@ pub mod operators {
       }
     }
 
-    pub fn token_to_binary(token_type: TokenType) -> Binary {
-      match token_type {
-        TokenType::PlusEqual => Binary::Plus,
-        TokenType::MinusEqual => Binary::Minus,
-        TokenType::StarEqual => Binary::Multiply,
-        TokenType::SlashEqual => Binary::Divide,
-        _ => unreachable!("The only supported assignment operators are: +, -, *, /"),
+    pub fn to_binary(self) -> Binary {
+      match self {
+        Self::Plus => Binary::Plus,
+        Self::Minus => Binary::Minus,
+        Self::Multiply => Binary::Multiply,
+        Self::Divide => Binary::Divide,
       }
     }
   }
@@ -267,4 +294,5 @@ pub struct Parameter<'s> {
   pub name: &'s str,
   pub span: Span,
   pub type_: Option<TypeExpression<'s>>,
+  pub default: Option<Expression<'s>>,
 }
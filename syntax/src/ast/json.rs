@@ -0,0 +1,457 @@
+use super::{
+  expression::{Expr, Expression, Parameter},
+  statement::{AliasItem, DeclarationIdentifier, Statement, Stmt},
+  types::{Type, TypeExpression},
+  Span,
+};
+use alloc::{
+  format,
+  string::{String, ToString},
+  vec,
+  vec::Vec,
+};
+use core::fmt::Write;
+
+fn string(value: &str) -> String {
+  let mut string = String::from("\"");
+
+  for character in value.chars() {
+    match character {
+      '"' => string.push_str("\\\""),
+      '\\' => string.push_str("\\\\"),
+      '\n' => string.push_str("\\n"),
+      '\r' => string.push_str("\\r"),
+      '\t' => string.push_str("\\t"),
+      character if character.is_control() => {
+        write!(string, "\\u{:04x}", u32::from(character)).expect("No errors writing to string");
+      }
+      character => string.push(character),
+    }
+  }
+
+  string.push('"');
+  string
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+  format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+fn optional(value: Option<String>) -> String {
+  value.unwrap_or_else(|| "null".to_string())
+}
+
+fn object(fields: &[(&str, String)]) -> String {
+  let fields = fields
+    .iter()
+    .map(|(key, value)| format!("{}:{value}", string(key)));
+
+  format!("{{{}}}", fields.collect::<Vec<_>>().join(","))
+}
+
+fn node(kind: &str, span: Span, fields: &[(&str, String)]) -> String {
+  let span = object(&[
+    ("start", span.start.to_string()),
+    ("end", span.end.to_string()),
+  ]);
+
+  let mut all_fields = vec![("kind", string(kind)), ("span", span)];
+  all_fields.extend_from_slice(fields);
+  object(&all_fields)
+}
+
+fn alias_item(item: &AliasItem) -> String {
+  node(
+    "AliasItem",
+    item.span,
+    &[
+      ("name", string(item.name)),
+      ("alias", optional(item.alias.map(string))),
+      ("default", optional(item.default.as_ref().map(expression))),
+    ],
+  )
+}
+
+fn parameter(parameter: &Parameter) -> String {
+  node(
+    "Parameter",
+    parameter.span,
+    &[
+      ("name", string(parameter.name)),
+      (
+        "typeAnnotation",
+        optional(parameter.type_.as_ref().map(type_)),
+      ),
+      (
+        "default",
+        optional(parameter.default.as_ref().map(expression)),
+      ),
+    ],
+  )
+}
+
+fn type_(type_expression: &TypeExpression) -> String {
+  let span = type_expression.span;
+
+  match &type_expression.type_ {
+    Type::Named(name) => node("Named", span, &[("name", string(name))]),
+    Type::StringLiteral(value) => node("StringLiteral", span, &[("value", string(value))]),
+    Type::Parameter(name, parameters) => node(
+      "Parameter",
+      span,
+      &[
+        ("name", string(name)),
+        ("parameters", list(parameters.iter().map(type_))),
+      ],
+    ),
+    Type::Union(left, right) => node(
+      "Union",
+      span,
+      &[("left", type_(left)), ("right", type_(right))],
+    ),
+    Type::Function(return_type, parameters) => node(
+      "Function",
+      span,
+      &[
+        ("returnType", type_(return_type)),
+        ("parameters", list(parameters.iter().map(type_))),
+      ],
+    ),
+    Type::Optional(type_expression) => node("Optional", span, &[("type", type_(type_expression))]),
+    Type::Group(type_expression) => node("Group", span, &[("type", type_(type_expression))]),
+    Type::List(type_expression) => node("List", span, &[("type", type_(type_expression))]),
+    Type::WithGeneric(generics, type_expression) => node(
+      "WithGeneric",
+      span,
+      &[
+        ("generics", list(generics.iter().map(|name| string(name)))),
+        ("type", type_(type_expression)),
+      ],
+    ),
+  }
+}
+
+fn expression(expression: &Expression) -> String {
+  let span = expression.span;
+
+  match &expression.expr {
+    Expr::Assignment {
+      identifier,
+      expression: value,
+      assignment_operator,
+    } => node(
+      "Assignment",
+      span,
+      &[
+        ("identifier", string(identifier)),
+        (
+          "operator",
+          optional(assignment_operator.map(|operator| string(&operator.to_string()))),
+        ),
+        ("expression", self::expression(value)),
+      ],
+    ),
+    Expr::Binary {
+      operator,
+      left,
+      right,
+    } => node(
+      "Binary",
+      span,
+      &[
+        ("operator", string(&operator.to_string())),
+        ("left", self::expression(left)),
+        ("right", self::expression(right)),
+      ],
+    ),
+    Expr::Call {
+      expression: callee,
+      arguments,
+    } => node(
+      "Call",
+      span,
+      &[
+        ("expression", self::expression(callee)),
+        ("arguments", list(arguments.iter().map(self::expression))),
+      ],
+    ),
+    Expr::Ascription {
+      expression: inner,
+      type_,
+    } => node(
+      "Ascription",
+      span,
+      &[
+        ("expression", self::expression(inner)),
+        ("typeAnnotation", self::type_(type_)),
+      ],
+    ),
+    Expr::Cast {
+      expression: inner,
+      type_,
+    } => node(
+      "Cast",
+      span,
+      &[
+        ("expression", self::expression(inner)),
+        ("typeAnnotation", self::type_(type_)),
+      ],
+    ),
+    Expr::Comment {
+      expression: inner,
+      text,
+    } => node(
+      "Comment",
+      span,
+      &[
+        ("expression", self::expression(inner)),
+        ("text", string(text)),
+      ],
+    ),
+    Expr::FormatString {
+      strings,
+      expressions,
+    } => node(
+      "FormatString",
+      span,
+      &[
+        ("strings", list(strings.iter().map(|part| string(part)))),
+        (
+          "expressions",
+          list(expressions.iter().map(self::expression)),
+        ),
+      ],
+    ),
+    Expr::Function {
+      parameters,
+      return_type,
+      body,
+      name,
+    } => node(
+      "Function",
+      span,
+      &[
+        ("name", optional(name.map(string))),
+        ("parameters", list(parameters.iter().map(parameter))),
+        ("returnType", optional(return_type.as_ref().map(type_))),
+        ("body", statement(body)),
+      ],
+    ),
+    Expr::Group { expression: inner } => {
+      node("Group", span, &[("expression", self::expression(inner))])
+    }
+    Expr::Index {
+      expression: inner,
+      index,
+    } => node(
+      "Index",
+      span,
+      &[
+        ("expression", self::expression(inner)),
+        ("index", self::expression(index)),
+      ],
+    ),
+    Expr::IndexAssignment {
+      expression: inner,
+      index,
+      value,
+      assignment_operator,
+    } => node(
+      "IndexAssignment",
+      span,
+      &[
+        ("expression", self::expression(inner)),
+        ("index", self::expression(index)),
+        (
+          "operator",
+          optional(assignment_operator.map(|operator| string(&operator.to_string()))),
+        ),
+        ("value", self::expression(value)),
+      ],
+    ),
+    Expr::Dictionary { items } => {
+      let items = items.iter().map(|(key, value)| {
+        object(&[
+          ("key", self::expression(key)),
+          ("value", self::expression(value)),
+        ])
+      });
+
+      node("Dictionary", span, &[("items", list(items))])
+    }
+    Expr::List { items } => node(
+      "List",
+      span,
+      &[("items", list(items.iter().map(self::expression)))],
+    ),
+    Expr::Literal { type_, value } => node(
+      "Literal",
+      span,
+      &[
+        ("type", string(&type_.to_string())),
+        ("value", string(value)),
+      ],
+    ),
+    Expr::Do { body } => node("Do", span, &[("body", statement(body))]),
+    Expr::Loop { body } => node("Loop", span, &[("body", statement(body))]),
+    Expr::ModuleAccess { module, item } => node(
+      "ModuleAccess",
+      span,
+      &[("module", string(module)), ("item", string(item))],
+    ),
+    Expr::NonNull { expression: inner } => {
+      node("NonNull", span, &[("expression", self::expression(inner))])
+    }
+    Expr::Unary {
+      operator,
+      expression: inner,
+    } => node(
+      "Unary",
+      span,
+      &[
+        ("operator", string(&operator.to_string())),
+        ("expression", self::expression(inner)),
+      ],
+    ),
+    Expr::Variable { name } => node("Variable", span, &[("name", string(name))]),
+  }
+}
+
+fn declaration_identifier(identifier: &DeclarationIdentifier) -> String {
+  match identifier {
+    DeclarationIdentifier::Variable(name) => {
+      object(&[("kind", string("Variable")), ("name", string(name))])
+    }
+    DeclarationIdentifier::Ordered(names) => object(&[
+      ("kind", string("Ordered")),
+      ("names", list(names.iter().map(|name| string(name)))),
+    ]),
+    DeclarationIdentifier::Named(items, rest) => object(&[
+      ("kind", string("Named")),
+      ("items", list(items.iter().map(alias_item))),
+      ("rest", optional(rest.map(string))),
+    ]),
+  }
+}
+
+fn statement(statement: &Statement) -> String {
+  let span = statement.span;
+
+  match &statement.stmt {
+    Stmt::Block { body } => node(
+      "Block",
+      span,
+      &[("body", list(body.iter().map(self::statement)))],
+    ),
+    Stmt::Declaration {
+      identifier,
+      type_,
+      expression,
+    } => node(
+      "Declaration",
+      span,
+      &[
+        ("identifier", declaration_identifier(identifier)),
+        ("typeAnnotation", optional(type_.as_ref().map(self::type_))),
+        (
+          "expression",
+          optional(expression.as_ref().map(self::expression)),
+        ),
+      ],
+    ),
+    Stmt::Expression { expression } => node(
+      "Expression",
+      span,
+      &[("expression", self::expression(expression))],
+    ),
+    Stmt::If {
+      condition,
+      then,
+      otherwise,
+    } => node(
+      "If",
+      span,
+      &[
+        ("condition", expression(condition)),
+        ("then", self::statement(then)),
+        (
+          "otherwise",
+          optional(otherwise.as_deref().map(self::statement)),
+        ),
+      ],
+    ),
+    Stmt::Import {
+      module,
+      alias,
+      items,
+      optional: is_optional,
+    } => node(
+      "Import",
+      span,
+      &[
+        ("module", string(module)),
+        ("alias", optional(alias.map(string))),
+        ("items", list(items.iter().map(alias_item))),
+        ("optional", is_optional.to_string()),
+      ],
+    ),
+    Stmt::Break { expression } => node(
+      "Break",
+      span,
+      &[(
+        "expression",
+        optional(expression.as_ref().map(self::expression)),
+      )],
+    ),
+    Stmt::Return { expression } => node(
+      "Return",
+      span,
+      &[(
+        "expression",
+        optional(expression.as_ref().map(self::expression)),
+      )],
+    ),
+    Stmt::While { condition, body } => node(
+      "While",
+      span,
+      &[
+        ("condition", expression(condition)),
+        ("body", self::statement(body)),
+      ],
+    ),
+    Stmt::Comment { text } => node("Comment", span, &[("text", string(text))]),
+  }
+}
+
+pub fn to_json(ast: &[Statement]) -> String {
+  list(ast.iter().map(statement))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::to_json;
+  use crate::parse;
+
+  #[test]
+  fn serialises_tree() {
+    let ast = parse("x = 'a\"b'\n").unwrap();
+
+    assert_eq!(
+      to_json(&ast),
+      concat!(
+        r#"[{"kind":"Expression","span":{"start":0,"end":9},"expression":"#,
+        r#"{"kind":"Assignment","span":{"start":0,"end":9},"identifier":"x","operator":null,"#,
+        r#""expression":{"kind":"Literal","span":{"start":4,"end":9},"type":"string","value":"a\"b"}}}]"#
+      )
+    );
+  }
+
+  #[test]
+  fn serialises_string_literal_types() {
+    let ast = parse("let a: 'read'\n").unwrap();
+
+    let json = to_json(&ast);
+
+    assert!(json.contains(r#"{"kind":"StringLiteral","span":{"start":7,"end":13},"value":"read"}"#));
+  }
+}
@@ -0,0 +1,237 @@
+use super::{
+  expression::{Expr, Expression},
+  statement::{DeclarationIdentifier, Statement, Stmt},
+  NodeId, Span,
+};
+use crate::tokens::CharacterPosition;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::iter;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Node<'a, 's> {
+  Statement(&'a Statement<'s>),
+  Expression(&'a Expression<'s>),
+}
+impl<'a, 's> Node<'a, 's> {
+  pub fn id(&self) -> NodeId {
+    match self {
+      Self::Statement(statement) => statement.id,
+      Self::Expression(expression) => expression.id,
+    }
+  }
+
+  pub fn span(&self) -> Span {
+    match self {
+      Self::Statement(statement) => statement.span,
+      Self::Expression(expression) => expression.span,
+    }
+  }
+
+  fn children(self) -> Vec<Self> {
+    match self {
+      Self::Statement(statement) => statement_children(statement),
+      Self::Expression(expression) => expression_children(expression),
+    }
+  }
+}
+
+fn statement_children<'a, 's>(statement: &'a Statement<'s>) -> Vec<Node<'a, 's>> {
+  let expression = |expression| Node::Expression(expression);
+  let statement_ = |statement| Node::Statement(statement);
+
+  match &statement.stmt {
+    Stmt::Block { body } => body.iter().map(statement_).collect(),
+    Stmt::Declaration {
+      identifier: DeclarationIdentifier::Named(items, _),
+      expression: e,
+      ..
+    } => e
+      .iter()
+      .chain(items.iter().filter_map(|item| item.default.as_ref()))
+      .map(expression)
+      .collect(),
+    Stmt::Declaration { expression: e, .. }
+    | Stmt::Return { expression: e }
+    | Stmt::Break { expression: e } => e.iter().map(expression).collect(),
+    Stmt::Expression { expression: e } => vec![expression(e)],
+    Stmt::If {
+      condition,
+      then,
+      otherwise,
+    } => {
+      let mut children = vec![expression(condition), statement_(then)];
+      children.extend(otherwise.as_deref().map(statement_));
+      children
+    }
+    Stmt::While { condition, body } => vec![expression(condition), statement_(body)],
+    Stmt::Import { .. } | Stmt::Comment { .. } => vec![],
+  }
+}
+
+fn expression_children<'a, 's>(expression: &'a Expression<'s>) -> Vec<Node<'a, 's>> {
+  let expression_ = |expression| Node::Expression(expression);
+
+  match &expression.expr {
+    Expr::Ascription { expression, .. }
+    | Expr::Assignment { expression, .. }
+    | Expr::Cast { expression, .. }
+    | Expr::Comment { expression, .. }
+    | Expr::Group { expression }
+    | Expr::NonNull { expression }
+    | Expr::Unary { expression, .. } => vec![expression_(expression)],
+    Expr::Binary { left, right, .. } => vec![expression_(left), expression_(right)],
+    Expr::Call {
+      expression,
+      arguments,
+    } => iter::once(expression.as_ref())
+      .chain(arguments)
+      .map(expression_)
+      .collect(),
+    Expr::FormatString { expressions, .. } | Expr::List { items: expressions } => {
+      expressions.iter().map(expression_).collect()
+    }
+    Expr::Function {
+      parameters, body, ..
+    } => parameters
+      .iter()
+      .filter_map(|parameter| parameter.default.as_ref())
+      .map(expression_)
+      .chain(iter::once(Node::Statement(body)))
+      .collect(),
+    Expr::Do { body } | Expr::Loop { body } => vec![Node::Statement(body)],
+    Expr::Index { expression, index } => vec![expression_(expression), expression_(index)],
+    Expr::IndexAssignment {
+      expression,
+      index,
+      value,
+      ..
+    } => vec![
+      expression_(expression),
+      expression_(index),
+      expression_(value),
+    ],
+    Expr::Dictionary { items } => items
+      .iter()
+      .flat_map(|(key, value)| [expression_(key), expression_(value)])
+      .collect(),
+    Expr::Literal { .. } | Expr::Variable { .. } | Expr::ModuleAccess { .. } => vec![],
+  }
+}
+
+// Lookups by id for a parsed tree, as nodes only own their children
+#[derive(Debug, Default)]
+pub struct AstMap<'a, 's> {
+  nodes: BTreeMap<NodeId, Node<'a, 's>>,
+  parents: BTreeMap<NodeId, NodeId>,
+}
+impl<'a, 's> AstMap<'a, 's> {
+  pub fn new(ast: &'a [Statement<'s>]) -> Self {
+    let mut map = Self::default();
+    for statement in ast {
+      map.insert(Node::Statement(statement), None);
+    }
+    map
+  }
+
+  fn insert(&mut self, node: Node<'a, 's>, parent: Option<NodeId>) {
+    self.nodes.insert(node.id(), node);
+    if let Some(parent) = parent {
+      self.parents.insert(node.id(), parent);
+    }
+
+    for child in node.children() {
+      self.insert(child, Some(node.id()));
+    }
+  }
+
+  pub fn get(&self, id: NodeId) -> Option<Node<'a, 's>> {
+    self.nodes.get(&id).copied()
+  }
+
+  pub fn parent(&self, id: NodeId) -> Option<Node<'a, 's>> {
+    self.parents.get(&id).and_then(|parent| self.get(*parent))
+  }
+
+  pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = Node<'a, 's>> + '_ {
+    iter::successors(self.parent(id), |node| self.parent(node.id()))
+  }
+
+  // The innermost node which covers the position
+  pub fn node_at(&self, position: CharacterPosition) -> Option<Node<'a, 's>> {
+    self
+      .nodes
+      .values()
+      .filter(|node| node.span().start <= position && position < node.span().end)
+      .min_by_key(|node| (node.span().end - node.span().start, u32::MAX - node.id()))
+      .copied()
+  }
+
+  // The innermost node with exactly the span, as a group and its contents can share one
+  pub fn node_with_span(&self, span: Span) -> Option<Node<'a, 's>> {
+    self
+      .nodes
+      .values()
+      .filter(|node| node.span() == span)
+      .max_by_key(|node| node.id())
+      .copied()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{AstMap, Node};
+  use crate::{ast::expression::Expr, parse, Span};
+
+  #[test]
+  fn numbers_nodes_uniquely() {
+    let ast = parse("let a = 1 + 2\nprint(a)\n").unwrap();
+    let map = AstMap::new(&ast);
+
+    assert!((1..=8).all(|id| map.get(id).is_some()));
+    assert!(map.get(9).is_none());
+    assert_eq!(ast[0].id, 1);
+    assert_eq!(ast[1].id, 5);
+  }
+
+  #[test]
+  fn finds_parents() {
+    let ast = parse("let a = (b) => c + d\n").unwrap();
+    let map = AstMap::new(&ast);
+
+    let variable = map.node_at(19).unwrap();
+    assert!(matches!(
+      variable,
+      Node::Expression(expression) if matches!(expression.expr, Expr::Variable { name: "d" })
+    ));
+
+    let ancestors: Vec<_> = map.ancestors(variable.id()).map(|node| node.id()).collect();
+    assert_eq!(ancestors, [4, 3, 2, 1]);
+    assert!(map.parent(ast[0].id).is_none());
+  }
+
+  #[test]
+  fn finds_destructuring_defaults() {
+    let ast = parse("let { x = a } = b\n").unwrap();
+    let map = AstMap::new(&ast);
+
+    let default = map.node_at(10).unwrap();
+    assert!(matches!(
+      default,
+      Node::Expression(expression) if matches!(expression.expr, Expr::Variable { name: "a" })
+    ));
+    assert_eq!(map.parent(default.id()).unwrap().id(), ast[0].id);
+  }
+
+  #[test]
+  fn finds_node_by_span() {
+    let ast = parse("x = (y)\n").unwrap();
+    let map = AstMap::new(&ast);
+
+    let node = map.node_with_span(Span { start: 5, end: 6 }).unwrap();
+    assert!(matches!(
+      node,
+      Node::Expression(expression) if matches!(expression.expr, Expr::Variable { name: "y" })
+    ));
+    assert!(map.node_with_span(Span { start: 1, end: 2 }).is_none());
+  }
+}
@@ -0,0 +1,364 @@
+use super::{
+  expression::{Expr, Expression},
+  statement::{Statement, Stmt},
+};
+use alloc::{
+  format,
+  string::{String, ToString},
+  vec::Vec,
+};
+use core::fmt::{self, Write};
+
+pub fn pretty(ast: &[Statement]) -> String {
+  let mut string = String::new();
+  fmt_ast(&mut string, ast).expect("No errors writing to string");
+  string
+}
+
+fn fmt_ast(f: &mut String, ast: &[Statement]) -> fmt::Result {
+  writeln!(f, "  ╭─[Abstract Syntax Tree]")?;
+  for statement in ast {
+    fmt_statement(f, statement, "  ├─ ", "  │  ")?;
+  }
+  writeln!(f, "──╯")
+}
+
+fn fmt_expression(
+  f: &mut String,
+  expression: &Expression,
+  prefix: &str,
+  prefix_raw: &str,
+) -> fmt::Result {
+  let prefix_start = &format!("{prefix_raw}╰─ ");
+  let prefix_blank = &format!("{prefix_raw}   ");
+  let prefix_start_indent = &format!("{prefix_raw}   ╰─ ");
+  let prefix_blank_indent = &format!("{prefix_raw}      ");
+  let prefix_list_inline_start = &format!("{prefix_raw}├─ ");
+  let prefix_list_inline = &format!("{prefix_raw}│  ");
+  let prefix_list_indent_start = &format!("{prefix_raw}   ├─ ");
+  let prefix_list_indent = &format!("{prefix_raw}   │  ");
+
+  match &expression.expr {
+    Expr::Literal { value, .. } => writeln!(f, "{prefix}Literal ({value})")?,
+    Expr::Group { expression, .. } => {
+      writeln!(f, "{prefix}Group")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::Unary {
+      expression,
+      operator,
+    } => {
+      writeln!(f, "{prefix}Unary ({operator})")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::NonNull { expression } => {
+      writeln!(f, "{prefix}Non Null Assertion")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::Cast { expression, .. } => {
+      writeln!(f, "{prefix}Cast")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::Ascription { expression, .. } => {
+      writeln!(f, "{prefix}Ascription")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::Binary {
+      left,
+      right,
+      operator,
+    } => {
+      writeln!(f, "{prefix}Binary ({operator})")?;
+      fmt_expression(f, left, prefix_list_inline_start, prefix_list_inline)?;
+      fmt_expression(f, right, prefix_start, prefix_blank)?;
+    }
+    Expr::Assignment {
+      expression,
+      identifier,
+      assignment_operator,
+    } => {
+      writeln!(
+        f,
+        "{prefix}Assignment ({identifier} {} value)",
+        assignment_operator
+          .map(|operator| operator.to_string())
+          .unwrap_or_else(|| "=".to_string())
+      )?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Expr::Variable { name, .. } => {
+      writeln!(f, "{prefix}Variable ({name})")?;
+    }
+    Expr::Call {
+      expression,
+      arguments,
+      ..
+    } => {
+      fmt_expression(f, expression, prefix, prefix_blank)?;
+      writeln!(f, "{prefix_start}Call")?;
+
+      if let Some((last, arguments)) = arguments.split_last() {
+        for arg in arguments {
+          fmt_expression(f, arg, prefix_list_indent_start, prefix_list_indent)?;
+        }
+        fmt_expression(f, last, prefix_start_indent, prefix_blank_indent)?;
+      }
+    }
+    Expr::Function {
+      body,
+      parameters,
+      name,
+      ..
+    } => {
+      let params = parameters
+        .iter()
+        .map(|param| param.name)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+      if let Some(name) = name {
+        writeln!(f, "{prefix}Function {name}({params})")?;
+      } else {
+        writeln!(f, "{prefix}Function ({params})")?;
+      }
+      for parameter in parameters {
+        if let Some(default) = &parameter.default {
+          writeln!(f, "{prefix_list_inline_start}Default ({})", parameter.name)?;
+          fmt_expression(
+            f,
+            default,
+            &format!("{prefix_list_inline}╰─ "),
+            &format!("{prefix_list_inline}   "),
+          )?;
+        }
+      }
+      fmt_statement(f, body, prefix_start, prefix_blank)?;
+    }
+    Expr::Comment { expression, text } => {
+      fmt_expression(f, expression, prefix, prefix_raw)?;
+      writeln!(f, "{prefix_start}Comment ({text})")?;
+    }
+    Expr::List { items } => {
+      writeln!(f, "{prefix_start}List")?;
+
+      if let Some((last, items)) = items.split_last() {
+        for item in items {
+          fmt_expression(f, item, prefix_list_indent_start, prefix_list_indent)?;
+        }
+        fmt_expression(f, last, prefix_start_indent, prefix_blank_indent)?;
+      }
+    }
+    Expr::Index { expression, index } => {
+      writeln!(f, "{prefix_start}Index (expression, index)")?;
+
+      fmt_expression(f, expression, prefix_list_indent_start, prefix_list_indent)?;
+      fmt_expression(f, index, prefix_start_indent, prefix_blank_indent)?;
+    }
+    Expr::IndexAssignment {
+      expression,
+      index,
+      value,
+      assignment_operator,
+    } => {
+      writeln!(
+        f,
+        "{}Index Assignment (expression[index] {} value)",
+        prefix_start,
+        assignment_operator
+          .map(|operator| operator.to_string())
+          .unwrap_or_else(|| "=".to_string())
+      )?;
+
+      fmt_expression(f, expression, prefix_list_indent_start, prefix_list_indent)?;
+      fmt_expression(f, index, prefix_list_indent_start, prefix_list_indent)?;
+      fmt_expression(f, value, prefix_start_indent, prefix_blank_indent)?;
+    }
+    Expr::FormatString {
+      strings,
+      expressions,
+    } => {
+      writeln!(f, "{prefix}Format String")?;
+
+      writeln!(f, "{prefix_list_inline_start}'{}'", strings[0])?;
+      for (index, item) in expressions.iter().enumerate() {
+        fmt_expression(f, item, prefix_list_inline_start, prefix_list_inline)?;
+
+        if index == expressions.len() - 1 {
+          writeln!(f, "{prefix_start}'{}'", strings[index + 1])?;
+        } else {
+          writeln!(f, "{prefix_list_inline_start}'{}'", strings[index + 1])?;
+        }
+      }
+    }
+    Expr::ModuleAccess { module, item } => {
+      writeln!(f, "{prefix}Module Access ({module}::{item})")?;
+    }
+    Expr::Loop { body } => {
+      writeln!(f, "{prefix}Loop")?;
+      fmt_statement(f, body, prefix_start, prefix_blank)?;
+    }
+    Expr::Do { body } => {
+      writeln!(f, "{prefix}Do")?;
+      fmt_statement(f, body, prefix_start, prefix_blank)?;
+    }
+    Expr::Dictionary { items } => {
+      writeln!(f, "{prefix_start}Dictionary")?;
+
+      for (key, value) in items {
+        fmt_expression(f, key, prefix_list_indent_start, prefix_list_indent)?;
+        fmt_expression(
+          f,
+          value,
+          &format!("{prefix_list_indent}╰─ "),
+          &format!("{prefix_raw}   │     "),
+        )?;
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn fmt_statement(
+  f: &mut String,
+  statement: &Statement,
+  prefix: &str,
+  prefix_raw: &str,
+) -> fmt::Result {
+  let prefix_indetented_start = &format!("{prefix_raw}   ╰─ ");
+  let prefix_indetented = &format!("{prefix_raw}      ");
+  let prefix_list_start = &format!("{prefix_raw}│  ╰─ ");
+  let prefix_list = &format!("{prefix_raw}│     ");
+  let prefix_start = &format!("{prefix_raw}╰─ ");
+  let prefix_blank = &format!("{prefix_raw}   ");
+  let prefix_list_inline_start = &format!("{prefix_raw}├─ ");
+  let prefix_list_inline = &format!("{prefix_raw}│  ");
+
+  match &statement.stmt {
+    Stmt::Declaration {
+      identifier,
+      expression,
+      ..
+    } => {
+      writeln!(f, "{prefix}Declaration ({identifier})")?;
+      if let Some(expression) = expression {
+        fmt_expression(f, expression, prefix_start, prefix_blank)?;
+      }
+    }
+    Stmt::If {
+      condition,
+      then,
+      otherwise,
+      ..
+    } => {
+      writeln!(f, "{prefix}If")?;
+      writeln!(f, "{prefix_raw}├─ Condition")?;
+      fmt_expression(f, condition, prefix_list_start, prefix_list)?;
+      writeln!(f, "{prefix_raw}├─ Then")?;
+      fmt_statement(f, then, prefix_list_start, prefix_list)?;
+      if let Some(ot) = otherwise {
+        writeln!(f, "{prefix_raw}╰─ Else")?;
+        fmt_statement(f, ot, prefix_indetented_start, prefix_indetented)?;
+      };
+    }
+    Stmt::Import {
+      module,
+      alias,
+      items,
+      optional,
+    } => {
+      let import = if *optional { "Import?" } else { "Import" };
+      if let Some(alias) = alias {
+        writeln!(f, "{prefix}From '{module}' as {alias} {import}")?;
+      } else {
+        writeln!(f, "{prefix}From '{module}' {import}")?;
+      }
+
+      for item in items {
+        if let Some(alias) = item.alias {
+          writeln!(f, "{prefix_raw}├─ {} as {alias}", item.name)?;
+        } else {
+          writeln!(f, "{prefix_raw}├─ {}", item.name)?;
+        }
+      }
+    }
+    Stmt::While {
+      condition, body, ..
+    } => {
+      writeln!(f, "{prefix}While")?;
+      writeln!(f, "{prefix_raw}├─ Condition")?;
+      fmt_expression(f, condition, prefix_list_start, prefix_list)?;
+      writeln!(f, "{prefix_raw}╰─ Body")?;
+      fmt_statement(f, body, prefix_indetented_start, prefix_indetented)?;
+    }
+    Stmt::Return { expression, .. } => {
+      writeln!(f, "{prefix}Return")?;
+      if let Some(expression) = expression {
+        fmt_expression(f, expression, prefix_start, prefix_blank)?;
+      }
+    }
+    Stmt::Break { expression } => {
+      writeln!(f, "{prefix}Break")?;
+      if let Some(expression) = expression {
+        fmt_expression(f, expression, prefix_start, prefix_blank)?;
+      }
+    }
+    Stmt::Block { body, .. } => {
+      writeln!(f, "{prefix}Block")?;
+      if let Some((last, statements)) = body.split_last() {
+        for stmt in statements {
+          fmt_statement(f, stmt, prefix_list_inline_start, prefix_list_inline)?;
+        }
+        fmt_statement(f, last, prefix_start, prefix_blank)?;
+      }
+    }
+    Stmt::Expression { expression, .. } => {
+      writeln!(f, "{prefix}Expression")?;
+      fmt_expression(f, expression, prefix_start, prefix_blank)?;
+    }
+    Stmt::Comment { text, .. } => {
+      writeln!(f, "{prefix}Comment ({text})")?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::pretty;
+  use crate::parse;
+
+  #[test]
+  fn pretty_prints_tree() {
+    let ast = parse("let x = a + 1\nx += 2\n").unwrap();
+
+    let expected = "  ╭─[Abstract Syntax Tree]
+  ├─ Declaration (x)
+  │  ╰─ Binary (+)
+  │     ├─ Variable (a)
+  │     ╰─ Literal (1)
+  ├─ Expression
+  │  ╰─ Assignment (x += value)
+  │     ╰─ Literal (2)
+──╯
+";
+    assert_eq!(pretty(&ast), expected);
+  }
+
+  #[test]
+  fn pretty_prints_default_parameters() {
+    let ast = parse("(a, b: number = 1) => a\n").unwrap();
+
+    let expected = "  ╭─[Abstract Syntax Tree]
+  ├─ Expression
+  │  ╰─ Function (a, b)
+  │     ├─ Default (b)
+  │     │  ╰─ Literal (1)
+  │     ╰─ Return
+  │        ╰─ Variable (a)
+──╯
+";
+    assert_eq!(pretty(&ast), expected);
+  }
+}
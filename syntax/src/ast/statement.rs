@@ -1,12 +1,14 @@
 use super::expression::Expression;
 use super::types::TypeExpression;
-use super::Span;
-use std::fmt;
+use super::{NodeId, Span};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
 
 #[derive(Clone, Debug)]
 pub struct Statement<'s> {
   pub stmt: Stmt<'s>,
   pub span: Span,
+  pub id: NodeId,
 }
 
 macro_rules! statement {
@@ -16,7 +18,8 @@ macro_rules! statement {
 
       Statement {
         stmt: Stmt::$type $struct,
-        span: Span { start: start.start, end: end.end }
+        span: Span { start: start.start, end: end.end },
+        id: 0,
       }
     }};
 
@@ -31,6 +34,9 @@ pub enum Stmt<'source> {
   Block {
     body: Vec<Statement<'source>>,
   },
+  Break {
+    expression: Option<Expression<'source>>,
+  },
   Declaration {
     identifier: DeclarationIdentifier<'source>,
     type_: Option<TypeExpression<'source>>,
@@ -46,7 +52,10 @@ pub enum Stmt<'source> {
   },
   Import {
     module: &'source str,
+    alias: Option<&'source str>,
     items: Vec<AliasItem<'source>>,
+    // With `import?`, items are null if the module or item isn't available
+    optional: bool,
   },
   Return {
     expression: Option<Expression<'source>>,
@@ -60,11 +69,13 @@ pub enum Stmt<'source> {
   },
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct AliasItem<'s> {
   pub name: &'s str,
   pub span: Span,
   pub alias: Option<&'s str>,
+  // Only in declarations, used when the dict doesn't have the key
+  pub default: Option<Expression<'s>>,
 }
 impl<'s> AliasItem<'s> {
   pub fn get_name(&self) -> &'s str {
@@ -76,7 +87,19 @@ impl<'s> AliasItem<'s> {
 pub enum DeclarationIdentifier<'source> {
   Variable(&'source str),
   Ordered(Vec<&'source str>),
-  Named(Vec<AliasItem<'source>>),
+  // The optional name after `..` holds the rest of the dict
+  Named(Vec<AliasItem<'source>>, Option<&'source str>),
+}
+impl<'source> DeclarationIdentifier<'source> {
+  pub fn get_names(&self) -> Vec<&'source str> {
+    match self {
+      DeclarationIdentifier::Variable(name) => vec![*name],
+      DeclarationIdentifier::Ordered(names) => names.clone(),
+      DeclarationIdentifier::Named(items, rest) => {
+        items.iter().map(AliasItem::get_name).chain(*rest).collect()
+      }
+    }
+  }
 }
 impl fmt::Display for DeclarationIdentifier<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -85,13 +108,16 @@ impl fmt::Display for DeclarationIdentifier<'_> {
       DeclarationIdentifier::Ordered(identifiers) => {
         write!(f, "{}", identifiers.join(", "))
       }
-      DeclarationIdentifier::Named(identifiers) => {
+      DeclarationIdentifier::Named(identifiers, rest) => {
         for identifier in identifiers {
           write!(f, "{}", identifier.name)?;
           if let Some(alias) = identifier.alias {
             write!(f, " as {alias}")?;
           }
         }
+        if let Some(rest) = rest {
+          write!(f, "..{rest}")?;
+        }
         write!(f, ", ")
       }
     }
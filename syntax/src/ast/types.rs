@@ -1,4 +1,5 @@
 use super::Span;
+use alloc::{boxed::Box, vec::Vec};
 
 #[derive(Clone, Debug)]
 pub struct TypeExpression<'s> {
@@ -9,6 +10,7 @@ pub struct TypeExpression<'s> {
 #[derive(Debug, Clone)]
 pub enum Type<'s> {
   Named(&'s str),
+  StringLiteral(&'s str),
   Parameter(&'s str, Vec<TypeExpression<'s>>),
   Union(Box<TypeExpression<'s>>, Box<TypeExpression<'s>>),
   Function(Box<TypeExpression<'s>>, Vec<TypeExpression<'s>>),
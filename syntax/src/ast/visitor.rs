@@ -0,0 +1,331 @@
+use super::{expression, statement};
+
+pub trait Visitor {
+  fn visit(&mut self, statements: &[statement::Statement]) {
+    statements.iter().for_each(|s| self.visit_statement(s));
+    self.exit_ast();
+  }
+
+  fn visit_statement(&mut self, statement: &statement::Statement) {
+    use statement::Stmt;
+    self.enter_statement(statement);
+
+    match &statement.stmt {
+      Stmt::Block { body, .. } => body.iter().for_each(|s| self.visit_statement(s)),
+      Stmt::Declaration {
+        identifier: statement::DeclarationIdentifier::Named(items, _),
+        expression,
+        ..
+      } => {
+        if let Some(expression) = expression {
+          self.visit_expression(expression);
+        }
+        let defaults = items.iter().filter_map(|item| item.default.as_ref());
+        defaults.for_each(|default| self.visit_expression(default));
+      }
+      Stmt::Declaration { expression, .. }
+      | Stmt::Return { expression, .. }
+      | Stmt::Break { expression } => {
+        if let Some(expression) = expression {
+          self.visit_expression(expression);
+        }
+      }
+      Stmt::Expression { expression, .. } => self.visit_expression(expression),
+      Stmt::If {
+        condition,
+        then,
+        otherwise,
+        ..
+      } => {
+        self.visit_expression(condition);
+        self.visit_statement(then);
+        if let Some(otherwise) = otherwise {
+          self.visit_statement(otherwise.as_ref());
+        }
+      }
+      Stmt::While {
+        condition, body, ..
+      } => {
+        self.visit_expression(condition);
+        self.visit_statement(body);
+      }
+      Stmt::Import { .. } | Stmt::Comment { .. } => {}
+    }
+
+    self.exit_statement(statement);
+  }
+
+  fn visit_expression(&mut self, expression: &expression::Expression) {
+    use expression::Expr;
+    self.enter_expression(expression);
+
+    match &expression.expr {
+      Expr::Ascription { expression, .. }
+      | Expr::Assignment { expression, .. }
+      | Expr::Cast { expression, .. }
+      | Expr::Comment { expression, .. }
+      | Expr::Group { expression, .. }
+      | Expr::NonNull { expression }
+      | Expr::Unary { expression, .. } => self.visit_expression(expression),
+      Expr::Binary { left, right, .. } => {
+        self.visit_expression(left);
+        self.visit_expression(right);
+      }
+      Expr::Call {
+        expression,
+        arguments,
+        ..
+      } => {
+        self.visit_expression(expression);
+        arguments.iter().for_each(|arg| self.visit_expression(arg));
+      }
+      Expr::FormatString {
+        strings: _,
+        expressions,
+      } => expressions.iter().for_each(|e| self.visit_expression(e)),
+      Expr::Function {
+        parameters, body, ..
+      } => {
+        parameters
+          .iter()
+          .filter_map(|parameter| parameter.default.as_ref())
+          .for_each(|default| self.visit_expression(default));
+        self.visit_statement(body);
+      }
+      Expr::List { items } => items.iter().for_each(|item| self.visit_expression(item)),
+      Expr::Do { body } | Expr::Loop { body } => self.visit_statement(body),
+      Expr::Index { expression, index } => {
+        self.visit_expression(expression);
+        self.visit_expression(index);
+      }
+      Expr::IndexAssignment {
+        expression,
+        index,
+        value,
+        ..
+      } => {
+        self.visit_expression(expression);
+        self.visit_expression(index);
+        self.visit_expression(value);
+      }
+      Expr::Dictionary { items } => items.iter().for_each(|(key, value)| {
+        self.visit_expression(key);
+        self.visit_expression(value);
+      }),
+      Expr::Literal { .. } | Expr::Variable { .. } | Expr::ModuleAccess { .. } => {}
+    }
+
+    self.exit_expression(expression);
+  }
+
+  fn enter_expression(&mut self, _expression: &expression::Expression) {}
+  fn enter_statement(&mut self, _statement: &statement::Statement) {}
+
+  fn exit_expression(&mut self, _expression: &expression::Expression) {}
+  fn exit_statement(&mut self, _statement: &statement::Statement) {}
+
+  fn exit_ast(&mut self) {}
+}
+
+pub trait Transformer<'s> {
+  fn transform(&mut self, statements: &mut [statement::Statement<'s>]) {
+    statements
+      .iter_mut()
+      .for_each(|s| self.transform_statement(s));
+  }
+
+  fn transform_statement(&mut self, statement: &mut statement::Statement<'s>) {
+    use statement::Stmt;
+    self.enter_statement(statement);
+
+    match &mut statement.stmt {
+      Stmt::Block { body, .. } => body.iter_mut().for_each(|s| self.transform_statement(s)),
+      Stmt::Declaration {
+        identifier: statement::DeclarationIdentifier::Named(items, _),
+        expression,
+        ..
+      } => {
+        if let Some(expression) = expression {
+          self.transform_expression(expression);
+        }
+        let defaults = items.iter_mut().filter_map(|item| item.default.as_mut());
+        defaults.for_each(|default| self.transform_expression(default));
+      }
+      Stmt::Declaration { expression, .. }
+      | Stmt::Return { expression, .. }
+      | Stmt::Break { expression } => {
+        if let Some(expression) = expression {
+          self.transform_expression(expression);
+        }
+      }
+      Stmt::Expression { expression, .. } => self.transform_expression(expression),
+      Stmt::If {
+        condition,
+        then,
+        otherwise,
+        ..
+      } => {
+        self.transform_expression(condition);
+        self.transform_statement(then);
+        if let Some(otherwise) = otherwise {
+          self.transform_statement(otherwise.as_mut());
+        }
+      }
+      Stmt::While {
+        condition, body, ..
+      } => {
+        self.transform_expression(condition);
+        self.transform_statement(body);
+      }
+      Stmt::Import { .. } | Stmt::Comment { .. } => {}
+    }
+
+    self.exit_statement(statement);
+  }
+
+  fn transform_expression(&mut self, expression: &mut expression::Expression<'s>) {
+    use expression::Expr;
+    self.enter_expression(expression);
+
+    match &mut expression.expr {
+      Expr::Ascription { expression, .. }
+      | Expr::Assignment { expression, .. }
+      | Expr::Cast { expression, .. }
+      | Expr::Comment { expression, .. }
+      | Expr::Group { expression, .. }
+      | Expr::NonNull { expression }
+      | Expr::Unary { expression, .. } => self.transform_expression(expression),
+      Expr::Binary { left, right, .. } => {
+        self.transform_expression(left);
+        self.transform_expression(right);
+      }
+      Expr::Call {
+        expression,
+        arguments,
+        ..
+      } => {
+        self.transform_expression(expression);
+        arguments
+          .iter_mut()
+          .for_each(|arg| self.transform_expression(arg));
+      }
+      Expr::FormatString {
+        strings: _,
+        expressions,
+      } => expressions
+        .iter_mut()
+        .for_each(|e| self.transform_expression(e)),
+      Expr::Function {
+        parameters, body, ..
+      } => {
+        parameters
+          .iter_mut()
+          .filter_map(|parameter| parameter.default.as_mut())
+          .for_each(|default| self.transform_expression(default));
+        self.transform_statement(body);
+      }
+      Expr::List { items } => items
+        .iter_mut()
+        .for_each(|item| self.transform_expression(item)),
+      Expr::Do { body } | Expr::Loop { body } => self.transform_statement(body),
+      Expr::Index { expression, index } => {
+        self.transform_expression(expression);
+        self.transform_expression(index);
+      }
+      Expr::IndexAssignment {
+        expression,
+        index,
+        value,
+        ..
+      } => {
+        self.transform_expression(expression);
+        self.transform_expression(index);
+        self.transform_expression(value);
+      }
+      Expr::Dictionary { items } => items.iter_mut().for_each(|(key, value)| {
+        self.transform_expression(key);
+        self.transform_expression(value);
+      }),
+      Expr::Literal { .. } | Expr::Variable { .. } | Expr::ModuleAccess { .. } => {}
+    }
+
+    self.exit_expression(expression);
+  }
+
+  // Replacing `expr` or `stmt` in a hook keeps the span of the original node
+  fn enter_expression(&mut self, _expression: &mut expression::Expression<'s>) {}
+  fn enter_statement(&mut self, _statement: &mut statement::Statement<'s>) {}
+
+  fn exit_expression(&mut self, _expression: &mut expression::Expression<'s>) {}
+  fn exit_statement(&mut self, _statement: &mut statement::Statement<'s>) {}
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse;
+  use expression::{Expr, Expression};
+
+  #[derive(Default)]
+  struct CountVariables {
+    count: usize,
+  }
+  impl Visitor for CountVariables {
+    fn enter_expression(&mut self, expression: &Expression) {
+      if let Expr::Variable { .. } = expression.expr {
+        self.count += 1;
+      }
+    }
+  }
+
+  struct RenameVariable;
+  impl<'s> Transformer<'s> for RenameVariable {
+    fn exit_expression(&mut self, expression: &mut Expression<'s>) {
+      if let Expr::Variable { name: "a" } = expression.expr {
+        expression.expr = Expr::Variable { name: "renamed" };
+      }
+    }
+  }
+
+  #[test]
+  fn visits_every_expression() {
+    let ast = parse("let x = a + b\nif (c) [d, e(f)]\n").unwrap();
+
+    let mut visitor = CountVariables::default();
+    visitor.visit(&ast);
+    assert_eq!(visitor.count, 6);
+  }
+
+  #[test]
+  fn visits_destructuring_defaults() {
+    let mut ast = parse("let { x = a, y = b } = c\n").unwrap();
+
+    let mut visitor = CountVariables::default();
+    visitor.visit(&ast);
+    assert_eq!(visitor.count, 3);
+
+    RenameVariable.transform(&mut ast);
+    assert!(format!("{ast:?}").contains("renamed"));
+  }
+
+  #[test]
+  fn transform_preserves_spans() {
+    let mut ast = parse("let x = (a) + 5\nwhile (a) print(a)\n").unwrap();
+    let spans: Vec<_> = ast.iter().map(|statement| statement.span).collect();
+
+    RenameVariable.transform(&mut ast);
+
+    let mut visitor = CountVariables::default();
+    visitor.visit(&ast);
+    assert_eq!(visitor.count, 4);
+    assert!(format!("{ast:?}").contains("renamed"));
+    assert!(!format!("{ast:?}").contains("name: \"a\""));
+    assert_eq!(
+      ast
+        .iter()
+        .map(|statement| statement.span)
+        .collect::<Vec<_>>(),
+      spans
+    );
+  }
+}
@@ -1,4 +1,7 @@
 #![feature(let_chains)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod ast;
 mod parser;
@@ -7,6 +10,9 @@ mod tokens;
 pub use ast::Span;
 pub use parser::Diagnostic;
 pub use parser::{parse, parse_type, Parser};
-pub use tokens::LineNumber;
+pub use tokens::{
+  shebang, ColumnNumber, LineNumber, Token, TokenType, Tokeniser, COMMENT_START,
+  FORMAT_STRING_START, KEYWORDS, LITERALS, OPERATORS, PUNCTUATION, QUOTES,
+};
 
-pub type Ast<'a> = Vec<ast::statement::Statement<'a>>;
+pub type Ast<'a> = alloc::vec::Vec<ast::statement::Statement<'a>>;
@@ -3,26 +3,34 @@ use crate::{
     expression::{expression, operators, Expr, Expression, LiteralType, Parameter},
     statement::{statement, AliasItem, DeclarationIdentifier, Statement, Stmt},
     types::{types, Type, TypeExpression},
+    NodeId, Transformer,
   },
   tokens::{Token, TokenType, Tokeniser},
-  LineNumber, Span,
+  ColumnNumber, LineNumber, Span,
 };
-use std::{error, fmt, iter, str};
+use alloc::{
+  boxed::Box,
+  format,
+  string::{String, ToString},
+  vec,
+  vec::Vec,
+};
+use core::{fmt, iter, str};
 
 #[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Eq)]
 enum Precedence {
   None = 1,
   Assignment, // =
-  Pipeline,   // >>
+  Pipeline,   // >> <<
   Or,         // or
   And,        // and
   Nullish,    // ??
   Equality,   // == !=
-  Comparison, // < > <= >=
+  Comparison, // < > <= >= in
   Term,       // + -
   Factor,     // * /
-  Unary,      // ! -
-  Call,       // () []
+  Unary,      // ! - as
+  Call,       // () [] !
   Primary,
   Comment,
 }
@@ -50,15 +58,18 @@ impl Precedence {
       TokenType::And => Self::And,
       TokenType::Or => Self::Or,
       TokenType::QuestionQuestion => Self::Nullish,
-      TokenType::LeftParen | TokenType::LeftSquare => Self::Call,
+      TokenType::LeftParen | TokenType::LeftSquare | TokenType::Bang => Self::Call,
+      TokenType::As => Self::Unary,
       TokenType::Plus | TokenType::Minus => Self::Term,
       TokenType::Star | TokenType::Slash | TokenType::Percent => Self::Factor,
       TokenType::BangEqual | TokenType::EqualEqual => Self::Equality,
-      TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
-        Self::Comparison
-      }
+      TokenType::Greater
+      | TokenType::GreaterEqual
+      | TokenType::Less
+      | TokenType::LessEqual
+      | TokenType::In => Self::Comparison,
       TokenType::Comment => Self::Comment,
-      TokenType::RightRight => Self::Pipeline,
+      TokenType::RightRight | TokenType::LeftLeft => Self::Pipeline,
       _ => Self::None,
     }
   }
@@ -83,6 +94,8 @@ enum Error {
   ExpectedType,
   ExpectedModuleItem,
   ExpectedColon,
+  TooDeeplyNested,
+  RequiredParameterAfterDefault,
 }
 impl Error {
   fn get_title(&self) -> &'static str {
@@ -104,11 +117,13 @@ impl Error {
       Self::ExpectedImportKeyword => "Expected 'import' keyword",
       Self::ExpectedType => "Expected Type",
       Self::ExpectedModuleItem => "Expected Module Item to Import",
+      Self::TooDeeplyNested => "Too Deeply Nested",
+      Self::RequiredParameterAfterDefault => "Required Parameter After Default",
       Self::EmptyStatement => unreachable!("EmptyStatement caught to return nothing"),
     }
   }
 
-  fn get_message(&self, source: &[u8], token: Token) -> String {
+  fn get_message(&self, source: &[u8], token: Token, expected: &[TokenType]) -> String {
     match self {
       Self::ExpectedOpeningBracket
       | Self::ExpectedClosingBracket
@@ -123,45 +138,76 @@ impl Error {
       | Self::ExpectedImportKeyword
       | Self::ExpectedModuleItem
       | Self::ExpectedColon
-      | Self::ExpectedType => format!("but recieved '{}'", token.get_value(source)),
+      | Self::ExpectedType => {
+        if expected.len() > 1 {
+          format!(
+            "Expected one of {}, found '{}'",
+            describe_tokens(expected),
+            token.get_value(source)
+          )
+        } else {
+          format!("but recieved '{}'", token.get_value(source))
+        }
+      }
       Self::UnexpectedCharacter => format!("Unknown character '{}'", token.get_value(source)),
       Self::UnterminatedString => {
         format!("Missing closing quote {}", &token.get_value(source)[0..1])
       }
       Self::InvalidAssignmentTarget => "Can't assign to an expression, only a variable".to_string(),
+      Self::TooDeeplyNested => format!("There is a limit of {MAX_DEPTH} levels of nesting"),
+      Self::RequiredParameterAfterDefault => {
+        "Parameters with default values must come after all other parameters".to_string()
+      }
       Self::EmptyStatement => unreachable!("EmptyStatement caught to return nothing"),
     }
   }
 
-  fn get_diagnostic(&self, source: &str, token: Token) -> Diagnostic {
+  fn get_diagnostic(&self, source: &str, token: Token, expected: &[TokenType]) -> Diagnostic {
     let span: Span = token.into();
 
     Diagnostic {
       title: self.get_title().to_string(),
-      message: self.get_message(source.as_bytes(), token),
+      message: self.get_message(source.as_bytes(), token, expected),
       line: span.get_line_number(source),
+      column: span.get_column_number(source),
       span,
     }
   }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+// Lists the tokens as `'a', 'b', or 'c'`
+fn describe_tokens(tokens: &[TokenType]) -> String {
+  let descriptions: Vec<_> = tokens.iter().map(|token| token.describe()).collect();
+
+  match descriptions.as_slice() {
+    [] => String::new(),
+    [only] => (*only).to_string(),
+    [first, second] => format!("{first} or {second}"),
+    [rest @ .., last] => format!("{}, or {last}", rest.join(", ")),
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Diagnostic {
   pub title: String,
   pub message: String,
   pub span: Span,
   pub line: LineNumber,
+  pub column: ColumnNumber,
 }
 impl fmt::Display for Diagnostic {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     write!(
       f,
-      "Error: {}\n\t{}\nat line {}",
-      self.title, self.message, self.line
+      "Error: {}\n\t{}\nat line {}, column {}",
+      self.title, self.message, self.line, self.column
     )
   }
 }
-impl error::Error for Diagnostic {}
+#[cfg(feature = "std")]
+impl std::error::Error for Diagnostic {}
+
+const MAX_DEPTH: u16 = 256;
 
 type ExpressionResult<'source> = Result<Expression<'source>, Error>;
 type StatementResult<'source> = Result<Statement<'source>, Error>;
@@ -173,6 +219,12 @@ pub struct Parser<'source> {
 
   current: Token,
   previous: Token,
+
+  depth: u16,
+  next_id: NodeId,
+
+  // Tokens which would have been accepted instead of the current token
+  expected: Vec<TokenType>,
 }
 
 impl<'source> Parser<'source> {
@@ -186,6 +238,11 @@ impl<'source> Parser<'source> {
 
       current,
       previous: Token::default(),
+
+      depth: 0,
+      next_id: 0,
+
+      expected: Vec::new(),
     }
   }
 
@@ -196,6 +253,10 @@ impl<'source> Parser<'source> {
       .expect("String to be valid number representation")
   }
 
+  pub fn string(string: &str) -> String {
+    string.replace("\r\n", "\n")
+  }
+
   fn at_end(&mut self) -> bool {
     self.current.ttype == TokenType::EndOfFile
   }
@@ -205,6 +266,7 @@ impl<'source> Parser<'source> {
 
     self.previous = self.current;
     self.current = token;
+    self.expected.clear();
 
     if token.ttype == TokenType::Whitespace {
       self.next()
@@ -222,10 +284,17 @@ impl<'source> Parser<'source> {
     self.previous
   }
 
+  fn add_expected(&mut self, token_type: TokenType) {
+    if !self.expected.contains(&token_type) {
+      self.expected.push(token_type);
+    }
+  }
+
   fn expect(&mut self, token_type: TokenType, message: Error) -> Result<Token, Error> {
     if self.current.ttype == token_type {
       Ok(self.current)
     } else {
+      self.add_expected(token_type);
       Err(message)
     }
   }
@@ -250,6 +319,16 @@ impl<'source> Parser<'source> {
     }
   }
 
+  fn nest(&mut self) -> Result<(), Error> {
+    self.depth += 1;
+
+    if self.depth > MAX_DEPTH {
+      Err(Error::TooDeeplyNested)
+    } else {
+      Ok(())
+    }
+  }
+
   fn ignore_newline(&mut self) {
     while self.matches(TokenType::EndOfLine) {}
   }
@@ -262,18 +341,34 @@ impl<'source> Parser<'source> {
     matches
   }
 
+  // Like `matches`, but the token is suggested if the current token causes an error
+  fn accepts(&mut self, token_type: TokenType) -> bool {
+    let matches = self.matches(token_type);
+    if !matches {
+      self.add_expected(token_type);
+    }
+    matches
+  }
+
   fn parse_expression(&mut self, precedence: Precedence) -> ExpressionResult<'source> {
     self.ignore_newline();
 
+    let depth = self.depth;
+    self.nest()?;
+
     let can_assign = precedence <= Precedence::Assignment;
     let prefix = self.prefix_rule(self.current.ttype, can_assign)?;
 
     let mut previous = prefix;
     while precedence <= Precedence::from(self.current.ttype) {
+      self.nest()?;
+
       let can_assign = precedence <= Precedence::Assignment;
       previous = self.infix_rule(self.current.ttype, previous, can_assign)?;
     }
 
+    self.depth = depth;
+
     if can_assign && self.matches(TokenType::Equal) {
       Err(Error::InvalidAssignmentTarget)
     } else {
@@ -294,7 +389,10 @@ impl<'source> Parser<'source> {
       TokenType::FormatStringStart => self.format_string(),
       TokenType::LeftSquare => self.list(),
       TokenType::LeftBrace => self.dictionary(),
+      TokenType::Loop => self.loop_expression(),
+      TokenType::Do => self.do_expression(),
       TokenType::Unknown => Err(Error::UnexpectedCharacter),
+      TokenType::UnterminatedString => Err(Error::UnterminatedString),
       _ => Err(Error::ExpectedExpression),
     }
   }
@@ -308,6 +406,8 @@ impl<'source> Parser<'source> {
     match token_type {
       TokenType::LeftParen => Ok(self.call(previous)?),
       TokenType::LeftSquare => Ok(self.index(previous, can_assign)?),
+      TokenType::Bang => Ok(self.non_null(previous)),
+      TokenType::As => Ok(self.cast(previous)?),
       TokenType::Comment => Ok(self.comment(previous)),
       TokenType::Plus
       | TokenType::Minus
@@ -320,10 +420,12 @@ impl<'source> Parser<'source> {
       | TokenType::GreaterEqual
       | TokenType::Less
       | TokenType::LessEqual
+      | TokenType::In
       | TokenType::And
       | TokenType::Or
       | TokenType::QuestionQuestion
-      | TokenType::RightRight => Ok(self.binary(previous)?),
+      | TokenType::RightRight
+      | TokenType::LeftLeft => Ok(self.binary(previous)?),
       _ => unreachable!(),
     }
   }
@@ -354,6 +456,9 @@ impl<'source> Parser<'source> {
       return self.stmt();
     }
 
+    let nesting_depth = self.depth;
+    self.nest()?;
+
     let mut statements = Vec::new();
 
     while last_token.ttype == TokenType::Whitespace
@@ -365,6 +470,10 @@ impl<'source> Parser<'source> {
       } else {
         statements.push(self.stmt()?);
       }
+      // Comments after statements without a value, e.g. `return // comment`
+      if self.current.ttype == TokenType::Comment {
+        statements.push(self.comment_statement()?);
+      }
       self.ignore_newline();
       last_token = self.previous;
     }
@@ -373,6 +482,8 @@ impl<'source> Parser<'source> {
       Err(Error::EmptyStatement)?;
     }
 
+    self.depth = nesting_depth;
+
     Ok(statement!(
       Block { body: statements },
       (statements[0].span, statements.last().unwrap().span)
@@ -384,6 +495,7 @@ impl<'source> Parser<'source> {
       TokenType::Let => self.var_declaration(),
       TokenType::If => self.if_statement(),
       TokenType::Return => self.return_statement(),
+      TokenType::Break => self.break_statement(),
       TokenType::While => self.while_statement(),
       TokenType::EndOfFile => Err(Error::EmptyStatement),
       TokenType::Comment => self.comment_statement(),
@@ -408,7 +520,7 @@ impl<'source> Parser<'source> {
         let mut identifiers = Vec::new();
         while self.current.ttype == TokenType::Identifier {
           identifiers.push(self.current_advance().get_value(self.source));
-          self.matches(TokenType::Comma);
+          self.accepts(TokenType::Comma);
           self.ignore_newline();
         }
         self.consume(TokenType::RightSquare, Error::ExpectedClosingSquare)?;
@@ -417,10 +529,10 @@ impl<'source> Parser<'source> {
       }
       TokenType::LeftBrace => {
         self.next();
-        let identifiers = self.alias_items()?;
+        let (identifiers, rest) = self.alias_items(true)?;
         let end_token = self.current_advance();
 
-        (DeclarationIdentifier::Named(identifiers), end_token)
+        (DeclarationIdentifier::Named(identifiers, rest), end_token)
       }
       _ => Err(Error::ExpectedIdentifier)?,
     };
@@ -454,9 +566,18 @@ impl<'source> Parser<'source> {
     ))
   }
 
+  // Statements without a value can be followed by a new line, the end of file, or a comment
+  fn at_statement_end(&mut self) -> bool {
+    self.matches(TokenType::EndOfLine)
+      || matches!(
+        self.current.ttype,
+        TokenType::EndOfFile | TokenType::Comment
+      )
+  }
+
   fn return_statement(&mut self) -> StatementResult<'source> {
     let token = self.current_advance();
-    if self.matches(TokenType::EndOfLine) {
+    if self.at_statement_end() {
       Ok(statement!(Return { expression: None }, token))
     } else {
       let expression = self.expression()?;
@@ -471,6 +592,23 @@ impl<'source> Parser<'source> {
     }
   }
 
+  fn break_statement(&mut self) -> StatementResult<'source> {
+    let token = self.current_advance();
+    if self.at_statement_end() {
+      Ok(statement!(Break { expression: None }, token))
+    } else {
+      let expression = self.expression()?;
+      self.expect_newline()?;
+
+      Ok(statement!(
+        Break {
+          expression: Some(expression)
+        },
+        (token, expression.span)
+      ))
+    }
+  }
+
   fn if_statement(&mut self) -> StatementResult<'source> {
     let if_token = self.current_advance();
     self.consume(TokenType::LeftParen, Error::ExpectedOpeningBracket)?;
@@ -531,13 +669,21 @@ impl<'source> Parser<'source> {
 
   fn expression_statement(&mut self) -> StatementResult<'source> {
     let expression = self.expression()?;
-    self.expect_newline()?;
+    // A block, such as the body of a trailing function, has already used the new line
+    if self.previous.ttype != TokenType::EndOfLine {
+      self.expect_newline()?;
+    }
 
     Ok(statement!(Expression { expression }, expression.span))
   }
 
-  fn alias_items(&mut self) -> Result<Vec<AliasItem<'source>>, Error> {
+  // Declarations can also give defaults, and a name for the rest of the dict
+  fn alias_items(
+    &mut self,
+    destructuring: bool,
+  ) -> Result<(Vec<AliasItem<'source>>, Option<&'source str>), Error> {
     let mut items = Vec::new();
+    let mut rest = None;
 
     loop {
       self.ignore_newline();
@@ -545,33 +691,52 @@ impl<'source> Parser<'source> {
         break;
       }
 
+      if destructuring && self.matches(TokenType::DotDot) {
+        let token = self.consume(TokenType::Identifier, Error::ExpectedIdentifier)?;
+        rest = Some(token.get_value(self.source));
+
+        self.accepts(TokenType::Comma);
+        self.ignore_newline();
+        self.expect(TokenType::RightBrace, Error::ExpectedClosingBrace)?;
+        break;
+      }
+
       let item = self.consume(TokenType::Identifier, Error::ExpectedIdentifier)?;
-      let alias = if self.matches(TokenType::As) {
+      let alias = if self.accepts(TokenType::As) {
         let token = self.consume(TokenType::Identifier, Error::ExpectedIdentifier)?;
         Some(token.get_value(self.source))
       } else {
         None
       };
+      let default = if destructuring && self.matches(TokenType::Equal) {
+        Some(self.expression()?)
+      } else {
+        None
+      };
 
       items.push(AliasItem {
         name: item.get_value(self.source),
         span: Span::from(item),
         alias,
+        default,
       });
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.ignore_newline();
         self.expect(TokenType::RightBrace, Error::ExpectedClosingBrace)?;
         break;
       }
     }
 
-    Ok(items)
+    Ok((items, rest))
   }
 
   fn import_statement(&mut self) -> StatementResult<'source> {
     let token = self.current_advance();
-    let module = if self.current.ttype == TokenType::String {
+    let module = if matches!(
+      self.current.ttype,
+      TokenType::String | TokenType::UnterminatedString
+    ) {
       let string = self.current_advance().get_value(self.source);
       Self::check_string(string)?
     } else {
@@ -580,15 +745,50 @@ impl<'source> Parser<'source> {
         .get_value(self.source)
     };
 
+    let alias_token = if self.matches(TokenType::As) {
+      Some(self.consume(TokenType::Identifier, Error::ExpectedIdentifier)?)
+    } else {
+      None
+    };
+
+    // An aliased module doesn't need to import any items
+    if let Some(alias_token) = alias_token
+      && let TokenType::EndOfLine | TokenType::EndOfFile = self.current.ttype
+    {
+      self.expect_newline()?;
+
+      let alias = Some(alias_token.get_value(self.source));
+      let items = Vec::new();
+      return Ok(statement!(
+        Import {
+          module,
+          alias,
+          items,
+          optional: false
+        },
+        (token, alias_token)
+      ));
+    }
+
     self.consume(TokenType::Import, Error::ExpectedImportKeyword)?;
+    let optional = self.matches(TokenType::Question);
     self.consume(TokenType::LeftBrace, Error::ExpectedOpeningBrace)?;
 
-    let items = self.alias_items()?;
+    let alias = alias_token.map(|token| token.get_value(self.source));
+    let (items, _) = self.alias_items(false)?;
     let end_token = self.current;
 
     self.expect_newline()?;
 
-    Ok(statement!(Import { module, items }, (token, end_token)))
+    Ok(statement!(
+      Import {
+        module,
+        alias,
+        items,
+        optional
+      },
+      (token, end_token)
+    ))
   }
 }
 
@@ -604,7 +804,8 @@ impl<'source> Parser<'source> {
 
     match self.current.ttype {
       TokenType::Identifier => match self.peek() {
-        TokenType::Colon | TokenType::Comma => self.function(opening_bracket),
+        TokenType::Colon => self.annotated_grouping_or_function(opening_bracket),
+        TokenType::Comma => self.function(opening_bracket, Vec::new()),
         TokenType::RightParen => {
           let identifier = self.current_advance();
           let closing_bracket = self.current_advance();
@@ -615,6 +816,7 @@ impl<'source> Parser<'source> {
                 name: identifier.get_value(self.source),
                 span: identifier.into(),
                 type_: None,
+                default: None,
               };
               self.function_body(opening_bracket, vec![parameter])
             }
@@ -636,13 +838,78 @@ impl<'source> Parser<'source> {
         }
         _ => self.grouping(opening_bracket),
       },
-      TokenType::RightParen => self.function(opening_bracket),
+      TokenType::RightParen => self.function(opening_bracket, Vec::new()),
       _ => self.grouping(opening_bracket),
     }
   }
 
-  fn function(&mut self, opening_bracket: Token) -> ExpressionResult<'source> {
-    let mut parameters = Vec::new();
+  // `(a: number)` is an ascription, unless it is followed by an arrow or more parameters
+  fn annotated_grouping_or_function(
+    &mut self,
+    opening_bracket: Token,
+  ) -> ExpressionResult<'source> {
+    let identifier = self.current_advance();
+    self.next();
+    let type_ = self.types()?;
+    self.ignore_newline();
+
+    let parameter = Parameter {
+      name: identifier.get_value(self.source),
+      span: identifier.into(),
+      type_: Some(type_.clone()),
+      default: None,
+    };
+    if self.current.ttype != TokenType::RightParen {
+      return self.remaining_parameters(opening_bracket, parameter);
+    }
+
+    let closing_bracket = self.current_advance();
+    if matches!(
+      self.current.ttype,
+      TokenType::RightArrow | TokenType::FatRightArrow
+    ) {
+      return self.function_body(opening_bracket, vec![parameter]);
+    }
+
+    let variable = expression!(
+      Variable {
+        name: identifier.get_value(self.source)
+      },
+      identifier
+    );
+    Ok(expression!(
+      Ascription {
+        expression: Box::new(variable),
+        type_,
+      },
+      (opening_bracket, closing_bracket)
+    ))
+  }
+
+  // The first parameter has been parsed, with its default value still to come
+  fn remaining_parameters(
+    &mut self,
+    opening_bracket: Token,
+    mut parameter: Parameter<'source>,
+  ) -> ExpressionResult<'source> {
+    if self.accepts(TokenType::Equal) {
+      parameter.default = Some(self.expression()?);
+    }
+
+    if !self.accepts(TokenType::Comma) {
+      self.ignore_newline();
+      self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
+      return self.function_body(opening_bracket, vec![parameter]);
+    }
+
+    self.function(opening_bracket, vec![parameter])
+  }
+
+  fn function(
+    &mut self,
+    opening_bracket: Token,
+    mut parameters: Vec<Parameter<'source>>,
+  ) -> ExpressionResult<'source> {
     loop {
       self.ignore_newline();
       if self.matches(TokenType::RightParen) {
@@ -650,19 +917,29 @@ impl<'source> Parser<'source> {
       }
 
       let parameter = self.consume(TokenType::Identifier, Error::ExpectedIdentifier)?;
-      let type_ = if self.matches(TokenType::Colon) {
+      let type_ = if self.accepts(TokenType::Colon) {
         Some(self.types()?)
       } else {
         None
       };
+      let default = if self.accepts(TokenType::Equal) {
+        Some(self.expression()?)
+      } else {
+        None
+      };
+
+      if default.is_none() && parameters.iter().any(|p: &Parameter| p.default.is_some()) {
+        return Err(Error::RequiredParameterAfterDefault);
+      }
 
       parameters.push(Parameter {
         name: parameter.get_value(self.source),
         span: Span::from(parameter),
         type_,
+        default,
       });
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.ignore_newline();
         self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
         break;
@@ -711,11 +988,78 @@ impl<'source> Parser<'source> {
     ))
   }
 
+  fn loop_expression(&mut self) -> ExpressionResult<'source> {
+    let token = self.current_advance();
+    self.ignore_newline();
+    let body = self.statement()?;
+
+    Ok(expression!(
+      Loop {
+        body: Box::new(body)
+      },
+      (token, body.span)
+    ))
+  }
+
+  fn do_expression(&mut self) -> ExpressionResult<'source> {
+    let token = self.current_advance();
+    self.ignore_newline();
+    let body = self.statement()?;
+
+    Ok(expression!(
+      Do {
+        body: Box::new(body)
+      },
+      (token, body.span)
+    ))
+  }
+
   fn grouping(&mut self, opening_bracket: Token) -> ExpressionResult<'source> {
     let expression = self.expression()?;
     self.ignore_newline();
+
+    // `(x = 1) => x` is a function with a default parameter, not an assignment
+    if let Expr::Assignment {
+      identifier,
+      expression: default,
+      assignment_operator: None,
+    } = &expression.expr
+      && self.matches(TokenType::Comma)
+    {
+      let parameter = Self::default_parameter(identifier, default, expression.span);
+      return self.function(opening_bracket, vec![parameter]);
+    }
+
+    if self.matches(TokenType::Colon) {
+      let type_ = self.types()?;
+      self.ignore_newline();
+      let end_token = self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
+
+      return Ok(expression!(
+        Ascription {
+          expression: Box::new(expression),
+          type_,
+        },
+        (opening_bracket, end_token)
+      ));
+    }
+
     let end_token = self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
 
+    if let Expr::Assignment {
+      identifier,
+      expression: default,
+      assignment_operator: None,
+    } = &expression.expr
+      && matches!(
+        self.current.ttype,
+        TokenType::RightArrow | TokenType::FatRightArrow
+      )
+    {
+      let parameter = Self::default_parameter(identifier, default, expression.span);
+      return self.function_body(opening_bracket, vec![parameter]);
+    }
+
     Ok(expression!(
       Group {
         expression: Box::new(expression),
@@ -724,6 +1068,26 @@ impl<'source> Parser<'source> {
     ))
   }
 
+  fn default_parameter(
+    identifier: &'source str,
+    default: &Expression<'source>,
+    span: Span,
+  ) -> Parameter<'source> {
+    #[allow(clippy::cast_possible_truncation)]
+    // identifiers are less than 2^32 characters
+    let end = span.start + identifier.len() as u32;
+
+    Parameter {
+      name: identifier,
+      span: Span {
+        start: span.start,
+        end,
+      },
+      type_: None,
+      default: Some(default.clone()),
+    }
+  }
+
   fn unary(&mut self) -> ExpressionResult<'source> {
     let token = self.current_advance();
     let expression = self.parse_expression(Precedence::Unary)?;
@@ -769,7 +1133,7 @@ impl<'source> Parser<'source> {
     let start = token.get_value(self.source);
     let quote = start.as_bytes()[0] as char;
 
-    let mut strings = vec![start[1..start.len() - 2].into()];
+    let mut strings = vec![Self::string(&start[1..start.len() - 2]).into()];
     let mut expressions = Vec::new();
 
     let end_token = loop {
@@ -778,14 +1142,14 @@ impl<'source> Parser<'source> {
       match self.current.ttype {
         TokenType::FormatStringPart => {
           let part = self.current_advance().get_value(self.source);
-          strings.push(part[1..part.len() - 2].into());
+          strings.push(Self::string(&part[1..part.len() - 2]).into());
         }
         TokenType::FormatStringEnd => {
           let part = self.current.get_value(self.source);
           if !part.ends_with(quote) {
             Err(Error::UnterminatedString)?;
           }
-          strings.push(part[1..part.len() - 1].into());
+          strings.push(Self::string(&part[1..part.len() - 1]).into());
           break self.current_advance();
         }
         _ => Err(Error::UnterminatedString)?,
@@ -813,7 +1177,7 @@ impl<'source> Parser<'source> {
 
       items.push(self.expression()?);
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.ignore_newline();
         break self.consume(TokenType::RightSquare, Error::ExpectedClosingSquare)?;
       }
@@ -855,9 +1219,9 @@ impl<'source> Parser<'source> {
         items.push((key, value));
       }
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.ignore_newline();
-        break self.consume(TokenType::RightBrace, Error::ExpectedClosingSquare)?;
+        break self.consume(TokenType::RightBrace, Error::ExpectedClosingBrace)?;
       }
     };
 
@@ -872,23 +1236,14 @@ impl<'source> Parser<'source> {
     if (true, true) == (can_assign, is_assignment_operator) {
       let operator = self.current_advance();
       let right = self.expression()?;
-      let end_span = right.span;
-
-      let binary = expression!(
-        Binary {
-          operator: operators::Assignment::token_to_binary(operator.ttype),
-          left: Box::new(expression!(Variable { name }, identifier)),
-          right: Box::new(right),
-        },
-        (identifier, end_span)
-      );
 
       Ok(expression!(
         Assignment {
           identifier: name,
-          expression: Box::new(binary)
+          assignment_operator: operators::Assignment::from_token(operator.ttype),
+          expression: Box::new(right)
         },
-        (identifier, end_span)
+        (identifier, right.span)
       ))
     } else if self.matches(TokenType::Equal) && can_assign {
       let expression = self.expression()?;
@@ -897,6 +1252,7 @@ impl<'source> Parser<'source> {
         Assignment {
           identifier: name,
           expression: Box::new(expression),
+          assignment_operator: None,
         },
         (identifier, expression.span)
       ))
@@ -980,12 +1336,34 @@ impl<'source> Parser<'source> {
 
       arguments.push(self.expression()?);
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.ignore_newline();
         break self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
       }
     };
 
+    // A function after the brackets is the last argument, and its parameter is called `it`
+    if let TokenType::RightArrow | TokenType::FatRightArrow = self.current.ttype {
+      let arrow = self.current;
+      let parameter = Parameter {
+        name: "it",
+        span: arrow.into(),
+        type_: None,
+        default: None,
+      };
+      let function = self.function_body(arrow, vec![parameter])?;
+      let end = function.span;
+      arguments.push(function);
+
+      return Ok(expression!(
+        Call {
+          expression: Box::new(previous),
+          arguments,
+        },
+        (previous.span, end)
+      ));
+    }
+
     Ok(expression!(
       Call {
         expression: Box::new(previous),
@@ -995,6 +1373,30 @@ impl<'source> Parser<'source> {
     ))
   }
 
+  fn non_null(&mut self, previous: Expression<'source>) -> Expression<'source> {
+    let token = self.current_advance();
+
+    expression!(
+      NonNull {
+        expression: Box::new(previous)
+      },
+      (previous.span, token)
+    )
+  }
+
+  fn cast(&mut self, previous: Expression<'source>) -> ExpressionResult<'source> {
+    self.next();
+    let type_ = self.types()?;
+
+    Ok(expression!(
+      Cast {
+        expression: Box::new(previous),
+        type_,
+      },
+      (previous.span, type_.span)
+    ))
+  }
+
   fn comment(&mut self, previous: Expression<'source>) -> Expression<'source> {
     let token = self.current_advance();
 
@@ -1047,6 +1449,12 @@ impl<'source> Parser<'source> {
           Ok(types!(Named(token.get_value(self.source)), token))
         }
       }
+      TokenType::String => {
+        self.next();
+
+        let value = token.get_value(self.source);
+        Ok(types!(StringLiteral(&value[1..value.len() - 1]), token))
+      }
       TokenType::LeftParen => self.type_group(),
       _ => Err(Error::ExpectedType),
     };
@@ -1153,7 +1561,7 @@ impl<'source> Parser<'source> {
   fn type_param(&mut self, name: Token) -> TypeResult<'source> {
     let mut params = vec![self.types()?];
 
-    while self.matches(TokenType::Comma) && self.current.ttype != TokenType::RightParen {
+    while self.accepts(TokenType::Comma) && self.current.ttype != TokenType::RightParen {
       params.push(self.types()?);
     }
     let end_token = self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
@@ -1177,7 +1585,7 @@ impl<'source> Parser<'source> {
 
       parameters.push(self.types()?);
 
-      if !self.matches(TokenType::Comma) {
+      if !self.accepts(TokenType::Comma) {
         self.consume(TokenType::RightParen, Error::ExpectedClosingBracket)?;
         break;
       }
@@ -1201,6 +1609,19 @@ impl<'source> Parser<'source> {
   }
 }
 
+struct NumberNodes(NodeId);
+impl<'s> Transformer<'s> for NumberNodes {
+  fn enter_statement(&mut self, statement: &mut Statement<'s>) {
+    self.0 += 1;
+    statement.id = self.0;
+  }
+
+  fn enter_expression(&mut self, expression: &mut Expression<'s>) {
+    self.0 += 1;
+    expression.id = self.0;
+  }
+}
+
 impl<'source> Iterator for Parser<'source> {
   type Item = Result<Statement<'source>, Diagnostic>;
 
@@ -1209,8 +1630,15 @@ impl<'source> Iterator for Parser<'source> {
       return None;
     }
 
+    self.depth = 0;
     match self.statement() {
-      Ok(stmt) => Some(Ok(stmt)),
+      Ok(mut stmt) => {
+        let mut numbering = NumberNodes(self.next_id);
+        numbering.transform_statement(&mut stmt);
+        self.next_id = numbering.0;
+
+        Some(Ok(stmt))
+      }
       Err(Error::EmptyStatement) => None,
       Err(err) => {
         let last_token = if self.current.ttype == TokenType::EndOfFile {
@@ -1218,10 +1646,18 @@ impl<'source> Iterator for Parser<'source> {
         } else {
           self.current
         };
-        Some(Err(err.get_diagnostic(
-          str::from_utf8(self.source).unwrap(),
-          last_token,
-        )))
+        let source = str::from_utf8(self.source).unwrap();
+        let diagnostic = err.get_diagnostic(source, last_token, &self.expected);
+
+        // Skip the rest of the line, so later errors can be reported
+        while !matches!(
+          self.current.ttype,
+          TokenType::EndOfLine | TokenType::EndOfFile
+        ) {
+          self.next();
+        }
+
+        Some(Err(diagnostic))
       }
     }
   }
@@ -1241,7 +1677,7 @@ pub fn parse_type(source: &str) -> Result<TypeExpression, Diagnostic> {
       parser.current
     };
 
-    err.get_diagnostic(str::from_utf8(source.as_bytes()).unwrap(), last_token)
+    err.get_diagnostic(source, last_token, &parser.expected)
   })
 }
 
@@ -1284,6 +1720,176 @@ mod tests {
     assert_eq!(result.unwrap_err().message, "Unknown character '&'");
   }
 
+  #[test]
+  fn should_list_expected_tokens() {
+    let message = |source| super::parse(source).unwrap_err().message;
+
+    assert_eq!(
+      message("print(1 if)"),
+      "Expected one of ',' or ')', found 'if'"
+    );
+    assert_eq!(message("[1 2]"), "Expected one of ',' or ']', found '2'");
+    assert_eq!(
+      message("from x import { a b }"),
+      "Expected one of 'as', ',', or '}', found 'b'"
+    );
+    assert_eq!(
+      message("(a, b c) => a"),
+      "Expected one of ':', '=', ',', or ')', found 'c'"
+    );
+
+    // Only a single token can follow, so the title says what is expected
+    let error = super::parse("(1 2)").unwrap_err();
+    assert_eq!(error.title, "Expected ')'");
+    assert_eq!(error.message, "but recieved '2'");
+  }
+
+  #[test]
+  fn should_report_errors_after_unterminated_string() {
+    let errors: Vec<_> = Parser::new("let a = 'hello\nlet b = 5\nlet c = &\n")
+      .filter_map(Result::err)
+      .collect();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].title, "Unterminated String");
+    assert_eq!(errors[0].line, 1);
+    assert_eq!(errors[1].message, "Unknown character '&'");
+    assert_eq!(errors[1].line, 3);
+  }
+
+  #[test]
+  fn should_parse_default_parameters() {
+    let ast = super::parse("(a, b = 10) => a\n(c = 5) => c\n(d = 5)\n").unwrap();
+
+    for (statement, names) in ast.iter().zip([vec!["a", "b"], vec!["c"]]) {
+      match unwrap_expression(statement) {
+        Expr::Function { parameters, .. } => {
+          assert_eq!(parameters.iter().map(|p| p.name).collect::<Vec<_>>(), names);
+          assert!(parameters.last().unwrap().default.is_some());
+        }
+        _ => panic!("Expected function"),
+      }
+    }
+    assert!(matches!(unwrap_expression(&ast[2]), Expr::Group { .. }));
+
+    let ast = super::parse("(a = 1, b = 2) => a\n").unwrap();
+    match unwrap_expression(&ast[0]) {
+      Expr::Function { parameters, .. } => assert_eq!(parameters.len(), 2),
+      _ => panic!("Expected function"),
+    }
+
+    let result = super::parse("(a = 1, b) => a");
+    assert_eq!(
+      result.unwrap_err().title,
+      "Required Parameter After Default"
+    );
+  }
+
+  #[test]
+  fn should_parse_trailing_functions() {
+    let ast = super::parse("map(items) => it * 2\neach() ->\n  print(it)\nf(a)\n").unwrap();
+
+    for statement in &ast[..2] {
+      match unwrap_expression(statement) {
+        Expr::Call { arguments, .. } => match &arguments.last().unwrap().expr {
+          Expr::Function { parameters, .. } => {
+            assert_eq!(parameters.len(), 1);
+            assert_eq!(parameters[0].name, "it");
+          }
+          _ => panic!("Expected function"),
+        },
+        _ => panic!("Expected call"),
+      }
+    }
+    match unwrap_expression(&ast[2]) {
+      Expr::Call { arguments, .. } => assert_eq!(arguments.len(), 1),
+      _ => panic!("Expected call"),
+    }
+  }
+
+  #[test]
+  fn should_parse_non_null_and_cast() {
+    let ast = super::parse("a!\n!b! as number\na != b\n").unwrap();
+
+    match unwrap_expression(&ast[0]) {
+      Expr::NonNull { expression } => assert_variable(&expression.expr, "a"),
+      _ => panic!("Expected non null assertion"),
+    }
+    match unwrap_expression(&ast[1]) {
+      Expr::Unary { expression, .. } => match &expression.expr {
+        Expr::Cast { expression, .. } => {
+          assert!(matches!(expression.expr, Expr::NonNull { .. }));
+        }
+        _ => panic!("Expected cast"),
+      },
+      _ => panic!("Expected unary"),
+    }
+    assert!(matches!(unwrap_expression(&ast[2]), Expr::Binary { .. }));
+  }
+
+  #[test]
+  fn should_parse_ascription() {
+    let ast = super::parse(
+      "([]: number[])
+(a: number)
+(a + 1: number)
+",
+    )
+    .unwrap();
+
+    for statement in &ast {
+      assert!(matches!(
+        unwrap_expression(statement),
+        Expr::Ascription { .. }
+      ));
+    }
+
+    let ast = super::parse(
+      "(a: number) => a
+(a: number, b) => a
+(a: number = 2) => a
+",
+    )
+    .unwrap();
+    for statement in &ast {
+      match unwrap_expression(statement) {
+        Expr::Function { parameters, .. } => assert!(parameters[0].type_.is_some()),
+        _ => panic!("Expected function"),
+      }
+    }
+  }
+
+  #[test]
+  fn should_error_when_too_deeply_nested() {
+    let chain = format!("1{}", " + 1".repeat(300));
+    let groups = format!("{}1{}", "(".repeat(300), ")".repeat(300));
+    let lists = format!("{}1{}", "[".repeat(300), "]".repeat(300));
+
+    for source in [chain, groups, lists] {
+      let result = super::parse(&source);
+
+      assert!(result.is_err());
+      assert_eq!(result.unwrap_err().title, "Too Deeply Nested");
+    }
+
+    let chain = format!("1{}", " + 1".repeat(200));
+    let groups = format!("{}1{}", "(".repeat(200), ")".repeat(200));
+    assert!(super::parse(&chain).is_ok());
+    assert!(super::parse(&groups).is_ok());
+  }
+
+  #[test]
+  fn should_report_line_and_column_of_errors() {
+    let error = super::parse("let a = 1\nlet b = (a + ]\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 14));
+
+    let error = super::parse("let a = 'é' + ]").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 15));
+
+    let error = super::parse("let a = 1\r\n  ?\r\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+  }
+
   #[test]
   fn should_parse_group() {
     let statements = super::parse("('hello world')\n").unwrap();
@@ -1416,6 +2022,34 @@ mod tests {
     }
   }
 
+  #[test]
+  fn should_parse_variable_declaration_dict_destructuring() {
+    let statements = super::parse("let { a = 1, b as bee, ..rest } = d\n").unwrap();
+
+    if let Stmt::Declaration { identifier, .. } = &statements[0].stmt {
+      match identifier {
+        DeclarationIdentifier::Named(items, rest) => {
+          assert_eq!(items.len(), 2);
+          assert_literal(
+            &items[0].default.as_ref().unwrap().expr,
+            "1",
+            LiteralType::Number,
+          );
+          assert_eq!(items[1].get_name(), "bee");
+          assert!(items[1].default.is_none());
+          assert_eq!(*rest, Some("rest"));
+        }
+        _ => panic!("Expected named destructuring"),
+      };
+    } else {
+      panic!("Expected declaration statement");
+    }
+
+    assert!(super::parse("let { ..rest, a } = d\n").is_err());
+    assert!(super::parse("from maths import { sqrt = 1 }\n").is_err());
+    assert!(super::parse("from maths import { ..rest }\n").is_err());
+  }
+
   #[test]
   fn should_parse_variable_declaration_without_initalizer() {
     let statements = super::parse("let b\n").unwrap();
@@ -1465,7 +2099,7 @@ mod tests {
   #[test]
   fn should_parse_imports() {
     let statements = super::parse("from x import {}\n").unwrap();
-    if let Stmt::Import { module, items } = &statements[0].stmt {
+    if let Stmt::Import { module, items, .. } = &statements[0].stmt {
       assert_eq!(items.len(), 0);
       assert_eq!(module, &"x");
     } else {
@@ -1473,14 +2107,46 @@ mod tests {
     }
 
     let statements = super::parse("from './abc/ef.bang' import { g, h, i }\n").unwrap();
-    if let Stmt::Import { module, items } = &statements[0].stmt {
+    if let Stmt::Import { module, items, .. } = &statements[0].stmt {
       assert_eq!(items.len(), 3);
       assert_eq!(module, &"./abc/ef.bang");
     } else {
       panic!("Not import statement")
     }
 
-    assert!(super::parse("from 'agd import {}\n").is_err())
+    let statements = super::parse("from './a/long/path.bang' as path\n").unwrap();
+    if let Stmt::Import {
+      module,
+      alias,
+      items,
+      ..
+    } = &statements[0].stmt
+    {
+      assert_eq!(items.len(), 0);
+      assert_eq!(module, &"./a/long/path.bang");
+      assert_eq!(alias, &Some("path"));
+    } else {
+      panic!("Not import statement")
+    }
+
+    let statements = super::parse("from maths as m import { sin }\n").unwrap();
+    if let Stmt::Import { alias, items, .. } = &statements[0].stmt {
+      assert_eq!(items.len(), 1);
+      assert_eq!(alias, &Some("m"));
+    } else {
+      panic!("Not import statement")
+    }
+
+    let statements = super::parse("from fs import? { readFile }\n").unwrap();
+    if let Stmt::Import { optional, .. } = &statements[0].stmt {
+      assert!(optional);
+    } else {
+      panic!("Not import statement")
+    }
+
+    assert!(super::parse("from 'agd import {}\n").is_err());
+    assert!(super::parse("from maths as\n").is_err());
+    assert!(super::parse("from maths as m sin\n").is_err());
   }
 
   #[test]
@@ -1498,6 +2164,56 @@ mod tests {
     }
   }
 
+  #[test]
+  fn should_parse_do() {
+    let statements = super::parse("let a = do\n  let b = 1\n  b\n").unwrap();
+
+    if let Stmt::Declaration {
+      expression: Some(expression),
+      ..
+    } = &statements[0].stmt
+      && let Expr::Do { body } = &expression.expr
+      && let Stmt::Block { body } = &body.stmt
+    {
+      assert!(matches!(body[0].stmt, Stmt::Declaration { .. }));
+      assert_variable(unwrap_expression(&body[1]), "b");
+    } else {
+      panic!("Expected do expression");
+    }
+  }
+
+  #[test]
+  fn should_parse_loop() {
+    let statements = super::parse("let a = loop\n  doStuff\n  break value\n").unwrap();
+
+    if let Stmt::Declaration {
+      expression: Some(expression),
+      ..
+    } = &statements[0].stmt
+      && let Expr::Loop { body } = &expression.expr
+      && let Stmt::Block { body } = &body.stmt
+    {
+      assert_variable(unwrap_expression(&body[0]), "doStuff");
+      if let Stmt::Break {
+        expression: Some(expression),
+      } = &body[1].stmt
+      {
+        assert_variable(&expression.expr, "value");
+      } else {
+        panic!("Expected break statement");
+      }
+    } else {
+      panic!("Expected loop expression");
+    }
+
+    let statements = super::parse("while (a) break\n").unwrap();
+    if let Stmt::While { body, .. } = &statements[0].stmt {
+      assert!(matches!(body.stmt, Stmt::Break { expression: None }));
+    } else {
+      panic!("Expected while statement");
+    }
+  }
+
   #[test]
   fn should_parse_if_else() {
     let statements = super::parse("if (true) doStuff\n").unwrap();
@@ -1534,6 +2250,26 @@ mod tests {
     }
   }
 
+  #[test]
+  fn should_parse_else_if_chain() {
+    let source = "if (a)\n  b\n  c\nelse if (d)\n  e\n  f\nelse if (g) h\nelse i\n";
+    let statements = super::parse(source).unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let mut branches = 0;
+    let mut statement = &statements[0];
+    while let Stmt::If { otherwise, .. } = &statement.stmt {
+      branches += 1;
+      match otherwise {
+        Some(otherwise) => statement = otherwise,
+        None => panic!("Expected else branch"),
+      }
+    }
+
+    assert_eq!(branches, 3);
+    assert_variable(unwrap_expression(statement), "i");
+  }
+
   #[test]
   fn should_parse_block() {
     let statements = super::parse("a\n\tdoStuff\n\totherStuff\n\tmoreStuff\n").unwrap();
@@ -1670,4 +2406,74 @@ from maths import { sin }";
     assert!(super::parse("{ false }").is_err());
     assert!(super::parse("{ 3 }").is_err());
   }
+
+  #[test]
+  fn should_parse_statements_at_end_of_file() {
+    let sources = [
+      "let a = 1",
+      "let a",
+      "let [a, b] = [1, 2]",
+      "return",
+      "return 5",
+      "let f = () ->\n  return",
+      "from maths import { PI }",
+      "if (true)\n  1\nelse\n  2",
+      "while (false) 1",
+      "loop\n  break",
+      "loop\n  break 4",
+      "print(1) // comment",
+      "// comment",
+      "let a = 1\r\n",
+      "",
+    ];
+
+    for source in sources {
+      assert!(super::parse(source).is_ok(), "Failed to parse {source:?}");
+    }
+  }
+
+  #[test]
+  fn should_parse_comments_after_statements_without_value() {
+    let ast = super::parse("let f = () ->\n  return // done\n  1\nlet b = 2").unwrap();
+    assert_eq!(ast.len(), 2);
+
+    let body = match &ast[0].stmt {
+      Stmt::Declaration {
+        expression:
+          Some(Expression {
+            expr: Expr::Function { body, .. },
+            ..
+          }),
+        ..
+      } => body,
+      _ => panic!("Expected function declaration"),
+    };
+    match &body.stmt {
+      Stmt::Block { body } => {
+        assert!(matches!(body[0].stmt, Stmt::Return { expression: None }));
+        assert!(matches!(body[1].stmt, Stmt::Comment { text: "// done" }));
+        assert_eq!(body.len(), 3);
+      }
+      _ => panic!("Expected block"),
+    }
+
+    assert!(super::parse("loop\n  break // done\n  1").is_ok());
+  }
+
+  #[test]
+  fn should_not_panic_on_truncated_programs() {
+    let programs = [
+      include_str!("../../examples/syntax.bang"),
+      include_str!("../../examples/bubbleSort.bang"),
+      include_str!("../../examples/stringBench.bang"),
+      include_str!("../../examples/recursiveFibonacci.bang"),
+    ];
+
+    // Every prefix is either parsed or reported as an error
+    for program in programs {
+      for (end, _) in program.char_indices() {
+        let _ = Parser::new(&program[..end]).count();
+      }
+    }
+  }
 }
@@ -1,7 +1,8 @@
+use core::str;
 use smallvec::SmallVec;
-use std::str;
 
 pub type LineNumber = u16;
+pub type ColumnNumber = u32;
 pub type CharacterPosition = u32;
 type TokenLength = usize;
 
@@ -38,6 +39,7 @@ pub enum TokenType {
   Or,
   QuestionQuestion,
   RightRight,
+  LeftLeft,
   Percent,
 
   // Comparators
@@ -58,6 +60,7 @@ pub enum TokenType {
   // Values
   Identifier,
   String,
+  UnterminatedString,
   Number,
   True,
   False,
@@ -70,11 +73,15 @@ pub enum TokenType {
 
   // Keywords
   As,
+  Break,
+  Do,
   Else,
   If,
   Import,
+  In,
   From,
   Let,
+  Loop,
   Return,
   While,
 
@@ -92,8 +99,143 @@ impl TokenType {
       Self::PlusEqual | Self::MinusEqual | Self::StarEqual | Self::SlashEqual
     )
   }
+
+  // How the token is shown in error messages
+  pub fn describe(self) -> &'static str {
+    match self {
+      Self::LeftParen => "'('",
+      Self::RightParen => "')'",
+      Self::LeftBrace => "'{'",
+      Self::RightBrace => "'}'",
+      Self::LeftSquare => "'['",
+      Self::RightSquare => "']'",
+      Self::Comma => "','",
+      Self::Dot => "'.'",
+      Self::Colon => "':'",
+      Self::ColonColon => "'::'",
+      Self::RightArrow => "'->'",
+      Self::FatRightArrow => "'=>'",
+      Self::DotDot => "'..'",
+      Self::Pipe => "'|'",
+      Self::Question => "'?'",
+      Self::Minus => "'-'",
+      Self::Plus => "'+'",
+      Self::Slash => "'/'",
+      Self::Star => "'*'",
+      Self::Bang => "'!'",
+      Self::And => "'and'",
+      Self::Or => "'or'",
+      Self::QuestionQuestion => "'??'",
+      Self::RightRight => "'>>'",
+      Self::LeftLeft => "'<<'",
+      Self::Percent => "'%'",
+      Self::BangEqual => "'!='",
+      Self::Equal => "'='",
+      Self::EqualEqual => "'=='",
+      Self::Greater => "'>'",
+      Self::GreaterEqual => "'>='",
+      Self::Less => "'<'",
+      Self::LessEqual => "'<='",
+      Self::PlusEqual => "'+='",
+      Self::MinusEqual => "'-='",
+      Self::StarEqual => "'*='",
+      Self::SlashEqual => "'/='",
+      Self::Identifier => "identifier",
+      Self::String | Self::UnterminatedString => "string",
+      Self::Number => "number",
+      Self::True => "'true'",
+      Self::False => "'false'",
+      Self::Null => "'null'",
+      Self::FormatStringStart | Self::FormatStringPart | Self::FormatStringEnd => "format string",
+      Self::As => "'as'",
+      Self::Break => "'break'",
+      Self::Do => "'do'",
+      Self::Else => "'else'",
+      Self::If => "'if'",
+      Self::Import => "'import'",
+      Self::In => "'in'",
+      Self::From => "'from'",
+      Self::Let => "'let'",
+      Self::Loop => "'loop'",
+      Self::Return => "'return'",
+      Self::While => "'while'",
+      Self::Comment => "comment",
+      Self::Whitespace => "whitespace",
+      Self::EndOfLine => "new line",
+      Self::EndOfFile => "end of file",
+      Self::Unknown => "unknown character",
+    }
+  }
 }
 
+// The spelling of each keyword and operator, used by the tokeniser and to generate editor syntax
+// highlighting
+pub const KEYWORDS: [(&str, TokenType); 12] = [
+  ("as", TokenType::As),
+  ("break", TokenType::Break),
+  ("do", TokenType::Do),
+  ("else", TokenType::Else),
+  ("from", TokenType::From),
+  ("if", TokenType::If),
+  ("import", TokenType::Import),
+  ("in", TokenType::In),
+  ("let", TokenType::Let),
+  ("loop", TokenType::Loop),
+  ("return", TokenType::Return),
+  ("while", TokenType::While),
+];
+pub const LITERALS: [(&str, TokenType); 3] = [
+  ("true", TokenType::True),
+  ("false", TokenType::False),
+  ("null", TokenType::Null),
+];
+pub const OPERATORS: [(&str, TokenType); 30] = [
+  ("and", TokenType::And),
+  ("or", TokenType::Or),
+  ("&&", TokenType::And),
+  ("||", TokenType::Or),
+  ("->", TokenType::RightArrow),
+  ("=>", TokenType::FatRightArrow),
+  ("..", TokenType::DotDot),
+  ("::", TokenType::ColonColon),
+  ("??", TokenType::QuestionQuestion),
+  (">>", TokenType::RightRight),
+  ("<<", TokenType::LeftLeft),
+  ("!=", TokenType::BangEqual),
+  ("==", TokenType::EqualEqual),
+  (">=", TokenType::GreaterEqual),
+  ("<=", TokenType::LessEqual),
+  ("+=", TokenType::PlusEqual),
+  ("-=", TokenType::MinusEqual),
+  ("*=", TokenType::StarEqual),
+  ("/=", TokenType::SlashEqual),
+  ("+", TokenType::Plus),
+  ("-", TokenType::Minus),
+  ("*", TokenType::Star),
+  ("/", TokenType::Slash),
+  ("%", TokenType::Percent),
+  ("!", TokenType::Bang),
+  ("=", TokenType::Equal),
+  ("<", TokenType::Less),
+  (">", TokenType::Greater),
+  ("|", TokenType::Pipe),
+  ("?", TokenType::Question),
+];
+pub const PUNCTUATION: [(&str, TokenType); 9] = [
+  ("(", TokenType::LeftParen),
+  (")", TokenType::RightParen),
+  ("{", TokenType::LeftBrace),
+  ("}", TokenType::RightBrace),
+  ("[", TokenType::LeftSquare),
+  ("]", TokenType::RightSquare),
+  (",", TokenType::Comma),
+  (".", TokenType::Dot),
+  (":", TokenType::Colon),
+];
+pub const QUOTES: [char; 3] = ['\'', '"', '`'];
+pub const COMMENT_START: &str = "//";
+pub const FORMAT_STRING_START: &str = "${";
+
 #[derive(Clone, Copy, Debug)]
 pub struct Token {
   pub ttype: TokenType,
@@ -109,6 +251,7 @@ impl Token {
     str::from_utf8(&source[start..end]).expect("Source to be valid utf8")
   }
 
+  #[allow(clippy::len_without_is_empty)]
   pub fn len(&self) -> CharacterPosition {
     self.end - self.start
   }
@@ -124,6 +267,16 @@ impl Default for Token {
   }
 }
 
+// The `#!` line at the start of an executable script, without its line break
+pub fn shebang(source: &str) -> Option<&str> {
+  if !source.starts_with("#!") {
+    return None;
+  }
+
+  let line = source.split('\n').next().unwrap_or(source);
+  Some(line.strip_suffix('\r').unwrap_or(line))
+}
+
 pub struct Tokeniser<'source> {
   source: &'source [u8],
 
@@ -132,6 +285,9 @@ pub struct Tokeniser<'source> {
 
   quote_stack: SmallVec<[u8; 8]>,
   last_type: TokenType,
+
+  // Stops an unterminated string consuming the rest of the file
+  cap_unterminated_strings: bool,
 }
 
 impl<'source> Tokeniser<'source> {
@@ -140,13 +296,21 @@ impl<'source> Tokeniser<'source> {
       source: source.as_bytes(),
 
       line: 1,
-      position: 0,
+      // The shebang is skipped, but its line break is kept so the next line starts a statement
+      position: shebang(source).map_or(0, str::len),
 
       quote_stack: SmallVec::new(),
       last_type: TokenType::Unknown,
+
+      cap_unterminated_strings: true,
     }
   }
 
+  pub fn cap_unterminated_strings(mut self, cap: bool) -> Self {
+    self.cap_unterminated_strings = cap;
+    self
+  }
+
   fn next_token(&mut self) -> Token {
     let (ttype, len) = self.next_token_type();
 
@@ -212,6 +376,14 @@ impl<'source> Tokeniser<'source> {
     position >= self.source.len()
   }
 
+  fn is_line_break(&self, position: usize) -> bool {
+    match self.source[position] {
+      b'\n' => true,
+      b'\r' => self.source.get(position + 1) == Some(&b'\n'),
+      _ => false,
+    }
+  }
+
   fn two_character_token(&self) -> Option<TokenType> {
     let character = &self.source[self.position];
     let next_character = self.source.get(self.position + 1);
@@ -231,8 +403,10 @@ impl<'source> Tokeniser<'source> {
       (b'|', b'|') => Some(TokenType::Or),
       (b'?', b'?') => Some(TokenType::QuestionQuestion),
       (b'>', b'>') => Some(TokenType::RightRight),
+      (b'<', b'<') => Some(TokenType::LeftLeft),
       (b'.', b'.') => Some(TokenType::DotDot),
       (b':', b':') => Some(TokenType::ColonColon),
+      (b'\r', b'\n') => Some(TokenType::EndOfLine),
       _ => None,
     }
   }
@@ -240,7 +414,10 @@ impl<'source> Tokeniser<'source> {
   fn whitespace(&self) -> (TokenType, TokenLength) {
     let mut position = self.position;
 
-    while !self.at_end(position) && matches!(self.source[position], b' ' | b'\t' | b'\r') {
+    while !self.at_end(position)
+      && matches!(self.source[position], b' ' | b'\t' | b'\r')
+      && !self.is_line_break(position)
+    {
       position += 1;
     }
 
@@ -250,7 +427,7 @@ impl<'source> Tokeniser<'source> {
   fn comment(&self) -> (TokenType, TokenLength) {
     let mut position = self.position + 2;
 
-    while !self.at_end(position) && self.source[position] != b'\n' {
+    while !self.at_end(position) && !self.is_line_break(position) {
       position += 1;
     }
 
@@ -259,10 +436,11 @@ impl<'source> Tokeniser<'source> {
 
   fn string(&mut self, quote: u8) -> (TokenType, TokenLength) {
     let mut pos = self.position + 1;
+    let line = self.line;
 
     loop {
       if self.at_end(pos) {
-        break (TokenType::String, pos - self.position);
+        break self.unterminated_string(line);
       } else if self.source[pos] == quote {
         break (TokenType::String, pos - self.position + 1);
       } else if self.source[pos] == b'$' && self.source.get(pos + 1) == Some(&b'{') {
@@ -281,10 +459,12 @@ impl<'source> Tokeniser<'source> {
   fn format_string(&mut self) -> (TokenType, TokenLength) {
     let quote = *self.quote_stack.last().unwrap();
     let mut pos = self.position + 1;
+    let line = self.line;
 
     loop {
       if self.at_end(pos) {
-        break (TokenType::FormatStringEnd, pos - self.position);
+        self.quote_stack.pop();
+        break self.unterminated_string(line);
       } else if self.source[pos] == quote {
         self.quote_stack.pop();
         break (TokenType::FormatStringEnd, pos - self.position + 1);
@@ -300,6 +480,21 @@ impl<'source> Tokeniser<'source> {
     }
   }
 
+  fn unterminated_string(&mut self, line: LineNumber) -> (TokenType, TokenLength) {
+    let mut pos = self.position + 1;
+
+    if self.cap_unterminated_strings {
+      while !self.at_end(pos) && !self.is_line_break(pos) {
+        pos += 1;
+      }
+      self.line = line;
+    } else {
+      pos = self.source.len();
+    }
+
+    (TokenType::UnterminatedString, pos - self.position)
+  }
+
   fn number(&self) -> (TokenType, TokenLength) {
     let mut position = self.position + 1;
 
@@ -309,7 +504,10 @@ impl<'source> Tokeniser<'source> {
 
     if !self.at_end(position)
       && self.source[position] == b'.'
-      && self.source[position + 1].is_ascii_digit()
+      && self
+        .source
+        .get(position + 1)
+        .is_some_and(u8::is_ascii_digit)
     {
       position += 1;
     }
@@ -334,46 +532,13 @@ impl<'source> Tokeniser<'source> {
     (self.identifier_type(length), length)
   }
 
+  // Keywords are found from the lists of spellings, so the lists can't miss any
   fn identifier_type(&self, length: TokenLength) -> TokenType {
-    match self.source[self.position] {
-      b'a' => match self.source.get(self.position + 1) {
-        Some(b'n') => self.check_keyword(length, "and", TokenType::And),
-        Some(b's') => self.check_keyword(length, "as", TokenType::As),
-        _ => TokenType::Identifier,
-      },
-      b'e' => self.check_keyword(length, "else", TokenType::Else),
-      b'f' => match self.source.get(self.position + 1) {
-        Some(b'a') => self.check_keyword(length, "false", TokenType::False),
-        Some(b'r') => self.check_keyword(length, "from", TokenType::From),
-        _ => TokenType::Identifier,
-      },
-      b'i' => match self.source.get(self.position + 1) {
-        Some(b'f') => self.check_keyword(length, "if", TokenType::If),
-        Some(b'm') => self.check_keyword(length, "import", TokenType::Import),
-        _ => TokenType::Identifier,
-      },
-      b'l' => self.check_keyword(length, "let", TokenType::Let),
-      b'n' => self.check_keyword(length, "null", TokenType::Null),
-      b'o' => self.check_keyword(length, "or", TokenType::Or),
-      b'r' => self.check_keyword(length, "return", TokenType::Return),
-      b't' => self.check_keyword(length, "true", TokenType::True),
-      b'w' => self.check_keyword(length, "while", TokenType::While),
-      _ => TokenType::Identifier,
-    }
-  }
+    let word = &self.source[self.position..self.position + length];
 
-  fn check_keyword(
-    &self,
-    length: TokenLength,
-    keyword: &'static str,
-    token_type: TokenType,
-  ) -> TokenType {
-    let end = self.position + length;
-    if &self.source[self.position..end] == keyword.as_bytes() {
-      token_type
-    } else {
-      TokenType::Identifier
-    }
+    (KEYWORDS.iter().chain(&LITERALS).chain(&OPERATORS))
+      .find(|(spelling, _)| spelling.as_bytes() == word)
+      .map_or(TokenType::Identifier, |(_, token_type)| *token_type)
   }
 }
 
@@ -425,6 +590,15 @@ mod tests {
     assert_eq!(tokens[0].ttype, TokenType::Plus);
   }
 
+  #[test]
+  fn should_tokenize_pipelines() {
+    let tokens = tokenize(">> << <=");
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].ttype, TokenType::RightRight);
+    assert_eq!(tokens[1].ttype, TokenType::LeftLeft);
+    assert_eq!(tokens[2].ttype, TokenType::LessEqual);
+  }
+
   #[test]
   fn should_tokenize_whitespace() {
     let tokens = tokenize(" ");
@@ -447,6 +621,25 @@ mod tests {
     assert_eq!(tokens[1].line, 2);
   }
 
+  #[test]
+  fn should_tokenize_crlf_as_single_line_break() {
+    let tokens = tokenize("\r\n \r\n");
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].ttype, TokenType::EndOfLine);
+    assert_eq!((tokens[0].start, tokens[0].end), (0, 2));
+    assert_eq!(tokens[1].ttype, TokenType::Whitespace);
+    assert_eq!(tokens[1].line, 2);
+    assert_eq!(tokens[2].ttype, TokenType::EndOfLine);
+    assert_eq!(tokens[2].line, 2);
+
+    let tokens = tokenize("// comment\r\n5");
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].ttype, TokenType::Comment);
+    assert_eq!((tokens[0].start, tokens[0].end), (0, 10));
+    assert_eq!(tokens[1].ttype, TokenType::EndOfLine);
+    assert_eq!(tokens[2].line, 2);
+  }
+
   #[test]
   fn should_tokenize_strings() {
     let tokens = tokenize("'hello'");
@@ -477,6 +670,31 @@ mod tests {
     assert_eq!(tokens.len(), 2);
   }
 
+  #[test]
+  fn should_cap_unterminated_strings_at_line_end() {
+    let tokens = tokenize("'hello\nlet a\r\n5");
+    assert_eq!(tokens.len(), 6);
+    assert_eq!(tokens[0].ttype, TokenType::UnterminatedString);
+    assert_eq!((tokens[0].start, tokens[0].end), (0, 6));
+    assert_eq!(tokens[1].ttype, TokenType::EndOfLine);
+    assert_eq!(tokens[2].ttype, TokenType::Let);
+    assert_eq!(tokens[2].line, 2);
+    assert_eq!(tokens[5].ttype, TokenType::Number);
+    assert_eq!(tokens[5].line, 3);
+
+    let tokens = tokenize("'a ${1} b\n}");
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[2].ttype, TokenType::UnterminatedString);
+    assert_eq!(tokens[4].ttype, TokenType::RightBrace);
+
+    let tokens: Vec<_> = Tokeniser::new("'hello\nlet a")
+      .cap_unterminated_strings(false)
+      .collect();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].ttype, TokenType::UnterminatedString);
+    assert_eq!(tokens[0].len(), 12);
+  }
+
   #[test]
   fn should_tokenize_format_strings() {
     let tokens = tokenize("'${}'");
@@ -554,6 +772,32 @@ mod tests {
     assert_eq!(tokens[7].ttype, TokenType::Return);
     assert_eq!(tokens[8].ttype, TokenType::True);
     assert_eq!(tokens[9].ttype, TokenType::While);
+
+    let tokens = tokenize("loop break lo brake do done");
+    assert_eq!(tokens[0].ttype, TokenType::Loop);
+    assert_eq!(tokens[1].ttype, TokenType::Break);
+    assert_eq!(tokens[2].ttype, TokenType::Identifier);
+    assert_eq!(tokens[3].ttype, TokenType::Identifier);
+    assert_eq!(tokens[4].ttype, TokenType::Do);
+    assert_eq!(tokens[5].ttype, TokenType::Identifier);
+
+    let tokens = tokenize("in inside");
+    assert_eq!(tokens[0].ttype, TokenType::In);
+    assert_eq!(tokens[1].ttype, TokenType::Identifier);
+  }
+
+  #[test]
+  fn should_skip_shebang() {
+    let tokens = tokenize("#!/usr/bin/env bang\nlet a");
+    assert_eq!(tokens[0].ttype, TokenType::EndOfLine);
+    assert_eq!(tokens[1].ttype, TokenType::Let);
+    assert_eq!(tokens[1].line, 2);
+
+    let tokens = tokenize("#!/usr/bin/env bang");
+    assert!(tokens.is_empty());
+
+    let tokens = tokenize("a #! b");
+    assert_eq!(tokens[1].ttype, TokenType::Unknown);
   }
 
   #[test]
@@ -594,4 +838,25 @@ mod tests {
     let tokens = tokenize("&");
     assert_eq!(tokens[0].ttype, TokenType::Unknown);
   }
+
+  #[test]
+  fn should_tokenize_listed_spellings() {
+    let spellings = (KEYWORDS.iter())
+      .chain(&LITERALS)
+      .chain(&OPERATORS)
+      .chain(&PUNCTUATION);
+
+    for (spelling, token_type) in spellings {
+      let tokens = tokenize(spelling);
+      assert_eq!(tokens.len(), 1, "{spelling}");
+      assert_eq!(tokens[0].ttype, *token_type, "{spelling}");
+    }
+  }
+
+  #[test]
+  fn should_describe_keywords_by_spelling() {
+    for (spelling, token_type) in KEYWORDS.iter().chain(&LITERALS) {
+      assert_eq!(token_type.describe(), format!("'{spelling}'"));
+    }
+  }
 }
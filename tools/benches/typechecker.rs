@@ -0,0 +1,39 @@
+#![feature(test)]
+extern crate test;
+use test::{black_box, Bencher};
+
+use bang_syntax::parse;
+use bang_tools::typecheck;
+
+// A large file where each block defines functions, collections and unions using the previous block
+fn generate_file(blocks: usize) -> String {
+  let mut source = String::from("let value0: number | string | null = 0\n");
+
+  for i in 1..=blocks {
+    let previous = i - 1;
+    source.push_str(&format!(
+      "
+let add{i} = (a: number, b: number) => a + b
+let increment{i} = (a: number) => add{i}(a, 1)
+let describe{i} = (a: number | string | null) -> string
+  if (a == null) return 'none'
+  return toString(a)
+let list{i} = [add{i}(1, 2), add{i}({i}, 3), 4]
+let dict{i} = {{ 'name': describe{i}(value{previous}), 'count': list{i}[0] ?? 0 }}
+let value{i}: number | string | null = dict{i}['count'] ?? describe{i}(null)
+let composed{i} = describe{i} << increment{i}
+"
+    ));
+  }
+
+  source
+}
+
+#[bench]
+fn typecheck_large_file(b: &mut Bencher) {
+  let source = generate_file(500);
+  let ast = parse(&source).unwrap();
+  assert!(typecheck(&ast).is_empty());
+
+  b.iter(|| typecheck(black_box(&ast)));
+}
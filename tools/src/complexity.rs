@@ -0,0 +1,96 @@
+use bang_syntax::{
+  ast::{
+    expression::{operators::Binary as BinaryOperator, Expr, Expression},
+    statement::{Statement, Stmt},
+    Visitor,
+  },
+  LineNumber,
+};
+
+#[derive(Debug)]
+pub struct Complexity {
+  pub name: String,
+  pub line: LineNumber,
+  pub branches: usize,
+  pub nesting: usize,
+}
+
+struct Function {
+  index: usize,
+  depth: usize,
+}
+
+struct ComplexityVisitor<'a> {
+  source: &'a str,
+  results: Vec<Complexity>,
+  functions: Vec<Function>,
+}
+impl ComplexityVisitor<'_> {
+  fn current(&mut self) -> (&mut Complexity, &mut Function) {
+    let function = self
+      .functions
+      .last_mut()
+      .expect("Function stack to have item");
+    (&mut self.results[function.index], function)
+  }
+}
+impl Visitor for ComplexityVisitor<'_> {
+  fn enter_statement(&mut self, statement: &Statement) {
+    if let Stmt::If { .. } | Stmt::While { .. } = statement.stmt {
+      let (result, function) = self.current();
+      function.depth += 1;
+      result.branches += 1;
+      result.nesting = result.nesting.max(function.depth);
+    }
+  }
+
+  fn exit_statement(&mut self, statement: &Statement) {
+    if let Stmt::If { .. } | Stmt::While { .. } = statement.stmt {
+      self.current().1.depth -= 1;
+    }
+  }
+
+  fn enter_expression(&mut self, expression: &Expression) {
+    match &expression.expr {
+      Expr::Binary {
+        operator: BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Nullish,
+        ..
+      } => self.current().0.branches += 1,
+      Expr::Function { name, .. } => {
+        self.functions.push(Function {
+          index: self.results.len(),
+          depth: 0,
+        });
+        self.results.push(Complexity {
+          name: name.unwrap_or("").to_string(),
+          line: expression.span.get_line_number(self.source),
+          branches: 0,
+          nesting: 0,
+        });
+      }
+      _ => {}
+    }
+  }
+
+  fn exit_expression(&mut self, expression: &Expression) {
+    if let Expr::Function { .. } = expression.expr {
+      self.functions.pop();
+    }
+  }
+}
+
+pub fn complexity(source: &str, ast: &[Statement]) -> Vec<Complexity> {
+  let mut visitor = ComplexityVisitor {
+    source,
+    results: vec![Complexity {
+      name: "Root".to_string(),
+      line: 1,
+      branches: 0,
+      nesting: 0,
+    }],
+    functions: vec![Function { index: 0, depth: 0 }],
+  };
+  visitor.visit(ast);
+
+  visitor.results
+}
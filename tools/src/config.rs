@@ -0,0 +1,242 @@
+use crate::formatter::{FormatOptions, LineEnding, QuoteStyle};
+use std::{
+  error, fmt, fs,
+  path::{Path, PathBuf},
+};
+use toml::de::{DeTable, DeValue};
+
+pub const CONFIG_FILENAME: &str = "bang.toml";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Error {
+  pub message: String,
+  pub path: Option<PathBuf>,
+  pub line: Option<usize>,
+}
+impl Error {
+  fn new(message: String, line: usize) -> Self {
+    Self {
+      message,
+      path: None,
+      line: Some(line),
+    }
+  }
+}
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.message)?;
+
+    match (&self.path, self.line) {
+      (Some(path), Some(line)) => write!(f, "\nat {}:{line}", path.display()),
+      (Some(path), None) => write!(f, "\nat {}", path.display()),
+      (None, Some(line)) => write!(f, "\nat line {line}"),
+      (None, None) => Ok(()),
+    }
+  }
+}
+impl error::Error for Error {}
+
+fn kind(value: &DeValue) -> &'static str {
+  match value {
+    DeValue::String(_) => "a string",
+    DeValue::Integer(_) => "an integer",
+    DeValue::Float(_) => "a float",
+    DeValue::Boolean(_) => "a boolean",
+    DeValue::Datetime(_) => "a date",
+    DeValue::Array(_) => "an array",
+    DeValue::Table(_) => "a table",
+  }
+}
+
+fn line_number(source: &str, offset: usize) -> usize {
+  source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+  pub path: Option<PathBuf>,
+  pub strict: Option<bool>,
+  pub strict_equality: Option<bool>,
+  pub disabled_rules: Vec<String>,
+  pub indent: Option<usize>,
+  pub quote: Option<QuoteStyle>,
+  pub line_width: Option<usize>,
+  pub trailing_commas: Option<bool>,
+  pub line_ending: Option<LineEnding>,
+}
+impl Config {
+  pub fn parse(source: &str) -> Result<Self, Error> {
+    let document = DeTable::parse(source).map_err(|error| Error {
+      message: error.message().trim_end().to_string(),
+      path: None,
+      line: error.span().map(|span| line_number(source, span.start)),
+    })?;
+
+    let mut config = Self::default();
+    for (key, value) in document.get_ref() {
+      let line = line_number(source, key.span().start);
+
+      match (key.get_ref().as_ref(), value.get_ref()) {
+        (name @ ("run" | "typecheck" | "lint" | "format"), DeValue::Table(table)) => {
+          for (key, value) in table {
+            config
+              .set(name, key.get_ref(), value.get_ref())
+              .map_err(|message| Error::new(message, line_number(source, key.span().start)))?;
+          }
+        }
+        (name, DeValue::Table(_)) => {
+          return Err(Error::new(format!("Unknown table `[{name}]`"), line))
+        }
+        (key, value) => config
+          .set("", key, value)
+          .map_err(|message| Error::new(message, line))?,
+      }
+    }
+
+    Ok(config)
+  }
+
+  fn set(&mut self, table: &str, key: &str, value: &DeValue) -> Result<(), String> {
+    match (table, key) {
+      ("run", "strict_equality") => self.strict_equality = Some(boolean(key, value)?),
+      ("typecheck", "strict") => self.strict = Some(boolean(key, value)?),
+      ("lint", "disabled") => self.disabled_rules = strings(key, value)?,
+      ("format", "indent") => self.indent = Some(size(key, value)?),
+      ("format", "line_width") => self.line_width = Some(size(key, value)?),
+      ("format", "trailing_commas") => self.trailing_commas = Some(boolean(key, value)?),
+      ("format", "quote") => {
+        self.quote = Some(match string(key, value)?.as_str() {
+          "single" => QuoteStyle::Single,
+          "double" => QuoteStyle::Double,
+          other => {
+            return Err(format!(
+              "Unknown quote style `{other}`, expected `single` or `double`"
+            ))
+          }
+        });
+      }
+      ("format", "line_ending") => {
+        self.line_ending = Some(match string(key, value)?.as_str() {
+          "preserve" => LineEnding::Preserve,
+          "lf" => LineEnding::Lf,
+          "crlf" => LineEnding::Crlf,
+          other => {
+            return Err(format!(
+              "Unknown line ending `{other}`, expected `preserve`, `lf` or `crlf`"
+            ))
+          }
+        });
+      }
+      ("", key) => return Err(format!("Unknown key `{key}`")),
+      (table, key) => return Err(format!("Unknown key `{key}` in `[{table}]`")),
+    }
+
+    Ok(())
+  }
+
+  pub fn load(path: &Path) -> Result<Self, Error> {
+    let with_path = |error: Error| Error {
+      path: Some(path.to_path_buf()),
+      ..error
+    };
+
+    let source = fs::read_to_string(path).map_err(|_| {
+      with_path(Error {
+        message: "Problem reading config file".to_string(),
+        path: None,
+        line: None,
+      })
+    })?;
+    let mut config = Self::parse(&source).map_err(with_path)?;
+    config.path = Some(path.to_path_buf());
+    Ok(config)
+  }
+
+  pub fn discover(file: &Path) -> Result<Self, Error> {
+    let file = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+
+    for directory in file.ancestors().skip(1) {
+      let path = directory.join(CONFIG_FILENAME);
+      if path.is_file() {
+        return Self::load(&path);
+      }
+    }
+
+    Ok(Self::default())
+  }
+
+  pub fn format_options(&self) -> FormatOptions {
+    let defaults = FormatOptions::default();
+
+    FormatOptions {
+      line_ending: self.line_ending.unwrap_or(defaults.line_ending),
+      indent: self.indent.unwrap_or(defaults.indent),
+      quote: self.quote.unwrap_or(defaults.quote),
+      line_width: self.line_width.unwrap_or(defaults.line_width),
+      trailing_commas: self.trailing_commas.unwrap_or(defaults.trailing_commas),
+    }
+  }
+
+  pub fn is_rule_enabled(&self, title: &str) -> bool {
+    let name = rule_name(title);
+    !self
+      .disabled_rules
+      .iter()
+      .any(|rule| rule_name(rule) == name)
+  }
+}
+
+// Rules can be written as their title or in kebab case, e.g. `no-float-equality`
+fn rule_name(rule: &str) -> String {
+  rule
+    .chars()
+    .filter(char::is_ascii_alphanumeric)
+    .map(|c| c.to_ascii_lowercase())
+    .collect()
+}
+
+fn boolean(key: &str, value: &DeValue) -> Result<bool, String> {
+  match value {
+    DeValue::Boolean(value) => Ok(*value),
+    value => Err(format!(
+      "Expected `{key}` to be a boolean, found {}",
+      kind(value)
+    )),
+  }
+}
+
+fn size(key: &str, value: &DeValue) -> Result<usize, String> {
+  match value {
+    DeValue::Integer(value) => i64::from_str_radix(value.as_str(), value.radix())
+      .ok()
+      .and_then(|value| usize::try_from(value).ok())
+      .ok_or_else(|| format!("Expected `{key}` to be a positive integer, found {value}")),
+    value => Err(format!(
+      "Expected `{key}` to be an integer, found {}",
+      kind(value)
+    )),
+  }
+}
+
+fn string(key: &str, value: &DeValue) -> Result<String, String> {
+  match value {
+    DeValue::String(value) => Ok(value.to_string()),
+    value => Err(format!(
+      "Expected `{key}` to be a string, found {}",
+      kind(value)
+    )),
+  }
+}
+
+fn strings(key: &str, value: &DeValue) -> Result<Vec<String>, String> {
+  match value {
+    DeValue::Array(items) => items
+      .iter()
+      .map(|item| string(key, item.get_ref()))
+      .collect(),
+    value => Err(format!(
+      "Expected `{key}` to be an array of strings, found {}",
+      kind(value)
+    )),
+  }
+}
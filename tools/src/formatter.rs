@@ -6,17 +6,38 @@ use bang_syntax::{
   },
   LineNumber, Parser, Span,
 };
-
-const INDENTATION: &str = "  ";
+use std::fmt::Write;
 
 struct Formatter<'source> {
   source: &'source str,
   ast: &'source [Statement<'source>],
+  options: FormatOptions,
 }
 
 impl<'source> Formatter<'source> {
-  fn new(source: &'source str, ast: &'source [Statement<'source>]) -> Self {
-    Self { source, ast }
+  fn new(source: &'source str, ast: &'source [Statement<'source>], options: FormatOptions) -> Self {
+    Self {
+      source,
+      ast,
+      options,
+    }
+  }
+
+  fn indent(&self, indentation: usize) -> String {
+    " ".repeat(indentation * self.options.indent)
+  }
+
+  fn quote(&self, strings: &[&str]) -> char {
+    let preferred = match self.options.quote {
+      QuoteStyle::Single => '\'',
+      QuoteStyle::Double => '"',
+    };
+
+    // Strings have no escapes, so use a different quote if the string contains the preferred one
+    [preferred, '\'', '"', '`']
+      .into_iter()
+      .find(|quote| !strings.iter().any(|string| string.contains(*quote)))
+      .unwrap_or(preferred)
   }
 
   fn line(&self, span: Span) -> LineNumber {
@@ -31,26 +52,58 @@ impl<'source> Formatter<'source> {
     &self,
     expression: &Expression,
     indentation: usize,
-    f: &mut std::fmt::Formatter,
+    f: &mut String,
   ) -> std::fmt::Result {
     write!(f, "(")?;
     if self.line(expression.span) == self.line_end(expression.span) {
       self.fmt_expression(expression, indentation + 1, f)?;
       write!(f, ")")?;
     } else {
-      write!(f, "\n{}", INDENTATION.repeat(indentation + 1))?;
+      write!(f, "\n{}", self.indent(indentation + 1))?;
       self.fmt_expression(expression, indentation + 1, f)?;
-      write!(f, "\n{})", INDENTATION.repeat(indentation))?;
+      write!(f, "\n{})", self.indent(indentation))?;
     }
 
     Ok(())
   }
 
+  // A function written after the brackets of a call, like `each(items) => print(it)`
+  fn is_trailing_function(&self, argument: &Expression) -> bool {
+    let source = &self.source[argument.span.start as usize..];
+    matches!(argument.expr, Expr::Function { .. })
+      && (source.starts_with("=>") || source.starts_with("->"))
+  }
+
+  fn write_function_body(
+    &self,
+    body: &Statement,
+    return_type: Option<&TypeExpression>,
+    indentation: usize,
+    f: &mut String,
+  ) -> std::fmt::Result {
+    if let Stmt::Return {
+      expression: Some(expression),
+      ..
+    } = &body.stmt
+    {
+      write!(f, " => ")?;
+      self.fmt_expression(expression, indentation, f)
+    } else {
+      write!(f, " ->")?;
+      if let Some(return_type) = return_type {
+        write!(f, " ")?;
+        self.fmt_type(return_type, f)?;
+      }
+      writeln!(f)?;
+      self.fmt_statement(body, indentation, false, f)
+    }
+  }
+
   fn write_statement_inline(
     &self,
     statement: &Statement,
     indentation: usize,
-    f: &mut std::fmt::Formatter,
+    f: &mut String,
   ) -> std::fmt::Result {
     if let Stmt::Block { body, .. } = &statement.stmt {
       if body.len() > 1 {
@@ -68,58 +121,80 @@ impl<'source> Formatter<'source> {
     Ok(())
   }
 
+  #[allow(clippy::too_many_arguments)]
   fn write_list<Item>(
+    &self,
     items: &[Item],
     get_line: impl Fn(&Item) -> LineNumber,
-    write_item: &mut dyn FnMut(&mut std::fmt::Formatter, &Item, usize) -> std::fmt::Result,
+    write_item: &mut dyn FnMut(&mut String, &Item, usize) -> std::fmt::Result,
     start_line: LineNumber,
     indentation: usize,
     padded: bool,
-    f: &mut std::fmt::Formatter,
+    f: &mut String,
   ) -> std::fmt::Result {
     let lines: Vec<LineNumber> = items.iter().map(get_line).collect();
     let all_same_line = lines.iter().all(|line| line == &lines[0]);
     let all_same_line_as_bracket = all_same_line && lines.contains(&start_line);
 
     if all_same_line_as_bracket || items.is_empty() {
+      let mut line = String::new();
       if padded {
-        write!(f, " ")?;
+        write!(line, " ")?;
       }
       for (i, item) in items.iter().enumerate() {
-        write_item(f, item, indentation)?;
+        write_item(&mut line, item, indentation)?;
         if i < items.len() - 1 {
-          write!(f, ", ")?;
+          write!(line, ", ")?;
         }
       }
       if padded {
-        write!(f, " ")?;
+        write!(line, " ")?;
+      }
+
+      // Lists which are too long are split onto a line per item
+      if items.is_empty() || self.fits_on_line(f, &line) {
+        return write!(f, "{line}");
       }
     } else if all_same_line {
-      write!(f, "\n{}", INDENTATION.repeat(indentation + 1))?;
-      for item in items {
+      write!(f, "\n{}", self.indent(indentation + 1))?;
+      for (i, item) in items.iter().enumerate() {
         write_item(f, item, indentation + 1)?;
-        write!(f, ", ")?;
+        if i < items.len() - 1 || self.options.trailing_commas {
+          write!(f, ", ")?;
+        }
       }
-      writeln!(f)?;
-    } else {
-      for arg in items {
-        write!(f, "\n{}", INDENTATION.repeat(indentation + 1))?;
-        write_item(f, arg, indentation + 1)?;
+      return writeln!(f);
+    }
+
+    for (i, item) in items.iter().enumerate() {
+      write!(f, "\n{}", self.indent(indentation + 1))?;
+      write_item(f, item, indentation + 1)?;
+      if i < items.len() - 1 || self.options.trailing_commas {
         write!(f, ",")?;
       }
-      write!(f, "\n{}", INDENTATION.repeat(indentation))?;
     }
+    write!(f, "\n{}", self.indent(indentation))
+  }
 
-    Ok(())
+  fn fits_on_line(&self, f: &str, text: &str) -> bool {
+    let column = f.rsplit('\n').next().map_or(0, |line| line.chars().count());
+    let width = text.lines().next().map_or(0, |line| line.chars().count());
+
+    // Leave space for the closing bracket
+    column + width < self.options.line_width
   }
 
-  fn fmt_type(t: &TypeExpression, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+  fn fmt_type(&self, t: &TypeExpression, f: &mut String) -> std::fmt::Result {
     match &t.type_ {
       Type::Named(name) => write!(f, "{name}")?,
+      Type::StringLiteral(value) => {
+        let quote = self.quote(&[value]);
+        write!(f, "{quote}{value}{quote}")?;
+      }
       Type::Parameter(name, params) => {
         write!(f, "{name}(")?;
         for (i, param) in params.iter().enumerate() {
-          Self::fmt_type(param, f)?;
+          self.fmt_type(param, f)?;
           if i < params.len() - 1 {
             write!(f, ", ")?;
           }
@@ -127,37 +202,37 @@ impl<'source> Formatter<'source> {
         write!(f, ")")?;
       }
       Type::Union(a, b) => {
-        Self::fmt_type(a, f)?;
+        self.fmt_type(a, f)?;
         write!(f, " | ")?;
-        Self::fmt_type(b, f)?;
+        self.fmt_type(b, f)?;
       }
       Type::Function(return_type, parameters) => {
         write!(f, "(")?;
         for (i, param) in parameters.iter().enumerate() {
-          Self::fmt_type(param, f)?;
+          self.fmt_type(param, f)?;
           if i < parameters.len() - 1 {
             write!(f, ", ")?;
           }
         }
         write!(f, ") -> ")?;
-        Self::fmt_type(return_type, f)?;
+        self.fmt_type(return_type, f)?;
       }
       Type::Group(type_) => {
         write!(f, "(")?;
-        Self::fmt_type(type_, f)?;
+        self.fmt_type(type_, f)?;
         write!(f, ")")?;
       }
       Type::Optional(type_) => {
-        Self::fmt_type(type_, f)?;
+        self.fmt_type(type_, f)?;
         write!(f, "?")?;
       }
       Type::List(type_) => {
-        Self::fmt_type(type_, f)?;
+        self.fmt_type(type_, f)?;
         write!(f, "[]")?;
       }
       Type::WithGeneric(generics, type_) => {
         write!(f, "<{}>", generics.join(", "))?;
-        Self::fmt_type(type_, f)?;
+        self.fmt_type(type_, f)?;
       }
     }
 
@@ -168,7 +243,7 @@ impl<'source> Formatter<'source> {
     &self,
     expression: &Expression,
     indentation: usize,
-    f: &mut std::fmt::Formatter,
+    f: &mut String,
   ) -> std::fmt::Result {
     let span = expression.span;
 
@@ -176,7 +251,15 @@ impl<'source> Formatter<'source> {
       Expr::Assignment {
         identifier,
         expression,
-        ..
+        assignment_operator: Some(assignment_operator),
+      } => {
+        write!(f, "{identifier} {assignment_operator} ")?;
+        self.fmt_expression(expression, indentation, f)?;
+      }
+      Expr::Assignment {
+        identifier,
+        expression,
+        assignment_operator: None,
       } => {
         if let Expr::Binary { operator, left, right, .. } = &expression.expr
           && let Expr::Variable { name } = &left.expr
@@ -207,8 +290,13 @@ impl<'source> Formatter<'source> {
       } => {
         self.fmt_expression(expression, indentation, f)?;
 
+        let (arguments, trailing) = match arguments.split_last() {
+          Some((last, rest)) if self.is_trailing_function(last) => (rest, Some(last)),
+          _ => (arguments.as_slice(), None),
+        };
+
         write!(f, "(")?;
-        Self::write_list(
+        self.write_list(
           arguments,
           |arg| self.line(arg.span),
           &mut |f, arg, i| self.fmt_expression(arg, i, f),
@@ -218,6 +306,16 @@ impl<'source> Formatter<'source> {
           f,
         )?;
         write!(f, ")")?;
+
+        if let Some(Expression {
+          expr: Expr::Function {
+            body, return_type, ..
+          },
+          ..
+        }) = trailing
+        {
+          self.write_function_body(body, return_type.as_ref(), indentation, f)?;
+        }
       }
       Expr::Comment {
         expression, text, ..
@@ -235,7 +333,7 @@ impl<'source> Formatter<'source> {
         }
 
         write!(f, "{{ ")?;
-        Self::write_list(
+        self.write_list(
           items,
           |item| self.line(item.0.span),
           &mut |f, item, i| {
@@ -258,13 +356,16 @@ impl<'source> Formatter<'source> {
         write!(f, " }}")?;
       }
       Expr::FormatString { expressions, strings } => {
-        write!(f, "'{}", strings[0])?;
+        let parts: Vec<&str> = strings.iter().map(|string| string.as_str()).collect();
+        let quote = self.quote(&parts);
+
+        write!(f, "{quote}{}", strings[0])?;
         for (index, expression) in expressions.iter().enumerate() {
           write!(f, "${{")?;
           self.fmt_expression(expression, indentation, f)?;
           write!(f, "}}{}", strings[index + 1])?;
         }
-        write!(f, "'")?;
+        write!(f, "{quote}")?;
       },
       Expr::Function {
         parameters,
@@ -273,15 +374,19 @@ impl<'source> Formatter<'source> {
         ..
       } => {
         write!(f, "(")?;
-        Self::write_list(
+        self.write_list(
           parameters,
           |param| self.line(param.span),
-          &mut |f, parameter, _| {
+          &mut |f, parameter, indentation| {
             write!(f, "{}", parameter.name)?;
 
             if let Some(type_) = &parameter.type_ {
               write!(f, ": ")?;
-              Self::fmt_type(type_, f)?;
+              self.fmt_type(type_, f)?;
+            }
+            if let Some(default) = &parameter.default {
+              write!(f, " = ")?;
+              self.fmt_expression(default, indentation, f)?;
             }
             Ok(())
           },
@@ -291,22 +396,8 @@ impl<'source> Formatter<'source> {
           f,
         )?;
 
-        if let Stmt::Return {
-          expression: Some(expression),
-          ..
-        } = &body.stmt
-        {
-          write!(f, ") => ")?;
-          self.fmt_expression(expression, indentation, f)?;
-        } else {
-          write!(f, ") ->")?;
-          if let Some(return_type) = return_type {
-            write!(f, " ")?;
-            Self::fmt_type(return_type, f)?;
-          }
-          writeln!(f)?;
-          self.fmt_statement(body, indentation, false, f)?;
-        }
+        write!(f, ")")?;
+        self.write_function_body(body, return_type.as_ref(), indentation, f)?;
       }
       Expr::Group { expression, .. } => {
         self.write_group(expression, indentation, f)?;
@@ -328,7 +419,7 @@ impl<'source> Formatter<'source> {
         items
       } => {
         write!(f, "[")?;
-        Self::write_list(
+        self.write_list(
           items,
           |item| self.line(item.span),
           &mut |f, item, i| self.fmt_expression(item, i, f),
@@ -341,7 +432,10 @@ impl<'source> Formatter<'source> {
       }
       Expr::Literal { type_, value, .. } => {
         match type_ {
-          LiteralType::String => write!(f, "'{value}'")?,
+          LiteralType::String => {
+            let quote = self.quote(&[value]);
+            write!(f, "{quote}{value}{quote}")?;
+          }
           LiteralType::Number => {
             if str::contains(value, "_") {
               write!(f, "{value}")?;
@@ -354,6 +448,14 @@ impl<'source> Formatter<'source> {
           LiteralType::Null => write!(f, "null")?,
         };
       }
+      Expr::Loop { body } => {
+        write!(f, "loop")?;
+        self.write_statement_inline(body, indentation, f)?;
+      }
+      Expr::Do { body } => {
+        write!(f, "do")?;
+        self.write_statement_inline(body, indentation, f)?;
+      }
       Expr::ModuleAccess { module, item } => {
         write!(f, "{module}::{item}")?;
       }
@@ -365,6 +467,22 @@ impl<'source> Formatter<'source> {
         write!(f, "{operator}")?;
         self.fmt_expression(expression, indentation, f)?;
       }
+      Expr::NonNull { expression } => {
+        self.fmt_expression(expression, indentation, f)?;
+        write!(f, "!")?;
+      }
+      Expr::Cast { expression, type_ } => {
+        self.fmt_expression(expression, indentation, f)?;
+        write!(f, " as ")?;
+        self.fmt_type(type_, f)?;
+      }
+      Expr::Ascription { expression, type_ } => {
+        write!(f, "(")?;
+        self.fmt_expression(expression, indentation, f)?;
+        write!(f, ": ")?;
+        self.fmt_type(type_, f)?;
+        write!(f, ")")?;
+      }
       Expr::Variable { name, .. } => {
         write!(f, "{name}")?;
       }
@@ -378,7 +496,7 @@ impl<'source> Formatter<'source> {
     statement: &Statement,
     indentation: usize,
     new_line: bool,
-    f: &mut std::fmt::Formatter,
+    f: &mut String,
   ) -> std::fmt::Result {
     let mut ending_new_line = new_line;
     let span = statement.span;
@@ -393,7 +511,7 @@ impl<'source> Formatter<'source> {
                 writeln!(f)?;
               }
 
-              write!(f, "{}", INDENTATION.repeat(indentation + 1))?;
+              write!(f, "{}", self.indent(indentation + 1))?;
               self.fmt_statement(stmt, indentation + 1, true, f)?;
               prev = stmt;
             }
@@ -403,7 +521,7 @@ impl<'source> Formatter<'source> {
             }
           }
 
-          write!(f, "{}", INDENTATION.repeat(indentation + 1))?;
+          write!(f, "{}", self.indent(indentation + 1))?;
           self.fmt_statement(last, indentation + 1, false, f)?;
         }
         ending_new_line = false;
@@ -421,7 +539,7 @@ impl<'source> Formatter<'source> {
           DeclarationIdentifier::Variable(identifier) => write!(f, "let {identifier}")?,
           DeclarationIdentifier::Ordered(list) => {
             write!(f, "let [")?;
-            Self::write_list(
+            self.write_list(
               list,
               |_| self.line(span),
               &mut |f, item, _| write!(f, "{item}"),
@@ -432,17 +550,33 @@ impl<'source> Formatter<'source> {
             )?;
             write!(f, "]")?;
           }
-          DeclarationIdentifier::Named(list) => {
+          DeclarationIdentifier::Named(list, rest) => {
+            // The rest has no position, so it is kept on the line of the item before it
+            let last_line = list
+              .last()
+              .map_or(self.line(span), |item| self.line(item.span));
+            let mut items: Vec<_> = list.iter().map(Some).collect();
+            if rest.is_some() {
+              items.push(None);
+            }
+
             write!(f, "let {{ ")?;
-            Self::write_list(
-              list,
-              |item| self.line(item.span),
-              &mut |f, item, _| {
-                write!(f, "{}", item.name)?;
-                if let Some(alias) = item.alias {
-                  write!(f, " as {alias}")?;
+            self.write_list(
+              &items,
+              |item| item.map_or(last_line, |item| self.line(item.span)),
+              &mut |f, item, indentation| match item {
+                Some(item) => {
+                  write!(f, "{}", item.name)?;
+                  if let Some(alias) = item.alias {
+                    write!(f, " as {alias}")?;
+                  }
+                  if let Some(default) = &item.default {
+                    write!(f, " = ")?;
+                    self.fmt_expression(default, indentation, f)?;
+                  }
+                  Ok(())
                 }
-                Ok(())
+                None => write!(f, "..{}", rest.unwrap_or_default()),
               },
               self.line(span),
               indentation,
@@ -450,11 +584,7 @@ impl<'source> Formatter<'source> {
               f,
             )?;
 
-            if list
-              .iter()
-              .last()
-              .map_or_else(|| true, |item| self.line(item.span) != self.line(span))
-            {
+            if items.is_empty() || last_line != self.line(span) {
               write!(f, "}}")?;
             } else {
               write!(f, " }}")?;
@@ -464,7 +594,7 @@ impl<'source> Formatter<'source> {
 
         if let Some(type_) = type_ {
           write!(f, ": ")?;
-          Self::fmt_type(type_, f)?;
+          self.fmt_type(type_, f)?;
         }
         if let Some(expression) = expression {
           write!(f, " = ")?;
@@ -485,32 +615,53 @@ impl<'source> Formatter<'source> {
         self.write_statement_inline(then, indentation, f)?;
 
         if let Some(otherwise) = otherwise {
-          write!(f, "\n{}else", INDENTATION.repeat(indentation))?;
+          write!(f, "\n{}else", self.indent(indentation))?;
           self.write_statement_inline(otherwise, indentation, f)?;
         }
       }
-      Stmt::Import { module, items, .. } => {
+      Stmt::Import {
+        module,
+        alias,
+        items,
+        optional,
+      } => {
         if module.chars().all(char::is_alphanumeric) {
-          write!(f, "from {module} import {{")?;
+          write!(f, "from {module}")?;
         } else {
-          write!(f, "from '{module}' import {{")?;
+          let quote = self.quote(&[module]);
+          write!(f, "from {quote}{module}{quote}")?;
+        }
+        if let Some(alias) = alias {
+          write!(f, " as {alias}")?;
+        }
+
+        // An aliased module may not import any items
+        if alias.is_none() || !items.is_empty() {
+          write!(f, " import{} {{", if *optional { "?" } else { "" })?;
+          self.write_list(
+            items,
+            |item| self.line(item.span),
+            &mut |f, item, _| {
+              write!(f, "{}", item.name)?;
+              if let Some(alias) = &item.alias {
+                write!(f, " as {alias}")?;
+              }
+              Ok(())
+            },
+            self.line(span),
+            indentation,
+            true,
+            f,
+          )?;
+          write!(f, "}}")?;
+        }
+      }
+      Stmt::Break { expression } => {
+        write!(f, "break")?;
+        if let Some(expression) = expression {
+          write!(f, " ")?;
+          self.fmt_expression(expression, indentation, f)?;
         }
-        Self::write_list(
-          items,
-          |item| self.line(item.span),
-          &mut |f, item, _| {
-            write!(f, "{}", item.name)?;
-            if let Some(alias) = &item.alias {
-              write!(f, " as {alias}")?;
-            }
-            Ok(())
-          },
-          self.line(span),
-          indentation,
-          true,
-          f,
-        )?;
-        write!(f, "}}")?;
       }
       Stmt::Return { expression, .. } => {
         write!(f, "return ")?;
@@ -537,24 +688,92 @@ impl<'source> Formatter<'source> {
 
 impl std::fmt::Display for Formatter<'_> {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let shebang = bang_syntax::shebang(self.source);
+    if let Some(shebang) = shebang {
+      writeln!(f, "{shebang}")?;
+    }
+
     if self.ast.is_empty() {
       return Ok(());
     }
 
+    let mut output = String::new();
+    if shebang.is_some() && self.line(self.ast[0].span) > 2 {
+      writeln!(output)?;
+    }
+
     let mut prev = &self.ast[0];
     for stmt in self.ast {
       if self.line_end(prev.span) < self.line(stmt.span) - 1 {
-        writeln!(f)?;
+        writeln!(output)?;
       }
 
-      self.fmt_statement(stmt, 0, true, f)?;
+      self.fmt_statement(stmt, 0, true, &mut output)?;
       prev = stmt;
     }
 
-    Ok(())
+    f.write_str(&output)
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+  #[default]
+  Preserve,
+  Lf,
+  Crlf,
+}
+impl LineEnding {
+  fn is_crlf(self, source: &str) -> bool {
+    match self {
+      Self::Preserve => source
+        .find('\n')
+        .is_some_and(|position| source[..position].ends_with('\r')),
+      Self::Lf => false,
+      Self::Crlf => true,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+  #[default]
+  Single,
+  Double,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+  pub line_ending: LineEnding,
+  pub indent: usize,
+  pub quote: QuoteStyle,
+  pub line_width: usize,
+  pub trailing_commas: bool,
+}
+impl Default for FormatOptions {
+  fn default() -> Self {
+    Self {
+      line_ending: LineEnding::default(),
+      indent: 2,
+      quote: QuoteStyle::default(),
+      line_width: 100,
+      trailing_commas: true,
+    }
   }
 }
 
 pub fn format(source: &str, ast: &[Statement]) -> String {
-  Formatter::new(source, ast).to_string()
+  format_with_options(source, ast, FormatOptions::default())
+}
+
+pub fn format_with_options(source: &str, ast: &[Statement], options: FormatOptions) -> String {
+  let formatted = Formatter::new(source, ast, options)
+    .to_string()
+    .replace("\r\n", "\n");
+
+  if options.line_ending.is_crlf(source) {
+    formatted.replace('\n', "\r\n")
+  } else {
+    formatted
+  }
 }
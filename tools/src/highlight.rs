@@ -0,0 +1,221 @@
+use bang_syntax::{
+  TokenType, COMMENT_START, FORMAT_STRING_START, KEYWORDS, LITERALS, OPERATORS, PUNCTUATION, QUOTES,
+};
+use std::fmt::Write;
+
+fn spellings(tokens: &'static [(&'static str, TokenType)]) -> impl Iterator<Item = &'static str> {
+  tokens.iter().map(|(spelling, _)| *spelling)
+}
+
+fn is_word(spelling: &str) -> bool {
+  spelling.bytes().all(|byte| byte.is_ascii_alphabetic())
+}
+
+fn word_operators() -> impl Iterator<Item = &'static str> {
+  spellings(&OPERATORS).filter(|spelling| is_word(spelling))
+}
+
+// Longer operators are listed first, so `==` is matched before `=`
+fn symbol_operators() -> impl Iterator<Item = &'static str> {
+  spellings(&OPERATORS).filter(|spelling| !is_word(spelling))
+}
+
+fn regex_escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for character in text.chars() {
+    if "\\^$.|?*+()[]{}/".contains(character) {
+      escaped.push('\\');
+    }
+    escaped.push(character);
+  }
+  escaped
+}
+
+fn json_string(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn join_words(words: impl Iterator<Item = &'static str>) -> String {
+  let words: Vec<_> = words.collect();
+  format!("\\b({})\\b", words.join("|"))
+}
+
+// A TextMate grammar, as used by VSCode and many other editors
+pub fn textmate_grammar() -> String {
+  let number = "\\b[0-9][0-9_]*(\\.[0-9][0-9_]*)?\\b|\\.[0-9][0-9_]*\\b";
+  let operators: Vec<_> = symbol_operators().map(regex_escape).collect();
+
+  let mut patterns = vec![
+    (
+      "comment.line.double-slash.bang",
+      format!("{}.*$", regex_escape(COMMENT_START)),
+    ),
+    ("keyword.control.bang", join_words(spellings(&KEYWORDS))),
+    ("constant.language.bang", join_words(spellings(&LITERALS))),
+    ("keyword.operator.word.bang", join_words(word_operators())),
+    ("constant.numeric.bang", number.to_owned()),
+    ("keyword.operator.bang", operators.join("|")),
+  ]
+  .into_iter()
+  .map(|(name, regex)| {
+    format!(
+      "    {{ \"name\": {}, \"match\": {} }}",
+      json_string(name),
+      json_string(&regex)
+    )
+  })
+  .collect::<Vec<_>>();
+
+  for quote in QUOTES {
+    let kind = match quote {
+      '\'' => "single",
+      '"' => "double",
+      _ => "other",
+    };
+    let quote = json_string(&regex_escape(&quote.to_string()));
+    patterns.push(format!(
+      "    {{\n      \"name\": \"string.quoted.{kind}.bang\",\n      \"begin\": {quote},\n      \"end\": {quote},\n      \"patterns\": [{{ \"include\": \"#interpolation\" }}]\n    }}"
+    ));
+  }
+
+  let mut grammar = String::new();
+  grammar.push_str("{\n");
+  grammar.push_str("  \"name\": \"Bang\",\n");
+  grammar.push_str("  \"scopeName\": \"source.bang\",\n");
+  grammar.push_str("  \"fileTypes\": [\"bang\"],\n");
+  grammar.push_str("  \"patterns\": [\n");
+  grammar.push_str(&patterns.join(",\n"));
+  grammar.push_str("\n  ],\n");
+  grammar.push_str("  \"repository\": {\n");
+  grammar.push_str("    \"interpolation\": {\n");
+  grammar.push_str("      \"name\": \"meta.embedded.bang\",\n");
+  let _ = writeln!(
+    grammar,
+    "      \"begin\": {},",
+    json_string(&regex_escape(FORMAT_STRING_START))
+  );
+  grammar.push_str("      \"end\": \"\\\\}\",\n");
+  grammar.push_str("      \"patterns\": [{ \"include\": \"$self\" }]\n");
+  grammar.push_str("    }\n");
+  grammar.push_str("  }\n");
+  grammar.push_str("}\n");
+  grammar
+}
+
+// Patterns are written with `#` around them, and start with `\V` so only backslashes are special
+fn vim_escape(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+// A Vim syntax file, to be saved as `syntax/bang.vim`
+pub fn vim_syntax() -> String {
+  let mut syntax = String::new();
+  syntax.push_str("\" Vim syntax file for Bang, generated by `bang highlight --vim`\n");
+  syntax.push_str("if exists('b:current_syntax')\n  finish\nendif\n\n");
+
+  let words = |words: Vec<&str>| words.join(" ");
+  let _ = writeln!(
+    syntax,
+    "syntax keyword bangKeyword {}",
+    words(spellings(&KEYWORDS).collect())
+  );
+  let _ = writeln!(
+    syntax,
+    "syntax keyword bangConstant {}",
+    words(spellings(&LITERALS).collect())
+  );
+  let _ = writeln!(
+    syntax,
+    "syntax keyword bangOperator {}",
+    words(word_operators().collect())
+  );
+
+  let operators: Vec<_> = symbol_operators().map(vim_escape).collect();
+  let _ = writeln!(
+    syntax,
+    "syntax match bangOperator #\\V{}#",
+    operators.join("\\|")
+  );
+  syntax.push_str("syntax match bangNumber #\\<\\d[0-9_]*\\%(\\.\\d[0-9_]*\\)\\=#\n");
+  syntax.push_str("syntax match bangNumber #\\.\\d[0-9_]*#\n");
+
+  for quote in QUOTES {
+    let quote = vim_escape(&quote.to_string());
+    let _ = writeln!(
+      syntax,
+      "syntax region bangString start=#\\V{quote}# end=#\\V{quote}# contains=bangInterpolation"
+    );
+  }
+  let _ = writeln!(
+    syntax,
+    "syntax region bangInterpolation matchgroup=bangDelimiter start=#\\V{}# end=#}}# contained contains=TOP",
+    vim_escape(FORMAT_STRING_START)
+  );
+  let _ = writeln!(
+    syntax,
+    "syntax match bangComment #\\V{}\\.\\*# contains=@Spell",
+    vim_escape(COMMENT_START)
+  );
+
+  syntax.push('\n');
+  for (group, link) in [
+    ("Keyword", "Keyword"),
+    ("Constant", "Constant"),
+    ("Operator", "Operator"),
+    ("Number", "Number"),
+    ("String", "String"),
+    ("Delimiter", "Delimiter"),
+    ("Comment", "Comment"),
+  ] {
+    let _ = writeln!(syntax, "highlight default link bang{group} {link}");
+  }
+
+  syntax.push_str("\nlet b:current_syntax = 'bang'\n");
+  syntax
+}
+
+fn tree_sitter_list(tokens: impl Iterator<Item = &'static str>, capture: &str) -> String {
+  let tokens: Vec<_> = tokens
+    .map(|token| format!("  {}", json_string(token)))
+    .collect();
+  format!("[\n{}\n] @{capture}\n\n", tokens.join("\n"))
+}
+
+// A `highlights.scm` query, matching the keywords and operators of a Bang tree-sitter grammar
+pub fn tree_sitter_highlights() -> String {
+  let mut query = String::new();
+  query.push_str("; Highlights for Bang, generated by `bang highlight --tree-sitter-query`\n\n");
+
+  query.push_str(&tree_sitter_list(spellings(&KEYWORDS), "keyword"));
+  for (spelling, token_type) in LITERALS {
+    let capture = match token_type {
+      TokenType::True | TokenType::False => "boolean",
+      _ => "constant.builtin",
+    };
+    let _ = writeln!(query, "{} @{capture}", json_string(spelling));
+  }
+  query.push('\n');
+  query.push_str(&tree_sitter_list(spellings(&OPERATORS), "operator"));
+
+  let (brackets, delimiters): (Vec<_>, Vec<_>) =
+    spellings(&PUNCTUATION).partition(|spelling| "()[]{}".contains(spelling));
+  query.push_str(&tree_sitter_list(
+    brackets.into_iter(),
+    "punctuation.bracket",
+  ));
+  query.push_str(&tree_sitter_list(
+    delimiters.into_iter(),
+    "punctuation.delimiter",
+  ));
+
+  let _ = writeln!(
+    query,
+    "{} @punctuation.special\n",
+    json_string(FORMAT_STRING_START)
+  );
+  query.push_str("(comment) @comment\n");
+  query.push_str("(string) @string\n");
+  query.push_str("(number) @number\n");
+  query.push_str("(identifier) @variable\n");
+  query
+}
@@ -1,14 +1,40 @@
 #![feature(let_chains)]
 
+mod complexity;
+mod config;
 mod formatter;
+mod highlight;
 mod linter;
+mod purity;
 mod typechecker;
+mod workspace;
 
 // Check an AST for common problems
-pub use linter::{lint, Diagnostic as LintDiagnostic};
+pub use linter::{
+  builtin_rules as builtin_lint_rules, lint, lint_with, lint_with_builtins,
+  lint_with_protected_globals, Diagnostic as LintDiagnostic, Fix as LintFix, Rule as LintRule,
+};
 
 // Format an AST in a opinionated manner
-pub use formatter::format;
+pub use formatter::{format, format_with_options, FormatOptions, LineEnding, QuoteStyle};
+
+// Generate editor syntax highlighting from the tokens of the language
+pub use highlight::{textmate_grammar, tree_sitter_highlights, vim_syntax};
 
 // Typecheck the code
-pub use typechecker::{typecheck, Error as TypecheckError};
+pub use typechecker::{
+  completions, typecheck, typecheck_with_strictness, Completion, CompletionKind,
+  Error as TypecheckError, Strictness as TypecheckStrictness,
+};
+
+// Measure the branching and nesting of each function
+pub use complexity::{complexity, Complexity};
+
+// Find which functions are free of side effects
+pub use purity::{FunctionPurity, Purity};
+
+// Keep the results of the tools for a set of files, until they change
+pub use workspace::{FileResult, Typechecked, Workspace};
+
+// Load project settings from a `bang.toml` file
+pub use config::{Config, Error as ConfigError, CONFIG_FILENAME};
@@ -1,29 +1,68 @@
 mod constant_condition;
+mod nan_comparison;
+mod null_comparison;
+mod numeric_literals;
+mod protected_globals;
+mod pure_calls;
 mod side_effects;
+mod unknown_module;
 mod unused_variables;
-mod visitor;
 
 use bang_syntax::{
   ast::{
-    expression::{operators::Binary as BinaryOperator, Expr, Expression},
+    expression::{
+      operators::{
+        Assignment as AssignmentOperator, Binary as BinaryOperator, Unary as UnaryOperator,
+      },
+      Expr, Expression, LiteralType,
+    },
     statement::{Statement, Stmt},
-    Span,
+    Span, Visitor,
   },
-  Diagnostic as ParserDiagnostic, LineNumber, Parser,
+  ColumnNumber, Diagnostic as ParserDiagnostic, LineNumber, Parser,
 };
 use std::{error, fmt};
-use visitor::Visitor;
+
+const MAX_ELSE_IF_BRANCHES: usize = 5;
 
 trait LintRule {
   fn check(source: &str, ast: &[Statement]) -> Diagnostic;
 }
 
+// Rules from outside the crate, which can be run alongside the builtin rules
+pub trait Rule {
+  fn check(&self, source: &str, ast: &[Statement]) -> Diagnostic;
+}
+
+struct Builtin(fn(&str, &[Statement]) -> Diagnostic);
+impl Rule for Builtin {
+  fn check(&self, source: &str, ast: &[Statement]) -> Diagnostic {
+    (self.0)(source, ast)
+  }
+}
+
+struct ShadowingBuiltins<'a>(&'a [&'a str]);
+impl Rule for ShadowingBuiltins<'_> {
+  fn check(&self, source: &str, ast: &[Statement]) -> Diagnostic {
+    protected_globals::NoShadowingBuiltins::check(source, ast, self.0)
+  }
+}
+
+struct UnknownModule<'a>(&'a [&'a str]);
+impl Rule for UnknownModule<'_> {
+  fn check(&self, source: &str, ast: &[Statement]) -> Diagnostic {
+    unknown_module::NoUnknownModule::check(source, ast, self.0)
+  }
+}
+
 #[derive(Debug)]
 pub struct Diagnostic {
   pub title: String,
   pub message: String,
   pub spans: Vec<Span>,
   pub lines: Vec<LineNumber>,
+  pub columns: Vec<ColumnNumber>,
+  pub fixes: Vec<Fix>,
 }
 impl fmt::Display for Diagnostic {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -35,13 +74,20 @@ impl fmt::Display for Diagnostic {
       self
         .lines
         .iter()
-        .map(ToString::to_string)
+        .zip(&self.columns)
+        .map(|(line, column)| format!("{line}:{column}"))
         .collect::<Vec<_>>()
         .join(", ")
     )
   }
 }
 impl error::Error for Diagnostic {}
+
+#[derive(Debug)]
+pub struct Fix {
+  pub span: Span,
+  pub replacement: String,
+}
 impl From<ParserDiagnostic> for Diagnostic {
   fn from(diagnostic: ParserDiagnostic) -> Self {
     Self {
@@ -49,6 +95,8 @@ impl From<ParserDiagnostic> for Diagnostic {
       message: diagnostic.message,
       spans: vec![diagnostic.span],
       lines: vec![diagnostic.line],
+      columns: vec![diagnostic.column],
+      fixes: Vec::new(),
     }
   }
 }
@@ -103,7 +151,9 @@ macro_rules! lint_rule {
           title: $title.to_string(),
           message: $message.to_string(),
           lines: visitor.issues.iter().map(|span| span.get_line_number(source)).collect(),
+          columns: visitor.issues.iter().map(|span| span.get_column_number(source)).collect(),
           spans: visitor.issues,
+          fixes: Vec::new(),
         }
       }
     }
@@ -154,7 +204,7 @@ lint_rule! {
       if let Expr::Assignment {
         identifier,
         expression,
-        ..
+        assignment_operator: None,
       } = &expression.expr
       {
         if let Expr::Variable { name, .. } = &expression.expr
@@ -167,10 +217,106 @@ lint_rule! {
   }
 }
 
+lint_rule! {
+  name: NoAssignmentInCondition;
+  title: "No Assignment In Condition";
+  message: "Assignment in a condition is likely meant to be a comparison with `==`, if not wrap it in parentheses";
+  visitor: {
+    fn exit_statement(&mut self, statement: &Statement) {
+      if let Stmt::If { condition, .. } | Stmt::While { condition, .. } = &statement.stmt
+        && let Expr::Assignment { assignment_operator: None, .. }
+          | Expr::IndexAssignment { assignment_operator: None, .. } = condition.expr
+      {
+        self.issues.push(condition.span);
+      }
+    }
+  }
+}
+
+lint_rule! {
+  name: NoFloatEquality;
+  title: "No Float Equality";
+  message: "Decimal numbers may not be represented exactly, use `maths::approxEqual` to compare with a tolerance";
+  visitor: {
+    fn exit_expression(&mut self, expression: &Expression) {
+      let is_decimal = |expression: &Expression| matches!(
+        expression.expr,
+        Expr::Literal { type_: LiteralType::Number, value } if Parser::number(value).fract() != 0.0
+      );
+
+      if let Expr::Binary { left, right, operator, ..} = &expression.expr
+        && let BinaryOperator::Equal | BinaryOperator::NotEqual = operator
+        && (is_decimal(left) || is_decimal(right))
+      {
+        self.issues.push(expression.span);
+      }
+    }
+  }
+}
+
+fn unwrap_group<'a, 's>(expression: &'a Expression<'s>) -> &'a Expression<'s> {
+  match &expression.expr {
+    Expr::Group { expression } | Expr::Comment { expression, .. } => unwrap_group(expression),
+    _ => expression,
+  }
+}
+
+fn source_text(source: &str, span: Span) -> &str {
+  &source[span.start as usize..span.end as usize]
+}
+
+fn is_zero(expression: &Expression) -> bool {
+  match &expression.expr {
+    Expr::Literal {
+      type_: LiteralType::Number,
+      value,
+    } => Parser::number(value) == 0.0,
+    Expr::Group { expression }
+    | Expr::Comment { expression, .. }
+    | Expr::Unary {
+      operator: UnaryOperator::Minus,
+      expression,
+    } => is_zero(expression),
+    _ => false,
+  }
+}
+
+lint_rule! {
+  name: NoDivisionByZero;
+  title: "No Division By Zero";
+  message: "Dividing by zero gives infinity or NaN, and is an error when checked arithmetic is enabled";
+  visitor: {
+    fn exit_expression(&mut self, expression: &Expression) {
+      let denominator = match &expression.expr {
+        Expr::Binary {
+          operator: BinaryOperator::Divide | BinaryOperator::Remainder,
+          right,
+          ..
+        } => right,
+        Expr::Assignment {
+          assignment_operator: Some(AssignmentOperator::Divide),
+          expression,
+          ..
+        }
+        | Expr::IndexAssignment {
+          assignment_operator: Some(AssignmentOperator::Divide),
+          value: expression,
+          ..
+        } => expression,
+        _ => return,
+      };
+
+      if is_zero(denominator) {
+        self.issues.push(expression.span);
+      }
+    }
+  }
+}
+
 lint_rule! {
   name: NoUnreachable;
   title: "No Unreachable Code";
-  message: "Code after a return can never be executed";
+  message: "Code after a return or break can never be executed";
   visitor: {
     fn exit_statement(&mut self, statement: &Statement) {
       if let Stmt::Block { body, .. } = &statement.stmt {
@@ -181,7 +327,7 @@ lint_rule! {
             break;
           }
 
-          if let Stmt::Return { .. } = statement.stmt {
+          if let Stmt::Return { .. } | Stmt::Break { .. } = statement.stmt {
             seen_return = true;
           }
         }
@@ -190,16 +336,92 @@ lint_rule! {
   }
 }
 
+lint_rule! {
+  name: NoLongElseIfChain;
+  title: "No Long Else If Chain";
+  message: "Long `else if` chains are hard to follow, consider a dictionary lookup or returning early";
+  data: Vec<Span>;
+  visitor: {
+    fn enter_statement(&mut self, statement: &Statement) {
+      // Each `else if` is the start of a shorter chain, so only the first branch is checked
+      if self.data.contains(&statement.span) {
+        return;
+      }
+
+      let mut branches = 0;
+      let mut current = statement;
+      while let Stmt::If { otherwise, .. } = &current.stmt {
+        branches += 1;
+        self.data.push(current.span);
+
+        match otherwise {
+          Some(otherwise) => current = otherwise,
+          None => break,
+        }
+      }
+
+      if branches >= MAX_ELSE_IF_BRANCHES {
+        self.issues.push(statement.span);
+      }
+    }
+  }
+}
+
 pub fn lint(source: &str, ast: &[Statement]) -> Vec<Diagnostic> {
-  let mut results = vec![
-    NoYodaEquality::check(source, ast),
-    NoNegativeZero::check(source, ast),
-    NoSelfAssign::check(source, ast),
-    NoUnreachable::check(source, ast),
-    constant_condition::NoConstantCondition::check(source, ast),
-    side_effects::NoSideEffectInIndex::check(source, ast),
-    unused_variables::NoUnusedVariables::check(source, ast),
-  ];
+  lint_with_protected_globals(source, ast, &[])
+}
+
+pub fn lint_with_protected_globals(
+  source: &str,
+  ast: &[Statement],
+  protected_globals: &[&str],
+) -> Vec<Diagnostic> {
+  lint_with_builtins(source, ast, protected_globals, &[])
+}
+
+// Uses the globals and modules of the runtime, such as from `Context`
+pub fn lint_with_builtins(
+  source: &str,
+  ast: &[Statement],
+  protected_globals: &[&str],
+  modules: &[&str],
+) -> Vec<Diagnostic> {
+  let rules = builtin_rules(protected_globals, modules);
+  let rules: Vec<&dyn Rule> = rules.iter().map(AsRef::as_ref).collect();
+
+  lint_with(source, ast, &rules)
+}
+
+pub fn builtin_rules<'a>(
+  protected_globals: &'a [&'a str],
+  modules: &'a [&'a str],
+) -> Vec<Box<dyn Rule + 'a>> {
+  vec![
+    Box::new(Builtin(NoYodaEquality::check)),
+    Box::new(Builtin(NoNegativeZero::check)),
+    Box::new(Builtin(NoSelfAssign::check)),
+    Box::new(Builtin(NoAssignmentInCondition::check)),
+    Box::new(Builtin(NoUnreachable::check)),
+    Box::new(Builtin(NoLongElseIfChain::check)),
+    Box::new(Builtin(NoFloatEquality::check)),
+    Box::new(Builtin(nan_comparison::NoNanComparison::check)),
+    Box::new(Builtin(NoDivisionByZero::check)),
+    Box::new(Builtin(numeric_literals::NoPrecisionLoss::check)),
+    Box::new(Builtin(numeric_literals::NoUnevenDigitGrouping::check)),
+    Box::new(Builtin(numeric_literals::NoLeadingZeros::check)),
+    Box::new(Builtin(constant_condition::NoConstantCondition::check)),
+    Box::new(Builtin(side_effects::NoSideEffectInIndex::check)),
+    Box::new(Builtin(unused_variables::NoUnusedVariables::check)),
+    Box::new(Builtin(null_comparison::PreferNullishCoalescing::check)),
+    Box::new(Builtin(null_comparison::NoNegatedNullComparison::check)),
+    Box::new(UnknownModule(modules)),
+    Box::new(ShadowingBuiltins(protected_globals)),
+    Box::new(Builtin(pure_calls::NoUnusedPureResult::check)),
+  ]
+}
+
+pub fn lint_with(source: &str, ast: &[Statement], rules: &[&dyn Rule]) -> Vec<Diagnostic> {
+  let mut results: Vec<_> = rules.iter().map(|rule| rule.check(source, ast)).collect();
 
   results.retain(|r| !r.lines.is_empty());
   results
@@ -7,7 +7,7 @@ use bang_syntax::ast::{
 
 pub fn is_constant(expr: &Expr) -> bool {
   match expr {
-    Expr::Call { .. } | Expr::Variable { .. } => false,
+    Expr::Call { .. } | Expr::Variable { .. } | Expr::Loop { .. } | Expr::Do { .. } => false,
     Expr::Function { .. }
     | Expr::Literal { .. }
     | Expr::ModuleAccess { .. }
@@ -16,6 +16,9 @@ pub fn is_constant(expr: &Expr) -> bool {
     Expr::Group { expression, .. }
     | Expr::Unary { expression, .. }
     | Expr::Assignment { expression, .. }
+    | Expr::Cast { expression, .. }
+    | Expr::Ascription { expression, .. }
+    | Expr::NonNull { expression }
     | Expr::Comment { expression, .. } => is_constant(&expression.expr),
     Expr::Binary {
       left,
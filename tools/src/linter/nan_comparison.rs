@@ -0,0 +1,76 @@
+use super::{source_text, unwrap_group, Diagnostic, Fix, LintRule, Visitor};
+use bang_syntax::ast::{
+  expression::{operators, Expr, Expression},
+  statement::Statement,
+  Span,
+};
+
+fn is_nan(expression: &Expression) -> bool {
+  matches!(
+    unwrap_group(expression).expr,
+    Expr::ModuleAccess {
+      module: "maths",
+      item: "NAN"
+    }
+  )
+}
+
+#[derive(Default)]
+pub struct NoNanComparison<'s> {
+  source: &'s str,
+  issues: Vec<Span>,
+  fixes: Vec<Fix>,
+}
+impl LintRule for NoNanComparison<'_> {
+  fn check(source: &str, ast: &[Statement]) -> Diagnostic {
+    let mut visitor = NoNanComparison {
+      source,
+      ..Default::default()
+    };
+    visitor.visit(ast);
+
+    Diagnostic {
+      title: "No NaN Comparison".to_string(),
+      message: "Whether a value equals NaN depends on how the NaN was made and the equality mode, so use `maths::isNaN` to check for it"
+        .to_string(),
+      lines: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: visitor.issues,
+      fixes: visitor.fixes,
+    }
+  }
+}
+impl Visitor for NoNanComparison<'_> {
+  fn exit_expression(&mut self, expression: &Expression) {
+    if let Expr::Binary {
+      operator: operator @ (operators::Binary::Equal | operators::Binary::NotEqual),
+      left,
+      right,
+    } = &expression.expr
+      && (is_nan(left) || is_nan(right))
+    {
+      let value = if is_nan(left) { right } else { left };
+      let negation = match operator {
+        operators::Binary::NotEqual => "!",
+        _ => "",
+      };
+
+      self.issues.push(expression.span);
+      self.fixes.push(Fix {
+        span: expression.span,
+        replacement: format!(
+          "{negation}maths::isNaN({})",
+          source_text(self.source, value.span)
+        ),
+      });
+    }
+  }
+}
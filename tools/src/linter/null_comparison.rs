@@ -0,0 +1,229 @@
+use super::{source_text, unwrap_group, Diagnostic, Fix, LintRule, Visitor};
+use bang_syntax::ast::{
+  expression::{operators, Expr, Expression, LiteralType},
+  statement::{Statement, Stmt},
+  Span,
+};
+
+fn is_null(expression: &Expression) -> bool {
+  matches!(
+    unwrap_group(expression).expr,
+    Expr::Literal {
+      type_: LiteralType::Null,
+      ..
+    }
+  )
+}
+
+fn null_comparison<'a, 's>(
+  expression: &'a Expression<'s>,
+) -> Option<(operators::Binary, &'a Expression<'s>)> {
+  if let Expr::Binary {
+    operator: operator @ (operators::Binary::Equal | operators::Binary::NotEqual),
+    left,
+    right,
+  } = &unwrap_group(expression).expr
+  {
+    if is_null(right) {
+      return Some((*operator, left));
+    }
+    if is_null(left) {
+      return Some((*operator, right));
+    }
+  }
+
+  None
+}
+
+fn single_assignment<'a, 's>(
+  statement: &'a Statement<'s>,
+) -> Option<(&'s str, &'a Expression<'s>)> {
+  match &statement.stmt {
+    Stmt::Block { body } if body.len() == 1 => single_assignment(&body[0]),
+    Stmt::Expression { expression } => match &expression.expr {
+      Expr::Assignment {
+        identifier,
+        expression,
+        assignment_operator: None,
+      } => Some((identifier, expression)),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+fn is_variable(expression: &Expression, name: &str) -> bool {
+  matches!(&unwrap_group(expression).expr, Expr::Variable { name: n } if *n == name)
+}
+
+fn nullish_operand(source: &str, expression: &Expression) -> String {
+  let text = source_text(source, expression.span);
+
+  match &expression.expr {
+    Expr::Assignment { .. }
+    | Expr::Binary {
+      operator:
+        operators::Binary::And
+        | operators::Binary::Or
+        | operators::Binary::Pipeline
+        | operators::Binary::Compose,
+      ..
+    } => format!("({text})"),
+    _ => text.to_string(),
+  }
+}
+
+#[derive(Default)]
+pub struct PreferNullishCoalescing<'s> {
+  source: &'s str,
+  issues: Vec<Span>,
+  fixes: Vec<Fix>,
+}
+impl PreferNullishCoalescing<'_> {
+  fn add_issue(&mut self, span: Span, target: &str, value: &str, default: &Expression) {
+    let default = nullish_operand(self.source, default);
+
+    self.issues.push(span);
+    self.fixes.push(Fix {
+      span,
+      replacement: format!("{target} = {value} ?? {default}"),
+    });
+  }
+
+  fn check_if_statement(
+    &mut self,
+    span: Span,
+    name: &str,
+    operator: operators::Binary,
+    (then_target, then_value): (&str, &Expression),
+    otherwise: Option<(&str, &Expression)>,
+  ) {
+    match (operator, otherwise) {
+      (operators::Binary::Equal, None) if then_target == name => {
+        self.add_issue(span, name, name, then_value);
+      }
+      (operators::Binary::Equal, Some((else_target, else_value)))
+        if then_target == else_target && is_variable(else_value, name) =>
+      {
+        self.add_issue(span, then_target, name, then_value);
+      }
+      (operators::Binary::NotEqual, Some((else_target, else_value)))
+        if then_target == else_target && is_variable(then_value, name) =>
+      {
+        self.add_issue(span, then_target, name, else_value);
+      }
+      _ => {}
+    }
+  }
+}
+impl LintRule for PreferNullishCoalescing<'_> {
+  fn check(source: &str, ast: &[Statement]) -> Diagnostic {
+    let mut visitor = PreferNullishCoalescing {
+      source,
+      ..Default::default()
+    };
+    visitor.visit(ast);
+
+    Diagnostic {
+      title: "Prefer Nullish Coalescing".to_string(),
+      message: "Checking for null before assigning a default can be replaced with `??`".to_string(),
+      lines: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: visitor.issues,
+      fixes: visitor.fixes,
+    }
+  }
+}
+impl Visitor for PreferNullishCoalescing<'_> {
+  fn exit_statement(&mut self, statement: &Statement) {
+    if let Stmt::If {
+      condition,
+      then,
+      otherwise,
+    } = &statement.stmt
+      && let Some((operator, variable)) = null_comparison(condition)
+      && let Expr::Variable { name } = variable.expr
+      && let Some((then_target, then_value)) = single_assignment(then)
+    {
+      self.check_if_statement(
+        statement.span,
+        name,
+        operator,
+        (then_target, then_value),
+        otherwise.as_deref().and_then(single_assignment),
+      );
+    }
+  }
+}
+
+#[derive(Default)]
+pub struct NoNegatedNullComparison<'s> {
+  source: &'s str,
+  issues: Vec<Span>,
+  fixes: Vec<Fix>,
+}
+impl LintRule for NoNegatedNullComparison<'_> {
+  fn check(source: &str, ast: &[Statement]) -> Diagnostic {
+    let mut visitor = NoNegatedNullComparison {
+      source,
+      ..Default::default()
+    };
+    visitor.visit(ast);
+
+    Diagnostic {
+      title: "No Negated Null Comparison".to_string(),
+      message: "Negating a comparison with null is harder to read than using the opposite operator"
+        .to_string(),
+      lines: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: visitor.issues,
+      fixes: visitor.fixes,
+    }
+  }
+}
+impl Visitor for NoNegatedNullComparison<'_> {
+  fn exit_expression(&mut self, expression: &Expression) {
+    if let Expr::Unary {
+      operator: operators::Unary::Not,
+      expression: inner,
+    } = &expression.expr
+      && let Expr::Binary {
+        operator,
+        left,
+        right,
+      } = &unwrap_group(inner).expr
+      && null_comparison(inner).is_some()
+    {
+      let operator = match operator {
+        operators::Binary::Equal => operators::Binary::NotEqual,
+        _ => operators::Binary::Equal,
+      };
+
+      self.issues.push(expression.span);
+      self.fixes.push(Fix {
+        span: expression.span,
+        replacement: format!(
+          "{} {operator} {}",
+          source_text(self.source, left.span),
+          source_text(self.source, right.span)
+        ),
+      });
+    }
+  }
+}
@@ -0,0 +1,95 @@
+use super::{lint_rule, Diagnostic, LintRule, Visitor};
+use bang_syntax::{
+  ast::{
+    expression::{Expr, Expression, LiteralType},
+    statement::Statement,
+    Span,
+  },
+  Parser,
+};
+
+fn number_literal<'s>(expression: &Expression<'s>) -> Option<&'s str> {
+  match expression.expr {
+    Expr::Literal {
+      type_: LiteralType::Number,
+      value,
+    } => Some(value),
+    _ => None,
+  }
+}
+
+fn integer_part(literal: &str) -> &str {
+  literal.split('.').next().unwrap_or_default()
+}
+
+fn is_rounded(literal: &str) -> bool {
+  if literal.contains('.') {
+    return false;
+  }
+
+  let digits = literal.replace('_', "");
+  let digits = match digits.trim_start_matches('0') {
+    "" => "0",
+    digits => digits,
+  };
+
+  format!("{:.0}", Parser::number(literal)) != digits
+}
+
+fn has_uneven_grouping(literal: &str) -> bool {
+  let mut groups = integer_part(literal).split('_');
+  let first = groups.next().unwrap_or_default();
+
+  literal.contains('_')
+    && (first.is_empty() || first.len() > 3 || groups.any(|group| group.len() != 3))
+}
+
+fn has_leading_zero(literal: &str) -> bool {
+  let integer = integer_part(literal);
+  integer.len() > 1 && integer.starts_with('0')
+}
+
+lint_rule! {
+  name: NoPrecisionLoss;
+  title: "No Precision Loss";
+  message: "Number literal is too large to be represented exactly, so it will be rounded";
+  visitor: {
+    fn exit_expression(&mut self, expression: &Expression) {
+      if let Some(literal) = number_literal(expression)
+        && is_rounded(literal)
+      {
+        self.issues.push(expression.span);
+      }
+    }
+  }
+}
+
+lint_rule! {
+  name: NoUnevenDigitGrouping;
+  title: "No Uneven Digit Grouping";
+  message: "Digit separators should split numbers into groups of three, `1_00` looks like 1000 but is 100";
+  visitor: {
+    fn exit_expression(&mut self, expression: &Expression) {
+      if let Some(literal) = number_literal(expression)
+        && has_uneven_grouping(literal)
+      {
+        self.issues.push(expression.span);
+      }
+    }
+  }
+}
+
+lint_rule! {
+  name: NoLeadingZeros;
+  title: "No Leading Zeros";
+  message: "Leading zeros don't make a number octal, `0123` is the same as `123`";
+  visitor: {
+    fn exit_expression(&mut self, expression: &Expression) {
+      if let Some(literal) = number_literal(expression)
+        && has_leading_zero(literal)
+      {
+        self.issues.push(expression.span);
+      }
+    }
+  }
+}
@@ -0,0 +1,81 @@
+use super::{Diagnostic, Visitor};
+use bang_syntax::ast::{
+  expression::{Expr, Expression},
+  statement::{Statement, Stmt},
+  Span,
+};
+
+pub struct NoShadowingBuiltins<'a> {
+  protected_globals: &'a [&'a str],
+  issues: Vec<Span>,
+}
+impl<'a> NoShadowingBuiltins<'a> {
+  pub fn check(source: &str, ast: &[Statement], protected_globals: &'a [&'a str]) -> Diagnostic {
+    let mut visitor = Self {
+      protected_globals,
+      issues: Vec::new(),
+    };
+    visitor.visit(ast);
+
+    Diagnostic {
+      title: "No Shadowing Builtins".to_string(),
+      message: "Redefining a builtin makes it unavailable to the rest of the scope".to_string(),
+      lines: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: visitor.issues,
+      fixes: Vec::new(),
+    }
+  }
+
+  fn is_protected(&self, name: &str) -> bool {
+    self.protected_globals.contains(&name)
+  }
+}
+impl Visitor for NoShadowingBuiltins<'_> {
+  fn enter_statement(&mut self, statement: &Statement) {
+    match &statement.stmt {
+      Stmt::Declaration { identifier, .. } => {
+        let shadows = identifier
+          .get_names()
+          .iter()
+          .any(|name| self.is_protected(name));
+
+        if shadows {
+          self.issues.push(statement.span);
+        }
+      }
+      Stmt::Import { items, .. } => {
+        for item in items {
+          if self.is_protected(item.get_name()) {
+            self.issues.push(item.span);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn enter_expression(&mut self, expression: &Expression) {
+    match &expression.expr {
+      Expr::Assignment { identifier, .. } if self.is_protected(identifier) => {
+        self.issues.push(expression.span);
+      }
+      Expr::Function { parameters, .. } => {
+        for parameter in parameters {
+          if self.is_protected(parameter.name) {
+            self.issues.push(parameter.span);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+}
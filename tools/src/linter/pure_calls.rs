@@ -0,0 +1,51 @@
+use super::{Diagnostic, Visitor};
+use crate::purity::Purity;
+use bang_syntax::ast::{
+  expression::Expr,
+  statement::{Statement, Stmt},
+  Span,
+};
+
+pub struct NoUnusedPureResult {
+  purity: Purity,
+  issues: Vec<Span>,
+}
+impl NoUnusedPureResult {
+  pub fn check(source: &str, ast: &[Statement]) -> Diagnostic {
+    let mut visitor = Self {
+      purity: Purity::analyse(source, ast),
+      issues: Vec::new(),
+    };
+    visitor.visit(ast);
+
+    Diagnostic {
+      title: "No Unused Pure Result".to_string(),
+      message: "Calling a function without side effects does nothing if the result is unused"
+        .to_string(),
+      lines: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: visitor
+        .issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: visitor.issues,
+      fixes: Vec::new(),
+    }
+  }
+}
+impl Visitor for NoUnusedPureResult {
+  fn enter_statement(&mut self, statement: &Statement) {
+    if let Stmt::Expression { expression } = &statement.stmt
+      && let Expr::Call {
+        expression: callee, ..
+      } = &expression.expr
+      && self.purity.is_pure_call(callee)
+    {
+      self.issues.push(statement.span);
+    }
+  }
+}
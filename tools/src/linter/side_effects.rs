@@ -7,13 +7,20 @@ use bang_syntax::ast::{
 
 fn has_possible_side_effect(expression: &Expr) -> bool {
   match expression {
-    Expr::Assignment { .. } | Expr::IndexAssignment { .. } | Expr::Call { .. } => true,
+    Expr::Assignment { .. }
+    | Expr::IndexAssignment { .. }
+    | Expr::Call { .. }
+    | Expr::Do { .. }
+    | Expr::Loop { .. } => true,
     Expr::Function { .. }
     | Expr::Literal { .. }
     | Expr::Variable { .. }
     | Expr::ModuleAccess { .. } => false,
     Expr::Comment { expression, .. }
+    | Expr::Cast { expression, .. }
+    | Expr::Ascription { expression, .. }
     | Expr::Group { expression }
+    | Expr::NonNull { expression }
     | Expr::Unary { expression, .. } => has_possible_side_effect(&expression.expr),
     Expr::Index { expression, index } => {
       has_possible_side_effect(&expression.expr) || has_possible_side_effect(&index.expr)
@@ -0,0 +1,65 @@
+use super::{Diagnostic, Visitor};
+use bang_syntax::ast::{
+  expression::{Expr, Expression},
+  statement::{Statement, Stmt},
+  Span,
+};
+
+pub struct NoUnknownModule<'a> {
+  modules: &'a [&'a str],
+  imported: Vec<String>,
+  accesses: Vec<(Span, String)>,
+}
+impl<'a> NoUnknownModule<'a> {
+  pub fn check(source: &str, ast: &[Statement], modules: &'a [&'a str]) -> Diagnostic {
+    let mut visitor = Self {
+      modules,
+      imported: Vec::new(),
+      accesses: Vec::new(),
+    };
+    visitor.visit(ast);
+
+    // Without the modules of the runtime, every module would be unknown
+    let issues: Vec<_> = if modules.is_empty() {
+      Vec::new()
+    } else {
+      (visitor.accesses.iter())
+        .filter(|(_, module)| !visitor.is_known(module))
+        .map(|(span, _)| *span)
+        .collect()
+    };
+
+    Diagnostic {
+      title: "No Unknown Module".to_string(),
+      message: "Module is not in the standard library or imported in this file, so accessing it may fail at runtime".to_string(),
+      lines: issues
+        .iter()
+        .map(|span| span.get_line_number(source))
+        .collect(),
+      columns: issues
+        .iter()
+        .map(|span| span.get_column_number(source))
+        .collect(),
+      spans: issues,
+      fixes: Vec::new(),
+    }
+  }
+
+  fn is_known(&self, module: &str) -> bool {
+    self.modules.contains(&module) || self.imported.iter().any(|name| name == module)
+  }
+}
+impl Visitor for NoUnknownModule<'_> {
+  fn enter_statement(&mut self, statement: &Statement) {
+    if let Stmt::Import { module, alias, .. } = &statement.stmt {
+      self.imported.push((*module).to_string());
+      self.imported.extend(alias.map(str::to_string));
+    }
+  }
+
+  fn enter_expression(&mut self, expression: &Expression) {
+    if let Expr::ModuleAccess { module, .. } = &expression.expr {
+      self.accesses.push((expression.span, (*module).to_string()));
+    }
+  }
+}
@@ -1,7 +1,7 @@
 use super::{lint_rule, Diagnostic, LintRule, Visitor};
 use bang_syntax::ast::{
   expression::{Expr, Expression},
-  statement::{DeclarationIdentifier, Statement, Stmt},
+  statement::{Statement, Stmt},
   Span,
 };
 
@@ -75,7 +75,11 @@ lint_rule! {
 
     fn enter_expression(&mut self, expression: &Expression) {
       if let Expr::Function { parameters, .. } = &expression.expr {
-        parameters.iter().for_each(|param| self.data.define(param.name, param.span));
+        // A trailing function's implicit `it` starts at the arrow, and doesn't need to be used
+        parameters
+          .iter()
+          .filter(|param| param.span.start != expression.span.start)
+          .for_each(|param| self.data.define(param.name, param.span));
       }
     }
 
@@ -85,14 +89,8 @@ lint_rule! {
       }
 
       if let Stmt::Declaration { identifier, .. } = &statement.stmt {
-        match identifier {
-          DeclarationIdentifier::Variable(name) => self.data.define(name, statement.span),
-          DeclarationIdentifier::Ordered(names) => names
-            .iter()
-            .for_each(|name| self.data.define(name, statement.span)),
-          DeclarationIdentifier::Named(names) => names
-            .iter()
-            .for_each(|name| self.data.define(name.get_name(), statement.span)),
+        for name in identifier.get_names() {
+          self.data.define(name, statement.span);
         }
       }
     }
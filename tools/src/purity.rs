@@ -0,0 +1,308 @@
+use bang_syntax::{
+  ast::{
+    expression::{operators::Binary as BinaryOperator, Expr, Expression},
+    statement::{Statement, Stmt},
+    Visitor,
+  },
+  LineNumber,
+};
+use std::collections::{HashMap, HashSet};
+
+const PURE_GLOBALS: [&str; 4] = ["type", "toString", "inspect", "deepCopy"];
+
+fn is_pure_native(module: &str, item: &str) -> bool {
+  match module {
+    "maths" => matches!(
+      item,
+      "floor"
+        | "ceil"
+        | "round"
+        | "abs"
+        | "sqrt"
+        | "cbrt"
+        | "sin"
+        | "cos"
+        | "tan"
+        | "asin"
+        | "acos"
+        | "atan"
+        | "sinh"
+        | "cosh"
+        | "tanh"
+        | "asinh"
+        | "acosh"
+        | "atanh"
+        | "isNan"
+        | "isNaN"
+        | "isFinite"
+        | "exp"
+        | "ln"
+        | "pow"
+        | "log"
+        | "radiansToDegrees"
+        | "degreesToRadians"
+        | "approxEqual"
+    ),
+    "string" => matches!(
+      item,
+      "length"
+        | "trim"
+        | "trimStart"
+        | "trimEnd"
+        | "repeat"
+        | "includes"
+        | "startsWith"
+        | "endsWith"
+        | "toUpperCase"
+        | "toLowerCase"
+        | "replace"
+        | "replaceOne"
+        | "toNumber"
+        | "split"
+        | "format"
+        | "sprintf"
+    ),
+    "list" => matches!(
+      item,
+      "length" | "isEmpty" | "includes" | "reverse" | "get" | "toSet"
+    ),
+    "set" => matches!(
+      item,
+      "new"
+        | "set"
+        | "size"
+        | "isEmpty"
+        | "includes"
+        | "isDisjoint"
+        | "isSuperset"
+        | "isSubset"
+        | "union"
+        | "difference"
+        | "intersection"
+        | "symmetricDifference"
+        | "toList"
+        | "fromList"
+        | "copy"
+    ),
+    // `freeze` changes the dict, and `sortedKeys` calls the comparison it is given
+    "dict" => matches!(
+      item,
+      "new"
+        | "dict"
+        | "size"
+        | "isEmpty"
+        | "keys"
+        | "values"
+        | "get"
+        | "hasKey"
+        | "copy"
+        | "toSortedList"
+    ),
+    "function" => matches!(item, "name" | "arity"),
+    "iter" => matches!(item, "range" | "repeat" | "from"),
+    _ => false,
+  }
+}
+
+fn resolve<'a>(aliases: &'a HashMap<String, String>, module: &'a str) -> &'a str {
+  aliases.get(module).map_or(module, String::as_str)
+}
+
+#[derive(Debug)]
+pub struct FunctionPurity {
+  pub name: String,
+  pub line: LineNumber,
+  pub pure: bool,
+}
+
+struct Frame {
+  name: String,
+  line: LineNumber,
+  locals: HashSet<String>,
+  calls: Vec<String>,
+  impure: bool,
+}
+impl Frame {
+  fn new(name: &str, line: LineNumber) -> Self {
+    Self {
+      name: name.to_string(),
+      line,
+      locals: HashSet::new(),
+      calls: Vec::new(),
+      impure: false,
+    }
+  }
+}
+
+struct PurityVisitor<'a> {
+  source: &'a str,
+  frames: Vec<Frame>,
+  stack: Vec<usize>,
+  imports: HashMap<String, (String, String)>,
+  aliases: HashMap<String, String>,
+}
+impl PurityVisitor<'_> {
+  fn current(&mut self) -> &mut Frame {
+    let index = *self.stack.last().expect("Frame stack to have item");
+    &mut self.frames[index]
+  }
+
+  fn call(&mut self, callee: &Expression) {
+    match &callee.expr {
+      Expr::Variable { name } => self.current().calls.push((*name).to_string()),
+      Expr::ModuleAccess { module, item }
+        if is_pure_native(resolve(&self.aliases, module), item) => {}
+      _ => self.current().impure = true,
+    }
+  }
+}
+impl Visitor for PurityVisitor<'_> {
+  fn enter_statement(&mut self, statement: &Statement) {
+    match &statement.stmt {
+      Stmt::Declaration { identifier, .. } => {
+        let names = identifier.get_names();
+
+        let locals = &mut self.current().locals;
+        locals.extend(names.into_iter().map(str::to_string));
+      }
+      Stmt::Import {
+        module,
+        alias,
+        items,
+        ..
+      } => {
+        if let Some(alias) = alias {
+          self
+            .aliases
+            .insert((*alias).to_string(), (*module).to_string());
+        }
+
+        for item in items {
+          self.imports.insert(
+            item.get_name().to_string(),
+            ((*module).to_string(), item.name.to_string()),
+          );
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn enter_expression(&mut self, expression: &Expression) {
+    match &expression.expr {
+      Expr::Function {
+        name, parameters, ..
+      } => {
+        let line = expression.span.get_line_number(self.source);
+        let mut frame = Frame::new(name.unwrap_or(""), line);
+        frame.locals = parameters.iter().map(|p| p.name.to_string()).collect();
+
+        self.stack.push(self.frames.len());
+        self.frames.push(frame);
+      }
+      Expr::Assignment { identifier, .. } => {
+        let frame = self.current();
+        if !frame.locals.contains(*identifier) {
+          frame.impure = true;
+        }
+      }
+      Expr::IndexAssignment { .. } => self.current().impure = true,
+      Expr::Call { expression, .. } => self.call(expression),
+      Expr::Binary {
+        operator: BinaryOperator::Pipeline,
+        right,
+        ..
+      } => {
+        let right = match &right.expr {
+          Expr::Comment { expression, .. } => expression,
+          _ => right,
+        };
+
+        if !matches!(right.expr, Expr::Call { .. }) {
+          self.call(right);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn exit_expression(&mut self, expression: &Expression) {
+    if let Expr::Function { .. } = expression.expr {
+      self.stack.pop();
+    }
+  }
+}
+
+pub struct Purity {
+  pub functions: Vec<FunctionPurity>,
+  imports: HashMap<String, (String, String)>,
+  aliases: HashMap<String, String>,
+}
+impl Purity {
+  pub fn analyse(source: &str, ast: &[Statement]) -> Self {
+    let mut visitor = PurityVisitor {
+      source,
+      frames: vec![Frame::new("Root", 1)],
+      stack: vec![0],
+      imports: HashMap::new(),
+      aliases: HashMap::new(),
+    };
+    visitor.visit(ast);
+
+    let mut frames = visitor.frames;
+    frames.remove(0);
+
+    let mut purity = Self {
+      functions: frames
+        .iter()
+        .map(|frame| FunctionPurity {
+          name: frame.name.clone(),
+          line: frame.line,
+          pure: !frame.impure,
+        })
+        .collect(),
+      imports: visitor.imports,
+      aliases: visitor.aliases,
+    };
+
+    // Functions are impure if they call anything impure, repeat until nothing changes
+    let mut changed = true;
+    while changed {
+      changed = false;
+
+      for (index, frame) in frames.iter().enumerate() {
+        if purity.functions[index].pure
+          && frame
+            .calls
+            .iter()
+            .any(|name| !purity.is_pure_function(name))
+        {
+          purity.functions[index].pure = false;
+          changed = true;
+        }
+      }
+    }
+
+    purity
+  }
+
+  fn is_pure_function(&self, name: &str) -> bool {
+    let mut functions = self.functions.iter().filter(|f| f.name == name).peekable();
+
+    if functions.peek().is_some() {
+      functions.all(|function| function.pure)
+    } else if let Some((module, item)) = self.imports.get(name) {
+      is_pure_native(module, item)
+    } else {
+      PURE_GLOBALS.contains(&name)
+    }
+  }
+
+  pub fn is_pure_call(&self, callee: &Expression) -> bool {
+    match &callee.expr {
+      Expr::Variable { name } => self.is_pure_function(name),
+      Expr::ModuleAccess { module, item } => is_pure_native(resolve(&self.aliases, module), item),
+      _ => false,
+    }
+  }
+}
@@ -1,10 +1,12 @@
 mod builtins;
+mod completions;
 mod expressions;
 mod narrowing;
 mod statements;
 mod types;
 
 use builtins::register_globals;
+pub use completions::{completions, Completion, CompletionKind};
 use statements::StatementType;
 use types::{Existential, Function, Literal, Type};
 
@@ -12,9 +14,9 @@ use bang_syntax::ast::{
   expression::{Expr, Expression},
   statement::{Statement, Stmt},
   types::{Type as TypeItem, TypeExpression},
-  Span,
+  Span, Visitor,
 };
-use rustc_hash::FxHashMap as HashMap;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use std::{error, fmt};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -28,7 +30,10 @@ pub enum ErrorKind {
   NotCallable(Type),
   WrongNumberArguments(usize, usize),
   WrongNumberTypeParameters(usize, usize),
+  NoMatchingOverload(Vec<(Type, Error)>),
   InfiniteLoop,
+  ReadonlyAssignment(Type),
+  MissingAnnotation(String),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -41,6 +46,18 @@ impl Error {
     Err(Self { kind, span })
   }
 
+  // Whether the error is about a value or variable of type `never`
+  fn is_from_never(&self) -> bool {
+    match &self.kind {
+      ErrorKind::ExpectedDifferentType(a, b) => *a == Type::Never || *b == Type::Never,
+      ErrorKind::NotCallable(ty) | ErrorKind::ReadonlyAssignment(ty) => *ty == Type::Never,
+      ErrorKind::NoMatchingOverload(errors) => {
+        errors.iter().all(|(_, error)| error.is_from_never())
+      }
+      _ => false,
+    }
+  }
+
   pub fn get_title(&self) -> &'static str {
     match self.kind {
       ErrorKind::UnknownType(_) => "Unknown Type",
@@ -52,7 +69,10 @@ impl Error {
       ErrorKind::NotCallable(_) => "Type Not Callable",
       ErrorKind::WrongNumberArguments(_, _) => "Incorrect Number of Arguments",
       ErrorKind::WrongNumberTypeParameters(_, _) => "Incorrect Number of Type Parameters",
+      ErrorKind::NoMatchingOverload(_) => "No Matching Overload",
       ErrorKind::InfiniteLoop => "Infinite Loop",
+      ErrorKind::ReadonlyAssignment(_) => "Assignment To Readonly Value",
+      ErrorKind::MissingAnnotation(_) => "Missing Type Annotation",
     }
   }
 
@@ -75,9 +95,23 @@ impl Error {
       ErrorKind::WrongNumberTypeParameters(a, b) => {
         format!("Expected {b} type parameters, but recieved {a}.")
       }
+      ErrorKind::NoMatchingOverload(overloads) => {
+        let reasons: Vec<_> = overloads
+          .iter()
+          .map(|(ty, error)| format!("\n  {ty}: {}", error.get_description()))
+          .collect();
+
+        format!("No overload matches this call.{}", reasons.concat())
+      }
       ErrorKind::InfiniteLoop => {
         "Condition is always true and there is no return in the loop.".to_string()
       }
+      ErrorKind::ReadonlyAssignment(ty) => {
+        format!("Type '{ty}' is readonly, so it can't be assigned to.")
+      }
+      ErrorKind::MissingAnnotation(name) => {
+        format!("Parameter '{name}' needs a type annotation in strict mode.")
+      }
     }
   }
 }
@@ -88,6 +122,51 @@ impl fmt::Display for Error {
   }
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+  #[default]
+  Default,
+  Strict,
+  NoCheck,
+}
+impl Strictness {
+  // Set by a `// @strict` or `// @nocheck` comment before any code in the file
+  pub fn from_pragmas(ast: &[Statement]) -> Self {
+    let mut strictness = Self::Default;
+
+    for statement in ast {
+      if let Stmt::Comment { text } = statement.stmt {
+        match text.trim_start_matches('/').trim() {
+          "@strict" => strictness = Self::Strict,
+          "@nocheck" => strictness = Self::NoCheck,
+          _ => {}
+        }
+      } else {
+        break;
+      }
+    }
+
+    strictness
+  }
+
+  // A project can make files strict by default, but a file's pragma still takes priority
+  pub fn or_strict(self, strict: bool) -> Self {
+    match self {
+      Self::Default if strict => Self::Strict,
+      strictness => strictness,
+    }
+  }
+}
+impl fmt::Display for Strictness {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Default => write!(f, "default"),
+      Self::Strict => write!(f, "strict"),
+      Self::NoCheck => write!(f, "nocheck"),
+    }
+  }
+}
+
 type ScopeDepth = u16;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -159,7 +238,9 @@ impl<'s> Scope<'s> {
     self.depth += 1;
   }
   fn end_scope(&mut self) {
-    while let Some(last) = self.variables.last() && last.depth >= self.depth {
+    while let Some(last) = self.variables.last()
+      && last.depth >= self.depth
+    {
       self.variables.pop();
     }
     self.depth -= 1;
@@ -169,13 +250,14 @@ impl<'s> Scope<'s> {
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct ContextItem {
   existential: Existential,
-  ty: Type,
   depth: ScopeDepth,
 }
+// Solutions are kept per existential, so looking one up doesn't search every solution
 #[derive(Default, Debug, Clone)]
 pub struct Context {
   existential_count: Existential,
   context: Vec<ContextItem>,
+  solutions: HashMap<Existential, Vec<Type>>,
   depth: ScopeDepth,
 }
 impl Context {
@@ -187,23 +269,24 @@ impl Context {
   fn solve(&mut self, existential: Existential, ty: Type) {
     self.context.push(ContextItem {
       existential,
-      ty,
       depth: self.depth,
     });
+    self.solutions.entry(existential).or_default().push(ty);
   }
   fn lookup(&self, existential: Existential) -> Option<Type> {
-    self
-      .context
-      .iter()
-      .rfind(|variable| variable.existential == existential)
-      .map(|variable| variable.ty.clone())
+    self.solutions.get(&existential)?.last().cloned()
   }
 
   fn begin_scope(&mut self) {
     self.depth += 1;
   }
   fn end_scope(&mut self) {
-    while let Some(ContextItem { depth, .. }) = self.context.last() && *depth == self.depth {
+    while let Some(ContextItem { depth, existential }) = self.context.last()
+      && *depth == self.depth
+    {
+      if let Some(solutions) = self.solutions.get_mut(existential) {
+        solutions.pop();
+      }
       self.context.pop();
     }
     self.depth -= 1;
@@ -214,8 +297,47 @@ impl Context {
 struct Typechecker<'s> {
   scope: Scope<'s>,
   context: Context,
+  module_aliases: HashMap<&'s str, &'s str>,
+  // Types given to `break` in each of the enclosing loops
+  loops: Vec<Type>,
+  // Returns from inside loop expressions, which can't be reported by the statement
+  expression_returns: Type,
+  // Parameters must be annotated, rather than being inferred from their uses
+  strict: bool,
+  // Where completions are requested, and the variables which are in scope there
+  completion_position: Option<u32>,
+  completion_scope: Option<Vec<(&'s str, Type)>>,
 }
 impl<'s> Typechecker<'s> {
+  fn with_globals(strict: bool) -> Self {
+    let mut typechecker = Self {
+      strict,
+      ..Default::default()
+    };
+
+    // The prompt of `input` is optional, which annotations can't describe
+    let optional_string = Type::Literal(Literal::String).union(Type::NULL);
+    typechecker.scope.insert(
+      "input",
+      Type::Function(Function {
+        parameters: vec![optional_string.clone()],
+        optional: 1,
+        return_type: optional_string.into(),
+      }),
+    );
+
+    register_globals!(&mut typechecker, {
+      print: "<T>(T) -> T",
+      prettyPrint: "<T>(T) -> T",
+      type: "(any) -> string",
+      toString: "(any) -> string",
+      inspect: "(any) -> dict(string, any)",
+      deepCopy: "<T>(T) -> T",
+    });
+
+    typechecker
+  }
+
   fn type_from_annotation(
     &mut self,
     annotation: &TypeExpression<'s>,
@@ -234,9 +356,10 @@ impl<'s> Typechecker<'s> {
         _ if generics.contains_key(name) => generics[name].clone(),
         ty => Error::new(ErrorKind::UnknownType(ty.to_string()), annotation.span)?,
       },
+      TypeItem::StringLiteral(value) => Type::StringValue((*value).to_string()),
       TypeItem::Parameter(name, param) => {
         let expected_params = match *name {
-          "set" | "list" => 1,
+          "set" | "list" | "sequence" | "task" | "readonly" => 1,
           "dict" => 2,
           ty => return Error::new(ErrorKind::UnknownType(ty.to_string()), annotation.span),
         };
@@ -249,7 +372,10 @@ impl<'s> Typechecker<'s> {
         }
         match *name {
           "set" => Type::Set(self.type_from_annotation(&param[0], generics)?.into()),
+          "sequence" => Type::Sequence(self.type_from_annotation(&param[0], generics)?.into()),
+          "task" => Type::Task(self.type_from_annotation(&param[0], generics)?.into()),
           "list" => Type::List(self.type_from_annotation(&param[0], generics)?.into()),
+          "readonly" => Type::Readonly(self.type_from_annotation(&param[0], generics)?.into()),
           "dict" => Type::Dict(
             self.type_from_annotation(&param[0], generics)?.into(),
             self.type_from_annotation(&param[1], generics)?.into(),
@@ -271,6 +397,7 @@ impl<'s> Typechecker<'s> {
 
         Type::Function(Function {
           parameters,
+          optional: 0,
           return_type,
         })
       }
@@ -303,16 +430,20 @@ impl<'s> Typechecker<'s> {
 
     match (a, b) {
       (Type::Existential(a), b) => {
-        self.context.solve(a, b);
+        self.context.solve(a, b.widen_string_values());
         true
       }
       (a, Type::Existential(b)) => {
-        self.context.solve(b, a);
+        self.context.solve(b, a.widen_string_values());
         true
       }
 
-      (Type::List(a), Type::List(b)) | (Type::Set(a), Type::Set(b)) => self.subtype(&a, &b),
+      (Type::List(a), Type::List(b))
+      | (Type::Set(a), Type::Set(b))
+      | (Type::Sequence(a), Type::Sequence(b))
+      | (Type::Task(a), Type::Task(b)) => self.subtype(&a, &b),
       (Type::Dict(a, b), Type::Dict(c, d)) => self.subtype(&a, &c) && self.subtype(&b, &d),
+      (Type::Readonly(a), Type::Readonly(b)) => self.subtype(&a, &b),
 
       (Type::Union(a), b) => a.into_iter().all(|a| self.subtype(&a, &b)),
       (a, Type::Union(b)) => b.into_iter().any(|b| self.subtype(&a, &b)),
@@ -351,7 +482,8 @@ impl<'s> Typechecker<'s> {
       Expr::Assignment {
         identifier,
         expression,
-      } => self.assignment_expression(expression, identifier, span),
+        assignment_operator,
+      } => self.assignment_expression(expression, identifier, *assignment_operator, span),
       Expr::Binary {
         operator,
         left,
@@ -364,6 +496,16 @@ impl<'s> Typechecker<'s> {
         let expression = self.synthesize_expression(expression)?;
         self.synthesize_application(&expression, arguments, span)
       }
+      Expr::Cast { expression, type_ } => {
+        self.synthesize_expression(expression)?;
+        self.type_from_annotation(type_, &mut HashMap::default())
+      }
+      Expr::Ascription { expression, type_ } => {
+        let expression_ty = self.synthesize_expression(expression)?;
+        let ty = self.type_from_annotation(type_, &mut HashMap::default())?;
+        self.assert_type(expression_ty, &ty, span)?;
+        Ok(ty)
+      }
       Expr::Comment { expression, .. } | Expr::Group { expression } => {
         self.synthesize_expression(expression)
       }
@@ -390,8 +532,18 @@ impl<'s> Typechecker<'s> {
         assignment_operator,
       } => self.index_assgnment_expression(expression, index, value, *assignment_operator, span),
       Expr::List { items } => self.list_expression(items),
+      Expr::Literal {
+        type_: Literal::String,
+        value,
+      } => Ok(Type::StringValue((*value).to_string())),
       Expr::Literal { type_, .. } => Ok(Type::Literal(*type_)),
+      Expr::Do { body } => self.do_expression(body),
+      Expr::Loop { body } => self.loop_expression(body),
       Expr::ModuleAccess { module, item } => self.module_access(module, item, span),
+      Expr::NonNull { expression } => {
+        let ty = self.synthesize_expression(expression)?;
+        Ok(ty.apply_context(&self.context).narrow(&Type::NULL))
+      }
       Expr::Unary {
         expression,
         operator,
@@ -404,6 +556,7 @@ impl<'s> Typechecker<'s> {
 
   fn synthesize_statement(&mut self, statement: &Statement<'s>) -> Result<StatementType, Error> {
     let span = statement.span;
+    self.record_completion_scope(span);
 
     match &statement.stmt {
       Stmt::Block { body } => self.block_statement(body),
@@ -422,8 +575,14 @@ impl<'s> Typechecker<'s> {
         then,
         otherwise,
       } => self.if_statement(condition, then, otherwise),
-      Stmt::Import { module, items } => self.import_statement(items, module),
+      Stmt::Import {
+        module,
+        alias,
+        items,
+        optional,
+      } => self.import_statement(items, module, *alias, *optional, span),
       Stmt::Return { expression } => self.return_statement(expression),
+      Stmt::Break { expression } => self.break_statement(expression),
       Stmt::While { condition, body } => self.while_statement(condition, body),
       Stmt::Comment { .. } => Ok(StatementType::NoReturn),
     }
@@ -465,6 +624,12 @@ impl<'s> Typechecker<'s> {
     arguments: &[Expression<'s>],
     span: Span,
   ) -> Result<Type, Error> {
+    if let Type::Union(overloads) = expression
+      && overloads.iter().all(|ty| matches!(ty, Type::Function(_)))
+    {
+      return self.synthesize_overloaded_application(overloads, arguments, span);
+    }
+
     self.context.begin_scope();
     self.scope.begin_scope();
 
@@ -479,6 +644,7 @@ impl<'s> Typechecker<'s> {
           *alpha,
           Type::Function(Function {
             parameters: alpha_args.clone(),
+            optional: 0,
             return_type: return_type.clone().into(),
           }),
         );
@@ -491,7 +657,8 @@ impl<'s> Typechecker<'s> {
         return_type.apply_context(&self.context)
       }
       Type::Function(function) => {
-        if arguments.len() != function.parameters.len() {
+        let required = function.parameters.len() - function.optional;
+        if arguments.len() > function.parameters.len() || arguments.len() < required {
           Error::new(
             ErrorKind::WrongNumberArguments(arguments.len(), function.parameters.len()),
             span,
@@ -508,7 +675,7 @@ impl<'s> Typechecker<'s> {
             Ok(())
           })?;
 
-        function.return_type.clone().apply_context(&self.context)
+        Type::clone(&function.return_type).apply_context(&self.context)
       }
       ty => Error::new(
         ErrorKind::NotCallable(ty.clone().apply_context(&self.context)),
@@ -521,20 +688,92 @@ impl<'s> Typechecker<'s> {
 
     Ok(type_)
   }
+
+  fn synthesize_overloaded_application(
+    &mut self,
+    overloads: &[Type],
+    arguments: &[Expression<'s>],
+    span: Span,
+  ) -> Result<Type, Error> {
+    let mut errors = Vec::new();
+
+    for overload in overloads {
+      let typechecker = self.clone();
+
+      match self.synthesize_application(overload, arguments, span) {
+        Ok(ty) => return Ok(ty),
+        Err(error) => {
+          *self = typechecker;
+          errors.push((overload.clone(), error));
+        }
+      }
+    }
+
+    Error::new(ErrorKind::NoMatchingOverload(errors), span)
+  }
 }
 
 pub fn typecheck(ast: &[Statement]) -> Vec<Error> {
-  let mut typechecker = Typechecker::default();
-
-  register_globals!(&mut typechecker, {
-    print: "<T>(T) -> T",
-    type: "(any) -> string",
-    toString: "(any) -> string",
-  });
-
-  ast
-    .iter()
-    .map(|stmt| typechecker.synthesize_statement(stmt))
-    .filter_map(Result::err)
-    .collect::<Vec<_>>()
+  typecheck_with_strictness(ast, Strictness::from_pragmas(ast))
+}
+
+pub fn typecheck_with_strictness(ast: &[Statement], strictness: Strictness) -> Vec<Error> {
+  if strictness == Strictness::NoCheck {
+    return Vec::new();
+  }
+
+  let mut typechecker = Typechecker::with_globals(strictness == Strictness::Strict);
+
+  let mut errors = Vec::new();
+  let mut failed = HashSet::default();
+  for statement in ast {
+    if let Err(error) = typechecker.synthesize_statement(statement) {
+      // Errors about `never` which use a variable from a failed declaration are caused by it
+      let is_secondary = error.is_from_never() && uses_variables(statement, &failed);
+
+      // Variables from a failed declaration are `never`, so their uses aren't also undefined
+      for name in declared_variables(statement) {
+        if !typechecker.scope.is_defined(name) {
+          typechecker.scope.insert(name, Type::Never);
+        }
+        failed.insert(name);
+      }
+
+      if !is_secondary && !errors.contains(&error) {
+        errors.push(error);
+      }
+    }
+  }
+
+  errors
+}
+
+fn declared_variables<'s>(statement: &Statement<'s>) -> Vec<&'s str> {
+  match &statement.stmt {
+    Stmt::Declaration { identifier, .. } => identifier.get_names(),
+    _ => Vec::new(),
+  }
+}
+
+struct UsesVariables<'a, 's> {
+  variables: &'a HashSet<&'s str>,
+  found: bool,
+}
+impl Visitor for UsesVariables<'_, '_> {
+  fn enter_expression(&mut self, expression: &Expression) {
+    if let Expr::Variable { name: identifier } | Expr::Assignment { identifier, .. } =
+      &expression.expr
+    {
+      self.found |= self.variables.contains(identifier);
+    }
+  }
+}
+
+fn uses_variables(statement: &Statement, variables: &HashSet<&str>) -> bool {
+  let mut visitor = UsesVariables {
+    variables,
+    found: false,
+  };
+  visitor.visit_statement(statement);
+  visitor.found
 }
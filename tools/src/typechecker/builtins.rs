@@ -23,125 +23,181 @@ macro_rules! register_globals {
 }
 pub(crate) use register_globals;
 
-macro_rules! module {
-  ($item:expr, $typechecker:expr, { $($name:ident: $text:literal,)* }) => {
-    match $item {
-      $(
-        stringify!($name) => {
-          ImportValue::Value(
-            $typechecker.type_from_annotation(
-              &bang_syntax::parse_type($text).expect("to be valid syntax"),
-              &mut HashMap::default(),
-            ).expect("annotation to be valid")
-          )
-        }
-      )*
-      _ => ImportValue::ItemNotFound,
-    }
+macro_rules! modules {
+  ($($module:literal => { $($name:ident: $text:literal,)* },)*) => {
+    // The items of each module, with the annotation of their type
+    pub const MODULES: &[(&str, &[(&str, &str)])] = &[
+      $(($module, &[$((stringify!($name), $text),)*]),)*
+    ];
   };
 }
 
+modules! {
+  "maths" => {
+    PI: "number",
+    E: "number",
+    INFINITY: "number",
+    NAN: "number",
+    floor: "(number) -> number",
+    ceil: "(number) -> number",
+    round: "(number) -> number",
+    abs: "(number) -> number",
+    sqrt: "(number) -> number",
+    cbrt: "(number) -> number",
+    sin: "(number) -> number",
+    cos: "(number) -> number",
+    tan: "(number) -> number",
+    asin: "(number) -> number",
+    acos: "(number) -> number",
+    atan: "(number) -> number",
+    sinh: "(number) -> number",
+    cosh: "(number) -> number",
+    tanh: "(number) -> number",
+    asinh: "(number) -> number",
+    acosh: "(number) -> number",
+    atanh: "(number) -> number",
+    isNan: "(number) -> boolean",
+    isNaN: "(number) -> boolean",
+    isFinite: "(number) -> boolean",
+    exp: "(number) -> number",
+    ln: "(number) -> number",
+    pow: "(number, number) -> number",
+    log: "(number, number) -> number",
+    radiansToDegrees: "(number) -> number",
+    degreesToRadians: "(number) -> number",
+    approxEqual: "(number, number, number) -> boolean",
+  },
+  "string" => {
+    NEW_LINE: "string",
+    TAB: "string",
+    CARRIAGE_RETURN: "string",
+    length: "(string) -> number",
+    trim: "(string) -> string",
+    trimStart: "(string) -> string",
+    trimEnd: "(string) -> string",
+    repeat: "(string, number) -> string",
+    includes: "(string, string) -> boolean",
+    startsWith: "(string, string) -> boolean",
+    endsWith: "(string, string) -> boolean",
+    toUpperCase: "(string) -> string",
+    toLowerCase: "(string) -> string",
+    replace: "(string, string, string) -> string",
+    replaceOne: "(string, string, string) -> string",
+    toNumber: "(string) -> number?",
+    split: "(string, string) -> string[]",
+    format: "(string, dict(string, any)) -> string?",
+    sprintf: "(string, any[]) -> string?",
+  },
+  "fs" => {
+    read: "(string) -> string?",
+    write: "(string, string) -> boolean",
+  },
+  "list" => {
+    length: "<T>(T[]) -> number",
+    isEmpty: "<T>(T[]) -> boolean",
+    push: "<T>(T[], T) -> T[]",
+    pop: "<T>(T[]) -> T?",
+    includes: "<T>(T[], T) -> boolean",
+    reverse: "<T>(T[]) -> T[]",
+    get: "<T>(T[], number) -> T?",
+    toSet: "<T>(T[]) -> set(T)",
+    indexOf: "<T>(T[], T) -> number?",
+    lastIndexOf: "<T>(T[], T) -> number?",
+    count: "<T>(T[], T) -> number",
+    sum: "(number[]) -> number",
+    freeze: "<T>(T[]) -> readonly(T[])",
+    copy: "<T>(T[]) -> T[]",
+    any: "<T>(T[], (T) -> boolean) -> boolean",
+    all: "<T>(T[], (T) -> boolean) -> boolean",
+    map: "<S, T>(T[], (T) -> S) -> S[]",
+    filter: "<T>(T[], (T) -> boolean) -> T[]",
+    reduce: "<S, T>(T[], S, (T) -> S) -> S",
+    find: "<T>(T[], (T) -> boolean) -> T?",
+    findIndex: "<T>(T[], (T) -> boolean) -> number?",
+    forEach: "<T>(T[], (T) -> any) -> null",
+    max: "((string[]) -> string) | ((number[]) -> number)",
+    min: "((string[]) -> string) | ((number[]) -> number)",
+    compare: "((string[], string[]) -> number) | ((number[], number[]) -> number) | (<T>(T[], T[], (T, T) -> number) -> number)",
+  },
+  "set" => {
+    new: "<T>() -> set(T)",
+    set: "<T>() -> set(T)",
+    size: "<T>(set(T)) -> number",
+    isEmpty: "<T>(set(T)) -> boolean",
+    insert: "<T>(set(T), T) -> boolean",
+    remove: "<T>(set(T), T) -> boolean",
+    includes: "<T>(set(T), T) -> boolean",
+    isDisjoint: "<T>(set(T), set(T)) -> boolean",
+    isSuperset: "<T>(set(T), set(T)) -> boolean",
+    isSubset: "<T>(set(T), set(T)) -> boolean",
+    union: "<T>(set(T), set(T)) -> set(T)",
+    difference: "<T>(set(T), set(T)) -> set(T)",
+    intersection: "<T>(set(T), set(T)) -> set(T)",
+    symmetricDifference: "<T>(set(T), set(T)) -> set(T)",
+    toList: "<T>(set(T)) -> T[]",
+    fromList: "<T>(T[]) -> set(T)",
+    copy: "<T>(set(T)) -> set(T)",
+    map: "<S, T>(set(T), (T) -> S) -> set(S)",
+    filter: "<T>(set(T), (T) -> boolean) -> set(T)",
+    fold: "<S, T>(set(T), S, (S, T) -> S) -> S",
+  },
+  "dict" => {
+    new: "<S, T>() -> dict(S, T)",
+    dict: "<S, T>() -> dict(S, T)",
+    size: "<S, T>(dict(S, T)) -> number",
+    isEmpty: "<S, T>(dict(S, T)) -> boolean",
+    keys: "<S, T>(dict(S, T)) -> S[]",
+    values: "<S, T>(dict(S, T)) -> S[]",
+    get: "<S, T>(dict(S, T), S) -> T?",
+    hasKey: "<S, T>(dict(S, T), S) -> boolean",
+    freeze: "<S, T>(dict(S, T)) -> readonly(dict(S, T))",
+    copy: "<S, T>(dict(S, T)) -> dict(S, T)",
+    // Dicts iterate in an unspecified order, these give a deterministic one
+    toSortedList: "<S, T>(dict(S, T)) -> ((S | T)[])[]",
+    sortedKeys: "(<S, T>(dict(S, T)) -> S[]) | (<S, T>(dict(S, T), (S, S) -> number) -> S[])",
+  },
+  "function" => {
+    name: "(any) -> string?",
+    arity: "(any) -> number?",
+  },
+  "iter" => {
+    range: "(number, number) -> sequence(number)",
+    repeat: "<T>(T) -> sequence(T)",
+    from: "<T>(sequence(T) | T[]) -> sequence(T)",
+    map: "<S, T>(sequence(T) | T[], (T) -> S) -> sequence(S)",
+    filter: "<T>(sequence(T) | T[], (T) -> boolean) -> sequence(T)",
+    take: "<T>(sequence(T) | T[], number) -> sequence(T)",
+    collect: "<T>(sequence(T) | T[]) -> T[]",
+  },
+  "memory" => {
+    usage: "(any) -> number",
+  },
+  "task" => {
+    spawn: "<T>(() -> T) -> task(T)",
+    join: "<T>(task(T)) -> T",
+  },
+  "config" => {
+    get: "(string) -> any",
+  },
+}
+
 impl Typechecker<'_> {
   pub fn get_module_item(&mut self, module: &str, item: &str) -> ImportValue {
-    match module {
-      "maths" => module!(item, self, {
-        PI: "number",
-        E: "number",
-        INFINITY: "number",
-        floor: "(number) -> number",
-        ceil: "(number) -> number",
-        round: "(number) -> number",
-        abs: "(number) -> number",
-        sqrt: "(number) -> number",
-        cbrt: "(number) -> number",
-        sin: "(number) -> number",
-        cos: "(number) -> number",
-        tan: "(number) -> number",
-        asin: "(number) -> number",
-        acos: "(number) -> number",
-        atan: "(number) -> number",
-        sinh: "(number) -> number",
-        cosh: "(number) -> number",
-        tanh: "(number) -> number",
-        asinh: "(number) -> number",
-        acosh: "(number) -> number",
-        atanh: "(number) -> number",
-        isNan: "(number) -> boolean",
-        exp: "(number) -> number",
-        ln: "(number) -> number",
-        pow: "(number, number) -> number",
-        log: "(number, number) -> number",
-        radiansToDegrees: "(number) -> number",
-        degreesToRadians: "(number) -> number",
-      }),
-      "string" => module!(item, self, {
-        NEW_LINE: "string",
-        TAB: "string",
-        CARRIAGE_RETURN: "string",
-        length: "(string) -> number",
-        trim: "(string) -> string",
-        trimStart: "(string) -> string",
-        trimEnd: "(string) -> string",
-        repeat: "(string, number) -> string",
-        includes: "(string, string) -> boolean",
-        startsWith: "(string, string) -> boolean",
-        endsWith: "(string, string) -> boolean",
-        toUpperCase: "(string) -> string",
-        toLowerCase: "(string) -> string",
-        replace: "(string, string, string) -> string",
-        replaceOne: "(string, string, string) -> string",
-        toNumber: "(string) -> number?",
-        split: "(string, string) -> string[]",
-      }),
-      "fs" => module!(item, self, {
-        read: "(string) -> string?",
-        write: "(string, string) -> boolean",
-      }),
-      "list" => module!(item, self, {
-        length: "<T>(T[]) -> number",
-        isEmpty: "<T>(T[]) -> boolean",
-        push: "<T>(T[], T) -> T[]",
-        pop: "<T>(T[]) -> T?",
-        includes: "<T>(T[], T) -> boolean",
-        reverse: "<T>(T[]) -> T[]",
-        get: "<T>(T[], number) -> T?",
-        toSet: "<T>(T[]) -> set(T)",
-        any: "<T>(T[], (T) -> boolean) -> boolean",
-        all: "<T>(T[], (T) -> boolean) -> boolean",
-        map: "<S, T>(T[], (T) -> S) -> S[]",
-        filter: "<T>(T[], (T) -> boolean) -> T[]",
-        reduce: "<S, T>(T[], S, (T) -> S) -> S",
-        find: "<T>(T[], (T) -> boolean) -> T?",
-        forEach: "<T>(T[], (T) -> any) -> null",
-        max: "((string[]) -> string) | ((number[]) -> number)",
-        min: "((string[]) -> string) | ((number[]) -> number)",
-      }),
-      "set" => module!(item, self, {
-        new: "<T>() -> set(T)",
-        set: "<T>() -> set(T)",
-        size: "<T>(set(T)) -> number",
-        isEmpty: "<T>(set(T)) -> boolean",
-        insert: "<T>(set(T), T) -> boolean",
-        remove: "<T>(set(T), T) -> boolean",
-        includes: "<T>(set(T), T) -> boolean",
-        isDisjoint: "<T>(set(T), set(T)) -> boolean",
-        isSuperset: "<T>(set(T), set(T)) -> boolean",
-        isSubset: "<T>(set(T), set(T)) -> boolean",
-        union: "<T>(set(T), set(T)) -> set(T)",
-        difference: "<T>(set(T), set(T)) -> set(T)",
-        intersection: "<T>(set(T), set(T)) -> set(T)",
-        symmetricDifference: "<T>(set(T), set(T)) -> set(T)",
-      }),
-      "dict" => module!(item, self, {
-        new: "<S, T>() -> dict(S, T)",
-        dict: "<S, T>() -> dict(S, T)",
-        size: "<S, T>(dict(S, T)) -> number",
-        isEmpty: "<S, T>(dict(S, T)) -> bool",
-        keys: "<S, T>(dict(S, T)) -> S[]",
-        values: "<S, T>(dict(S, T)) -> S[]",
-        get: "<S, T>(dict(S, T), S) -> T?",
-      }),
-      _ => ImportValue::ModuleNotFound,
+    match MODULES.iter().find(|(name, _)| *name == module) {
+      Some((_, items)) => match items.iter().find(|(name, _)| *name == item) {
+        Some((_, annotation)) => ImportValue::Value(self.annotation_type(annotation)),
+        None => ImportValue::ItemNotFound,
+      },
+      None => ImportValue::ModuleNotFound,
     }
   }
+
+  pub fn annotation_type(&mut self, annotation: &'static str) -> Type {
+    self
+      .type_from_annotation(
+        &bang_syntax::parse_type(annotation).expect("to be valid syntax"),
+        &mut HashMap::default(),
+      )
+      .expect("annotation to be valid")
+  }
 }
@@ -0,0 +1,176 @@
+use super::{builtins::MODULES, declared_variables, Literal, Type, Typechecker};
+use bang_syntax::ast::{
+  expression::{Expr, Expression},
+  statement::{DeclarationIdentifier, Statement, Stmt},
+  Span, Visitor,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+  Variable,
+  ModuleItem,
+  DictKey,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+  pub kind: CompletionKind,
+  pub label: String,
+  // The type of a variable or module item, or the dict which has the key
+  pub detail: String,
+}
+
+impl<'s> Typechecker<'s> {
+  // Before each statement which holds or follows the position, as nested statements are more precise
+  pub(super) fn record_completion_scope(&mut self, span: Span) {
+    if let Some(position) = self.completion_position
+      && span.end >= position
+      && (span.start <= position || self.completion_scope.is_none())
+    {
+      self.completion_scope = Some(self.variables_in_scope());
+    }
+  }
+
+  fn variables_in_scope(&self) -> Vec<(&'s str, Type)> {
+    let mut variables: Vec<(&'s str, Type)> = Vec::new();
+
+    for variable in self.scope.variables.iter().rev() {
+      if !variables.iter().any(|(name, _)| *name == variable.name) {
+        let ty = variable.ty.clone().apply_context(&self.context);
+        variables.push((variable.name, ty));
+      }
+    }
+
+    variables.sort_by_key(|(name, _)| *name);
+    variables
+  }
+}
+
+// Keys given to dicts declared in the code, from literals and index assignments
+#[derive(Default)]
+struct DictKeys {
+  position: u32,
+  keys: Vec<(String, String)>,
+}
+impl DictKeys {
+  fn add(&mut self, dict: &str, key: &str) {
+    let entry = (dict.to_string(), key.to_string());
+    if !self.keys.contains(&entry) {
+      self.keys.push(entry);
+    }
+  }
+}
+impl Visitor for DictKeys {
+  fn enter_statement(&mut self, statement: &Statement) {
+    if statement.span.end > self.position {
+      return;
+    }
+
+    if let Stmt::Declaration {
+      identifier: DeclarationIdentifier::Variable(name),
+      expression: Some(expression),
+      ..
+    } = &statement.stmt
+      && let Expr::Dictionary { items } = &expression.expr
+    {
+      for (key, _) in items {
+        if let Some(key) = string_literal(key) {
+          self.add(name, key);
+        }
+      }
+    }
+  }
+
+  fn enter_expression(&mut self, expression: &Expression) {
+    if expression.span.end > self.position {
+      return;
+    }
+
+    if let Expr::IndexAssignment {
+      expression, index, ..
+    } = &expression.expr
+      && let Expr::Variable { name } = expression.expr
+      && let Some(key) = string_literal(index)
+    {
+      self.add(name, key);
+    }
+  }
+}
+
+fn string_literal<'s>(expression: &Expression<'s>) -> Option<&'s str> {
+  match expression.expr {
+    Expr::Literal {
+      type_: Literal::String,
+      value,
+    } => Some(value),
+    _ => None,
+  }
+}
+
+// Everything which could be written at a byte position in the source
+pub fn completions(ast: &[Statement], position: u32) -> Vec<Completion> {
+  let mut typechecker = Typechecker::with_globals(false);
+  typechecker.completion_position = Some(position);
+
+  for statement in ast {
+    if typechecker.synthesize_statement(statement).is_err() {
+      for name in declared_variables(statement) {
+        if !typechecker.scope.is_defined(name) {
+          typechecker.scope.insert(name, Type::Never);
+        }
+      }
+    }
+  }
+
+  let variables = match typechecker.completion_scope.take() {
+    Some(variables) => variables,
+    None => typechecker.variables_in_scope(),
+  };
+
+  let mut dict_keys = DictKeys {
+    position,
+    ..Default::default()
+  };
+  dict_keys.visit(ast);
+
+  let mut completions: Vec<_> = variables
+    .iter()
+    .map(|(name, ty)| Completion {
+      kind: CompletionKind::Variable,
+      label: (*name).to_string(),
+      detail: ty.to_string(),
+    })
+    .collect();
+
+  completions.extend(
+    dict_keys
+      .keys
+      .into_iter()
+      .filter(|(dict, _)| variables.iter().any(|(name, _)| name == dict))
+      .map(|(dict, key)| Completion {
+        kind: CompletionKind::DictKey,
+        label: key,
+        detail: dict,
+      }),
+  );
+
+  let mut aliases: Vec<_> = typechecker.module_aliases.clone().into_iter().collect();
+  aliases.sort_unstable();
+  let mut modules: Vec<_> = MODULES
+    .iter()
+    .map(|(module, _)| (*module, *module))
+    .collect();
+  modules.extend(aliases);
+  for (prefix, module) in modules {
+    let items = MODULES.iter().find(|(name, _)| *name == module);
+    for (item, annotation) in items.map(|(_, items)| *items).unwrap_or_default() {
+      completions.push(Completion {
+        kind: CompletionKind::ModuleItem,
+        label: format!("{prefix}::{item}"),
+        detail: typechecker.annotation_type(annotation).to_string(),
+      });
+    }
+  }
+
+  completions
+}
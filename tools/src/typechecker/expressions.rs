@@ -1,24 +1,35 @@
 use super::{
   builtins::ImportValue,
-  statements::StatementType,
-  types::{Function, Literal, Type},
+  statements::{ReturnsLikelihood, StatementType},
+  types::{Function, Interned, Literal, Type},
   Error, ErrorKind, HashMap, Typechecker,
 };
 use bang_syntax::ast::{
-  expression::{operators, Expression, Parameter},
-  statement::Statement,
+  expression::{operators, Expr, Expression, Parameter},
+  statement::{Statement, Stmt},
   types::TypeExpression,
   Span,
 };
+use std::{mem, slice};
 
 impl<'s> Typechecker<'s> {
   pub fn assignment_expression(
     &mut self,
     expression: &Expression<'s>,
     identifier: &'s str,
+    assignment_operator: Option<operators::Assignment>,
     span: Span,
   ) -> Result<Type, Error> {
-    let expression_ty = self.synthesize_expression(expression)?;
+    let expression_ty = if let Some(operator) = assignment_operator {
+      let variable = Expression {
+        expr: Expr::Variable { name: identifier },
+        span,
+        id: 0,
+      };
+      self.binary_expression(operator.to_binary(), &variable, expression, span)?
+    } else {
+      self.synthesize_expression(expression)?
+    };
     let variable_ty = self
       .scope
       .lookup_initialization(identifier)
@@ -47,6 +58,12 @@ impl<'s> Typechecker<'s> {
     let l = self.synthesize_expression(left)?;
     let r = self.synthesize_expression(right)?;
 
+    // String literal types are only kept for logical operators, so `'a' + 'b'` is a string
+    let (l, r) = match operator {
+      BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Nullish => (l, r),
+      _ => (l.widen_string_values(), r.widen_string_values()),
+    };
+
     let ty = match operator {
       BinaryOperator::Plus => {
         self.assert_type(r, &l, span)?;
@@ -71,6 +88,8 @@ impl<'s> Typechecker<'s> {
         self.assert_type(l, &Type::string_or_number(), span)?;
         Type::boolean()
       }
+      BinaryOperator::In => self.membership(l, r, span)?,
+      BinaryOperator::Compose => self.composition(l, r, span)?,
       BinaryOperator::And => match &l {
         type_ if type_.is_falsy() => l,
         type_ if type_.is_truthy() => r,
@@ -107,25 +126,33 @@ impl<'s> Typechecker<'s> {
     } else {
       self.context.new_existential()
     };
-    let arg_types = parameters
-      .iter()
-      .map(|param| {
-        if let Some(ty) = &param.type_ {
-          self.type_from_annotation(ty, &mut HashMap::default())
-        } else {
-          Ok(self.context.new_existential())
-        }
-      })
-      .collect::<Result<Vec<_>, _>>()?;
-
     self.scope.begin_scope();
 
-    for (type_, param) in arg_types.iter().cloned().zip(parameters.iter()) {
-      self.scope.define(param.name, type_, span)?;
+    let mut arg_types = Vec::with_capacity(parameters.len());
+    for param in parameters {
+      let type_ = match (&param.type_, &param.default) {
+        (Some(ty), default) => {
+          let ty = self.type_from_annotation(ty, &mut HashMap::default())?;
+          if let Some(default) = default {
+            let default_ty = self.synthesize_expression(default)?;
+            self.assert_type(default_ty, &ty, default.span)?;
+          }
+          ty
+        }
+        (None, Some(default)) => self
+          .synthesize_expression(default)?
+          .uplevel_boolean()
+          .widen_string_values(),
+        (None, None) => self.context.new_existential(),
+      };
+
+      self.scope.define(param.name, type_.clone(), span)?;
+      arg_types.push(type_);
     }
 
     let function = Type::Function(Function {
-      parameters: arg_types,
+      parameters: arg_types.clone(),
+      optional: parameters.iter().filter(|p| p.default.is_some()).count(),
       return_type: return_type.clone().into(),
     });
 
@@ -133,18 +160,91 @@ impl<'s> Typechecker<'s> {
       self.scope.define(name, function.clone(), span)?;
     };
 
+    let loops = mem::take(&mut self.loops);
+    let expression_returns = mem::replace(&mut self.expression_returns, Type::Never);
+
     let ty = if let StatementType::Returns(ty, _) = self.synthesize_statement(body)? {
       ty
     } else {
       Type::NULL
     };
+    let returns = mem::replace(&mut self.expression_returns, expression_returns);
+    let ty = ty.union(returns);
+    self.loops = loops;
     self.assert_type(ty, &return_type, span)?;
 
+    // In strict mode, parameters need an annotation if their type can't be inferred from the body
+    if self.strict {
+      for (param, ty) in parameters.iter().zip(&arg_types) {
+        if let Type::Existential(_) = ty.clone().apply_context(&self.context) {
+          return Error::new(
+            ErrorKind::MissingAnnotation(param.name.to_string()),
+            param.span,
+          );
+        }
+      }
+    }
+
     self.scope.end_scope();
 
     Ok(function)
   }
 
+  pub fn loop_expression(&mut self, body: &Statement<'s>) -> Result<Type, Error> {
+    self.loops.push(Type::Never);
+    let body_ty = self.synthesize_statement(body);
+    let break_ty = self.loops.pop().expect("Loop stack to have item");
+
+    if let StatementType::Returns(ty, _) = body_ty? {
+      let returns = mem::replace(&mut self.expression_returns, Type::Never);
+      self.expression_returns = returns.union(ty);
+    }
+
+    // Without a `break` the loop never finishes, so it is a subtype of everything
+    if break_ty == Type::Never {
+      Ok(Type::Union(Vec::new()))
+    } else {
+      Ok(break_ty)
+    }
+  }
+
+  // The value is the last statement if it is an expression, otherwise null
+  pub fn do_expression(&mut self, body: &Statement<'s>) -> Result<Type, Error> {
+    let statements = match &body.stmt {
+      Stmt::Block { body } => body.as_slice(),
+      _ => slice::from_ref(body),
+    };
+    let last = statements
+      .iter()
+      .rposition(|statement| !matches!(statement.stmt, Stmt::Comment { .. }));
+
+    self.scope.begin_scope();
+    let mut ty = Type::NULL;
+    for (index, statement) in statements.iter().enumerate() {
+      let statement_ty = match &statement.stmt {
+        Stmt::Expression { expression } if Some(index) == last => {
+          ty = self.synthesize_expression(expression)?;
+          StatementType::NoReturn
+        }
+        _ => self.synthesize_statement(statement)?,
+      };
+
+      // A `return` inside the block returns from the enclosing function
+      if let StatementType::Returns(return_ty, likelihood) = statement_ty {
+        let returns = mem::replace(&mut self.expression_returns, Type::Never);
+        self.expression_returns = returns.union(return_ty);
+
+        if likelihood == ReturnsLikelihood::Definite {
+          ty = Type::Union(Vec::new());
+          break;
+        }
+      }
+    }
+    self.scope.end_scope();
+
+    Ok(ty)
+  }
+
   pub fn list_expression(&mut self, items: &[Expression<'s>]) -> Result<Type, Error> {
     let inner_ty = if items.is_empty() {
       self.context.new_existential()
@@ -155,6 +255,7 @@ impl<'s> Typechecker<'s> {
         .collect::<Result<Vec<_>, Error>>()?
         .into_iter()
         .fold(Type::Never, Type::union)
+        .widen_string_values()
     };
 
     Ok(Type::List(inner_ty.into()))
@@ -173,6 +274,7 @@ impl<'s> Typechecker<'s> {
         .collect::<Result<Vec<_>, Error>>()?
         .into_iter()
         .fold(Type::Never, Type::union)
+        .widen_string_values()
     };
     let value_ty = if items.is_empty() {
       self.context.new_existential()
@@ -183,6 +285,7 @@ impl<'s> Typechecker<'s> {
         .collect::<Result<Vec<_>, Error>>()?
         .into_iter()
         .fold(Type::Never, Type::union)
+        .widen_string_values()
     };
 
     Ok(Type::Dict(key_ty.into(), value_ty.into()))
@@ -195,14 +298,17 @@ impl<'s> Typechecker<'s> {
     span: Span,
   ) -> Result<Type, Error> {
     let index_ty = self.synthesize_expression(index)?;
-    let expression_ty = self.synthesize_expression(expression)?;
+    let expression_ty = match self.synthesize_expression(expression)? {
+      Type::Readonly(inner) => inner.as_ref().clone(),
+      ty => ty,
+    };
 
     let dict_type = Type::Dict(
       self.context.new_existential().into(),
       self.context.new_existential().into(),
     );
 
-    if expression_ty == Type::Literal(Literal::String) {
+    if expression_ty.is_subtype_of(&Type::Literal(Literal::String)) {
       self.assert_type(index_ty, &Type::Literal(Literal::Number), span)?;
       Ok(Type::Literal(Literal::String))
     } else if !index_ty.is_subtype_of(&Type::Literal(Literal::Number))
@@ -214,10 +320,57 @@ impl<'s> Typechecker<'s> {
     }
   }
 
+  fn membership(&mut self, item_ty: Type, collection_ty: Type, span: Span) -> Result<Type, Error> {
+    let collection_ty = collection_ty.apply_context(&self.context);
+    let element = self.context.new_existential();
+
+    let readable_ty = match &collection_ty {
+      Type::Readonly(inner) => inner.as_ref(),
+      ty => ty,
+    };
+    let expected_ty = match readable_ty {
+      ty if ty.is_subtype_of(&Type::Literal(Literal::String)) => {
+        self.assert_type(item_ty, &Type::Literal(Literal::String), span)?;
+        return Ok(Type::boolean());
+      }
+      Type::Dict(..) => Type::Dict(
+        Interned::new(element.clone()),
+        Interned::new(self.context.new_existential()),
+      ),
+      Type::Set(_) => Type::Set(Interned::new(element.clone())),
+      _ => Type::List(Interned::new(element.clone())),
+    };
+
+    self.assert_type(collection_ty, &expected_ty, span)?;
+    self.assert_type(item_ty, &element, span)?;
+    Ok(Type::boolean())
+  }
+
+  // `(B -> C) << (A -> B)` is `A -> C`
+  fn composition(&mut self, outer_ty: Type, inner_ty: Type, span: Span) -> Result<Type, Error> {
+    let (a, b, c) = (
+      self.context.new_existential(),
+      self.context.new_existential(),
+      self.context.new_existential(),
+    );
+    let function = |parameter: &Type, return_type: &Type| {
+      Type::Function(Function {
+        parameters: vec![parameter.clone()],
+        optional: 0,
+        return_type: Interned::new(return_type.clone()),
+      })
+    };
+
+    self.assert_type(inner_ty, &function(&a, &b), span)?;
+    self.assert_type(outer_ty, &function(&b, &c), span)?;
+
+    Ok(function(&a, &c).apply_context(&self.context))
+  }
+
   fn dict_index(&mut self, index_ty: Type, expression_ty: Type, span: Span) -> Result<Type, Error> {
     let keys = self.context.new_existential();
     let values = self.context.new_existential();
-    let dict_ty = Type::Dict(Box::new(keys.clone()), Box::new(values.clone()));
+    let dict_ty = Type::Dict(Interned::new(keys.clone()), Interned::new(values.clone()));
 
     self.assert_type(index_ty, &keys, span)?;
     self.assert_type(expression_ty, &dict_ty, span)?;
@@ -229,7 +382,7 @@ impl<'s> Typechecker<'s> {
     self.assert_type(index_ty, &Type::Literal(Literal::Number), span)?;
 
     let list_interior = self.context.new_existential();
-    let list_ty = Type::List(Box::new(list_interior.clone()));
+    let list_ty = Type::List(Interned::new(list_interior.clone()));
     self.assert_type(expression_ty, &list_ty, span)?;
 
     Ok(list_interior)
@@ -244,12 +397,19 @@ impl<'s> Typechecker<'s> {
     span: Span,
   ) -> Result<Type, Error> {
     let list_interior = self.context.new_existential();
-    let list_ty = Type::List(Box::new(list_interior.clone()));
+    let list_ty = Type::List(Interned::new(list_interior.clone()));
 
     let expression_ty = self.synthesize_expression(expression)?;
     let index_ty = self.synthesize_expression(index)?;
     let value_ty = self.synthesize_expression(value)?;
 
+    if let Type::Readonly(_) = expression_ty.clone().apply_context(&self.context) {
+      return Error::new(
+        ErrorKind::ReadonlyAssignment(expression_ty),
+        expression.span,
+      );
+    }
+
     self.assert_type(expression_ty, &list_ty, expression.span)?;
     self.assert_type(index_ty, &Type::Literal(Literal::Number), index.span)?;
     let value_ty = self.assert_type(value_ty, &list_interior, value.span)?;
@@ -270,6 +430,8 @@ impl<'s> Typechecker<'s> {
   }
 
   pub fn module_access(&mut self, module: &str, item: &str, span: Span) -> Result<Type, Error> {
+    let module = self.module_aliases.get(module).copied().unwrap_or(module);
+
     match self.get_module_item(module, item) {
       ImportValue::Value(ty) => Ok(ty),
       ImportValue::ModuleNotFound => {
@@ -61,7 +61,8 @@ impl<'s> Typechecker<'s> {
         let r = self.synthesize_expression(right)?;
 
         if let Expr::Variable { name } = &left.expr
-          && let Type::Literal(Literal::Null | Literal::True | Literal::False) = &r
+          && let Type::Literal(Literal::Null | Literal::True | Literal::False)
+          | Type::StringValue(_) = &r
         {
           return Ok(vec![Restriction(name, l.narrow(&r))]);
         }
@@ -74,7 +74,10 @@ impl<'s> Typechecker<'s> {
     let annotation = if let Some(annotation) = type_ {
       self.type_from_annotation(annotation, &mut HashMap::default())?
     } else if let Some(expression) = expression {
-      self.synthesize_expression(expression)?.uplevel_boolean()
+      self
+        .synthesize_expression(expression)?
+        .uplevel_boolean()
+        .widen_string_values()
     } else {
       Type::NULL
     };
@@ -114,17 +117,28 @@ impl<'s> Typechecker<'s> {
           }
         }
       }
-      DeclarationIdentifier::Named(identifiers) => {
+      DeclarationIdentifier::Named(identifiers, rest) => {
         let key_ty = Type::Literal(Literal::String);
         let value_ty = self.context.new_existential();
         let dict_type = Type::Dict(key_ty.into(), value_ty.clone().into());
 
         self.assert_type(annotation, &dict_type, span)?;
+        let value_ty = value_ty.apply_context(&self.context);
 
         for identifier in identifiers {
-          self
-            .scope
-            .define(identifier.get_name(), value_ty.clone(), span)?;
+          // Keys with a default may be missing, so have the type of the default as well
+          let ty = if let Some(default) = &identifier.default {
+            let default_ty = self.synthesize_expression(default)?;
+            value_ty.clone().union(default_ty.widen_string_values())
+          } else {
+            value_ty.clone()
+          };
+
+          self.scope.define(identifier.get_name(), ty, span)?;
+        }
+        if let Some(rest) = rest {
+          let dict_type = dict_type.apply_context(&self.context);
+          self.scope.define(rest, dict_type, span)?;
         }
       }
     };
@@ -167,13 +181,31 @@ impl<'s> Typechecker<'s> {
   pub fn import_statement(
     &mut self,
     items: &[AliasItem<'s>],
-    module: &str,
+    module: &'s str,
+    alias: Option<&'s str>,
+    optional: bool,
+    span: Span,
   ) -> Result<StatementType, Error> {
+    if let Some(alias) = alias {
+      if let ImportValue::ModuleNotFound = self.get_module_item(module, "") {
+        Error::new(ErrorKind::ImportModuleNotFound(module.to_string()), span)?;
+      }
+
+      self.module_aliases.insert(alias, module);
+    }
+
     for item in items {
       match self.get_module_item(module, item.name) {
+        ImportValue::Value(ty) if optional => {
+          let ty = ty.union(Type::NULL);
+          self.scope.define(item.get_name(), ty, item.span)?;
+        }
         ImportValue::Value(ty) => {
           self.scope.define(item.get_name(), ty, item.span)?;
         }
+        ImportValue::ModuleNotFound | ImportValue::ItemNotFound if optional => {
+          self.scope.define(item.get_name(), Type::NULL, item.span)?;
+        }
         ImportValue::ModuleNotFound => {
           Error::new(
             ErrorKind::ImportModuleNotFound(module.to_string()),
@@ -205,6 +237,23 @@ impl<'s> Typechecker<'s> {
     Ok(StatementType::Returns(ty, ReturnsLikelihood::Definite))
   }
 
+  pub fn break_statement(
+    &mut self,
+    expression: &Option<Expression<'s>>,
+  ) -> Result<StatementType, Error> {
+    let ty = if let Some(expression) = expression {
+      self.synthesize_expression(expression)?
+    } else {
+      Type::NULL
+    };
+
+    if let Some(break_ty) = self.loops.pop() {
+      self.loops.push(break_ty.union(ty));
+    }
+
+    Ok(StatementType::NoReturn)
+  }
+
   pub fn while_statement(
     &mut self,
     condition: &Expression<'s>,
@@ -216,9 +265,12 @@ impl<'s> Typechecker<'s> {
     if condition_type.truthiness() == Truthiness::False {
       Ok(StatementType::NoReturn)
     } else {
-      let ty = self.synthesize_statement_with_restrictions(statement, restrictions)?;
+      self.loops.push(Type::Never);
+      let ty = self.synthesize_statement_with_restrictions(statement, restrictions);
+      let has_break = self.loops.pop() != Some(Type::Never);
+      let ty = ty?;
 
-      if ty == StatementType::NoReturn && condition_type.is_truthy() {
+      if ty == StatementType::NoReturn && condition_type.is_truthy() && !has_break {
         Error::new(ErrorKind::InfiniteLoop, condition.span)?;
       }
 
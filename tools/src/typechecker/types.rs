@@ -1,6 +1,13 @@
-use super::Context;
+use super::{Context, HashMap};
 pub use bang_syntax::ast::expression::LiteralType as Literal;
-use std::{fmt::Display, string::ToString};
+use std::{
+  cell::RefCell,
+  fmt::Display,
+  hash::{Hash, Hasher},
+  ops::Deref,
+  rc::{Rc, Weak},
+  string::ToString,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Truthiness {
@@ -9,26 +16,105 @@ pub enum Truthiness {
   Unknown,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Function {
   pub parameters: Vec<Type>,
-  pub return_type: Box<Type>,
+  pub optional: usize,
+  pub return_type: Interned,
 }
 
 pub type Existential = u16;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Type {
   Any,
   Never,
   Literal(Literal),
-  List(Box<Type>),
-  Set(Box<Type>),
-  Dict(Box<Type>, Box<Type>),
+  StringValue(String),
+  List(Interned),
+  Set(Interned),
+  Sequence(Interned),
+  Task(Interned),
+  Dict(Interned, Interned),
+  Readonly(Interned),
   Function(Function),
   Union(Vec<Type>),
   Existential(Existential),
 }
+
+// A type shared with every equal type, so cloning is cheap and equality is a pointer comparison
+#[derive(Clone, Debug)]
+pub struct Interned(Rc<Type>);
+impl Interned {
+  pub fn new(ty: Type) -> Self {
+    INTERNER.with(|interner| interner.borrow_mut().intern(ty))
+  }
+
+  pub fn apply_context(&self, context: &Context) -> Self {
+    Self::new(Type::clone(self).apply_context(context))
+  }
+}
+impl Deref for Interned {
+  type Target = Type;
+
+  fn deref(&self) -> &Type {
+    &self.0
+  }
+}
+impl AsRef<Type> for Interned {
+  fn as_ref(&self) -> &Type {
+    &self.0
+  }
+}
+impl From<Type> for Interned {
+  fn from(ty: Type) -> Self {
+    Self::new(ty)
+  }
+}
+impl PartialEq for Interned {
+  fn eq(&self, other: &Self) -> bool {
+    Rc::ptr_eq(&self.0, &other.0)
+  }
+}
+impl Eq for Interned {}
+impl Hash for Interned {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    Rc::as_ptr(&self.0).hash(state);
+  }
+}
+impl Display for Interned {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+// As the parts of a type are already interned, a type is hashed and compared without recursing.
+// Types which are no longer used are removed once the table doubles in size.
+#[derive(Default)]
+struct Interner {
+  types: HashMap<Type, Weak<Type>>,
+  next_cleanup: usize,
+}
+impl Interner {
+  fn intern(&mut self, ty: Type) -> Interned {
+    if let Some(existing) = self.types.get(&ty).and_then(Weak::upgrade) {
+      return Interned(existing);
+    }
+
+    if self.types.len() >= self.next_cleanup {
+      self.types.retain(|_, ty| ty.strong_count() > 0);
+      self.next_cleanup = (self.types.len() * 2).max(1024);
+    }
+
+    let interned = Rc::new(ty.clone());
+    self.types.insert(ty, Rc::downgrade(&interned));
+    Interned(interned)
+  }
+}
+
+thread_local! {
+  static INTERNER: RefCell<Interner> = RefCell::default();
+}
 impl Type {
   pub const NULL: Self = Self::Literal(Literal::Null);
 
@@ -78,8 +164,14 @@ impl Type {
       (_, Self::Never) => false,
       (a, b) if a == b => true,
 
-      (Self::List(a), Self::List(b)) | (Self::Set(a), Self::Set(b)) => a.is_subtype_of(b),
+      (Self::StringValue(_), Self::Literal(Literal::String)) => true,
+
+      (Self::List(a), Self::List(b))
+      | (Self::Set(a), Self::Set(b))
+      | (Self::Sequence(a), Self::Sequence(b))
+      | (Self::Task(a), Self::Task(b)) => a.is_subtype_of(b),
       (Self::Dict(a, b), Self::Dict(c, d)) => a.is_subtype_of(c) && b.is_subtype_of(d),
+      (Self::Readonly(a), Self::Readonly(b)) => a.is_subtype_of(b),
 
       (Self::Union(a), b) => a.iter().all(|a| a.is_subtype_of(b)),
       (a, Self::Union(b)) => b.iter().any(|b| a.is_subtype_of(b)),
@@ -106,22 +198,28 @@ impl Type {
       (a, b) if a.is_subtype_of(&b) => b,
       (a, b) if b.is_subtype_of(&a) => a,
 
-      (Self::Union(mut a), Self::Union(b)) => {
-        for b in b {
-          if a.iter().any(|a| !b.is_subtype_of(a)) {
-            a.push(b);
+      // Members covered by another member are dropped, so unions don't grow with repeated types
+      (a, b) => {
+        let mut types = a.into_members();
+        for ty in b.into_members() {
+          if !types.iter().any(|existing| ty.is_subtype_of(existing)) {
+            types.retain(|existing| !existing.is_subtype_of(&ty));
+            types.push(ty);
           }
         }
-        Self::Union(a)
-      }
-      (a, Self::Union(mut b)) | (Self::Union(mut b), a) => {
-        if !b.iter().any(|b| a.is_subtype_of(b)) {
-          b.push(a);
+
+        match types.len() {
+          1 => types.remove(0),
+          _ => Self::Union(types),
         }
-        Self::Union(b)
       }
+    }
+  }
 
-      (a, b) => Self::Union(vec![a, b]),
+  fn into_members(self) -> Vec<Self> {
+    match self {
+      Self::Union(types) => types,
+      ty => vec![ty],
     }
   }
 
@@ -164,12 +262,27 @@ impl Type {
     }
   }
 
+  // String literal types are only kept where they are annotated, otherwise `'a'` is a `string`
+  pub fn widen_string_values(self) -> Self {
+    match self {
+      Self::StringValue(_) => Self::Literal(Literal::String),
+      Self::Union(types) => types
+        .into_iter()
+        .map(Self::widen_string_values)
+        .fold(Self::Never, Self::union),
+      _ => self,
+    }
+  }
+
   pub fn apply_context(self, context: &Context) -> Self {
     match self {
       Self::Existential(a) => context.lookup(a).unwrap_or(Self::Existential(a)),
 
-      Self::List(a) => Self::List(a.apply_context(context).into()),
-      Self::Set(a) => Self::Set(a.apply_context(context).into()),
+      Self::List(a) => Self::List(a.apply_context(context)),
+      Self::Set(a) => Self::Set(a.apply_context(context)),
+      Self::Sequence(a) => Self::Sequence(a.apply_context(context)),
+      Self::Task(a) => Self::Task(a.apply_context(context)),
+      Self::Readonly(a) => Self::Readonly(a.apply_context(context)),
 
       Self::Function(a) => Self::Function(Function {
         parameters: a
@@ -177,7 +290,8 @@ impl Type {
           .into_iter()
           .map(|ty| ty.apply_context(context))
           .collect(),
-        return_type: a.return_type.apply_context(context).into(),
+        optional: a.optional,
+        return_type: a.return_type.apply_context(context),
       }),
 
       Self::Union(a) => Self::Union(a.into_iter().map(|ty| ty.apply_context(context)).collect()),
@@ -199,20 +313,31 @@ impl Display for Type {
       Self::Any => write!(f, "any"),
       Self::Never => write!(f, "never"),
       Self::Literal(literal) => write!(f, "{literal}"),
+      Self::StringValue(value) => write!(f, "'{value}'"),
       Self::List(ty) => write!(f, "{ty}[]"),
       Self::Set(ty) => write!(f, "set({ty})"),
+      Self::Sequence(ty) => write!(f, "sequence({ty})"),
+      Self::Task(ty) => write!(f, "task({ty})"),
+      Self::Readonly(ty) => write!(f, "readonly({ty})"),
       Self::Dict(key, values) => write!(f, "set({key}, {values})"),
-      Self::Function(func) => write!(
-        f,
-        "({}) -> {}",
-        func
+      Self::Function(func) => {
+        // Optional parameters are shown in square brackets, e.g. `(number, [string]) -> null`
+        let required = func.parameters.len() - func.optional.min(func.parameters.len());
+        let parameters: Vec<_> = func
           .parameters
           .iter()
-          .map(ToString::to_string)
-          .collect::<Vec<_>>()
-          .join(", "),
-        func.return_type
-      ),
+          .enumerate()
+          .map(|(index, ty)| {
+            if index < required {
+              ty.to_string()
+            } else {
+              format!("[{ty}]")
+            }
+          })
+          .collect();
+
+        write!(f, "({}) -> {}", parameters.join(", "), func.return_type)
+      }
       Self::Union(types) => write!(
         f,
         "{}",
@@ -226,3 +351,39 @@ impl Display for Type {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{Interned, Literal, Type};
+
+  #[test]
+  fn equal_types_are_shared() {
+    let number = || Type::Literal(Literal::Number);
+    let a = Interned::new(Type::List(Interned::new(number())));
+    let b = Interned::new(Type::List(Interned::new(number())));
+    let c = Interned::new(Type::Set(Interned::new(number())));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(a.to_string(), "number[]");
+  }
+
+  #[test]
+  fn unions_are_deduplicated() {
+    let number = Type::Literal(Literal::Number);
+    let string = Type::Literal(Literal::String);
+
+    let a = Type::NULL.union(number.clone());
+    let b = string.clone().union(number.clone());
+    assert_eq!(a.union(b).to_string(), "null | number | string");
+
+    let values = Type::StringValue("a".into()).union(Type::StringValue("b".into()));
+    assert_eq!(values.union(string).to_string(), "string");
+
+    let mut repeated = Type::Never;
+    for _ in 0..100 {
+      repeated = repeated.union(number.clone()).union(Type::NULL);
+    }
+    assert_eq!(repeated.to_string(), "number | null");
+  }
+}
@@ -0,0 +1,223 @@
+use crate::{
+  formatter::{format_with_options, FormatOptions},
+  linter::{lint_with_builtins, Diagnostic as LintDiagnostic},
+  typechecker::{typecheck_with_strictness, Error as TypecheckError, Strictness},
+};
+use bang_syntax::{ast::statement::Statement, parse, Diagnostic};
+use std::collections::BTreeMap;
+
+// The result of a tool for a file, alongside its source to show diagnostics
+pub struct FileResult<'a, T> {
+  pub filename: &'a str,
+  pub source: &'a str,
+  pub result: &'a Result<T, Diagnostic>,
+}
+
+pub struct Typechecked {
+  pub errors: Vec<TypecheckError>,
+  pub strictness: Strictness,
+}
+
+#[derive(Default)]
+struct File {
+  // Borrows from the source, so is declared first to be dropped before it
+  ast: Option<Result<Vec<Statement<'static>>, Diagnostic>>,
+  source: String,
+  lint: Option<Result<Vec<LintDiagnostic>, Diagnostic>>,
+  typecheck: Option<Result<Typechecked, Diagnostic>>,
+  format: Option<Result<String, Diagnostic>>,
+  format_options: Option<FormatOptions>,
+}
+impl File {
+  fn new(source: String) -> Self {
+    Self {
+      source,
+      ..Self::default()
+    }
+  }
+
+  // The file is parsed once, and the AST is shared by each tool until the file changes
+  fn parse(&mut self) -> (&str, &Result<Vec<Statement<'_>>, Diagnostic>) {
+    if self.ast.is_none() {
+      // The source isn't changed whilst the AST exists, and its contents are on the heap so
+      // stay in the same place when the file is moved
+      let source: &'static str = unsafe { &*(self.source.as_str() as *const str) };
+      self.ast = Some(parse(source));
+    }
+
+    (&self.source, self.ast.as_ref().expect("AST to be parsed"))
+  }
+}
+
+// A set of files, which keeps the results of each tool until the file changes
+#[derive(Default)]
+pub struct Workspace {
+  files: BTreeMap<String, File>,
+  protected_globals: Vec<String>,
+  modules: Vec<String>,
+  strict: bool,
+}
+impl Workspace {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn set_protected_globals(&mut self, protected_globals: &[&str]) {
+    if self.protected_globals.iter().eq(protected_globals) {
+      return;
+    }
+    self.protected_globals = protected_globals.iter().map(ToString::to_string).collect();
+
+    for file in self.files.values_mut() {
+      file.lint = None;
+    }
+  }
+
+  pub fn set_modules(&mut self, modules: &[&str]) {
+    if self.modules.iter().eq(modules) {
+      return;
+    }
+    self.modules = modules.iter().map(ToString::to_string).collect();
+
+    for file in self.files.values_mut() {
+      file.lint = None;
+    }
+  }
+
+  // Files without a pragma are typechecked strictly, such as when set by the config
+  pub fn set_strict(&mut self, strict: bool) {
+    if self.strict == strict {
+      return;
+    }
+    self.strict = strict;
+
+    for file in self.files.values_mut() {
+      file.typecheck = None;
+    }
+  }
+
+  // Adds or updates a file, keeping the existing results if its source hasn't changed
+  pub fn set_file(&mut self, filename: &str, source: String) {
+    match self.files.get(filename) {
+      Some(file) if file.source == source => {}
+      _ => {
+        self.files.insert(filename.to_string(), File::new(source));
+      }
+    }
+  }
+
+  pub fn remove_file(&mut self, filename: &str) {
+    self.files.remove(filename);
+  }
+
+  // Forgets the results for a file, such as when a file it depends on changes
+  pub fn invalidate(&mut self, filename: &str) {
+    if let Some(file) = self.files.get_mut(filename) {
+      *file = File::new(std::mem::take(&mut file.source));
+    }
+  }
+
+  pub fn filenames(&self) -> impl Iterator<Item = &str> {
+    self.files.keys().map(String::as_str)
+  }
+
+  pub fn source(&self, filename: &str) -> Option<&str> {
+    self.files.get(filename).map(|file| file.source.as_str())
+  }
+
+  pub fn lint(&mut self, filename: &str) -> Option<FileResult<'_, Vec<LintDiagnostic>>> {
+    let protected_globals: Vec<_> = self.protected_globals.iter().map(String::as_str).collect();
+    let modules: Vec<_> = self.modules.iter().map(String::as_str).collect();
+    let file = self.files.get_mut(filename)?;
+
+    if file.lint.is_none() {
+      let (source, ast) = file.parse();
+      let diagnostics = (ast.as_ref())
+        .map(|ast| lint_with_builtins(source, ast, &protected_globals, &modules))
+        .map_err(Clone::clone);
+      file.lint = Some(diagnostics);
+    }
+
+    self.result(filename, |file| file.lint.as_ref())
+  }
+
+  pub fn lint_all(&mut self) -> Vec<FileResult<'_, Vec<LintDiagnostic>>> {
+    for filename in self.files.keys().cloned().collect::<Vec<_>>() {
+      self.lint(&filename);
+    }
+
+    self.results(|file| file.lint.as_ref())
+  }
+
+  pub fn typecheck(&mut self, filename: &str) -> Option<FileResult<'_, Typechecked>> {
+    let strict = self.strict;
+    let file = self.files.get_mut(filename)?;
+
+    if file.typecheck.is_none() {
+      let (_, ast) = file.parse();
+      let typechecked = (ast.as_ref())
+        .map(|ast| {
+          let strictness = Strictness::from_pragmas(ast).or_strict(strict);
+          Typechecked {
+            errors: typecheck_with_strictness(ast, strictness),
+            strictness,
+          }
+        })
+        .map_err(Clone::clone);
+      file.typecheck = Some(typechecked);
+    }
+
+    self.result(filename, |file| file.typecheck.as_ref())
+  }
+
+  pub fn typecheck_all(&mut self) -> Vec<FileResult<'_, Typechecked>> {
+    for filename in self.files.keys().cloned().collect::<Vec<_>>() {
+      self.typecheck(&filename);
+    }
+
+    self.results(|file| file.typecheck.as_ref())
+  }
+
+  // Formatting is redone if the options differ from when it was last formatted
+  pub fn format_file(
+    &mut self,
+    filename: &str,
+    options: FormatOptions,
+  ) -> Option<FileResult<'_, String>> {
+    let file = self.files.get_mut(filename)?;
+
+    if file.format.is_none() || file.format_options != Some(options) {
+      let (source, ast) = file.parse();
+      let formatted = (ast.as_ref())
+        .map(|ast| format_with_options(source, ast, options))
+        .map_err(Clone::clone);
+      file.format = Some(formatted);
+      file.format_options = Some(options);
+    }
+
+    self.result(filename, |file| file.format.as_ref())
+  }
+
+  fn result<'a, T>(
+    &'a self,
+    filename: &str,
+    result: impl Fn(&'a File) -> Option<&'a Result<T, Diagnostic>>,
+  ) -> Option<FileResult<'a, T>> {
+    let (filename, file) = self.files.get_key_value(filename)?;
+
+    Some(FileResult {
+      filename,
+      source: &file.source,
+      result: result(file)?,
+    })
+  }
+
+  fn results<'a, T>(
+    &'a self,
+    result: impl Fn(&'a File) -> Option<&'a Result<T, Diagnostic>>,
+  ) -> Vec<FileResult<'a, T>> {
+    (self.files.keys())
+      .filter_map(|filename| self.result(filename, &result))
+      .collect()
+  }
+}
@@ -0,0 +1,95 @@
+use bang_syntax::parse;
+use bang_tools::{completions, Completion, CompletionKind};
+
+fn completions_at(source: &str, marker: &str) -> Vec<Completion> {
+  let position = source.find(marker).expect("marker to be in source");
+  let ast = parse(source).unwrap();
+  completions(&ast, u32::try_from(position).unwrap())
+}
+
+fn find<'a>(completions: &'a [Completion], label: &str) -> Option<&'a Completion> {
+  completions
+    .iter()
+    .find(|completion| completion.label == label)
+}
+
+#[test]
+fn variables_with_types() {
+  let completions = completions_at("let a = 5\nlet b = 'hello'\nlet c = a\n", "let c");
+
+  let a = find(&completions, "a").unwrap();
+  assert_eq!(a.kind, CompletionKind::Variable);
+  assert_eq!(a.detail, "number");
+  assert_eq!(find(&completions, "b").unwrap().detail, "'hello'");
+  assert!(find(&completions, "print").is_some());
+
+  // Variables declared after the position aren't in scope yet
+  assert!(find(&completions, "c").is_none());
+}
+
+#[test]
+fn variables_in_functions() {
+  let source = "
+let outer = 5
+let f = (x: number) ->
+  let inner = x + 1
+  return inner
+let after = 2
+";
+  let completions = completions_at(source, "return");
+  assert_eq!(find(&completions, "x").unwrap().detail, "number");
+  assert!(find(&completions, "inner").is_some());
+  assert!(find(&completions, "outer").is_some());
+  assert!(find(&completions, "after").is_none());
+
+  let completions = completions_at(source, "let after");
+  assert!(find(&completions, "x").is_none());
+  assert!(find(&completions, "inner").is_none());
+}
+
+#[test]
+fn module_items() {
+  let completions = completions_at("from maths as m\nlet a = 1\n", "let a");
+
+  let sqrt = find(&completions, "maths::sqrt").unwrap();
+  assert_eq!(sqrt.kind, CompletionKind::ModuleItem);
+  assert_eq!(sqrt.detail, "(number) -> number");
+  assert!(find(&completions, "m::sqrt").is_some());
+  assert!(find(&completions, "string::trim").is_some());
+}
+
+#[test]
+fn dict_keys() {
+  let source = "
+let config = { 'name': 'bang', 'version': 2 }
+config['debug'] = true
+let a = config
+config['later'] = false
+";
+  let completions = completions_at(source, "let a");
+  let keys: Vec<_> = completions
+    .iter()
+    .filter(|completion| completion.kind == CompletionKind::DictKey)
+    .map(|completion| (completion.label.as_str(), completion.detail.as_str()))
+    .collect();
+
+  assert_eq!(
+    keys,
+    [
+      ("name", "config"),
+      ("version", "config"),
+      ("debug", "config")
+    ]
+  );
+}
+
+#[test]
+fn at_end_of_file() {
+  let source = "let a = [1, 2]\n";
+  let during = completions_at(source, "\n");
+  assert!(find(&during, "a").is_none());
+
+  let ast = parse(source).unwrap();
+  let after = completions(&ast, u32::try_from(source.len()).unwrap());
+  assert_eq!(find(&after, "a").unwrap().detail, "number[]");
+}
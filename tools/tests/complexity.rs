@@ -0,0 +1,33 @@
+use bang_syntax::parse;
+use bang_tools::complexity;
+
+#[test]
+fn branches_and_nesting() {
+  let code = "
+let outer = (x) ->
+  if (x > 3)
+    while (x > 0)
+      x = x - 1
+  let inner = () => x ?? 1 or 2
+  inner()
+if (outer(4)) print(1)
+";
+  let ast = parse(code).unwrap();
+  let results = complexity(code, &ast);
+
+  let summary: Vec<_> = results
+    .iter()
+    .map(|function| {
+      (
+        function.name.as_str(),
+        function.line,
+        function.branches,
+        function.nesting,
+      )
+    })
+    .collect();
+  assert_eq!(
+    summary,
+    vec![("Root", 1, 1, 1), ("outer", 2, 2, 2), ("inner", 6, 2, 0)]
+  );
+}
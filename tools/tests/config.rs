@@ -0,0 +1,114 @@
+use bang_tools::{Config, LineEnding, QuoteStyle, CONFIG_FILENAME};
+use std::fs;
+
+#[test]
+fn parses_settings() {
+  let config = Config::parse(
+    "
+# Project settings
+[run]
+strict_equality = true
+
+[typecheck]
+strict = true
+
+[lint]
+disabled = [
+  'no-float-equality', # comments are allowed
+  'No Yoda Equality',
+]
+
+[format]
+indent = 4
+quote = 'double'
+line_width = 80
+trailing_commas = false
+line_ending = 'crlf'
+",
+  )
+  .unwrap();
+
+  assert_eq!(config.strict, Some(true));
+  assert_eq!(config.strict_equality, Some(true));
+  assert!(!config.is_rule_enabled("No Float Equality"));
+  assert!(!config.is_rule_enabled("No Yoda Equality"));
+  assert!(config.is_rule_enabled("No Self Assign"));
+
+  let options = config.format_options();
+  assert_eq!(options.indent, 4);
+  assert_eq!(options.quote, QuoteStyle::Double);
+  assert_eq!(options.line_width, 80);
+  assert!(!options.trailing_commas);
+  assert_eq!(options.line_ending, LineEnding::Crlf);
+}
+
+#[test]
+fn missing_settings_use_defaults() {
+  let config = Config::parse("[format]\nindent = 3\n").unwrap();
+  let options = config.format_options();
+
+  assert_eq!(config.strict, None);
+  assert_eq!(config.strict_equality, None);
+  assert_eq!(options.indent, 3);
+  assert_eq!(options.quote, QuoteStyle::Single);
+  assert_eq!(options.line_width, 100);
+  assert!(options.trailing_commas);
+}
+
+#[test]
+fn invalid_config() {
+  let error = |source| Config::parse(source).unwrap_err();
+
+  assert_eq!(error("[format]\n\nindent = 'four'").line, Some(3));
+  assert_eq!(
+    error("[format]\n\nindent = 'four'").message,
+    "Expected `indent` to be an integer, found a string"
+  );
+  assert_eq!(error("[other]").message, "Unknown table `[other]`");
+  assert_eq!(error("colour = true").message, "Unknown key `colour`");
+  assert_eq!(
+    error("[lint]\nindent = 2").message,
+    "Unknown key `indent` in `[lint]`"
+  );
+  assert_eq!(error("[format]\nindent = -2").line, Some(2));
+  assert_eq!(error("[typecheck]\nstrict").line, Some(2));
+  assert_eq!(error("[typecheck]\n\nstrict = yes").line, Some(3));
+  assert_eq!(error("[typecheck]\nstrict = true false").line, Some(2));
+  assert!(Config::parse("[lint]\ndisabled = ['a'").is_err());
+  assert_eq!(
+    error("[typecheck]\nstrict = 'yes'").message,
+    "Expected `strict` to be a boolean, found a string"
+  );
+  assert_eq!(error("strict = true").message, "Unknown key `strict`");
+  assert_eq!(
+    error("[typecheck]\nstrict_equality = true").message,
+    "Unknown key `strict_equality` in `[typecheck]`"
+  );
+  assert_eq!(error("[format]\nquote = 'back'").line, Some(2));
+}
+
+#[test]
+fn discovers_config_in_parent_directory() {
+  let root = std::env::temp_dir().join(format!("bang-config-{}", std::process::id()));
+  let nested = root.join("src").join("nested");
+  fs::create_dir_all(&nested).unwrap();
+  fs::write(
+    root.join(CONFIG_FILENAME),
+    "[typecheck]\nstrict = true\n[format]\nindent = 8\n",
+  )
+  .unwrap();
+
+  let config = Config::discover(&nested.join("main.bang")).unwrap();
+  let root = fs::canonicalize(&root).unwrap();
+
+  assert_eq!(config.path, Some(root.join(CONFIG_FILENAME)));
+  assert_eq!(config.indent, Some(8));
+  assert_eq!(config.strict, Some(true));
+
+  fs::write(root.join(CONFIG_FILENAME), "[format]\nindent = true\n").unwrap();
+  let error = Config::discover(&nested.join("main.bang")).unwrap_err();
+  assert_eq!(error.path, Some(root.join(CONFIG_FILENAME)));
+  assert_eq!(error.line, Some(2));
+
+  fs::remove_dir_all(&root).unwrap();
+}
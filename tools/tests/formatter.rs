@@ -28,6 +28,16 @@ fn blank_program_outputs_nothing() {
   assert_format!("", "");
 }
 
+#[test]
+fn keeps_shebang() {
+  assert_format!(
+    "#!/usr/bin/env bang\nprint( 5 )\n",
+    "#!/usr/bin/env bang\nprint(5)\n"
+  );
+  assert_format!("#!/usr/bin/env bang\n\nlet a = 5\n");
+  assert_format!("#!/usr/bin/env bang\n");
+}
+
 #[test]
 fn binary_expression() {
   assert_format!("3+4", "3 + 4");
@@ -42,6 +52,7 @@ fn binary_expression() {
   assert_format!("3 >= 4");
   assert_format!("3 <= 4");
   assert_format!("null ?? 4");
+  assert_format!("3   in[3]", "3 in [3]");
 }
 
 #[test]
@@ -194,6 +205,21 @@ fn function_with_expression_body() {
   assert_format!("(  a : string )   =>null", "(a: string) => null");
 }
 
+#[test]
+fn function_with_default_parameters() {
+  assert_format!("(a,b=10)=>a+b", "(a, b = 10) => a + b");
+  assert_format!("(a : number=  5) => a", "(a: number = 5) => a");
+  assert_format!("(a=5)=>a", "(a = 5) => a");
+}
+
+#[test]
+fn trailing_function() {
+  assert_format!("map(items)=>it*2", "map(items) => it * 2");
+  assert_format!("each( )    =>   print(it)", "each() => print(it)");
+  assert_format!("items >> each() ->\n  print(it)\n");
+  assert_format!("map(items, (x) => x)");
+}
+
 #[test]
 fn function_with_parameters_multiline() {
   assert_format!(
@@ -210,6 +236,22 @@ fn function_with_parameters_multiline() {
   );
 }
 
+#[test]
+fn function_with_default_parameters_multiline() {
+  assert_format!(
+    "(a: number = 5,\n b: string[] = ['a', 'b'],\n c: (number) -> number = (x) => x + 1) => a",
+    "(\n  a: number = 5,\n  b: string[] = ['a', 'b'],\n  c: (number) -> number = (x) => x + 1,\n) => a"
+  );
+  assert_format!(
+    "(\na: number?   =null, b = { 'key': 1 }) => a",
+    "(\n  a: number? = null, b = { 'key': 1 }, \n) => a"
+  );
+  assert_format!(
+    "let f = (a: number, b: dict(string, number) = dict::new()) ->\n  return a\n",
+    "let f = (a: number, b: dict(string, number) = dict::new()) ->\n  return a\n"
+  );
+}
+
 #[test]
 fn declaration_statement() {
   assert_format!("let x = 7", "let x = 7");
@@ -297,6 +339,10 @@ fn else_if_statement() {
     "if (true) doStuff()\nelse if (false) dontDo()\nelse doOtherStuff()",
     "if (true) doStuff()\nelse if (false) dontDo()\nelse doOtherStuff()"
   );
+  assert_format!(
+    "if (a)\n  b()\n  c()\nelse\n  if (d)\n    e()\n    f()\n  else if (g) h()\n  else i()",
+    "if (a)\n  b()\n  c()\nelse if (d)\n  e()\n  f()\nelse if (g) h()\nelse i()"
+  );
 }
 
 #[test]
@@ -310,6 +356,30 @@ fn while_statement() {
   );
 }
 
+#[test]
+fn loop_expression() {
+  assert_format!("let a = loop   break  5", "let a = loop break 5");
+  assert_format!("loop\n  break", "loop break");
+  assert_format!(
+    "let a = loop\n  i += 1\n  if (i > 3) break i",
+    "let a = loop\n  i += 1\n  if (i > 3) break i"
+  );
+  assert_format!("while (true)\n  break", "while (true) break");
+}
+
+#[test]
+fn do_expression() {
+  assert_format!("let a = do   5", "let a = do 5");
+  assert_format!(
+    "let a = do\n  let b = 1\n  b   +  2",
+    "let a = do\n  let b = 1\n  b + 2"
+  );
+  assert_format!(
+    "let f = (a) => do\n  let b = a\n  b",
+    "let f = (a) => do\n  let b = a\n  b"
+  );
+}
+
 #[test]
 fn assignment_operator() {
   assert_format!("x += 1", "x += 1");
@@ -322,6 +392,20 @@ fn assignment_operator() {
   assert_format!("x = x / 1", "x /= 1");
 }
 
+#[test]
+fn non_null_and_cast() {
+  assert_format!("a[0]!", "a[0]!");
+  assert_format!("a  as   number?", "a as number?");
+  assert_format!("!a! as boolean", "!a! as boolean");
+}
+
+#[test]
+fn ascription() {
+  assert_format!("( [ ] :number[] )", "([]: number[])");
+  assert_format!("(a : number)", "(a: number)");
+  assert_format!("(\n  a + 1 : number\n)", "(a + 1: number)");
+}
+
 #[test]
 fn fibonacci_iterative() {
   let fibonacci_iterative = "
@@ -428,6 +512,10 @@ fn import_statement() {
   assert_format!("from maths import { sqrt }");
   assert_format!("from maths import { sqrt, pow }");
   assert_format!("from maths import { sqrt, pow, abs }");
+  assert_format!(
+    "from fs import?{ readFile }",
+    "from fs import? { readFile }"
+  );
   assert_format!("from maths import {\n  sqrt, pow, abs, floor, \n}");
   assert_format!("from maths import {\n  sqrt,\n  pow,\n  abs,\n  floor,\n}");
   assert_format!("from 'maths' import { sqrt }", "from maths import { sqrt }");
@@ -452,6 +540,14 @@ fn import_statement_alias() {
   );
 }
 
+#[test]
+fn import_statement_module_alias() {
+  assert_format!("from maths as m");
+  assert_format!("from maths as m import { sqrt }");
+  assert_format!("from 'maths'   as   m", "from maths as m");
+  assert_format!("from './a/long/path.bang' as path");
+}
+
 #[test]
 fn declaration_statement_with_type() {
   assert_format!("let x :   number = 7", "let x: number = 7");
@@ -492,6 +588,8 @@ fn types() {
 
   assert_format!("let b: (number) -> string");
   assert_format!("let b: () -> string");
+  assert_format!("let g: 'read' | 'write'");
+  assert_format!("let g: \"read\"", "let g: 'read'");
 }
 
 #[test]
@@ -499,6 +597,11 @@ fn pipeline() {
   assert_format!("7 >> multiply(4)");
 }
 
+#[test]
+fn composition() {
+  assert_format!("let a = double<<  increment", "let a = double << increment");
+}
+
 #[test]
 fn index() {
   assert_format!("'hello' [ 3 ] ", "'hello'[3]");
@@ -533,6 +636,15 @@ fn named_destructuring() {
     "let { \n  a as b,\n  c,\n  d as e,\n} = something"
   );
   assert_format!("let { } = something");
+  assert_format!(
+    "let {a=1,b as c= 'x',..rest}=something",
+    "let { a = 1, b as c = 'x', ..rest } = something"
+  );
+  assert_format!("let { ..rest } = something");
+  assert_format!(
+    "let { a = 1,\n b, ..rest } = something",
+    "let { \n  a = 1,\n  b,\n  ..rest,\n} = something"
+  );
 }
 
 #[test]
@@ -625,3 +737,82 @@ fn dictionary() {
   assert_format!("{ 'hello': hello }", "{ hello }");
   assert_format!("{}");
 }
+
+#[test]
+fn line_endings() {
+  use bang_tools::{format_with_options, FormatOptions, LineEnding};
+
+  let format_line_ending = |source: &str, line_ending| {
+    let ast = parse(source).unwrap();
+    let options = FormatOptions {
+      line_ending,
+      ..Default::default()
+    };
+    format_with_options(source, &ast, options)
+  };
+
+  let source = "let a = 1 // comment\r\nlet b = 'multi\r\nline'\r\n";
+  assert_eq!(format(source, &parse(source).unwrap()), source);
+  assert_eq!(
+    format_line_ending(source, LineEnding::Lf),
+    "let a = 1 // comment\nlet b = 'multi\nline'\n"
+  );
+
+  let source = "let a = 1\nlet b = 2\n";
+  assert_eq!(format(source, &parse(source).unwrap()), source);
+  assert_eq!(
+    format_line_ending(source, LineEnding::Crlf),
+    "let a = 1\r\nlet b = 2\r\n"
+  );
+}
+
+#[test]
+fn format_options() {
+  use bang_tools::{format_with_options, FormatOptions, QuoteStyle};
+
+  let format_options = |source: &str, options| {
+    let ast = parse(source).unwrap();
+    format_with_options(source, &ast, options)
+  };
+
+  let options = FormatOptions {
+    indent: 4,
+    quote: QuoteStyle::Double,
+    ..Default::default()
+  };
+  assert_eq!(
+    format_options("if (a)\n  b = 'hello'\n  c = \"it's\"\n", options),
+    "if (a)\n    b = \"hello\"\n    c = \"it's\"\n"
+  );
+  assert_eq!(
+    format_options("let a = 'say \"hi\"'\n", options),
+    "let a = 'say \"hi\"'\n"
+  );
+
+  let options = FormatOptions {
+    line_width: 20,
+    ..Default::default()
+  };
+  assert_eq!(
+    format_options("print(first, second)\n", options),
+    "print(first, second)\n"
+  );
+  assert_eq!(
+    format_options("let list = [first, second, third]\n", options),
+    "let list = [\n  first,\n  second,\n  third,\n]\n"
+  );
+  assert_eq!(
+    format_options("callFunction(first, [second, third])\n", options),
+    "callFunction(\n  first,\n  [second, third],\n)\n"
+  );
+
+  let options = FormatOptions {
+    line_width: 20,
+    trailing_commas: false,
+    ..Default::default()
+  };
+  assert_eq!(
+    format_options("let list = [first, second, third]\n", options),
+    "let list = [\n  first,\n  second,\n  third\n]\n"
+  );
+}
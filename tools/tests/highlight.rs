@@ -0,0 +1,51 @@
+use bang_syntax::{KEYWORDS, LITERALS, OPERATORS};
+use bang_tools::{textmate_grammar, tree_sitter_highlights, vim_syntax};
+
+#[test]
+fn every_keyword_is_highlighted() {
+  let (textmate, vim, tree_sitter) = (textmate_grammar(), vim_syntax(), tree_sitter_highlights());
+
+  let vim_keywords: Vec<_> = (vim.lines())
+    .filter(|line| line.starts_with("syntax keyword"))
+    .flat_map(|line| line.split_whitespace().skip(3))
+    .collect();
+
+  for (keyword, _) in KEYWORDS.iter().chain(&LITERALS) {
+    assert!(
+      textmate.contains(&format!("{keyword}|")) || textmate.contains(&format!("|{keyword})"))
+    );
+    assert!(vim_keywords.contains(keyword), "{keyword}");
+    assert!(tree_sitter.contains(&format!("\"{keyword}\"")), "{keyword}");
+  }
+}
+
+#[test]
+fn every_operator_is_highlighted() {
+  let tree_sitter = tree_sitter_highlights();
+
+  for (operator, _) in OPERATORS {
+    assert!(
+      tree_sitter.contains(&format!("  \"{operator}\"\n")),
+      "{operator}"
+    );
+  }
+}
+
+#[test]
+fn textmate_grammar_escapes_operators() {
+  let grammar = textmate_grammar();
+
+  assert!(grammar.contains(r#""keyword.control.bang", "match": "\\b(as|break|do|"#));
+  assert!(grammar.contains(r"\\?\\?|>>|<<"));
+  assert!(grammar.contains(r#""begin": "\\$\\{""#));
+  assert!(grammar.contains(r#""begin": "`""#));
+}
+
+#[test]
+fn vim_syntax_matches_operators_literally() {
+  let syntax = vim_syntax();
+
+  assert!(syntax.contains("syntax keyword bangOperator and or\n"));
+  assert!(syntax.contains(r"syntax match bangOperator #\V&&\|||\|->\|=>\|"));
+  assert!(syntax.contains("let b:current_syntax = 'bang'"));
+}
@@ -48,6 +48,24 @@ if ({})
   "No Constant Conditions" [2 8 10 17 19 21 23]
 );
 
+bang_lint!(no_long_else_if_chain
+"
+if (a == 1) one()
+else if (a == 2) two()
+else if (a == 3) three()
+else if (a == 4) four()
+else if (a == 5) five()
+else other()
+
+if (a == 1) one()
+else if (a == 2) two()
+else if (a == 3) three()
+else if (a == 4) four()
+else other()
+"
+  "No Long Else If Chain" [2]
+);
+
 bang_lint!(no_negative_zero
 "
 let a = -0
@@ -58,6 +76,16 @@ let d = -0.0
   "No Negative Zero" [2 4 5]
 );
 
+bang_lint!(no_float_equality
+"
+let a = 0.1 + 0.2 == 0.3
+let b = a != 1.5
+let c = 2 == 2.0
+let d = a == 1_000
+"
+  "No Float Equality" [2 3]
+);
+
 bang_lint!(no_self_assign
 "
 let a = 8
@@ -69,6 +97,20 @@ b = b
   "No Self Assign" [6]
 );
 
+bang_lint!(no_assignment_in_condition
+"
+let a = 1
+let list = [1]
+if (a = 5) print(a)
+while (a = next()) print(a)
+if ((a = 5)) print(a)
+if (a == 5) print(a)
+while (a += 1) print(a)
+if (list[0] = 2) print(list)
+"
+  "No Assignment In Condition" [4 5 9]
+);
+
 bang_lint!(no_unreachable_code
 "
 let x = () -> number
@@ -88,6 +130,19 @@ let y = () -> number | null
   "No Unreachable Code" [5 13]
 );
 
+bang_lint!(no_unreachable_code_after_break
+"
+let x = 0
+while (true)
+  break
+  x += 1
+let y = loop
+  if (x > 2) break x
+  x += 1
+"
+  "No Unreachable Code" [5]
+);
+
 bang_lint!(no_yoda_equality_check
 "
 x == y
@@ -158,3 +213,260 @@ let { k } = 55
 "
   "No Unused Variables" [8 6 13 12 11 9 9 2]
 );
+
+bang_lint!(no_unused_variables_trailing_function
+"
+list::map([1, 2]) => 3
+list::map([1, 2]) => it + 1
+list::map([1, 2], (it) => 3)
+"
+  "No Unused Variables" [4]
+);
+
+bang_lint!(prefer_nullish_coalescing
+"
+if (a == null) a = 5
+if (null == b)
+  b = 'default'
+if (c == null) d = 5
+if (e == null) f = 1
+else f = e
+if (g != null) h = g
+else h = 2
+if (i == null) i = 5
+else i = 6
+"
+  "Prefer Nullish Coalescing" [2 3 6 8]
+);
+
+bang_lint!(no_negated_null_comparison
+"
+let a = !(b == null)
+let c = !(d != null)
+let e = !(f > null)
+let g = !h
+"
+  "No Negated Null Comparison" [2 3]
+);
+
+#[test]
+fn null_comparison_fixes() {
+  let code = "if (a == null) a = b or c\nlet x = !(y != null)\n";
+  let ast = parse(code).unwrap();
+  let fixes: Vec<_> = lint(code, &ast)
+    .into_iter()
+    .flat_map(|warning| warning.fixes)
+    .map(|fix| fix.replacement)
+    .collect();
+
+  assert_eq!(fixes, vec!["a = a ?? (b or c)", "y == null"]);
+}
+
+bang_lint!(no_nan_comparison
+"
+let a = b == maths::NAN
+let c = maths::NAN != d
+let e = (f == (maths::NAN))
+let g = maths::isNaN(h)
+let i = j == maths::INFINITY
+"
+  "No NaN Comparison" [2 3 4]
+);
+
+bang_lint!(no_division_by_zero
+"
+let a = 1 / 0
+let b = a % (0.0)
+let c = a / -0_0
+a /= 0
+let d = [1]
+d[0] /= 0
+let e = a / 1 + 0 / a
+let f = a / (1 - 1)
+"
+  "No Division By Zero" [2 3 4 5 7]
+);
+
+#[test]
+fn nan_comparison_fixes() {
+  let code = "let a = b == maths::NAN\nlet c = maths::NAN != d + 1\n";
+  let ast = parse(code).unwrap();
+  let fixes: Vec<_> = lint(code, &ast)
+    .into_iter()
+    .flat_map(|warning| warning.fixes)
+    .map(|fix| fix.replacement)
+    .collect();
+
+  assert_eq!(fixes, vec!["maths::isNaN(b)", "!maths::isNaN(d + 1)"]);
+}
+
+#[test]
+fn diagnostics_include_columns() {
+  let code = "let x = 5\nif (!(x == null)) x\n";
+  let ast = parse(code).unwrap();
+  let warnings = lint(code, &ast);
+
+  assert_eq!(warnings.len(), 1);
+  assert_eq!(warnings[0].lines, vec![2]);
+  assert_eq!(warnings[0].columns, vec![5]);
+}
+
+bang_lint!(numeric_literals
+"
+let a = 9007199254740992
+let b = 9007199254740993
+let c = 100_000_000_000_000_000_001
+let d = 1_000_000
+let e = 1_00
+let f = 10_0000.5
+let g = 0123
+let h = 0.5
+let i = 0
+let j = 00.5
+"
+  "No Precision Loss" [3 4]
+  "No Uneven Digit Grouping" [6 7]
+  "No Leading Zeros" [8 11]
+);
+
+#[test]
+fn numeric_literal_spans() {
+  let code = "let x = [1, 0123]
+";
+  let ast = parse(code).unwrap();
+  let warnings = lint(code, &ast);
+  let warning = warnings
+    .iter()
+    .find(|warning| warning.title == "No Leading Zeros")
+    .unwrap();
+
+  assert_eq!(warning.columns, vec![13]);
+  let span = warning.spans[0];
+  assert_eq!(&code[span.start as usize..span.end as usize], "0123");
+}
+
+#[test]
+fn no_unknown_module() {
+  use bang_tools::lint_with_builtins;
+
+  let code = "
+from mylib import { a }
+let b = maths::floor(2.5)
+let c = mylib::b
+let d = unknown::c
+let e = other::d + string::length('')
+from './lib.bang' as lib
+let f = lib::g
+";
+  let ast = parse(code).unwrap();
+
+  let warnings = lint_with_builtins(code, &ast, &[], &["maths", "string"]);
+  let warning = warnings
+    .iter()
+    .find(|warning| warning.title == "No Unknown Module")
+    .unwrap();
+  assert_eq!(warning.lines, vec![5, 6]);
+
+  // Without knowing the modules, none are reported
+  let warnings = lint(code, &ast);
+  assert!(!warnings
+    .iter()
+    .any(|warning| warning.title == "No Unknown Module"));
+}
+
+#[test]
+fn no_shadowing_builtins() {
+  use bang_tools::lint_with_protected_globals;
+
+  let code = "
+let print = 5
+let toString = (type) => type
+from maths import { floor as type }
+let [a, type] = [1, 2]
+print = 7
+";
+  let ast = parse(code).unwrap();
+
+  let warnings = lint_with_protected_globals(code, &ast, &["print", "type", "toString"]);
+  let warning = warnings
+    .iter()
+    .find(|warning| warning.title == "No Shadowing Builtins")
+    .unwrap();
+  assert_eq!(warning.lines, vec![2, 3, 3, 4, 5, 6]);
+
+  let warnings = lint(code, &ast);
+  assert!(!warnings
+    .iter()
+    .any(|warning| warning.title == "No Shadowing Builtins"));
+}
+
+bang_lint!(no_unused_pure_result
+"
+let add = (a, b) => a + b
+let log = (a) => print(a)
+let double = (a) => add(a, a)
+add(1, 2)
+log(1)
+double(3)
+maths::floor(2.5)
+list::push([], 4)
+let x = add(1, 2)
+dict::freeze({})
+dict::sortedKeys({}, (a, b) => log(a))
+dict::keys({})
+"
+  "No Unused Pure Result" [5 7 8 13]
+);
+
+#[test]
+fn custom_rules() {
+  use bang_syntax::ast::statement::{Statement, Stmt};
+  use bang_tools::{builtin_lint_rules, lint_with, LintDiagnostic, LintRule};
+
+  struct NoWhileLoops;
+  impl LintRule for NoWhileLoops {
+    fn check(&self, source: &str, ast: &[Statement]) -> LintDiagnostic {
+      let spans: Vec<_> = ast
+        .iter()
+        .filter(|statement| matches!(statement.stmt, Stmt::While { .. }))
+        .map(|statement| statement.span)
+        .collect();
+
+      LintDiagnostic {
+        title: "No While Loops".to_string(),
+        message: "Use list functions instead".to_string(),
+        lines: spans
+          .iter()
+          .map(|span| span.get_line_number(source))
+          .collect(),
+        columns: spans
+          .iter()
+          .map(|span| span.get_column_number(source))
+          .collect(),
+        spans,
+        fixes: Vec::new(),
+      }
+    }
+  }
+
+  let code = "
+let a = 5
+while (a > 0) a = a - 1
+if (a == 0.5) print(a)
+";
+  let ast = parse(code).unwrap();
+
+  let warnings = lint_with(code, &ast, &[&NoWhileLoops]);
+  assert_eq!(warnings.len(), 1);
+  assert_eq!(warnings[0].title, "No While Loops");
+  assert_eq!(warnings[0].lines, vec![3]);
+
+  let mut rules = builtin_lint_rules(&[], &[]);
+  rules.push(Box::new(NoWhileLoops));
+  let rules: Vec<&dyn LintRule> = rules.iter().map(AsRef::as_ref).collect();
+  let titles: Vec<_> = lint_with(code, &ast, &rules)
+    .into_iter()
+    .map(|warning| warning.title)
+    .collect();
+  assert_eq!(titles, vec!["No Float Equality", "No While Loops"]);
+}
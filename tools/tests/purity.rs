@@ -0,0 +1,51 @@
+use bang_syntax::parse;
+use bang_tools::Purity;
+
+#[test]
+fn classifies_functions() {
+  let code = "
+from maths import { floor }
+from list as l
+let count = 0
+let add = (a, b) => a + b
+let increment = () => count = count + 1
+let round = (a) => floor(a) + add(a, 0.5)
+let report = (a) => print(a)
+let factorial = (n) ->
+  if (n <= 1) return 1
+  return n * factorial(n - 1)
+let callsReport = () => report(5)
+let callsParameter = (f) => f()
+let local = () ->
+  let x = [1, 2]
+  x = list::reverse(x)
+  return x
+let mutates = (xs) => xs[0] = 1
+let aliased = (xs) => l::reverse(xs)
+let aliasedPush = (xs) => l::push(xs, 1)
+";
+  let ast = parse(code).unwrap();
+  let purity = Purity::analyse(code, &ast);
+
+  let results: Vec<_> = purity
+    .functions
+    .iter()
+    .map(|function| (function.name.as_str(), function.pure))
+    .collect();
+  assert_eq!(
+    results,
+    vec![
+      ("add", true),
+      ("increment", false),
+      ("round", true),
+      ("report", false),
+      ("factorial", true),
+      ("callsReport", false),
+      ("callsParameter", false),
+      ("local", true),
+      ("mutates", false),
+      ("aliased", true),
+      ("aliasedPush", false),
+    ]
+  );
+}
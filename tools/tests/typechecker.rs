@@ -19,6 +19,58 @@ macro_rules! assert_fails {
   };
 }
 
+#[test]
+fn string_literal_types() {
+  assert_correct!("let mode: 'read' | 'write' = 'read'");
+  assert_correct!("let mode: string = 'read' as 'read'");
+  assert_fails!("let mode: 'read' | 'write' = 'append'");
+  assert_fails!("let mode: 'read' = 'read' + 'write'");
+  assert_fails!("let open = (mode: 'read' | 'write') => mode\nopen('append')");
+  assert_fails!("let mode: 'read' = 'read'\nmode = 'write'");
+
+  // Without an annotation, string literals are strings
+  assert_correct!("let mode = 'read'\nmode = 'write'");
+  assert_correct!("let modes = ['read']\nmodes[0] = 'write'");
+  assert_correct!("let a: string = 'read' + 'write'");
+  assert_correct!("let a = 'a' == 'b'");
+
+  let ast = parse("let a: 'read' = 'write'").unwrap();
+  let result = typecheck(&ast);
+  assert_eq!(
+    result[0].get_description(),
+    "Expected type ''read'' but recieved ''write''."
+  );
+}
+
+#[test]
+fn cascading_errors_are_reported_once() {
+  let source = "
+let a: number = 'hello'
+let b = a + 1
+b = b * 2
+let [c, d] = b
+let e: string = c
+let f: string = 5
+";
+  let ast = parse(source).unwrap();
+  let result = typecheck(&ast);
+
+  assert_eq!(result.len(), 2);
+  assert_eq!(result[0].span.start, 1);
+  assert_eq!(
+    result[1].get_description(),
+    "Expected type 'string' but recieved 'number'."
+  );
+
+  let ast = parse("let a = b\nlet c = b\n").unwrap();
+  assert_eq!(typecheck(&ast).len(), 2);
+
+  let ast = parse("let a: number = 'hello'\nlet b = [a, list::length(5)]\n").unwrap();
+  let result = typecheck(&ast);
+  assert_eq!(result.len(), 2);
+  assert!(result[1].get_description().ends_with("recieved 'number'."));
+}
+
 #[test]
 fn literals() {
   assert_correct!("let a: string = 'Hello, World!'");
@@ -94,6 +146,96 @@ let b: number? = a(44)
   );
 
   assert_fails!("while (true) 4");
+  assert_correct!("while (true) break");
+}
+
+#[test]
+fn do_expression() {
+  assert_correct!(
+    "
+let a: number = do
+  let b = 1
+  b + 2
+"
+  );
+  assert_correct!(
+    "
+let a: null = do
+  let b = 1
+"
+  );
+  assert_correct!(
+    "
+let f: (number) -> number = (a: number) => do
+  let b = a * 2
+  b
+"
+  );
+  assert_fails!(
+    "
+let a: string = do
+  let b = 1
+  b
+"
+  );
+  assert_fails!(
+    "
+let a = do
+  let b = 1
+let c = b
+"
+  );
+}
+
+#[test]
+fn loop_expression() {
+  assert_correct!(
+    "
+let i = 0
+let a: number = loop
+  i += 1
+  if (i > 5) break i
+"
+  );
+  assert_correct!(
+    "
+let a: number? = loop
+  if (true) break 5
+  break
+"
+  );
+  assert_fails!(
+    "
+let a: number = loop
+  break 'a'
+"
+  );
+  assert_fails!(
+    "
+let a: number = loop
+  if (true) break 5
+  break
+"
+  );
+
+  // Without a break the loop has no value, so can be used as anything
+  assert_correct!(
+    "
+let a = () ->
+  let b: string = loop
+    return 5
+let c: number? = a()
+"
+  );
+  assert_fails!(
+    "
+let a = () ->
+  let b = loop
+    return 5
+  return 'a'
+let c: number = a()
+"
+  );
 }
 
 #[test]
@@ -163,6 +305,10 @@ from string import { includes }
 let a: (string, string) -> boolean = includes
 "
   );
+  assert_correct!("let a: string? = string::format('{a}', { 'a': 1 })");
+  assert_correct!("let a: string? = string::sprintf('%d', [1])");
+  assert_fails!("let a: string = string::sprintf('%d', [1])");
+  assert_fails!("let a = string::sprintf('%d', 1)");
 
   assert_correct!(
     r"
@@ -177,6 +323,40 @@ let a: (number) -> number = sin
 "
   );
 
+  assert_correct!(
+    r"
+from function import { name, arity }
+let a: string? = name(print)
+let b: number? = arity(print)
+"
+  );
+  assert_fails!("let a: string = function::name(print)");
+  assert_fails!("let a: number = function::arity(5)");
+  assert_correct!(
+    r"
+from iter import { range, map, collect }
+let a: number[] = collect(map(range(0, 5), (x: number) => x * 2))
+let b: sequence(string) = map(['a'], (x: string) => x)
+"
+  );
+  assert_correct!(
+    r"
+from maths as m
+from string as s import { trim }
+let a: number = m::floor(2.5)
+let b: (string) -> string = s::trim
+"
+  );
+  assert_fails!("from unknown as u\n");
+  assert_fails!("from maths as m\nlet a: string = m::floor(2.5)\n");
+  assert_correct!(
+    r"
+from task import { spawn, join }
+let a: task(number) = spawn(() => 5)
+let b: number = join(a)
+"
+  );
+
   assert_fails!("from maths import { unknown }");
   assert_fails!("from list import { unknown }");
   assert_fails!("from unknown import { unknown }");
@@ -186,6 +366,15 @@ let a: (number) -> number = sin
   assert_fails!("let a = unknown::x");
 }
 
+#[test]
+fn optional_imports() {
+  assert_correct!("from maths import? { floor }\nlet a: ((number) -> number)? = floor");
+  assert_correct!("from unknown import? { item }\nlet a: null = item");
+  assert_correct!("from maths import? { tau }\nlet a: null = tau");
+  assert_fails!("from maths import? { floor }\nfloor(5)");
+  assert_correct!("from maths import? { floor }\nif (floor != null) floor(5)");
+}
+
 #[test]
 fn unions() {
   assert_correct!(
@@ -263,6 +452,38 @@ mod variables {
     assert_correct!("let a: number | null = 4\n a = null\n let b: null = a");
   }
 
+  #[test]
+  fn assignment_operator() {
+    assert_correct!("let a = 42\na += 5\nlet b: number = a");
+    assert_correct!("let a = 'hello'\na += ' world'");
+    assert_correct!("let a = 42\nlet f = () => a *= 2");
+
+    assert_fails!("b += 5\n");
+    assert_fails!("let a = 'hello'\na -= 'h'\n");
+    assert_fails!("let a = 42\na += 'h'\n");
+  }
+
+  #[test]
+  fn non_null_and_cast() {
+    assert_correct!("let a = (x: number?) => x\nlet b: number = a(5)!");
+    assert_correct!("let a = (x: number?) => x\nlet b: string = a(5) as string");
+    assert_correct!("let a = [1, null]\nlet b: number = a[0]!");
+
+    assert_fails!("let a: string = 'hello' as number");
+    assert_fails!("let a: number = 'hello' as unknownType");
+  }
+
+  #[test]
+  fn ascription() {
+    assert_correct!("let a: number[] = ([]: number[])");
+    assert_correct!("let a = ([]: number[])\nlet b: number = a[0] ?? 0");
+    assert_correct!("let a = (5: number | string)\nlet b: number | string = a");
+
+    assert_fails!("let a = ('hello': number)");
+    assert_fails!("let a = ([]: number[])\nlet b: string[] = a");
+    assert_fails!("let a = (5: number | string)\nlet b: number = a");
+  }
+
   #[test]
   fn variable_not_defined() {
     assert_fails!("a\n");
@@ -309,6 +530,22 @@ let a: number = x
     "
     );
     assert_fails!("let {a, b} = []");
+    assert_correct!(
+      "
+let dict: dict(string, number) = dict::new()
+let { x = 1, y = 'none', ..rest } = dict
+let a: number = x
+let b: number | string = y
+let c: dict(string, number) = rest
+    "
+    );
+    assert_fails!(
+      "
+let dict: dict(string, number) = dict::new()
+let { y = 'none' } = dict
+let a: number = y
+    "
+    );
   }
 }
 
@@ -354,6 +591,20 @@ mod operators {
     assert_fails!("null + true");
   }
 
+  #[test]
+  fn membership() {
+    assert_correct!("let a: boolean = 5 in [1, 2, 3]");
+    assert_correct!("let a: boolean = 'ell' in 'hello'");
+    assert_correct!("let a: dict(string, number) = dict::new()\nlet b: boolean = 'key' in a");
+    assert_correct!("from set import { fromList }\nlet a: boolean = 1 in fromList([1])");
+    assert_correct!("let a = (x) => 5 in x");
+    assert_fails!("'a' in [1, 2, 3]");
+    assert_fails!("5 in 'hello'");
+    assert_fails!("let a: dict(string, number) = dict::new()\n5 in a");
+    assert_fails!("5 in 5");
+    assert_fails!("let a: number = 5 in [5]");
+  }
+
   #[test]
   fn minus() {
     assert_fails!("'a' - 8");
@@ -433,6 +684,28 @@ mod operators {
     );
   }
 
+  #[test]
+  fn composition() {
+    assert_correct!(
+      "
+  let double = (a: number) => a * 2
+  let show = (a: number) => toString(a)
+
+  let a: (number) -> string = show << double
+  let b: string = (show << double << double)(3)
+  "
+    );
+    assert_fails!(
+      "
+  let double = (a: number) => a * 2
+  let show = (a: number) => toString(a)
+
+  let a = double << show
+  "
+    );
+    assert_fails!("let a = 5 << toString");
+  }
+
   #[test]
   fn truthyness_with_unions() {
     assert_correct!(
@@ -497,6 +770,15 @@ let d: number = identity(3.5)
     assert_fails!("((a: number, b: number) => a + b)(7, null)");
   }
 
+  #[test]
+  fn input() {
+    assert_correct!("let a: string? = input()");
+    assert_correct!("let b: string? = input('Name: ')");
+    assert_fails!("let c: string = input()");
+    assert_fails!("input(5)");
+    assert_fails!("input('a', 'b')");
+  }
+
   #[test]
   fn functions() {
     assert_correct!("let func: (number, number) -> number = (a: number, b: number) => a + b");
@@ -509,6 +791,71 @@ let d: number = identity(3.5)
     assert_fails!("let func: (number, string) -> number  = (a: number, b: string) => a || b");
   }
 
+  #[test]
+  fn default_parameters() {
+    assert_correct!("let add = (a: number, b = 10) => a + b\nlet c: number = add(1)\n");
+    assert_correct!("let add = (a: number, b: number = 10) => a + b\nlet c: number = add(1, 2)\n");
+    assert_fails!("((a: number, b = 10) => a + b)()");
+    assert_fails!("((a: number, b = 10) => a + b)(1, 2, 3)");
+    assert_fails!("((a: number, b = 10) => a + b)(1, 'a')");
+    assert_fails!("(a: string = 10) => a");
+
+    // Defaults are widened like declarations, so other values can be passed
+    assert_correct!("let s = (a = true) => a\ns(false)\nlet b: boolean = s()\n");
+    assert_correct!(
+      "let greet = (name: string, greeting = 'Hello') => '{greeting} {name}'\ngreet('Bob', 'Hi')\n"
+    );
+    assert_fails!("let greet = (greeting = 'Hello') => greeting\ngreet(5)\n");
+  }
+
+  #[test]
+  fn overloads() {
+    assert_correct!("let a: number = list::max([1, 2, 3])");
+    assert_correct!("let a: string = list::min(['a', 'b'])");
+    assert_fails!("let a: string = list::max([1, 2, 3])");
+    assert_fails!("list::max([true])");
+    assert_correct!(
+      "
+let apply = (func: ((number, number) -> number) | ((string) -> string)) ->
+  let a: number = func(1, 2)
+  let b: string = func('hello')
+      "
+    );
+    assert_fails!(
+      "
+let apply = (func: ((number, number) -> number) | ((string) -> string)) ->
+  func(1)
+      "
+    );
+  }
+
+  #[test]
+  fn overload_error_lists_each_overload() {
+    let ast = parse("list::max([true])").unwrap();
+    let result = typecheck(&ast);
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].get_title(), "No Matching Overload");
+    assert_eq!(
+      result[0].get_description(),
+      "No overload matches this call.
+  (string[]) -> string: Expected type 'string[]' but recieved 'true[]'.
+  (number[]) -> number: Expected type 'number[]' but recieved 'true[]'."
+    );
+  }
+
+  #[test]
+  fn optional_parameters_in_signature() {
+    let ast = parse("let a: number = (a: number, b: string = '') => a").unwrap();
+    let result = typecheck(&ast);
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+      result[0].get_description(),
+      "Expected type 'number' but recieved '(number, [string]) -> number'."
+    );
+  }
+
   #[test]
   fn recursive() {
     assert_correct!(
@@ -764,6 +1111,26 @@ let boolean = (b: boolean) ->
     );
   }
 
+  #[test]
+  fn string_values() {
+    assert_correct!(
+      "
+let read = (mode: 'read') => mode
+let write = (mode: 'write') => mode
+let open = (mode: 'read' | 'write') ->
+  if (mode == 'read') read(mode)
+  else write(mode)
+"
+    );
+    assert_correct!(
+      "
+let write = (mode: 'write') => mode
+let open = (mode: 'read' | 'write') ->
+  if (mode != 'read') write(mode)
+"
+    );
+  }
+
   #[test]
   fn to_union() {
     assert_correct!(
@@ -807,6 +1174,28 @@ mod compound_structures {
   let g: (number | string)[] = [1, 'hello', 3] >> reverse()
 "
     );
+    assert_correct!(
+      "
+  from list import { compare }
+
+  let a: number = compare([1, 2], [1, 3])
+  let b: number = compare(['a'], ['b'])
+  let c: number = compare([[1]], [[2]], (x: number[], y: number[]) => compare(x, y))
+"
+    );
+    assert_correct!(
+      "
+  from list import { findIndex, indexOf, lastIndexOf, count, sum }
+
+  let a: number? = [1, 2, 3] >> findIndex((x) => x > 1)
+  let b: number? = ['a', 'b'] >> indexOf('b')
+  let c: number? = [1, 2, 1] >> lastIndexOf(1)
+  let d: number = [true, false] >> count(true)
+  let e: number = sum([1, 2, 3])
+"
+    );
+    assert_fails!("list::sum(['a', 'b'])");
+    assert_fails!("list::indexOf([1, 2, 3], 'a')");
   }
 
   #[test]
@@ -828,6 +1217,22 @@ mod compound_structures {
   let j = symmetricDifference(h, i)
   "
     );
+    assert_correct!(
+      "
+  from set import { fromList, toList, copy, map, filter, fold }
+
+  let a = fromList([1, 2, 3])
+  let b: number[] = toList(a)
+  let c = copy(a)
+  let d = map(a, (x: number) => toString(x))
+  let e: string[] = toList(d)
+  let f = filter(a, (x: number) => x > 1)
+  let g: number = fold(a, 0, (total: number, x: number) => total + x)
+  "
+    );
+    assert_fails!(
+      "let a: string = set::fold(set::fromList([1]), 0, (a: number, b: number) => a + b)"
+    );
   }
 
   #[test]
@@ -867,6 +1272,20 @@ mod compound_structures {
     assert_fails!("let a: (boolean|number)[] = [1, false, 3]\n a[0] += 4");
   }
 
+  #[test]
+  fn readonly() {
+    assert_correct!("let a: readonly(number[]) = list::freeze([1, 2, 3])");
+    assert_correct!("let a: number = list::freeze([1, 2, 3])[0]");
+    assert_fails!("let a: number[] = list::freeze([1, 2, 3])");
+    assert_fails!("list::push(list::freeze([1, 2, 3]), 4)");
+    assert_fails!("let a = list::pop(list::freeze([1, 2, 3]))");
+    assert_correct!("let a: number = dict::freeze({ 'a': 1 })['a']");
+    assert_fails!("let a: readonly(number[]) = [1, 2, 3]");
+    assert_fails!("let a = list::freeze([1, 2, 3])\n a[0] = 4");
+    assert_fails!("let a: readonly(number[]) = list::freeze([1])\n a[0] += 4");
+    assert_fails!("let a = dict::freeze({ 'a': 1 })\n a['a'] = 4");
+  }
+
   #[test]
   fn dict() {
     assert_correct!("let a: dict(string, number) = dict::new()");
@@ -889,4 +1308,57 @@ let a = (a) ->
     assert_correct!("let a: dict(string, number) = { 'hello': 7 }");
     assert_correct!("let a: dict(string, number) = { }");
   }
+
+  #[test]
+  fn dict_sorted() {
+    assert_correct!("let a: string[] = dict::sortedKeys({ 'a': 1 })");
+    assert_correct!(
+      "let a: number[] = dict::sortedKeys({ 1: 'a' }, (x: number, y: number) => y - x)"
+    );
+    assert_correct!("let a: string | number = dict::toSortedList({ 'a': 1 })[0][1]");
+    assert_fails!("let a: string = dict::toSortedList({ 'a': 1 })[0][0]");
+    assert_fails!("let a: number[] = dict::sortedKeys({ 'a': 1 })");
+    assert_fails!("let a: string[] = dict::sortedKeys([1, 2])");
+  }
+}
+
+mod pragmas {
+  use super::*;
+  use bang_tools::TypecheckStrictness as Strictness;
+
+  #[test]
+  fn strict_requires_parameter_annotations() {
+    assert_correct!("// @strict\nlet a = (x: number) => x + 1");
+    assert_correct!("// @strict\nlet a = (x = 1) => x + 1");
+    assert_correct!("// @strict\nlet a = (x) => x + 1");
+    assert_correct!("// @strict\nlet a = (x, y) => x * y");
+    assert_fails!("// @strict\nlet a = (x) => x");
+    assert_fails!("// @strict\nlet a = list::map([1], (x) => x)");
+
+    let ast = parse("// @strict\nlet a = (x: number, y) => x").unwrap();
+    let result = typecheck(&ast);
+    assert_eq!(
+      result[0].get_description(),
+      "Parameter 'y' needs a type annotation in strict mode."
+    );
+  }
+
+  #[test]
+  fn nocheck_skips_file() {
+    assert_correct!("// @nocheck\nlet a: number = 'hello'");
+    assert_correct!("// A description\n// @nocheck\nlet a: number = 'hello'");
+    assert_fails!("let a = 1\n// @nocheck\nlet b: number = 'hello'");
+  }
+
+  #[test]
+  fn pragmas_only_in_leading_comments() {
+    let strictness = |source| Strictness::from_pragmas(&parse(source).unwrap());
+
+    assert_eq!(strictness("let a = 1"), Strictness::Default);
+    assert_eq!(strictness("// @strict\nlet a = 1"), Strictness::Strict);
+    assert_eq!(strictness("//@nocheck\n"), Strictness::NoCheck);
+    assert_eq!(strictness("let a = 1\n// @strict\n"), Strictness::Default);
+    assert_eq!(strictness("// @strictly\n"), Strictness::Default);
+    assert_correct!("let a = 1 // @strict\nlet b = (x) => x");
+  }
 }
@@ -0,0 +1,112 @@
+use bang_tools::{FormatOptions, Workspace};
+
+#[test]
+fn lints_all_files() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "if (true) print(1)\n".to_string());
+  workspace.set_file("b.bang", "print(2)\n".to_string());
+  workspace.set_file("c.bang", "let = \n".to_string());
+
+  let results = workspace.lint_all();
+  let filenames: Vec<_> = results.iter().map(|file| file.filename).collect();
+  assert_eq!(filenames, vec!["a.bang", "b.bang", "c.bang"]);
+
+  let warnings = results[0].result.as_ref().unwrap();
+  let titles: Vec<_> = warnings
+    .iter()
+    .map(|warning| warning.title.as_str())
+    .collect();
+  assert!(titles.contains(&"No Constant Conditions"));
+  assert!(results[1].result.as_ref().unwrap().is_empty());
+  assert!(results[2].result.is_err());
+}
+
+#[test]
+fn typechecks_all_files() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "let a: number = 'hello'\n".to_string());
+  workspace.set_file("b.bang", "let b: number = 5\n".to_string());
+
+  let results = workspace.typecheck_all();
+  assert_eq!(results.len(), 2);
+  assert_eq!(results[0].result.as_ref().unwrap().errors.len(), 1);
+  assert!(results[1].result.as_ref().unwrap().errors.is_empty());
+  assert_eq!(results[1].source, "let b: number = 5\n");
+}
+
+#[test]
+fn keeps_results_until_the_file_changes() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "let a: number = 'hello'\n".to_string());
+  assert_eq!(errors(&mut workspace), 1);
+
+  workspace.set_file("a.bang", "let a: number = 'hello'\n".to_string());
+  assert_eq!(errors(&mut workspace), 1);
+
+  workspace.set_file("a.bang", "let a: number = 5\n".to_string());
+  assert_eq!(errors(&mut workspace), 0);
+
+  workspace.invalidate("a.bang");
+  assert_eq!(errors(&mut workspace), 0);
+
+  workspace.remove_file("a.bang");
+  assert!(workspace.typecheck("a.bang").is_none());
+  assert_eq!(workspace.filenames().count(), 0);
+}
+
+#[test]
+fn strict_setting_applies_to_files_without_a_pragma() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "let a = (x) => x\n".to_string());
+  workspace.set_file("b.bang", "// @nocheck\nlet b = (x) => x\n".to_string());
+  assert_eq!(errors(&mut workspace), 0);
+
+  workspace.set_strict(true);
+  assert_eq!(errors(&mut workspace), 1);
+  let file = workspace.typecheck("b.bang").unwrap();
+  assert!(file.result.as_ref().unwrap().errors.is_empty());
+
+  workspace.set_strict(false);
+  assert_eq!(errors(&mut workspace), 0);
+}
+
+fn errors(workspace: &mut Workspace) -> usize {
+  let file = workspace.typecheck("a.bang").unwrap();
+  file.result.as_ref().unwrap().errors.len()
+}
+
+#[test]
+fn formats_file_with_options() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "let a = 'hello'".to_string());
+
+  let formatted = workspace.format_file("a.bang", FormatOptions::default());
+  assert_eq!(
+    formatted.unwrap().result.as_ref().unwrap(),
+    "let a = 'hello'\n"
+  );
+
+  let options = FormatOptions {
+    quote: bang_tools::QuoteStyle::Double,
+    ..FormatOptions::default()
+  };
+  let formatted = workspace.format_file("a.bang", options);
+  assert_eq!(
+    formatted.unwrap().result.as_ref().unwrap(),
+    "let a = \"hello\"\n"
+  );
+}
+
+#[test]
+fn protected_globals_are_used_when_linting() {
+  let mut workspace = Workspace::new();
+  workspace.set_file("a.bang", "let print = 5\n".to_string());
+  let warnings = |workspace: &mut Workspace| {
+    let file = workspace.lint("a.bang").unwrap();
+    file.result.as_ref().unwrap().len()
+  };
+
+  let before = warnings(&mut workspace);
+  workspace.set_protected_globals(&["print"]);
+  assert!(warnings(&mut workspace) > before);
+}